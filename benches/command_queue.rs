@@ -0,0 +1,215 @@
+//! Benchmarks for queueing audio commands on channels
+//!
+//! Run with `cargo bench --bench command_queue`.
+//! Many systems queue commands on the same channel in parallel, so these benchmarks
+//! measure queueing throughput with an increasing number of producer threads. Besides the
+//! channel itself, they compare the queue it uses with a mutex, a lock-free queue and a queue
+//! sharded by producer thread, each holding a payload the size of an audio command.
+
+use bevy::asset::Handle;
+use bevy_kira_audio::prelude::*;
+use concurrent_queue::ConcurrentQueue;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use parking_lot::{Mutex, RwLock};
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::thread;
+
+const COMMANDS: usize = 10_000;
+const THREADS: [usize; 3] = [1, 2, 4];
+const SHARDS: usize = 8;
+
+/// Stand-in for an audio command, which is 56 bytes large
+#[derive(Clone, Copy, Debug)]
+struct Payload([u64; 7]);
+
+/// The queues the channels could use
+///
+/// Retried commands go back to the front of the queue, which the lock-free queue cannot do,
+/// so draining it has to collect the commands first.
+trait Queue: Default + Sync {
+    fn push(&self, shard: usize, payload: Payload);
+    fn drain(&self) -> usize;
+}
+
+#[derive(Default)]
+struct RwLockQueue(RwLock<VecDeque<Payload>>);
+
+impl Queue for RwLockQueue {
+    fn push(&self, _shard: usize, payload: Payload) {
+        self.0.write().push_front(payload);
+    }
+
+    fn drain(&self) -> usize {
+        let mut commands = self.0.write();
+        let len = commands.len();
+        while let Some(payload) = commands.pop_back() {
+            black_box(payload.0);
+        }
+        len
+    }
+}
+
+#[derive(Default)]
+struct MutexQueue(Mutex<VecDeque<Payload>>);
+
+impl Queue for MutexQueue {
+    fn push(&self, _shard: usize, payload: Payload) {
+        self.0.lock().push_front(payload);
+    }
+
+    fn drain(&self) -> usize {
+        let mut commands = self.0.lock();
+        let len = commands.len();
+        while let Some(payload) = commands.pop_back() {
+            black_box(payload.0);
+        }
+        len
+    }
+}
+
+struct LockFreeQueue {
+    commands: ConcurrentQueue<Payload>,
+    retry: Mutex<Vec<Payload>>,
+}
+
+impl Default for LockFreeQueue {
+    fn default() -> Self {
+        LockFreeQueue {
+            commands: ConcurrentQueue::unbounded(),
+            retry: Mutex::default(),
+        }
+    }
+}
+
+impl Queue for LockFreeQueue {
+    fn push(&self, _shard: usize, payload: Payload) {
+        self.commands.push(payload).unwrap();
+    }
+
+    fn drain(&self) -> usize {
+        let mut retry = self.retry.lock();
+        retry.extend(self.commands.try_iter());
+        let len = retry.len();
+        for payload in retry.drain(..) {
+            black_box(payload.0);
+        }
+        len
+    }
+}
+
+#[derive(Default)]
+struct ShardedQueue([Mutex<VecDeque<Payload>>; SHARDS]);
+
+impl Queue for ShardedQueue {
+    fn push(&self, shard: usize, payload: Payload) {
+        self.0[shard % SHARDS].lock().push_front(payload);
+    }
+
+    fn drain(&self) -> usize {
+        let mut len = 0;
+        for shard in &self.0 {
+            let mut commands = shard.lock();
+            len += commands.len();
+            while let Some(payload) = commands.pop_back() {
+                black_box(payload.0);
+            }
+        }
+        len
+    }
+}
+
+fn fill<Q: Queue>(queue: &Q, threads: usize) {
+    thread::scope(|scope| {
+        for shard in 0..threads {
+            scope.spawn(move || {
+                for index in 0..COMMANDS / threads {
+                    queue.push(shard, Payload([index as u64; 7]));
+                }
+            });
+        }
+    });
+}
+
+fn bench_queue<Q: Queue>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("queue strategies/{name}"));
+    group.throughput(Throughput::Elements(COMMANDS as u64));
+    for threads in THREADS {
+        group.bench_with_input(
+            BenchmarkId::new("push and drain", threads),
+            &threads,
+            |b, &threads| {
+                let queue = Q::default();
+                b.iter(|| {
+                    fill(&queue, threads);
+                    black_box(queue.drain())
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn queue_strategies(c: &mut Criterion) {
+    bench_queue::<RwLockQueue>(c, "rwlock (used)");
+    bench_queue::<MutexQueue>(c, "mutex");
+    bench_queue::<LockFreeQueue>(c, "lock-free");
+    bench_queue::<ShardedQueue>(c, "sharded");
+}
+
+fn channel_play(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue play commands");
+    group.throughput(Throughput::Elements(COMMANDS as u64));
+    for threads in THREADS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let channel = Audio::default();
+                    thread::scope(|scope| {
+                        for _ in 0..threads {
+                            scope.spawn(|| {
+                                for _ in 0..COMMANDS / threads {
+                                    black_box(channel.play(Handle::default()).handle());
+                                }
+                            });
+                        }
+                    });
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn channel_tweens(c: &mut Criterion) {
+    c.bench_function("queue tween commands", |b| {
+        b.iter(|| {
+            let channel = Audio::default();
+            for index in 0..COMMANDS {
+                channel.set_volume(index as f64 / COMMANDS as f64);
+            }
+        });
+    });
+}
+
+fn state_lookup(c: &mut Criterion) {
+    let channel = Audio::default();
+    let instance = channel.play(Handle::default()).handle();
+    for _ in 0..100 {
+        channel.play(Handle::default());
+    }
+    c.bench_function("state of queued instance", |b| {
+        b.iter(|| black_box(channel.state(&instance)));
+    });
+}
+
+criterion_group!(
+    benches,
+    channel_play,
+    channel_tweens,
+    state_lookup,
+    queue_strategies
+);
+criterion_main!(benches);
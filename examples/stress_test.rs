@@ -17,6 +17,7 @@ fn main() {
         .insert_resource(AudioSettings {
             sound_capacity: 8192,
             command_capacity: 4096,
+            ..default()
         })
         .add_plugins((DefaultPlugins, AudioPlugin))
         .add_systems(Startup, prepare)
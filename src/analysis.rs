@@ -0,0 +1,398 @@
+//! Frequency spectrum of channels for visualizers
+
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use bevy::ecs::system::{NonSend, ResMut, Resource};
+use bevy::utils::HashMap;
+use kira::clock::clock_info::ClockInfoProvider;
+use kira::dsp::Frame;
+use kira::modulator::value_provider::ModulatorValueProvider;
+use kira::track::effect::{Effect, EffectBuilder};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// How the spectrum of a channel is computed, see [`ChannelSettings::with_spectrum`](crate::ChannelSettings::with_spectrum)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrumSettings {
+    /// Number of samples per transform, rounded up to a power of two
+    ///
+    /// The spectrum has half as many bins. Larger sizes resolve lower frequencies, but react slower.
+    pub size: usize,
+    /// How often the spectrum is computed per second
+    pub updates_per_second: f64,
+    /// How much of the previous magnitudes is kept in every update, from `0.0` to `1.0`
+    ///
+    /// Higher values make visualizers calmer, but slower to react. The default is `0.5`.
+    pub smoothing: f32,
+}
+
+impl Default for SpectrumSettings {
+    fn default() -> Self {
+        SpectrumSettings {
+            size: 1024,
+            updates_per_second: 30.,
+            smoothing: 0.5,
+        }
+    }
+}
+
+/// Resource to compute the frequency spectrum of the main mix
+///
+/// Like [`AudioSettings`](crate::AudioSettings), it needs to be inserted before adding the
+/// [`AudioPlugin`](crate::AudioPlugin) and is consumed by it. The spectrum is available
+/// through [`AudioSpectrum::main`].
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// App::new()
+///     .insert_resource(MainTrackSpectrum(SpectrumSettings {
+///         size: 2048,
+///         ..default()
+///     }))
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
+/// ```
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MainTrackSpectrum(pub SpectrumSettings);
+
+/// Resource with the frequency spectrum of all channels that have one
+///
+/// The spectrum is computed on the audio thread and copied into this resource once per frame.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel_with_settings::<Music>(
+///         ChannelSettings::default().with_spectrum(SpectrumSettings::default()),
+///     )
+///     .add_systems(Update, pulse_with_bass);
+///
+/// fn pulse_with_bass(spectrum: Res<AudioSpectrum>) {
+///     let Some(spectrum) = spectrum.get(&Channel::typed::<Music>()) else {
+///         return;
+///     };
+///     let bass: f32 = spectrum
+///         .magnitudes()
+///         .iter()
+///         .enumerate()
+///         .filter(|(bin, _)| spectrum.frequency(*bin) < 150.)
+///         .map(|(_, magnitude)| magnitude)
+///         .sum();
+///     info!("Bass: {bass}");
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct AudioSpectrum {
+    channels: HashMap<Channel, Spectrum>,
+    main: Option<Spectrum>,
+}
+
+impl AudioSpectrum {
+    /// The spectrum of the given channel
+    pub fn get(&self, channel: &Channel) -> Option<&Spectrum> {
+        self.channels.get(channel)
+    }
+
+    /// The spectrum of the main mix, if [`MainTrackSpectrum`] was inserted
+    pub fn main(&self) -> Option<&Spectrum> {
+        self.main.as_ref()
+    }
+}
+
+/// Frequency spectrum of a channel
+#[derive(Clone, Debug, Default)]
+pub struct Spectrum {
+    magnitudes: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl Spectrum {
+    /// Magnitude per frequency bin, from `0` up to about `1` for a full-scale sine wave
+    pub fn magnitudes(&self) -> &[f32] {
+        &self.magnitudes
+    }
+
+    /// Center frequency of the given bin in Hz
+    pub fn frequency(&self, bin: usize) -> f32 {
+        bin as f32 * self.sample_rate as f32 / (self.magnitudes.len() * 2) as f32
+    }
+}
+
+/// Spectrum shared between the audio thread and the main thread
+pub(crate) struct SharedSpectrum {
+    magnitudes: Vec<AtomicU32>,
+    sample_rate: AtomicU32,
+}
+
+impl SharedSpectrum {
+    fn read_into(&self, spectrum: &mut Spectrum) {
+        spectrum.magnitudes.clear();
+        spectrum.magnitudes.extend(
+            self.magnitudes
+                .iter()
+                .map(|magnitude| f32::from_bits(magnitude.load(Ordering::Relaxed))),
+        );
+        spectrum.sample_rate = self.sample_rate.load(Ordering::Relaxed);
+    }
+}
+
+/// Effect passing the signal through unchanged while computing its spectrum
+pub(crate) struct SpectrumTap {
+    shared: Arc<SharedSpectrum>,
+    samples: Vec<f32>,
+    next_sample: usize,
+    window: Vec<f32>,
+    real: Vec<f32>,
+    imaginary: Vec<f32>,
+    interval: f64,
+    since_update: f64,
+    smoothing: f32,
+}
+
+pub(crate) struct SpectrumTapBuilder {
+    pub(crate) settings: SpectrumSettings,
+    pub(crate) shared: Arc<SharedSpectrum>,
+}
+
+impl SpectrumTapBuilder {
+    pub(crate) fn new(settings: SpectrumSettings) -> Self {
+        let size = settings.size.max(2).next_power_of_two();
+        SpectrumTapBuilder {
+            settings: SpectrumSettings { size, ..settings },
+            shared: Arc::new(SharedSpectrum {
+                magnitudes: (0..size / 2).map(|_| AtomicU32::new(0)).collect(),
+                sample_rate: AtomicU32::new(0),
+            }),
+        }
+    }
+}
+
+impl EffectBuilder for SpectrumTapBuilder {
+    type Handle = ();
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        let size = self.settings.size;
+        // Hann window against leakage between bins
+        let window = (0..size)
+            .map(|index| 0.5 - 0.5 * (2. * PI * index as f32 / size as f32).cos())
+            .collect();
+        let tap = SpectrumTap {
+            shared: self.shared,
+            samples: vec![0.; size],
+            next_sample: 0,
+            window,
+            real: vec![0.; size],
+            imaginary: vec![0.; size],
+            interval: 1. / self.settings.updates_per_second.max(f64::EPSILON),
+            since_update: 0.,
+            smoothing: self.settings.smoothing.clamp(0., 1.),
+        };
+
+        (Box::new(tap), ())
+    }
+}
+
+impl SpectrumTap {
+    fn update(&mut self) {
+        let size = self.samples.len();
+        let samples = self.real.iter_mut().zip(self.imaginary.iter_mut());
+        for (index, ((real, imaginary), window)) in samples.zip(&self.window).enumerate() {
+            *real = self.samples[(self.next_sample + index) % size] * window;
+            *imaginary = 0.;
+        }
+        fft(&mut self.real, &mut self.imaginary);
+        // the window halves the amplitude
+        let scale = 4. / size as f32;
+        for (bin, magnitude) in self.shared.magnitudes.iter().enumerate() {
+            let value = self.real[bin].hypot(self.imaginary[bin]) * scale;
+            let previous = f32::from_bits(magnitude.load(Ordering::Relaxed));
+            let value = previous * self.smoothing + value * (1. - self.smoothing);
+            magnitude.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl Effect for SpectrumTap {
+    fn init(&mut self, sample_rate: u32) {
+        self.shared
+            .sample_rate
+            .store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn on_change_sample_rate(&mut self, sample_rate: u32) {
+        self.shared
+            .sample_rate
+            .store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn process(
+        &mut self,
+        input: Frame,
+        dt: f64,
+        _clock_info_provider: &ClockInfoProvider,
+        _modulator_value_provider: &ModulatorValueProvider,
+    ) -> Frame {
+        self.samples[self.next_sample] = (input.left + input.right) / 2.;
+        self.next_sample = (self.next_sample + 1) % self.samples.len();
+        self.since_update += dt;
+        if self.since_update >= self.interval {
+            self.since_update -= self.interval;
+            self.update();
+        }
+
+        input
+    }
+}
+
+/// In-place radix-2 fast Fourier transform, the length has to be a power of two
+fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let size = real.len();
+    let mut target = 0;
+    for index in 1..size {
+        let mut bit = size >> 1;
+        while target & bit != 0 {
+            target ^= bit;
+            bit >>= 1;
+        }
+        target |= bit;
+        if index < target {
+            real.swap(index, target);
+            imaginary.swap(index, target);
+        }
+    }
+    let mut length = 2;
+    while length <= size {
+        let angle = -2. * PI / length as f32;
+        for start in (0..size).step_by(length) {
+            for offset in 0..length / 2 {
+                let (sin, cos) = (angle * offset as f32).sin_cos();
+                let even = start + offset;
+                let odd = even + length / 2;
+                let odd_real = real[odd] * cos - imaginary[odd] * sin;
+                let odd_imaginary = real[odd] * sin + imaginary[odd] * cos;
+                real[odd] = real[even] - odd_real;
+                imaginary[odd] = imaginary[even] - odd_imaginary;
+                real[even] += odd_real;
+                imaginary[even] += odd_imaginary;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+pub(crate) fn update_audio_spectrum(
+    audio_output: NonSend<AudioOutput>,
+    mut audio_spectrum: ResMut<AudioSpectrum>,
+) {
+    let spectrums = audio_output.spectrums();
+    audio_spectrum
+        .channels
+        .retain(|channel, _| spectrums.contains_key(channel));
+    for (channel, shared) in spectrums {
+        shared.read_into(audio_spectrum.channels.entry(channel.clone()).or_default());
+    }
+    if let Some(shared) = audio_output.main_spectrum() {
+        shared.read_into(audio_spectrum.main.get_or_insert_with(Spectrum::default));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kira::clock::clock_info::MockClockInfoProviderBuilder;
+    use kira::modulator::value_provider::MockModulatorValueProviderBuilder;
+
+    #[test]
+    fn sine_waves_peak_in_their_bin() {
+        let size = 64;
+        let mut real: Vec<f32> = (0..size)
+            .map(|index| (2. * PI * 5. * index as f32 / size as f32).sin())
+            .collect();
+        let mut imaginary = vec![0.; size];
+
+        fft(&mut real, &mut imaginary);
+
+        let magnitudes: Vec<f32> = (0..size / 2)
+            .map(|bin| real[bin].hypot(imaginary[bin]) * 2. / size as f32)
+            .collect();
+        assert!((magnitudes[5] - 1.).abs() < 1e-4);
+        assert!(magnitudes
+            .iter()
+            .enumerate()
+            .all(|(bin, magnitude)| bin == 5 || *magnitude < 1e-4));
+    }
+
+    #[test]
+    fn smoothing_keeps_part_of_the_previous_magnitudes() {
+        let builder = SpectrumTapBuilder::new(SpectrumSettings {
+            size: 64,
+            updates_per_second: 8.,
+            smoothing: 0.5,
+        });
+        let shared = builder.shared.clone();
+        let (mut tap, _) = builder.build();
+        let sample_rate = 512;
+        tap.init(sample_rate);
+        let clock_info_provider = MockClockInfoProviderBuilder::new(0).build();
+        let modulator_value_provider = MockModulatorValueProviderBuilder::new(0).build();
+        let mut process = |sample: f32| {
+            tap.process(
+                Frame::from_mono(sample),
+                1. / sample_rate as f64,
+                &clock_info_provider,
+                &modulator_value_provider,
+            );
+        };
+        // a constant signal only has a DC component
+        for _ in 0..64 {
+            process(1.);
+        }
+
+        let mut spectrum = Spectrum::default();
+        shared.read_into(&mut spectrum);
+        let first = spectrum.magnitudes()[0];
+        assert!(first > 0.);
+        for _ in 0..64 {
+            process(0.);
+        }
+        shared.read_into(&mut spectrum);
+        assert!((spectrum.magnitudes()[0] - first / 2.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn tap_reports_the_spectrum_of_its_input() {
+        let builder = SpectrumTapBuilder::new(SpectrumSettings {
+            size: 60,
+            updates_per_second: 10.,
+            smoothing: 0.,
+        });
+        let shared = builder.shared.clone();
+        let (mut tap, _) = builder.build();
+        let sample_rate = 640;
+        tap.init(sample_rate);
+        let clock_info_provider = MockClockInfoProviderBuilder::new(0).build();
+        let modulator_value_provider = MockModulatorValueProviderBuilder::new(0).build();
+        for index in 0..sample_rate {
+            let sample = (2. * PI * 80. * index as f32 / sample_rate as f32).sin();
+            tap.process(
+                Frame::from_mono(sample),
+                1. / sample_rate as f64,
+                &clock_info_provider,
+                &modulator_value_provider,
+            );
+        }
+
+        let mut spectrum = Spectrum::default();
+        shared.read_into(&mut spectrum);
+        assert_eq!(spectrum.magnitudes().len(), 32);
+        let loudest = (0..32)
+            .max_by(|a, b| spectrum.magnitudes()[*a].total_cmp(&spectrum.magnitudes()[*b]))
+            .unwrap();
+        assert_eq!(spectrum.frequency(loudest), 80.);
+        assert!((spectrum.magnitudes()[loudest] - 1.).abs() < 0.05);
+    }
+}
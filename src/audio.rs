@@ -1,31 +1,113 @@
 //! Common audio types
 
 use crate::audio_output::{play_audio_channel, update_instance_states};
-use crate::channel::typed::AudioChannel;
-use crate::channel::AudioCommandQue;
+use crate::channel::typed::{apply_play_command_queueing, AudioChannel};
+use crate::channel::{AudioCommandQue, Channel, ChannelSettings};
+use crate::clock::AudioClock;
+use crate::diagnostics::TypedChannelNames;
+use crate::dialogue::{run_dialogue_audio, DialogueAudio};
+use crate::ducking::{DuckChannel, DuckingMatrix};
 use crate::instance::AudioInstance;
+use crate::layered_music::{run_layered_music, LayeredMusic};
+use crate::playlist::{run_audio_playlist, AudioPlaylist};
+use crate::sequencer::{run_step_sequencer, StepSequencer};
 use crate::source::AudioSource;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::StreamingAudioSource;
 use crate::AudioSystemSet;
-use bevy::app::{App, PreUpdate};
-use bevy::asset::{AssetId, Handle};
+use bevy::app::{App, PreStartup, PreUpdate};
+use bevy::asset::{AssetId, AssetPath, Handle, UntypedAssetId};
 use bevy::ecs::system::Resource;
-use bevy::prelude::{default, IntoSystemConfigs, PostUpdate};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle};
+use bevy::log::warn;
+use bevy::prelude::{default, resource_exists, IntoSystemConfigs, PostUpdate};
+use bevy::reflect::std_traits::ReflectDefault;
+use bevy::reflect::Reflect;
+#[cfg(feature = "serialize")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
+use kira::clock::{ClockSpeed, ClockTime};
+use kira::sound::static_sound::{StaticSoundHandle, StaticSoundSettings};
 use kira::sound::EndPosition;
 use kira::tween::Value;
 use kira::Volume;
+use serde::{Deserialize, Serialize};
+use std::any::{type_name, TypeId};
+use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
 pub(crate) enum AudioCommand {
-    Play(PlayAudioSettings),
+    Play(Box<PlayAudioSettings>),
+    /// Sounds with shared settings that start together
+    PlayMany(Vec<PlayAudioSettings>),
+    AddClock(ClockSpeed, AssetId<AudioClock>),
     SetVolume(Volume, Option<AudioTween>),
-    SetPanning(f64, Option<AudioTween>),
+    SetPanning(Panning, Option<AudioTween>),
     SetPlaybackRate(f64, Option<AudioTween>),
-    Stop(Option<AudioTween>),
-    Pause(Option<AudioTween>),
-    Resume(Option<AudioTween>),
+    Stop(Option<AudioTween>, Option<Duration>),
+    Pause(Option<AudioTween>, Option<Duration>),
+    Resume(Option<AudioTween>, ResumeTargets),
+    Seek(SeekPosition),
+    /// Mute all sounds but the given one, or unmute them again with `None`
+    Solo(Option<Handle<AudioInstance>>),
+}
+
+impl AudioCommand {
+    pub(crate) fn is_play(&self) -> bool {
+        matches!(self, AudioCommand::Play(_) | AudioCommand::PlayMany(_))
+    }
+
+    /// Tracing span for running this command on the given channel
+    #[cfg(feature = "trace")]
+    pub(crate) fn span(&self, channel: &Channel) -> bevy::utils::tracing::Span {
+        let (command, source, instance) = match self {
+            AudioCommand::Play(settings) => (
+                "play",
+                Some(settings.source.id()),
+                Some(settings.instance_handle.id()),
+            ),
+            AudioCommand::PlayMany(_) => ("play_many", None, None),
+            AudioCommand::AddClock(..) => ("add_clock", None, None),
+            AudioCommand::SetVolume(..) => ("set_volume", None, None),
+            AudioCommand::SetPanning(..) => ("set_panning", None, None),
+            AudioCommand::SetPlaybackRate(..) => ("set_playback_rate", None, None),
+            AudioCommand::Stop(..) => ("stop", None, None),
+            AudioCommand::Pause(..) => ("pause", None, None),
+            AudioCommand::Resume(..) => ("resume", None, None),
+            AudioCommand::Seek(_) => ("seek", None, None),
+            AudioCommand::Solo(instance) => ("solo", None, instance.as_ref().map(Handle::id)),
+        };
+
+        bevy::log::info_span!(
+            "audio_command",
+            command,
+            channel = ?channel,
+            source = ?source,
+            instance = ?instance
+        )
+    }
+}
+
+/// Which sounds a resume command resumes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ResumeTargets {
+    /// Paused and pausing sounds
+    #[default]
+    Paused,
+    /// Also sounds that are stopping, cancelling their fade-out
+    All,
+}
+
+/// Target of a seek command
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SeekPosition {
+    /// Seek to the position in seconds
+    To(f64),
+    /// Seek by the amount of seconds, backwards if negative
+    By(f64),
 }
 
 #[derive(Clone, Default)]
@@ -35,20 +117,238 @@ pub(crate) struct PartialSoundSettings {
     pub(crate) volume: Option<Volume>,
     pub(crate) playback_rate: Option<f64>,
     pub(crate) start_position: Option<f64>,
+    /// Start at a random position between the bounds, up to the end of the sound if `None`
+    pub(crate) random_start: Option<(f64, Option<f64>)>,
     pub(crate) end_position: Option<f64>,
-    pub(crate) panning: Option<f64>,
+    pub(crate) panning: Option<Panning>,
     pub(crate) reverse: Option<bool>,
     pub(crate) paused: bool,
     pub(crate) fade_in: Option<AudioTween>,
+    pub(crate) category: Option<Cow<'static, str>>,
+    pub(crate) start: Option<ScheduledStart>,
+    /// Fade out all other sounds of the channel when this sound starts
+    pub(crate) crossfade: Option<AudioTween>,
+    /// Stop the looped sound after it was silent for the duration, until it is audible again
+    pub(crate) stop_when_silent: Option<Duration>,
+    /// Sounds with a higher priority keep playing when the voice cap is reached
+    pub(crate) priority: i32,
+    /// Multiply the playback rate by a random factor between the bounds on every play
+    pub(crate) pitch_variation: Option<(f64, f64)>,
+    /// Multiply the volume by a random factor between the bounds on every play
+    pub(crate) volume_variation: Option<(f64, f64)>,
+    /// Mix stereo sources down to mono before playing them
+    pub(crate) mono: bool,
+}
+
+/// When a sound should start, if not right away
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ScheduledStart {
+    /// When the clock reaches the tick
+    Tick(AssetId<AudioClock>, u64),
+    /// After the duration passed
+    Delay(Duration),
+    /// At the given time of a clock that is not an asset
+    Clock(ClockTime),
+}
+
+/// The highest playback rate, in either direction, that sounds can be played with
+///
+/// Playback rates outside of `-MAX_PLAYBACK_RATE..=MAX_PLAYBACK_RATE` are clamped.
+pub const MAX_PLAYBACK_RATE: f64 = 32.;
+
+/// Clamp a playback rate into the supported range, warning about invalid values
+pub(crate) fn valid_playback_rate(playback_rate: f64) -> f64 {
+    if !playback_rate.is_finite() {
+        warn!("Invalid playback rate {playback_rate}, using 1 instead");
+        return 1.;
+    }
+    if playback_rate.abs() > MAX_PLAYBACK_RATE {
+        warn!("Playback rate {playback_rate} is out of range, clamping it to {MAX_PLAYBACK_RATE}");
+    }
+
+    playback_rate.clamp(-MAX_PLAYBACK_RATE, MAX_PLAYBACK_RATE)
+}
+
+/// Stereo panning of a sound
+///
+/// Kira uses `0` for hard left, `0.5` for center, and `1` for hard right. Plain `f64` values
+/// are converted in that convention. Use [`Panning::from_signed`] for the common convention from
+/// `-1` (hard left) to `1` (hard right).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Reflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[reflect(Debug, PartialEq, Default)]
+pub struct Panning(f64);
+
+impl Panning {
+    /// Hard left
+    pub const LEFT: Panning = Panning(0.);
+    /// Center
+    pub const CENTER: Panning = Panning(0.5);
+    /// Hard right
+    pub const RIGHT: Panning = Panning(1.);
+
+    /// Create a panning from `0` (hard left) to `1` (hard right)
+    pub fn from_unit(panning: f64) -> Self {
+        Panning(panning.clamp(0., 1.))
+    }
+
+    /// Create a panning from `-1` (hard left) to `1` (hard right)
+    pub fn from_signed(pan: f64) -> Self {
+        Panning::from_unit((pan + 1.) / 2.)
+    }
+
+    /// The panning from `0` (hard left) to `1` (hard right)
+    pub fn as_unit(self) -> f64 {
+        self.0
+    }
+
+    /// The panning from `-1` (hard left) to `1` (hard right)
+    pub fn as_signed(self) -> f64 {
+        self.0 * 2. - 1.
+    }
+
+    /// Combine two pannings by adding up their offsets from the center
+    ///
+    /// This is how the panning of a channel and the panning of a sound played in it are combined.
+    pub fn combine(self, other: Panning) -> Self {
+        Panning::from_signed(self.as_signed() + other.as_signed())
+    }
 }
 
+impl Default for Panning {
+    fn default() -> Self {
+        Panning::CENTER
+    }
+}
+
+impl From<f64> for Panning {
+    fn from(panning: f64) -> Self {
+        Panning::from_unit(panning)
+    }
+}
+
+/// Pan the sound, combining the panning with any fixed panning already in the settings
+pub(crate) fn combine_panning(settings: &mut StaticSoundSettings, panning: Panning) {
+    let panning = match settings.panning {
+        Value::Fixed(current) => Panning::from_unit(current).combine(panning),
+        _ => panning,
+    };
+    settings.panning = Value::Fixed(panning.as_unit());
+}
+
+/// How a [`Panning`] maps to the gains of the left and right speaker
+///
+/// Both laws keep center-panned sounds at their volume.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[reflect(Debug, PartialEq, Default)]
+pub enum PanLaw {
+    /// Keep the overall power constant, so sounds are equally loud at any panning
+    ///
+    /// A hard-panned sound plays 3 dB louder on its side than on each side when centered.
+    #[default]
+    ConstantPower,
+    /// Change the gains of both sides linearly with the panning
+    ///
+    /// A hard-panned sound plays 6 dB louder on its side than on each side when centered,
+    /// so panned sounds stand out more.
+    Linear,
+}
+
+impl PanLaw {
+    /// The panning to send to kira, which pans with constant power, and the volume factor to get this law
+    pub(crate) fn backend_panning(self, panning: Panning) -> (f64, f64) {
+        match self {
+            PanLaw::ConstantPower => (panning.as_unit(), 1.),
+            PanLaw::Linear => {
+                let right = panning.as_unit();
+                let left = 1. - right;
+                let power = right * right + left * left;
+
+                (right * right / power, (2. * power).sqrt())
+            }
+        }
+    }
+}
+
+/// Exponent of the power curve approximating the sine curve of [`AudioEasing::EqualPower`]
+///
+/// Kira has no custom easings. `1 - (1 - x)^1.744` stays within `0.014` of `sin(x * PI / 2)`.
+const EQUAL_POWER_EXPONENT: f64 = 1.744;
+
 /// Different kinds of easing for fade-in and fade-out
-pub type AudioEasing = kira::tween::Easing;
+///
+/// All easings except [`EqualPower`](AudioEasing::EqualPower) match the kira easings of the
+/// same name.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum AudioEasing {
+    /// Maintains a constant speed for the duration of the tween
+    #[default]
+    Linear,
+    /// Starts slow and speeds up, a higher value speeds up more dramatically
+    InPowi(i32),
+    /// Starts fast and slows down, a higher value slows down more dramatically
+    OutPowi(i32),
+    /// Starts slow, speeds up, and slows down again, a higher value has more dramatic changes
+    InOutPowi(i32),
+    /// Like [`InPowi`](AudioEasing::InPowi), but with a float value at a higher CPU cost
+    InPowf(f64),
+    /// Like [`OutPowi`](AudioEasing::OutPowi), but with a float value at a higher CPU cost
+    OutPowf(f64),
+    /// Like [`InOutPowi`](AudioEasing::InOutPowi), but with a float value at a higher CPU cost
+    InOutPowf(f64),
+    /// Keeps the summed power of a fade-in and a fade-out constant, for crossfades
+    ///
+    /// Linear fades dip in perceived loudness in the middle of a crossfade. With this easing,
+    /// fade-ins follow a sine curve and fade-outs a cosine curve in amplitude, so both sounds
+    /// are at about -3 dB halfway through. It applies to fade-ins of played sounds and to
+    /// stopping instances; other transitions treat it like [`Linear`](AudioEasing::Linear).
+    EqualPower,
+}
+
+/// Easings added to kira in the future are converted to [`AudioEasing::Linear`]
+impl From<kira::tween::Easing> for AudioEasing {
+    fn from(easing: kira::tween::Easing) -> Self {
+        match easing {
+            kira::tween::Easing::Linear => AudioEasing::Linear,
+            kira::tween::Easing::InPowi(power) => AudioEasing::InPowi(power),
+            kira::tween::Easing::OutPowi(power) => AudioEasing::OutPowi(power),
+            kira::tween::Easing::InOutPowi(power) => AudioEasing::InOutPowi(power),
+            kira::tween::Easing::InPowf(power) => AudioEasing::InPowf(power),
+            kira::tween::Easing::OutPowf(power) => AudioEasing::OutPowf(power),
+            kira::tween::Easing::InOutPowf(power) => AudioEasing::InOutPowf(power),
+            _ => AudioEasing::Linear,
+        }
+    }
+}
+
+impl From<AudioEasing> for kira::tween::Easing {
+    fn from(easing: AudioEasing) -> Self {
+        match easing {
+            AudioEasing::Linear | AudioEasing::EqualPower => kira::tween::Easing::Linear,
+            AudioEasing::InPowi(power) => kira::tween::Easing::InPowi(power),
+            AudioEasing::OutPowi(power) => kira::tween::Easing::OutPowi(power),
+            AudioEasing::InOutPowi(power) => kira::tween::Easing::InOutPowi(power),
+            AudioEasing::InPowf(power) => kira::tween::Easing::InPowf(power),
+            AudioEasing::OutPowf(power) => kira::tween::Easing::OutPowf(power),
+            AudioEasing::InOutPowf(power) => kira::tween::Easing::InOutPowf(power),
+        }
+    }
+}
 
-#[derive(Clone)]
 /// A tween for audio transitions
 ///
-/// Use the default for almost instantaneous transitions without audio artifacts
+/// Use the default for almost instantaneous transitions without audio artifacts.
+/// With the `serialize` feature, tweens can be serialized, for example to configure fades in
+/// `ron` files. Tweens are reflected as opaque values.
+#[derive(Clone, Debug, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(Serialize, Deserialize),
+    reflect_value(Debug, Default, Serialize, Deserialize)
+)]
+#[cfg_attr(not(feature = "serialize"), reflect_value(Debug, Default))]
 pub struct AudioTween {
     duration: Duration,
     easing: AudioEasing,
@@ -68,6 +368,49 @@ impl AudioTween {
         }
     }
 
+    /// Create a new [equal power](AudioEasing::EqualPower) tween with the given duration
+    ///
+    /// Use it for crossfades, for example with [`AudioControl::crossfade_to`](crate::AudioControl::crossfade_to).
+    pub const fn equal_power(duration: Duration) -> Self {
+        AudioTween {
+            duration,
+            easing: AudioEasing::EqualPower,
+        }
+    }
+
+    /// The duration of the tween
+    pub const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The easing of the tween
+    pub const fn easing(&self) -> AudioEasing {
+        self.easing
+    }
+
+    /// Returns `true` for tweens with [`AudioEasing::EqualPower`]
+    pub(crate) fn is_equal_power(&self) -> bool {
+        self.easing == AudioEasing::EqualPower
+    }
+
+    /// Kira tween fading the amplitude of an equal power fade-in
+    pub(crate) fn equal_power_fade_in(&self) -> kira::tween::Tween {
+        kira::tween::Tween {
+            duration: self.duration,
+            easing: kira::tween::Easing::OutPowf(EQUAL_POWER_EXPONENT),
+            ..default()
+        }
+    }
+
+    /// Kira tween fading the amplitude of an equal power fade-out
+    pub(crate) fn equal_power_fade_out(&self) -> kira::tween::Tween {
+        kira::tween::Tween {
+            duration: self.duration,
+            easing: kira::tween::Easing::InPowf(EQUAL_POWER_EXPONENT),
+            ..default()
+        }
+    }
+
     /// Set an easing for the tween
     pub const fn with_easing(mut self, easing: AudioEasing) -> Self {
         self.easing = easing;
@@ -99,65 +442,152 @@ impl From<&AudioTween> for kira::tween::Tween {
     fn from(tween: &AudioTween) -> Self {
         kira::tween::Tween {
             duration: tween.duration,
-            easing: tween.easing,
+            easing: tween.easing.into(),
             ..default()
         }
     }
 }
 
 impl PartialSoundSettings {
-    pub(crate) fn apply(&self, sound: &mut StaticSoundData) {
+    pub(crate) fn apply(&self, settings: &mut StaticSoundSettings) {
         if let Some(loop_start) = self.loop_start {
-            sound
-                .settings
+            settings
                 .loop_region
                 .get_or_insert_with(Default::default)
                 .start = loop_start.into();
         }
         if let Some(loop_end) = self.loop_end {
-            sound
-                .settings
+            settings
                 .loop_region
                 .get_or_insert_with(Default::default)
                 .end = EndPosition::Custom(loop_end.into());
         }
         if let Some(volume) = self.volume {
-            if let Value::Fixed(channel_volume) = sound.settings.volume {
-                sound.settings.volume =
+            if let Value::Fixed(channel_volume) = settings.volume {
+                settings.volume =
                     Value::Fixed((volume.as_amplitude() * channel_volume.as_amplitude()).into());
             } else {
-                sound.settings.volume = Value::Fixed(volume);
+                settings.volume = Value::Fixed(volume);
             }
         }
         if let Some(playback_rate) = self.playback_rate {
-            sound.settings.playback_rate = playback_rate.into();
+            settings.playback_rate = playback_rate.into();
         }
         if let Some(start) = self.start_position {
-            sound.settings.playback_region.start = start.into();
+            settings.playback_region.start = start.into();
         }
         if let Some(end) = self.end_position {
-            sound.settings.playback_region.end = EndPosition::Custom(end.into());
+            settings.playback_region.end = EndPosition::Custom(end.into());
         }
         if let Some(panning) = self.panning {
-            sound.settings.panning = Value::Fixed(panning);
+            combine_panning(settings, panning);
         }
         if let Some(reverse) = self.reverse {
-            sound.settings.reverse = reverse;
+            settings.reverse = reverse;
+        }
+        // equal power fade-ins are applied to the volume of the instance
+        if let Some(fade_in) = self.fade_in.as_ref().filter(|fade| !fade.is_equal_power()) {
+            settings.fade_in_tween = Some(fade_in.into());
+        }
+    }
+
+    /// Settings with the pitch and volume variations rolled, if there are any
+    ///
+    /// The variations are folded into the playback rate and volume, so restarts of the same
+    /// instance keep the rolled values.
+    pub(crate) fn resolve_variations(&self) -> Option<PartialSoundSettings> {
+        if self.pitch_variation.is_none() && self.volume_variation.is_none() {
+            return None;
         }
-        if let Some(AudioTween { duration, easing }) = self.fade_in {
-            sound.settings.fade_in_tween = Some(kira::tween::Tween {
-                duration,
-                easing,
-                ..default()
-            });
+        let mut settings = self.clone();
+        if let Some(bounds) = settings.pitch_variation.take() {
+            let playback_rate = settings.playback_rate.unwrap_or(1.) * random_factor(bounds);
+            settings.playback_rate = Some(valid_playback_rate(playback_rate));
         }
+        if let Some(bounds) = settings.volume_variation.take() {
+            let volume = settings.volume.map_or(1., |volume| volume.as_amplitude());
+            settings.volume = Some(Volume::Amplitude(volume * random_factor(bounds)));
+        }
+
+        Some(settings)
+    }
+}
+
+/// The bounds of a range of random factors, unbounded sides default to `1`
+fn variation_bounds(range: impl RangeBounds<f64>) -> (f64, f64) {
+    let bound = |bound: Bound<&f64>| match bound {
+        Bound::Included(factor) | Bound::Excluded(factor) => factor.max(0.),
+        Bound::Unbounded => 1.,
+    };
+
+    (bound(range.start_bound()), bound(range.end_bound()))
+}
+
+fn random_factor((min, max): (f64, f64)) -> f64 {
+    min + fastrand::f64() * (max - min)
+}
+
+/// The start and optional end of a range of start positions
+fn random_start_bounds(range: impl RangeBounds<f64>) -> (f64, Option<f64>) {
+    let start = match range.start_bound() {
+        Bound::Included(start) | Bound::Excluded(start) => start.max(0.),
+        Bound::Unbounded => 0.,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) | Bound::Excluded(end) => Some(*end),
+        Bound::Unbounded => None,
+    };
+
+    (start, end)
+}
+
+/// Handle to any kind of playable audio source
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AudioSourceHandle {
+    Static(Handle<AudioSource>),
+    #[cfg(not(target_arch = "wasm32"))]
+    Streaming(Handle<StreamingAudioSource>),
+}
+
+impl From<Handle<AudioSource>> for AudioSourceHandle {
+    fn from(handle: Handle<AudioSource>) -> Self {
+        AudioSourceHandle::Static(handle)
+    }
+}
+
+impl AudioSourceHandle {
+    /// Asset path of the source and whether it is streamed
+    pub(crate) fn path(&self) -> Option<(&AssetPath<'static>, bool)> {
+        match self {
+            AudioSourceHandle::Static(handle) => handle.path().map(|path| (path, false)),
+            #[cfg(not(target_arch = "wasm32"))]
+            AudioSourceHandle::Streaming(handle) => handle.path().map(|path| (path, true)),
+        }
+    }
+
+    pub(crate) fn id(&self) -> UntypedAssetId {
+        match self {
+            AudioSourceHandle::Static(handle) => handle.id().untyped(),
+            #[cfg(not(target_arch = "wasm32"))]
+            AudioSourceHandle::Streaming(handle) => handle.id().untyped(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<Handle<StreamingAudioSource>> for AudioSourceHandle {
+    fn from(handle: Handle<StreamingAudioSource>) -> Self {
+        AudioSourceHandle::Streaming(handle)
     }
 }
 
 pub struct PlayAudioSettings {
     pub(crate) instance_handle: Handle<AudioInstance>,
-    pub(crate) source: Handle<AudioSource>,
+    pub(crate) source: AudioSourceHandle,
     pub(crate) settings: PartialSoundSettings,
+    pub(crate) condition: Option<PlayCondition>,
+    pub(crate) chain: Vec<ChainedSound>,
+    pub(crate) intro: Option<IntroSound>,
 }
 
 impl<'a> From<&mut PlayAudioCommand<'a>> for PlayAudioSettings {
@@ -166,37 +596,94 @@ impl<'a> From<&mut PlayAudioCommand<'a>> for PlayAudioSettings {
             instance_handle: command.instance_handle.clone(),
             source: command.source.clone(),
             settings: command.settings.clone(),
+            condition: command.condition.take(),
+            chain: std::mem::take(&mut command.chain),
+            intro: command.intro.take(),
         }
     }
 }
 
 /// A command for interacting with playing sound.
+///
+/// By default, the command is queued when it is dropped, which usually happens at the end of the
+/// statement calling [`AudioControl::play`](crate::AudioControl::play). If you store the command
+/// to configure it later, call [`queue`](Self::queue) once it is ready, or switch to
+/// [`PlayCommandQueueing::Explicit`](crate::PlayCommandQueueing::Explicit).
 pub struct PlayAudioCommand<'a> {
     pub(crate) instance_handle: Handle<AudioInstance>,
-    pub(crate) source: Handle<AudioSource>,
+    pub(crate) source: AudioSourceHandle,
     pub(crate) settings: PartialSoundSettings,
+    pub(crate) condition: Option<PlayCondition>,
+    pub(crate) chain: Vec<ChainedSound>,
+    pub(crate) intro: Option<IntroSound>,
     pub(crate) que: &'a dyn AudioCommandQue,
+    queued: bool,
+    explicit: bool,
 }
 
 impl<'a> Drop for PlayAudioCommand<'a> {
     fn drop(&mut self) {
-        self.que.que(AudioCommand::Play(self.into()));
+        if self.queued {
+            return;
+        }
+        if self.explicit {
+            warn!(
+                "A play command was dropped without calling `queue`, so the sound will not play. \
+                Play commands are queued explicitly because of `PlayCommandQueueing::Explicit`."
+            );
+            return;
+        }
+        self.que.que(AudioCommand::Play(Box::new(self.into())));
     }
 }
 
 impl<'a> PlayAudioCommand<'a> {
-    pub(crate) fn new(source: Handle<AudioSource>, que: &'a dyn AudioCommandQue) -> Self {
+    pub(crate) fn new(source: impl Into<AudioSourceHandle>, que: &'a dyn AudioCommandQue) -> Self {
         let asset_id = AssetId::from(Uuid::new_v4());
         Self {
             instance_handle: Handle::<AudioInstance>::Weak(asset_id),
-            source,
+            source: source.into(),
             settings: PartialSoundSettings::default(),
+            condition: None,
+            chain: Vec::new(),
+            intro: None,
             que,
+            queued: false,
+            explicit: que.explicit_queueing(),
+        }
+    }
+
+    /// Queue the command now instead of when it is dropped.
+    ///
+    /// Changing the command after it was queued has no effect. In debug builds, doing so logs
+    /// a warning.
+    pub fn queue(&mut self) -> Handle<AudioInstance> {
+        if self.queued {
+            warn!("The play command was already queued");
+        } else {
+            self.queued = true;
+            self.que.que(AudioCommand::Play(Box::new(self.into())));
+        }
+
+        self.instance_handle.clone()
+    }
+
+    /// Returns `true` if the command was already queued with [`queue`](Self::queue)
+    pub fn is_queued(&self) -> bool {
+        self.queued
+    }
+
+    /// Warn about changes to a command that was already sent to the audio backend
+    fn check_not_queued(&self) {
+        #[cfg(debug_assertions)]
+        if self.queued {
+            warn!("A play command was changed after it was queued. The change has no effect.");
         }
     }
 
     /// Loop the playing sound.
     pub fn looped(&mut self) -> &mut Self {
+        self.check_not_queued();
         self.settings.loop_start = Some(0.0);
 
         self
@@ -204,6 +691,7 @@ impl<'a> PlayAudioCommand<'a> {
 
     /// Start the sound paused.
     pub fn paused(&mut self) -> &mut Self {
+        self.check_not_queued();
         self.settings.paused = true;
 
         self
@@ -211,6 +699,7 @@ impl<'a> PlayAudioCommand<'a> {
 
     /// Loop the playing sound, starting from the given position in seconds.
     pub fn loop_from(&mut self, loop_start_position: f64) -> &mut Self {
+        self.check_not_queued();
         self.settings.loop_start = Some(loop_start_position);
 
         self
@@ -218,6 +707,7 @@ impl<'a> PlayAudioCommand<'a> {
 
     /// Loop the playing sound, ending at the given position in seconds.
     pub fn loop_until(&mut self, loop_end_position: f64) -> &mut Self {
+        self.check_not_queued();
         self.settings.loop_end = Some(loop_end_position);
 
         self
@@ -225,27 +715,99 @@ impl<'a> PlayAudioCommand<'a> {
 
     /// Set the volume of the sound.
     pub fn with_volume(&mut self, volume: impl Into<Volume>) -> &mut Self {
+        self.check_not_queued();
         self.settings.volume = Some(volume.into());
 
         self
     }
 
+    /// Set the volume of the sound in decibels.
+    ///
+    /// `0` dB is the original volume. The volume is combined with the volume of the channel, so
+    /// `-6` dB on a channel at `-6` dB plays the sound at `-12` dB.
+    pub fn with_volume_db(&mut self, decibels: f64) -> &mut Self {
+        self.with_volume(Volume::Decibels(decibels))
+    }
+
     /// Set the playback rate of the sound.
+    ///
+    /// Negative rates play the sound backwards. The rate is clamped to
+    /// [`MAX_PLAYBACK_RATE`] in either direction.
     pub fn with_playback_rate(&mut self, playback_rate: f64) -> &mut Self {
-        self.settings.playback_rate = Some(playback_rate);
+        self.check_not_queued();
+        self.settings.playback_rate = Some(valid_playback_rate(playback_rate));
+
+        self
+    }
+
+    /// Multiply the playback rate by a random factor from the range every time the sound plays.
+    ///
+    /// Use this to keep sounds that play often, like footsteps or gun shots, from sounding
+    /// repetitive. The factor is rolled when the sound starts and combined with the playback rate.
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// fn shoot(audio: Res<Audio>, asset_server: Res<AssetServer>) {
+    ///     audio
+    ///         .play(asset_server.load("sounds/shot.ogg"))
+    ///         .with_pitch_variation(0.9..1.1)
+    ///         .with_volume_variation(0.8..=1.);
+    /// }
+    /// ```
+    pub fn with_pitch_variation(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.check_not_queued();
+        self.settings.pitch_variation = Some(variation_bounds(range));
+
+        self
+    }
+
+    /// Multiply the volume by a random factor from the range every time the sound plays.
+    ///
+    /// See [`with_pitch_variation`](Self::with_pitch_variation).
+    pub fn with_volume_variation(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.check_not_queued();
+        self.settings.volume_variation = Some(variation_bounds(range));
 
         self
     }
 
     /// Start the sound from the given position in seconds.
     pub fn start_from(&mut self, start_position: f64) -> &mut Self {
+        self.check_not_queued();
         self.settings.start_position = Some(start_position);
+        self.settings.random_start = None;
+
+        self
+    }
+
+    /// Start the sound from a random position in the given range of seconds.
+    ///
+    /// Without an end, the range reaches to the end of the sound. Use this to keep several copies
+    /// of a looped ambience from playing in sync. This replaces any previous
+    /// [`start_from`](Self::start_from).
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// fn start_machines(audio: Res<Audio>, asset_server: Res<AssetServer>) {
+    ///     for _ in 0..4 {
+    ///         audio
+    ///             .play(asset_server.load("sounds/machine.ogg"))
+    ///             .looped()
+    ///             .start_from_random(..);
+    ///     }
+    /// }
+    /// ```
+    pub fn start_from_random(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.check_not_queued();
+        self.settings.random_start = Some(random_start_bounds(range));
+        self.settings.start_position = None;
 
         self
     }
 
     /// End the sound at the given position in seconds.
     pub fn end_at(&mut self, end_position: f64) -> &mut Self {
+        self.check_not_queued();
         self.settings.end_position = Some(end_position);
 
         self
@@ -256,22 +818,47 @@ impl<'a> PlayAudioCommand<'a> {
     /// The default value is 0.5.
     /// Values up to 1.0 pan to the right,
     /// while values down to 0.0 pan to the left.
-    pub fn with_panning(&mut self, panning: f64) -> &mut Self {
-        self.settings.panning = Some(panning);
+    /// The panning is combined with the panning of the channel, see [`Panning::combine`].
+    pub fn with_panning(&mut self, panning: impl Into<Panning>) -> &mut Self {
+        self.check_not_queued();
+        self.settings.panning = Some(panning.into());
 
         self
     }
 
+    /// Set the panning of the sound from `-1` (hard left) to `1` (hard right).
+    ///
+    /// See [`with_panning`](Self::with_panning) for kira's `0` to `1` convention.
+    pub fn with_pan(&mut self, pan: f64) -> &mut Self {
+        self.with_panning(Panning::from_signed(pan))
+    }
+
     /// Reverse the playing sound.
     pub fn reverse(&mut self) -> &mut Self {
+        self.check_not_queued();
         let current = self.settings.reverse.unwrap_or(false);
         self.settings.reverse = Some(!current);
 
         self
     }
 
+    /// Mix the sound down to mono before playing it.
+    ///
+    /// Panning a stereo sound turns down one of its sides, so spatial audio works best with mono
+    /// sounds. The mono version of each source is computed once. Sounds played through
+    /// [`AudioCommandsExt`](crate::AudioCommandsExt) on an entity are mixed down automatically,
+    /// unless the entity has a [`KeepStereo`](crate::prelude::KeepStereo) component. Streaming
+    /// sounds are always played as they are.
+    pub fn mono(&mut self) -> &mut Self {
+        self.check_not_queued();
+        self.settings.mono = true;
+
+        self
+    }
+
     /// Set how long will the sound fade in linearly.
     pub fn linear_fade_in(&mut self, duration: Duration) -> &mut Self {
+        self.check_not_queued();
         self.settings.fade_in = Some(AudioTween::linear(duration));
 
         self
@@ -280,37 +867,501 @@ impl<'a> PlayAudioCommand<'a> {
     /// Set how will the sound fade in,
     /// given its duration and easing.
     pub fn fade_in(&mut self, tween: AudioTween) -> &mut Self {
+        self.check_not_queued();
         self.settings.fade_in = Some(tween);
 
         self
     }
 
+    /// Assign the sound to a category for rate limiting.
+    ///
+    /// See [`SoundRateLimits`](crate::SoundRateLimits) for configuring limits per category.
+    pub fn with_category(&mut self, category: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.check_not_queued();
+        self.settings.category = Some(category.into());
+
+        self
+    }
+
+    /// Stop the looped sound while it is silent and restart it once it is audible again.
+    ///
+    /// After the sound's volume, including the volume of its channel's mixer track, stayed at
+    /// zero for the given duration, the sound is stopped to free its voice. It restarts at the
+    /// position it would have reached, so it sounds like it never stopped. This is useful for
+    /// looped ambience that is far away or ducked. While stopped, the instance reports
+    /// [`PlaybackState::Playing`] and only volume changes and stopping have an effect.
+    ///
+    /// Only works with looped sounds that are not streamed.
+    pub fn stop_when_silent(&mut self, after: Duration) -> &mut Self {
+        self.check_not_queued();
+        self.settings.stop_when_silent = Some(after);
+
+        self
+    }
+
+    /// Set the priority of the sound for the global voice cap.
+    ///
+    /// When more sounds play than
+    /// [`AudioSettings::max_audible_instances`](crate::AudioSettings::max_audible_instances)
+    /// allows, sounds with a lower priority are stopped first, and quieter sounds before louder
+    /// ones with the same priority. The default priority is `0`.
+    pub fn with_priority(&mut self, priority: i32) -> &mut Self {
+        self.check_not_queued();
+        self.settings.priority = priority;
+
+        self
+    }
+
+    /// Start the sound after the given delay.
+    ///
+    /// The delay is scheduled in the audio backend, so no timer system is needed. Until the sound
+    /// starts, its state is [`PlaybackState::Queued`]. This replaces any previous
+    /// [`start_at_tick`](Self::start_at_tick).
+    pub fn delayed(&mut self, delay: Duration) -> &mut Self {
+        self.check_not_queued();
+        self.settings.start = Some(ScheduledStart::Delay(delay));
+
+        self
+    }
+
+    /// Start the sound when the given clock reaches the given tick.
+    ///
+    /// If the clock already passed the tick, the sound starts right away. Until the sound
+    /// starts, its state is [`PlaybackState::Queued`]. This replaces any previous
+    /// [`delayed`](Self::delayed).
+    /// See [`AudioClock`] for an example.
+    pub fn start_at_tick(&mut self, clock: &Handle<AudioClock>, tick: u64) -> &mut Self {
+        self.check_not_queued();
+        self.settings.start = Some(ScheduledStart::Tick(clock.id(), tick));
+
+        self
+    }
+
+    /// Defer playing the sound until the given condition is met.
+    ///
+    /// The condition is checked every frame before the command is executed. It can be a closure
+    /// or a [`PlaySignal`] that is fired from a system.
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// #[derive(Resource)]
+    /// struct BossIntroFinished(PlaySignal);
+    ///
+    /// fn start_boss_music(
+    ///     mut commands: Commands,
+    ///     audio: Res<Audio>,
+    ///     asset_server: Res<AssetServer>,
+    /// ) {
+    ///     let intro_finished = PlaySignal::default();
+    ///     audio
+    ///         .play(asset_server.load("sounds/boss.ogg"))
+    ///         .play_when(intro_finished.clone());
+    ///     commands.insert_resource(BossIntroFinished(intro_finished));
+    /// }
+    ///
+    /// fn finish_intro(intro_finished: Res<BossIntroFinished>) {
+    ///     intro_finished.0.fire();
+    /// }
+    /// ```
+    pub fn play_when(&mut self, condition: impl Into<PlayCondition>) -> &mut Self {
+        self.check_not_queued();
+        self.condition = Some(condition.into());
+
+        self
+    }
+
+    /// Play another sound on the same channel once this one finishes.
+    ///
+    /// Calling this multiple times plays the sounds one after another. Stopping the channel cancels
+    /// all chained sounds that did not start yet. Looped sounds never finish, so nothing chained to
+    /// them will play. Pass a [`ChainTransition`] alone, or [`ChainedSoundSettings`] to also loop,
+    /// fade in, or set the volume of the chained sound.
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// # use std::time::Duration;
+    /// fn play_music(audio: Res<Audio>, asset_server: Res<AssetServer>) {
+    ///     audio
+    ///         .play(asset_server.load("sounds/stinger.ogg"))
+    ///         .then_play(
+    ///             asset_server.load("sounds/theme.ogg"),
+    ///             ChainedSoundSettings::new(ChainTransition::Overlap(Duration::from_millis(500)))
+    ///                 .looped()
+    ///                 .with_volume(0.5),
+    ///         );
+    /// }
+    /// ```
+    pub fn then_play(
+        &mut self,
+        source: Handle<AudioSource>,
+        settings: impl Into<ChainedSoundSettings>,
+    ) -> &mut Self {
+        self.check_not_queued();
+        let ChainedSoundSettings {
+            transition,
+            settings,
+        } = settings.into();
+        self.chain.push(ChainedSound {
+            source,
+            transition,
+            settings,
+            instance_handle: Handle::<AudioInstance>::Weak(AssetId::from(Uuid::new_v4())),
+        });
+
+        self
+    }
+
     /// Get the handle of the audio instance.
     pub fn handle(&mut self) -> Handle<AudioInstance> {
         self.instance_handle.clone()
     }
 }
 
+/// A command playing several sounds with the same settings at once
+///
+/// The sounds are queued as a single command when it is dropped. They start in the same frame,
+/// once all of them are loaded. See [`AudioControl::play_many`](crate::AudioControl::play_many).
+pub struct PlayManyCommand<'a> {
+    pub(crate) sounds: Vec<(Handle<AudioSource>, Handle<AudioInstance>)>,
+    pub(crate) settings: PartialSoundSettings,
+    pub(crate) que: &'a dyn AudioCommandQue,
+}
+
+impl<'a> Drop for PlayManyCommand<'a> {
+    fn drop(&mut self) {
+        if self.sounds.is_empty() {
+            return;
+        }
+        let sounds = self
+            .sounds
+            .drain(..)
+            .map(|(source, instance_handle)| PlayAudioSettings {
+                instance_handle,
+                source: source.into(),
+                settings: self.settings.clone(),
+                condition: None,
+                chain: Vec::new(),
+                intro: None,
+            })
+            .collect();
+        self.que.que(AudioCommand::PlayMany(sounds));
+    }
+}
+
+impl<'a> PlayManyCommand<'a> {
+    pub(crate) fn new(
+        sources: impl IntoIterator<Item = Handle<AudioSource>>,
+        que: &'a dyn AudioCommandQue,
+    ) -> Self {
+        Self {
+            sounds: sources
+                .into_iter()
+                .map(|source| {
+                    let asset_id = AssetId::from(Uuid::new_v4());
+                    (source, Handle::<AudioInstance>::Weak(asset_id))
+                })
+                .collect(),
+            settings: PartialSoundSettings::default(),
+            que,
+        }
+    }
+
+    /// Loop the playing sounds.
+    pub fn looped(&mut self) -> &mut Self {
+        self.settings.loop_start = Some(0.0);
+
+        self
+    }
+
+    /// Start the sounds paused.
+    pub fn paused(&mut self) -> &mut Self {
+        self.settings.paused = true;
+
+        self
+    }
+
+    /// Set the volume of every sound.
+    pub fn with_volume(&mut self, volume: impl Into<Volume>) -> &mut Self {
+        self.settings.volume = Some(volume.into());
+
+        self
+    }
+
+    /// Set the volume of every sound in decibels.
+    pub fn with_volume_db(&mut self, decibels: f64) -> &mut Self {
+        self.with_volume(Volume::Decibels(decibels))
+    }
+
+    /// Set the playback rate of every sound.
+    ///
+    /// See [`PlayAudioCommand::with_playback_rate`].
+    pub fn with_playback_rate(&mut self, playback_rate: f64) -> &mut Self {
+        self.settings.playback_rate = Some(valid_playback_rate(playback_rate));
+
+        self
+    }
+
+    /// Multiply the playback rate of every sound by its own random factor from the range.
+    ///
+    /// See [`PlayAudioCommand::with_pitch_variation`].
+    pub fn with_pitch_variation(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.settings.pitch_variation = Some(variation_bounds(range));
+
+        self
+    }
+
+    /// Multiply the volume of every sound by its own random factor from the range.
+    ///
+    /// See [`PlayAudioCommand::with_volume_variation`].
+    pub fn with_volume_variation(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.settings.volume_variation = Some(variation_bounds(range));
+
+        self
+    }
+
+    /// Set the panning of every sound.
+    ///
+    /// See [`PlayAudioCommand::with_panning`].
+    pub fn with_panning(&mut self, panning: impl Into<Panning>) -> &mut Self {
+        self.settings.panning = Some(panning.into());
+
+        self
+    }
+
+    /// Start every sound from the given position in seconds.
+    pub fn start_from(&mut self, start_position: f64) -> &mut Self {
+        self.settings.start_position = Some(start_position);
+        self.settings.random_start = None;
+
+        self
+    }
+
+    /// Start every sound from its own random position in the given range of seconds.
+    ///
+    /// See [`PlayAudioCommand::start_from_random`].
+    pub fn start_from_random(&mut self, range: impl RangeBounds<f64>) -> &mut Self {
+        self.settings.random_start = Some(random_start_bounds(range));
+        self.settings.start_position = None;
+
+        self
+    }
+
+    /// Set how will the sounds fade in,
+    /// given its duration and easing.
+    pub fn fade_in(&mut self, tween: AudioTween) -> &mut Self {
+        self.settings.fade_in = Some(tween);
+
+        self
+    }
+
+    /// Assign the sounds to a category for rate limiting.
+    ///
+    /// Every sound counts towards the limit of the category.
+    pub fn with_category(&mut self, category: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.settings.category = Some(category.into());
+
+        self
+    }
+
+    /// Set the priority of the sounds for the global voice cap.
+    ///
+    /// See [`PlayAudioCommand::with_priority`].
+    pub fn with_priority(&mut self, priority: i32) -> &mut Self {
+        self.settings.priority = priority;
+
+        self
+    }
+
+    /// Get the handles of the audio instances in the order of the sources.
+    pub fn handles(&mut self) -> Vec<Handle<AudioInstance>> {
+        self.sounds
+            .iter()
+            .map(|(_, instance_handle)| instance_handle.clone())
+            .collect()
+    }
+}
+
+/// How a chained sound follows the sound before it
+///
+/// See [`PlayAudioCommand::then_play`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ChainTransition {
+    /// Start as soon as the previous sound finished
+    #[default]
+    Immediate,
+    /// Wait for the given duration after the previous sound finished
+    Gap(Duration),
+    /// Start the given duration before the previous sound finishes
+    ///
+    /// The length of streaming sounds is unknown, so after them this behaves like
+    /// [`ChainTransition::Immediate`].
+    Overlap(Duration),
+}
+
+/// How a chained sound starts and plays
+///
+/// See [`PlayAudioCommand::then_play`].
+#[derive(Clone, Default)]
+pub struct ChainedSoundSettings {
+    transition: ChainTransition,
+    settings: PartialSoundSettings,
+}
+
+impl ChainedSoundSettings {
+    /// Follow the previous sound with the given transition
+    pub fn new(transition: ChainTransition) -> Self {
+        ChainedSoundSettings {
+            transition,
+            ..default()
+        }
+    }
+
+    /// Loop the chained sound
+    pub fn looped(mut self) -> Self {
+        self.settings.loop_start = Some(0.0);
+
+        self
+    }
+
+    /// Set the volume of the chained sound
+    pub fn with_volume(mut self, volume: impl Into<Volume>) -> Self {
+        self.settings.volume = Some(volume.into());
+
+        self
+    }
+
+    /// Set the playback rate of the chained sound
+    pub fn with_playback_rate(mut self, playback_rate: f64) -> Self {
+        self.settings.playback_rate = Some(valid_playback_rate(playback_rate));
+
+        self
+    }
+
+    /// Set the panning of the chained sound
+    pub fn with_panning(mut self, panning: impl Into<Panning>) -> Self {
+        self.settings.panning = Some(panning.into());
+
+        self
+    }
+
+    /// Fade in the chained sound
+    pub fn fade_in(mut self, tween: AudioTween) -> Self {
+        self.settings.fade_in = Some(tween);
+
+        self
+    }
+
+    /// Assign the chained sound to a category for rate limiting
+    pub fn with_category(mut self, category: impl Into<Cow<'static, str>>) -> Self {
+        self.settings.category = Some(category.into());
+
+        self
+    }
+}
+
+impl From<ChainTransition> for ChainedSoundSettings {
+    fn from(transition: ChainTransition) -> Self {
+        ChainedSoundSettings::new(transition)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ChainedSound {
+    pub(crate) source: Handle<AudioSource>,
+    pub(crate) transition: ChainTransition,
+    pub(crate) settings: PartialSoundSettings,
+    pub(crate) instance_handle: Handle<AudioInstance>,
+}
+
+/// A sound played once before a looping sound, see [`AudioControl::play_with_intro`](crate::AudioControl::play_with_intro)
+#[derive(Clone)]
+pub(crate) struct IntroSound {
+    pub(crate) source: Handle<AudioSource>,
+    pub(crate) instance_handle: Handle<AudioInstance>,
+}
+
+impl IntroSound {
+    pub(crate) fn new(source: Handle<AudioSource>) -> Self {
+        IntroSound {
+            source,
+            instance_handle: Handle::<AudioInstance>::Weak(AssetId::from(Uuid::new_v4())),
+        }
+    }
+}
+
+/// A condition deciding when a deferred sound starts playing
+///
+/// See [`PlayAudioCommand::play_when`].
+#[derive(Clone)]
+pub struct PlayCondition(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl PlayCondition {
+    /// Create a condition from a closure that is evaluated once per frame
+    pub fn new(condition: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        PlayCondition(Arc::new(condition))
+    }
+
+    pub(crate) fn is_met(&self) -> bool {
+        (self.0)()
+    }
+}
+
+impl<F: Fn() -> bool + Send + Sync + 'static> From<F> for PlayCondition {
+    fn from(condition: F) -> Self {
+        PlayCondition::new(condition)
+    }
+}
+
+impl From<PlaySignal> for PlayCondition {
+    fn from(signal: PlaySignal) -> Self {
+        PlayCondition::new(move || signal.is_fired())
+    }
+}
+
+/// A flag that can be fired from anywhere to start sounds deferred with
+/// [`PlayAudioCommand::play_when`]
+///
+/// Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct PlaySignal(Arc<AtomicBool>);
+
+impl PlaySignal {
+    /// Fire the signal, allowing all sounds waiting on it to play
+    pub fn fire(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Reset the signal, so sounds queued afterwards wait for it to be fired again
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+
+    /// Has the signal been fired?
+    pub fn is_fired(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
 pub(crate) enum TweenCommandKind {
     SetVolume(Volume),
-    SetPanning(f64),
+    SetPanning(Panning),
     SetPlaybackRate(f64),
     Stop,
     Pause,
-    Resume,
+    Resume(ResumeTargets),
 }
 
 impl TweenCommandKind {
-    fn to_command(&self, tween: Option<AudioTween>) -> AudioCommand {
+    fn to_command(&self, tween: Option<AudioTween>, delay: Option<Duration>) -> AudioCommand {
         match self {
             TweenCommandKind::SetVolume(volume) => AudioCommand::SetVolume(*volume, tween),
             TweenCommandKind::SetPanning(panning) => AudioCommand::SetPanning(*panning, tween),
             TweenCommandKind::SetPlaybackRate(playback_rate) => {
                 AudioCommand::SetPlaybackRate(*playback_rate, tween)
             }
-            TweenCommandKind::Stop => AudioCommand::Stop(tween),
-            TweenCommandKind::Pause => AudioCommand::Pause(tween),
-            TweenCommandKind::Resume => AudioCommand::Resume(tween),
+            TweenCommandKind::Stop => AudioCommand::Stop(tween, delay),
+            TweenCommandKind::Pause => AudioCommand::Pause(tween, delay),
+            TweenCommandKind::Resume(targets) => AudioCommand::Resume(tween, *targets),
         }
     }
 }
@@ -319,18 +1370,22 @@ impl TweenCommandKind {
 pub struct FadeIn;
 /// Marker trait for tween commands that are fading out.
 pub struct FadeOut;
+/// Marker trait for resume commands, which are fading in.
+pub struct ResumeFadeIn;
 
 /// A command for interacting with the tweening of the playing sound.
 pub struct TweenCommand<'a, Fade> {
     pub(crate) kind: TweenCommandKind,
     pub(crate) tween: Option<AudioTween>,
+    pub(crate) delay: Option<Duration>,
     pub(crate) que: &'a dyn AudioCommandQue,
     _marker: PhantomData<Fade>,
 }
 
 impl<'a, Fade> Drop for TweenCommand<'a, Fade> {
     fn drop(&mut self) {
-        self.que.que(self.kind.to_command(self.tween.take()));
+        self.que
+            .que(self.kind.to_command(self.tween.take(), self.delay.take()));
     }
 }
 
@@ -339,6 +1394,7 @@ impl<'a, Fade> TweenCommand<'a, Fade> {
         Self {
             kind,
             tween: None,
+            delay: None,
             que,
             _marker: PhantomData::<Fade>,
         }
@@ -362,6 +1418,47 @@ impl<'a> TweenCommand<'a, FadeIn> {
     }
 }
 
+impl<'a> TweenCommand<'a, ResumeFadeIn> {
+    /// Set how long will the sound fade in linearly.
+    pub fn linear_fade_in(&mut self, duration: Duration) -> &mut Self {
+        self.tween = Some(AudioTween::linear(duration));
+
+        self
+    }
+
+    /// Set how will the sound fade in,
+    /// given its duration and easing.
+    pub fn fade_in(&mut self, tween: AudioTween) -> &mut Self {
+        self.tween = Some(tween);
+
+        self
+    }
+
+    /// Only resume paused and pausing sounds
+    ///
+    /// This is the default. Sounds that are fading out to stop keep stopping.
+    pub fn resume_paused_only(&mut self) -> &mut Self {
+        self.kind = TweenCommandKind::Resume(ResumeTargets::Paused);
+
+        self
+    }
+
+    /// Also resume sounds that are stopping, cancelling their fade-out
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// fn cancel_game_over(music: Res<Audio>) {
+    ///     // the music was fading out with `music.stop()`
+    ///     music.resume().resume_all();
+    /// }
+    /// ```
+    pub fn resume_all(&mut self) -> &mut Self {
+        self.kind = TweenCommandKind::Resume(ResumeTargets::All);
+
+        self
+    }
+}
+
 impl<'a> TweenCommand<'a, FadeOut> {
     /// Set how long will the sound fade out linearly.
     pub fn linear_fade_out(&mut self, duration: Duration) -> &mut Self {
@@ -377,6 +1474,28 @@ impl<'a> TweenCommand<'a, FadeOut> {
 
         self
     }
+
+    /// Delay the command by the given duration.
+    ///
+    /// The delay is scheduled in the audio backend, so no timer system is needed. Only sounds that
+    /// are already playing when the command runs are affected. A delayed pause does not pause
+    /// the channel itself, so sounds played during the delay start normally.
+    ///
+    /// During the delay, the affected sounds keep playing, but their state is already
+    /// [`PlaybackState::Stopping`] or [`PlaybackState::Pausing`].
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// # use std::time::Duration;
+    /// fn end_scene(audio: Res<Audio>) {
+    ///     audio.stop().after(Duration::from_secs(2)).linear_fade_out(Duration::from_secs(1));
+    /// }
+    /// ```
+    pub fn after(&mut self, delay: Duration) -> &mut Self {
+        self.delay = Some(delay);
+
+        self
+    }
 }
 
 pub enum AudioCommandResult {
@@ -416,6 +1535,11 @@ pub enum PlaybackState {
 }
 
 impl PlaybackState {
+    /// Returns `true` if both states are the same variant, ignoring their positions
+    pub fn is_same_kind(&self, other: &PlaybackState) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
     /// Get the playback position in seconds
     pub fn position(&self) -> Option<f64> {
         match self {
@@ -426,6 +1550,75 @@ impl PlaybackState {
             | PlaybackState::Stopping { position } => Some(*position),
         }
     }
+
+    /// Get the playback progress through the given source from `0` to `1`
+    ///
+    /// Returns `None` if the instance is queued or stopped.
+    pub fn progress(&self, source: &AudioSource) -> Option<f32> {
+        let duration = source.sound.duration().as_secs_f64();
+        if duration <= 0. {
+            return None;
+        }
+
+        self.position()
+            .map(|position| (position / duration).clamp(0., 1.) as f32)
+    }
+
+    /// Get the time until the instance finishes playing the given source
+    ///
+    /// This assumes a playback rate of `1`. Returns `None` if the instance is queued or stopped,
+    /// or if the source loops and the instance will never finish on its own. Instances looped
+    /// with [`PlayAudioCommand::looped`] report the time remaining in the current loop.
+    pub fn time_remaining(&self, source: &AudioSource) -> Option<Duration> {
+        if source.sound.settings.loop_region.is_some() {
+            return None;
+        }
+        let duration = source.sound.duration().as_secs_f64();
+
+        self.position()
+            .map(|position| Duration::from_secs_f64((duration - position).max(0.)))
+    }
+}
+
+/// Source and playback position of a sound, to continue playing it later
+///
+/// Bookmarks implement `Serialize` and `Deserialize`, so they can be stored with the save game to
+/// continue the soundtrack where the player left off in the last session. Only sounds loaded from
+/// an asset path can be bookmarked.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Radio;
+///
+/// #[derive(Resource)]
+/// struct SaveGame {
+///     radio: Option<PlaybackBookmark>,
+/// }
+///
+/// fn save(radio: Res<AudioChannel<Radio>>, mut save_game: ResMut<SaveGame>) {
+///     save_game.radio = radio.playback_bookmark();
+/// }
+///
+/// fn restore(
+///     radio: Res<AudioChannel<Radio>>,
+///     save_game: Res<SaveGame>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     if let Some(bookmark) = &save_game.radio {
+///         radio.resume_from_bookmark(bookmark, &asset_server).looped();
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackBookmark {
+    /// Asset path of the sound
+    pub source: AssetPath<'static>,
+    /// Playback position in seconds
+    pub position: f64,
+    /// Whether the sound was played with [`play_streaming`](crate::AudioControl::play_streaming)
+    #[serde(default)]
+    pub streaming: bool,
 }
 
 impl From<StaticSoundHandle> for PlaybackState {
@@ -436,8 +1629,13 @@ impl From<StaticSoundHandle> for PlaybackState {
 
 impl From<&StaticSoundHandle> for PlaybackState {
     fn from(sound_handle: &StaticSoundHandle) -> Self {
-        let position = sound_handle.position();
-        match sound_handle.state() {
+        PlaybackState::from_kira(sound_handle.state(), sound_handle.position())
+    }
+}
+
+impl PlaybackState {
+    pub(crate) fn from_kira(state: kira::sound::PlaybackState, position: f64) -> Self {
+        match state {
             kira::sound::PlaybackState::Playing => PlaybackState::Playing { position },
             kira::sound::PlaybackState::Paused => PlaybackState::Paused { position },
             kira::sound::PlaybackState::Stopped => PlaybackState::Stopped,
@@ -472,18 +1670,303 @@ pub trait AudioApp {
     /// struct Background;
     /// ```
     fn add_audio_channel<T: Resource>(&mut self) -> &mut Self;
+
+    /// Add a new audio channel with the given settings to the application
+    ///
+    /// See [`ChannelSettings`] for an example.
+    fn add_audio_channel_with_settings<T: Resource>(
+        &mut self,
+        settings: ChannelSettings,
+    ) -> &mut Self;
+
+    /// Duck the channel `Target` while sounds play on the channel `Trigger`
+    ///
+    /// Both channels need to be added to the app. The ducking is added as a rule to the
+    /// [`DuckingMatrix`] resource. See [`DuckChannel`] for an example.
+    fn add_channel_ducking<Trigger: Resource, Target: Resource>(
+        &mut self,
+        ducking: DuckChannel,
+    ) -> &mut Self;
 }
 
 impl AudioApp for App {
     fn add_audio_channel<T: Resource>(&mut self) -> &mut Self {
-        self.add_systems(
-            PostUpdate,
-            play_audio_channel::<T>.in_set(AudioSystemSet::PlayTypedChannels),
-        )
-        .add_systems(
-            PreUpdate,
-            update_instance_states::<T>.after(AudioSystemSet::InstanceCleanup),
-        )
-        .insert_resource(AudioChannel::<T>::default())
+        self.add_audio_channel_with_settings::<T>(ChannelSettings::default())
+    }
+
+    fn add_audio_channel_with_settings<T: Resource>(
+        &mut self,
+        settings: ChannelSettings,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(TypedChannelNames::default)
+            .0
+            .insert(TypeId::of::<T>(), type_name::<T>());
+        self.add_systems(PreStartup, apply_play_command_queueing::<T>)
+            .add_systems(
+                PostUpdate,
+                play_audio_channel::<T>.in_set(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PreUpdate,
+                update_instance_states::<T>.after(AudioSystemSet::InstanceCleanup),
+            )
+            .add_systems(
+                PostUpdate,
+                run_step_sequencer::<T>
+                    .before(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<StepSequencer<T>>),
+            )
+            .add_systems(
+                PostUpdate,
+                run_audio_playlist::<T>
+                    .before(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<AudioPlaylist<T>>),
+            )
+            .add_systems(
+                PostUpdate,
+                run_dialogue_audio::<T>
+                    .before(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<DialogueAudio<T>>),
+            )
+            .add_systems(
+                PostUpdate,
+                run_layered_music::<T>
+                    .before(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<LayeredMusic<T>>),
+            )
+            .insert_resource(AudioChannel::<T>::with_settings(settings))
+    }
+
+    fn add_channel_ducking<Trigger: Resource, Target: Resource>(
+        &mut self,
+        ducking: DuckChannel,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(DuckingMatrix::default)
+            .add_rule(
+                Channel::typed::<Trigger>(),
+                [Channel::typed::<Target>()],
+                ducking,
+            );
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::channel::AudioControl;
+    use crate::{Audio, AudioPlugin, AudioSettings, DynamicAudioChannels, PlayCommandQueueing};
+    use bevy::asset::AssetPlugin;
+    use bevy::MinimalPlugins;
+    use kira::manager::backend::mock::MockBackend;
+    use kira::sound::static_sound::StaticSoundData;
+
+    fn plop() -> AudioSource {
+        AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn equal_power_fades_keep_the_power_constant() {
+        for step in 0..=100 {
+            let x = step as f64 / 100.;
+            let fade_in = 1. - (1. - x).powf(EQUAL_POWER_EXPONENT);
+            let fade_out = 1. - x.powf(EQUAL_POWER_EXPONENT);
+            assert!((fade_in - (x * std::f64::consts::FRAC_PI_2).sin()).abs() < 0.014);
+            assert!((fade_in * fade_in + fade_out * fade_out - 1.).abs() < 0.03);
+        }
+    }
+
+    #[test]
+    fn decibel_volumes_add_up_with_the_channel_volume() {
+        let mut settings = StaticSoundSettings::default();
+        settings.volume = Value::Fixed(Volume::Decibels(-6.));
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        let mut command = channel.play(Handle::default());
+        command.with_volume_db(-6.);
+        command.settings.apply(&mut settings);
+
+        match settings.volume {
+            Value::Fixed(volume) => assert!((volume.as_decibels() + 12.).abs() < 1e-9),
+            _ => panic!("expected a fixed volume"),
+        }
+    }
+
+    #[test]
+    fn random_start_ranges_default_to_the_whole_sound() {
+        assert_eq!(random_start_bounds(..), (0., None));
+        assert_eq!(random_start_bounds(2.0..), (2., None));
+        assert_eq!(random_start_bounds(-1.0..=3.), (0., Some(3.)));
+
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        let mut command = channel.play(Handle::default());
+        command.start_from(1.).start_from_random(..4.0);
+        assert_eq!(command.settings.start_position, None);
+        assert_eq!(command.settings.random_start, Some((0., Some(4.))));
+    }
+
+    #[test]
+    fn variations_are_rolled_into_rate_and_volume() {
+        assert_eq!(variation_bounds(0.9..1.1), (0.9, 1.1));
+        assert_eq!(variation_bounds(..=1.2), (1., 1.2));
+
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        let mut command = channel.play(Handle::default());
+        command.with_playback_rate(2.).with_volume(0.5);
+        assert!(command.settings.resolve_variations().is_none());
+
+        command
+            .with_pitch_variation(0.9..1.1)
+            .with_volume_variation(0.5..=1.);
+        for _ in 0..100 {
+            let settings = command.settings.resolve_variations().unwrap();
+            assert_eq!(settings.pitch_variation, None);
+            assert_eq!(settings.volume_variation, None);
+            let playback_rate = settings.playback_rate.unwrap();
+            assert!((1.8..=2.2).contains(&playback_rate));
+            let volume = settings.volume.unwrap().as_amplitude();
+            assert!((0.25..=0.5).contains(&volume));
+        }
+    }
+
+    #[test]
+    fn explicitly_queued_commands_are_queued_once() {
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        let handle = {
+            let mut command = channel.play(Handle::default());
+            command.looped();
+            let handle = command.queue();
+            assert!(command.is_queued());
+            command.queue();
+            handle
+        };
+
+        assert_eq!(channel.commands.len(), 1);
+        match channel.commands.read().front() {
+            Some(AudioCommand::Play(settings)) => {
+                assert_eq!(settings.instance_handle, handle);
+                assert_eq!(settings.settings.loop_start, Some(0.));
+            }
+            _ => panic!("expected a play command"),
+        };
+    }
+
+    #[test]
+    fn explicit_commands_are_not_queued_on_drop() {
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        channel.commands.set_queueing(PlayCommandQueueing::Explicit);
+        {
+            let _command = channel.play(Handle::default());
+        }
+
+        assert!(channel.commands.is_empty());
+    }
+
+    #[test]
+    fn play_command_queueing_is_set_per_app() {
+        let app = |play_command_queueing| {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+                .insert_resource(AudioSettings {
+                    play_command_queueing,
+                    ..default()
+                })
+                .add_plugins(AudioPlugin::<MockBackend>::default());
+            app.update();
+            app
+        };
+        let mut explicit = app(PlayCommandQueueing::Explicit);
+        let on_drop = app(PlayCommandQueueing::OnDrop);
+
+        explicit.world().resource::<Audio>().play(Handle::default());
+        on_drop.world().resource::<Audio>().play(Handle::default());
+        explicit
+            .world_mut()
+            .resource_mut::<DynamicAudioChannels>()
+            .create_channel("dynamic")
+            .play(Handle::default());
+
+        assert!(explicit.world().resource::<Audio>().commands.is_empty());
+        assert_eq!(on_drop.world().resource::<Audio>().commands.len(), 1);
+        let dynamic_channels = explicit.world().resource::<DynamicAudioChannels>();
+        assert!(dynamic_channels.channel("dynamic").commands.is_empty());
+    }
+
+    #[test]
+    fn progress_and_time_remaining_use_source_duration() {
+        let source = plop();
+        let duration = source.sound.duration().as_secs_f64();
+        let state = PlaybackState::Playing {
+            position: duration / 4.,
+        };
+
+        assert_eq!(state.progress(&source), Some(0.25));
+        assert_eq!(
+            state.time_remaining(&source),
+            Some(Duration::from_secs_f64(duration * 0.75))
+        );
+        assert_eq!(PlaybackState::Queued.progress(&source), None);
+        assert_eq!(PlaybackState::Stopped.time_remaining(&source), None);
+    }
+
+    #[test]
+    fn looping_source_has_no_time_remaining() {
+        let mut source = plop();
+        source.sound.settings = source.sound.settings.loop_region(..);
+        let state = PlaybackState::Paused { position: 0. };
+
+        assert_eq!(state.progress(&source), Some(0.));
+        assert_eq!(state.time_remaining(&source), None);
+    }
+
+    #[test]
+    fn playback_rates_are_clamped_to_supported_range() {
+        assert_eq!(valid_playback_rate(1.5), 1.5);
+        assert_eq!(valid_playback_rate(-2.), -2.);
+        assert_eq!(valid_playback_rate(1000.), MAX_PLAYBACK_RATE);
+        assert_eq!(valid_playback_rate(-1000.), -MAX_PLAYBACK_RATE);
+        assert_eq!(valid_playback_rate(f64::NAN), 1.);
+        assert_eq!(valid_playback_rate(f64::INFINITY), 1.);
+    }
+
+    #[test]
+    fn panning_converts_between_conventions() {
+        assert_eq!(Panning::from_signed(-1.), Panning::LEFT);
+        assert_eq!(Panning::from_signed(0.), Panning::CENTER);
+        assert_eq!(Panning::from_signed(-0.5).as_unit(), 0.25);
+        assert_eq!(Panning::from(0.75).as_signed(), 0.5);
+        assert_eq!(Panning::from_signed(3.), Panning::RIGHT);
+    }
+
+    #[test]
+    fn panning_combines_offsets_from_center() {
+        assert_eq!(Panning::CENTER.combine(Panning::LEFT), Panning::LEFT);
+        assert_eq!(
+            Panning::from_signed(0.5).combine(Panning::from_signed(-1.)),
+            Panning::from_signed(-0.5)
+        );
+        assert_eq!(Panning::RIGHT.combine(Panning::RIGHT), Panning::RIGHT);
+    }
+
+    #[test]
+    fn linear_pan_law_scales_gains_linearly() {
+        for panning in [0., 0.2, 0.5, 0.9, 1.] {
+            let (backend_panning, gain) =
+                PanLaw::Linear.backend_panning(Panning::from_unit(panning));
+            // kira's constant power panning, normalized to unity gain at the center
+            let left = 2_f64.sqrt() * (1. - backend_panning).sqrt() * gain;
+            let right = 2_f64.sqrt() * backend_panning.sqrt() * gain;
+
+            assert!((left - 2. * (1. - panning)).abs() < 1e-9);
+            assert!((right - 2. * panning).abs() < 1e-9);
+        }
+        assert_eq!(
+            PanLaw::ConstantPower.backend_panning(Panning::from_unit(0.2)),
+            (0.2, 1.)
+        );
     }
 }
@@ -1,557 +1,5124 @@
 //! The internal audio systems and resource
 
-use crate::audio::{map_tween, AudioCommand, AudioCommandResult, AudioTween, PartialSoundSettings};
-use std::any::TypeId;
+#[cfg(feature = "analysis")]
+use crate::analysis::{MainTrackSpectrum, SharedSpectrum, SpectrumSettings, SpectrumTapBuilder};
+use crate::audio::{
+    map_tween, AudioCommand, AudioCommandResult, AudioSourceHandle, AudioTween, ChainTransition,
+    ChainedSound, IntroSound, PartialSoundSettings, PlayAudioSettings, ResumeTargets,
+    ScheduledStart, SeekPosition,
+};
+use std::any::{Any, TypeId};
 
-use crate::backend_settings::AudioSettings;
+use crate::backend_settings::{
+    AppPlayCommandQueueing, AudioSettings, DisabledPlayback, GlobalAudio, RampUpAudio, StartupRamp,
+};
+use crate::beat::{MusicBar, MusicBeat, Tempo};
+use crate::capture::SharedCapture;
 use crate::channel::dynamic::DynamicAudioChannels;
 use crate::channel::typed::AudioChannel;
-use crate::channel::{Channel, ChannelState};
-use crate::instance::AudioInstance;
+use crate::channel::{
+    Channel, ChannelActivity, ChannelSettingsChanged, ChannelState, PendingChannelSettings,
+    PlayCommandsExecuted, VoiceStealing,
+};
+use crate::clock::AudioClock;
+use crate::device::AudioOutputDevice;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::device::{ConfigPreferences, OutputBackend, OutputBackendSettings};
+use crate::instance::{
+    AudioInstance, AudioInstanceStopped, InstanceHandle, LoopCompleted, PlaybackStateChanged,
+};
+use crate::metering::SharedLevels;
+use crate::offline::OfflineBackendSettings;
+use crate::rate_limit::{Exclusivity, SoundRateLimits};
 use crate::source::AudioSource;
-use crate::PlaybackState;
-use bevy::asset::{Assets, Handle};
-use bevy::ecs::change_detection::{NonSendMut, ResMut};
-use bevy::ecs::system::{NonSend, Res, Resource};
-use bevy::ecs::world::{FromWorld, World};
-use bevy::log::{error, warn};
-use kira::manager::backend::{Backend, DefaultBackend};
-use kira::manager::AudioManager;
-use kira::{sound::PlaybackRate, CommandError, Volume};
-use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::StreamingAudioSource;
+use crate::{PanLaw, Panning, PlaybackState};
+use bevy::asset::{AssetId, Assets, Handle, UntypedAssetId};
+use bevy::ecs::change_detection::{DetectChangesMut, NonSendMut, ResMut};
+use bevy::ecs::event::{EventReader, EventWriter};
+use bevy::ecs::system::{Local, NonSend, Res, Resource, SystemId};
+use bevy::ecs::world::World;
+use bevy::log::{debug, error, info, warn};
+use bevy::utils::{HashMap, Instant};
+use kira::clock::{ClockHandle, ClockId, ClockSpeed, ClockTime};
+use kira::dsp::Frame;
+use kira::manager::backend::mock::MockBackendSettings;
+use kira::manager::backend::Backend;
+use kira::manager::error::{AddClockError, AddSubTrackError, PlaySoundError};
+use kira::manager::{AudioManager, AudioManagerSettings};
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+#[cfg(not(target_arch = "wasm32"))]
+use kira::sound::{
+    streaming::{StreamingSoundData, StreamingSoundHandle},
+    FromFileError,
+};
+use kira::track::{TrackBuilder, TrackHandle};
+use kira::tween::{Tween, Value};
+use kira::{sound::PlaybackRate, CommandError, StartTime, Volume};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often creating the audio manager is retried while it is not available
+const MANAGER_RETRY_INTERVAL: Duration = Duration::from_secs(2);
 
 /// Non-send resource that acts as audio output
 ///
 /// This struct holds the [`AudioManager`] to play audio through. It also
 /// keeps track of all audio instance handles and which sounds are playing in which channel.
-pub(crate) struct AudioOutput<B: Backend = DefaultBackend> {
-    manager: Option<AudioManager<B>>,
+pub(crate) struct AudioOutput {
+    /// The manager with the backend of the [`AudioPlugin`](crate::AudioPlugin)
+    manager: Option<Box<dyn DynAudioManager>>,
+    /// Settings to create the manager with, while it could not be set up
+    setup: Option<ManagerSetup>,
+    /// The manager is only created after the first user input, commands are kept until then
+    locked: bool,
     instances: HashMap<Channel, Vec<Handle<AudioInstance>>>,
     channels: HashMap<Channel, ChannelState>,
+    /// The mixer tracks that sounds of each channel are played through
+    ///
+    /// Channels are only in here once they are set up. `None` means creating the track failed.
+    tracks: HashMap<Channel, Option<TrackHandle>>,
+    /// Parents of channels that are sub-mixes of another channel
+    parents: HashMap<Channel, Channel>,
+    /// Reused between frames to avoid allocating while processing channel commands
+    commands_to_retry: Vec<AudioCommand>,
+    chains: Vec<PendingChain>,
+    /// Sounds that were dropped before they started, reported like stopped instances
+    dropped_instances: Vec<AudioInstanceStopped>,
+    /// Instances that were started at playback rate zero and still need their rate restored
+    playback_rate_repairs: Vec<(Handle<AudioInstance>, Value<PlaybackRate>)>,
+    /// Channels that had active instances when activity was last checked
+    active_channels: HashSet<Channel>,
+    /// Ids of all created clocks, `None` once the clock asset was removed
+    clocks: HashMap<AssetId<AudioClock>, Option<ClockId>>,
+    /// Clocks that still need to be inserted as assets
+    new_clocks: Vec<(AssetId<AudioClock>, ClockHandle)>,
+    /// Maximum duration and fade out of instances per channel
+    max_instance_durations: HashMap<Channel, (Duration, AudioTween)>,
+    /// Maximum number of playing instances and voice stealing policy per channel
+    max_instances: HashMap<Channel, (usize, VoiceStealing)>,
+    /// Maximum number of sounds of the same source per channel and the stealing policy
+    max_instances_per_source: HashMap<Channel, (usize, VoiceStealing)>,
+    /// Pan law of all channels without their own
+    pub(crate) pan_law: PanLaw,
+    /// Pan laws of channels that set their own
+    pan_laws: HashMap<Channel, PanLaw>,
+    /// Instances of channels with a maximum instance duration, with the time they need to stop
+    expiring_instances: Vec<(Handle<AudioInstance>, Instant, AudioTween)>,
+    /// Tempo of channels that send beat events
+    tempos: HashMap<Channel, Tempo>,
+    /// Last beat that was sent per instance
+    beats: HashMap<AssetId<AudioInstance>, u64>,
+    /// Last position, completed loops, and seek count per looping instance
+    loop_positions: HashMap<AssetId<AudioInstance>, (f64, u64, u64)>,
+    /// Clock ticking every millisecond to schedule delayed commands
+    scheduler: Option<ClockHandle>,
+    /// Instances waiting for their scheduled start
+    scheduled_instances: Vec<(Handle<AudioInstance>, ClockTime)>,
+    /// Clocks ticking once at the end of an intro, until the loop body after it started
+    intro_clocks: Vec<ClockHandle>,
+    /// Volume of the mixer tracks of channels, if it was changed
+    track_volumes: HashMap<Channel, Volume>,
+    /// Instances that can be stopped while silent or over the voice cap
+    virtual_instances: Vec<VirtualInstance>,
+    /// Maximum number of sounds that are sent to kira at a time
+    pub(crate) max_audible_instances: Option<usize>,
+    /// Channels whose volume, panning, playback rate, or pause state changed this frame
+    changed_channels: Vec<Channel>,
+    /// Channels that ran their last pending play command this frame
+    executed_play_commands: Vec<Channel>,
+    /// What happens to play commands while audio is disabled, `None` while it is enabled
+    disabled: Option<DisabledPlayback>,
+    /// Channels that were paused for an ongoing audio interruption
+    interrupted_channels: Vec<Channel>,
+    /// Channels that were paused while the app is not focused, `None` while it is focused
+    unfocused_channels: Option<Vec<Channel>>,
+    /// The only instance that is not muted, while a solo is active
+    solo: Option<Handle<AudioInstance>>,
+    /// Instances that were muted for the solo
+    muted_for_solo: Vec<Handle<AudioInstance>>,
+    /// Commands sent as events that are retried next frame
+    event_commands: Vec<(Channel, AudioCommand)>,
+    /// Number of processed and retried commands since the diagnostics were last updated
+    command_counts: CommandCounts,
+    /// Frames of sources that were played in mono, and their mono frames
+    mono_sources: HashMap<AssetId<AudioSource>, MonoFrames>,
+    /// Levels measured by the mixer track of channels
+    meters: HashMap<Channel, Arc<SharedLevels>>,
+    /// Output captured by the mixer track of channels
+    captures: HashMap<Channel, Arc<SharedCapture>>,
+    /// Spectrum computed by the mixer track of channels
+    #[cfg(feature = "analysis")]
+    spectrums: HashMap<Channel, Arc<SharedSpectrum>>,
+    /// Spectrum computed by the main track
+    #[cfg(feature = "analysis")]
+    main_spectrum: Option<Arc<SharedSpectrum>>,
 }
 
-impl FromWorld for AudioOutput {
-    fn from_world(world: &mut World) -> Self {
-        let settings = world.remove_resource::<AudioSettings>().unwrap_or_default();
-        let manager = AudioManager::new(settings.into());
-        if let Err(ref setup_error) = manager {
-            warn!("Failed to setup audio: {:?}", setup_error);
+/// The original frames of a source and the same frames mixed down to mono
+type MonoFrames = (Arc<[Frame]>, Arc<[Frame]>);
+
+/// The parts of an [`AudioManager`] used by [`AudioOutput`], independent of its backend
+///
+/// The systems of the plugin are registered without knowing the backend, for example by
+/// [`add_audio_channel`](crate::AudioApp::add_audio_channel), so the backend is erased here.
+trait DynAudioManager {
+    fn main_track(&self) -> TrackHandle;
+
+    fn pause(&self, fade_out_tween: Tween) -> Result<(), CommandError>;
+
+    fn resume(&self, fade_in_tween: Tween) -> Result<(), CommandError>;
+
+    fn add_clock(&mut self, speed: ClockSpeed) -> Result<ClockHandle, AddClockError>;
+
+    fn add_sub_track(&mut self, builder: TrackBuilder) -> Result<TrackHandle, AddSubTrackError>;
+
+    fn play_static(
+        &mut self,
+        sound: StaticSoundData,
+    ) -> Result<StaticSoundHandle, PlaySoundError<()>>;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_streaming(
+        &mut self,
+        sound: StreamingSoundData<FromFileError>,
+    ) -> Result<StreamingSoundHandle<FromFileError>, PlaySoundError<FromFileError>>;
+
+    /// The backend, to be downcast to the concrete type
+    fn backend_any(&mut self) -> &mut dyn Any;
+}
+
+impl<B: Backend + 'static> DynAudioManager for AudioManager<B> {
+    fn main_track(&self) -> TrackHandle {
+        AudioManager::main_track(self)
+    }
+
+    fn pause(&self, fade_out_tween: Tween) -> Result<(), CommandError> {
+        AudioManager::pause(self, fade_out_tween)
+    }
+
+    fn resume(&self, fade_in_tween: Tween) -> Result<(), CommandError> {
+        AudioManager::resume(self, fade_in_tween)
+    }
+
+    fn add_clock(&mut self, speed: ClockSpeed) -> Result<ClockHandle, AddClockError> {
+        AudioManager::add_clock(self, speed)
+    }
+
+    fn add_sub_track(&mut self, builder: TrackBuilder) -> Result<TrackHandle, AddSubTrackError> {
+        AudioManager::add_sub_track(self, builder)
+    }
+
+    fn play_static(
+        &mut self,
+        sound: StaticSoundData,
+    ) -> Result<StaticSoundHandle, PlaySoundError<()>> {
+        self.play(sound)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_streaming(
+        &mut self,
+        sound: StreamingSoundData<FromFileError>,
+    ) -> Result<StreamingSoundHandle<FromFileError>, PlaySoundError<FromFileError>> {
+        self.play(sound)
+    }
+
+    fn backend_any(&mut self) -> &mut dyn Any {
+        self.backend_mut()
+    }
+}
+
+/// Creates the audio manager with the backend and backend settings of the plugin
+type CreateManager =
+    Box<dyn Fn(&ManagerSetup, Option<String>) -> Result<Box<dyn DynAudioManager>, String>>;
+
+/// Creates the settings of a kira backend, so the audio manager can be created again
+pub(crate) type BackendSettingsFn<B> = Arc<dyn Fn() -> <B as Backend>::Settings + Send + Sync>;
+
+/// What is needed to create the audio manager again
+struct ManagerSetup {
+    settings: AudioSettings,
+    #[cfg(feature = "analysis")]
+    main_spectrum: Option<(SpectrumSettings, Arc<SharedSpectrum>)>,
+    create: CreateManager,
+    retry_at: Instant,
+}
+
+/// Number of channel commands that were processed, and how many of them have to be retried
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CommandCounts {
+    pub(crate) processed: usize,
+    pub(crate) retried: usize,
+}
+
+/// An instance that is stopped while silent or over the voice cap, and restarted later
+struct VirtualInstance {
+    instance: Handle<AudioInstance>,
+    channel: Channel,
+    source: Handle<AudioSource>,
+    settings: PartialSoundSettings,
+    /// How long the sound needs to be silent before it is stopped
+    after: Option<Duration>,
+    silent_since: Option<Instant>,
+    stopped_at: Option<Instant>,
+}
+
+/// Chained sounds waiting for the instance before them to finish
+struct PendingChain {
+    channel: Channel,
+    previous: Handle<AudioInstance>,
+    /// Length of the previous sound in seconds, if it is known and the sound is not looped
+    previous_duration: Option<f64>,
+    finished_at: Option<Instant>,
+    sounds: VecDeque<ChainedSound>,
+}
+
+impl VirtualInstance {
+    /// The position the stopped sound would have reached by now
+    ///
+    /// Returns `None` if a sound that is not looped would have finished.
+    fn position(&self, source: &AudioSource, position: f64, now: Instant) -> Option<f64> {
+        let elapsed = self.stopped_at.map_or(0., |stopped_at| {
+            now.duration_since(stopped_at).as_secs_f64()
+        }) * self.settings.playback_rate.unwrap_or(1.).abs();
+        let position = position + elapsed;
+        if self.settings.loop_start.is_some() {
+            return Some(self.loop_position(source, position));
         }
+        let end = self
+            .settings
+            .end_position
+            .unwrap_or_else(|| source.sound.duration().as_secs_f64());
 
-        Self {
-            manager: manager.ok(),
-            instances: HashMap::default(),
-            channels: HashMap::default(),
+        (position < end).then_some(position)
+    }
+
+    /// Stop the instance and remember its position
+    fn virtualize(&mut self, instance: &mut AudioInstance, now: Instant) {
+        let position = instance.handle.position();
+        match instance.stop_with_kira_tween(kira::tween::Tween::default()) {
+            Ok(()) => {
+                instance.virtualized = Some(position);
+                self.stopped_at = Some(now);
+            }
+            Err(error) => warn!("Failed to stop sound to virtualize it due to {:?}", error),
+        }
+    }
+
+    /// Wrap a position past the end of the loop back into the loop region
+    fn loop_position(&self, source: &AudioSource, position: f64) -> f64 {
+        let end = self
+            .settings
+            .loop_end
+            .unwrap_or_else(|| source.sound.duration().as_secs_f64());
+        let start = self.settings.loop_start.unwrap_or(0.).min(end);
+        if position < end || end <= start {
+            return position.min(end);
         }
+
+        start + (position - start) % (end - start)
     }
 }
 
-impl<B: Backend> AudioOutput<B> {
-    fn stop(
-        &mut self,
-        channel: &Channel,
-        audio_instances: &mut Assets<AudioInstance>,
-        tween: &Option<AudioTween>,
-    ) -> AudioCommandResult {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instances {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    match instance.handle.stop(tween) {
-                        Err(CommandError::CommandQueueFull) => {
-                            return AudioCommandResult::Retry;
-                        }
-                        Err(error) => {
-                            error!("Failed to stop instance: {:?}", error);
-                        }
-                        _ => (),
-                    }
-                }
+/// The audio source assets that sounds can be played from
+pub(crate) struct AudioSources<'a> {
+    pub(crate) sources: &'a Assets<AudioSource>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) streaming_sources: Option<&'a Assets<StreamingAudioSource>>,
+}
+
+impl<'a> AudioSources<'a> {
+    fn get(&self, handle: &AudioSourceHandle) -> Option<PlayableSource<'a>> {
+        match handle {
+            AudioSourceHandle::Static(handle) => {
+                self.sources.get(handle).map(PlayableSource::Static)
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            AudioSourceHandle::Streaming(handle) => self
+                .streaming_sources
+                .and_then(|sources| sources.get(handle))
+                .map(PlayableSource::Streaming),
         }
+    }
+}
 
-        AudioCommandResult::Ok
+impl<'a> From<&'a Assets<AudioSource>> for AudioSources<'a> {
+    fn from(sources: &'a Assets<AudioSource>) -> Self {
+        AudioSources {
+            sources,
+            #[cfg(not(target_arch = "wasm32"))]
+            streaming_sources: None,
+        }
     }
+}
 
-    fn pause(
-        &mut self,
-        channel: &Channel,
-        audio_instances: &mut Assets<AudioInstance>,
-        tween: &Option<AudioTween>,
-    ) {
-        if let Some(instance_handles) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instance_handles.iter_mut() {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    if kira::sound::PlaybackState::Playing == instance.handle.state() {
-                        if let Err(error) = instance.handle.pause(tween) {
-                            error!("Failed to pause instance: {:?}", error);
-                        }
-                    }
+/// A loaded audio source, ready to be played
+#[derive(Clone, Copy)]
+pub(crate) enum PlayableSource<'a> {
+    Static(&'a AudioSource),
+    #[cfg(not(target_arch = "wasm32"))]
+    Streaming(&'a StreamingAudioSource),
+}
+
+impl<'a> PlayableSource<'a> {
+    fn settings(&self) -> StaticSoundSettings {
+        match self {
+            PlayableSource::Static(source) => source.sound.settings,
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayableSource::Streaming(source) => source.static_settings(),
+        }
+    }
+
+    /// Length of the sound in seconds, if it is known without decoding it
+    fn duration(&self) -> Option<f64> {
+        match self {
+            PlayableSource::Static(source) => Some(source.sound.duration().as_secs_f64()),
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayableSource::Streaming(_) => None,
+        }
+    }
+}
+
+/// Create the audio manager playing on the device with the given name, or the default device
+///
+/// Choosing the device and the stream configuration only works with the
+/// [`OutputBackend`](crate::OutputBackend), the sample rate is also used by the
+/// [`OfflineBackend`](crate::OfflineBackend) and kira's mock backend. Other backends are created
+/// with the given backend settings as they are.
+fn create_manager<B>(
+    setup: &ManagerSetup,
+    device: Option<String>,
+    backend_settings: B::Settings,
+) -> Result<Box<dyn DynAudioManager>, String>
+where
+    B: Backend + 'static,
+    B::Settings: Default + 'static,
+    B::Error: Debug,
+{
+    let mut manager_settings: AudioManagerSettings<B> = setup.settings.into();
+    manager_settings.backend_settings = backend_settings;
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(backend_settings) = (&mut manager_settings.backend_settings as &mut dyn Any)
+        .downcast_mut::<OutputBackendSettings>()
+    {
+        backend_settings.device = device;
+        backend_settings.preferences = ConfigPreferences {
+            buffer_size: setup.settings.buffer_size,
+            sample_rate: setup.settings.sample_rate,
+        };
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = device;
+    let backend_settings = &mut manager_settings.backend_settings as &mut dyn Any;
+    if let Some(sample_rate) = setup.settings.sample_rate {
+        if let Some(backend_settings) = backend_settings.downcast_mut::<OfflineBackendSettings>() {
+            backend_settings.sample_rate = sample_rate;
+        } else if let Some(backend_settings) =
+            backend_settings.downcast_mut::<MockBackendSettings>()
+        {
+            backend_settings.sample_rate = sample_rate;
+        }
+    }
+    #[cfg(feature = "analysis")]
+    if let Some((settings, shared)) = &setup.main_spectrum {
+        manager_settings
+            .main_track_builder
+            .add_effect(SpectrumTapBuilder {
+                settings: *settings,
+                shared: shared.clone(),
+            });
+    }
+
+    AudioManager::<B>::new(manager_settings)
+        .map(|manager| Box::new(manager) as Box<dyn DynAudioManager>)
+        .map_err(|error| format!("{error:?}"))
+}
+
+impl AudioOutput {
+    /// Create the audio output playing through the given backend
+    pub(crate) fn with_backend<B>(world: &mut World, backend_settings: BackendSettingsFn<B>) -> Self
+    where
+        B: Backend + 'static,
+        B::Settings: Default + 'static,
+        B::Error: Debug,
+    {
+        let settings = world.remove_resource::<AudioSettings>().unwrap_or_default();
+        world.insert_resource(AppPlayCommandQueueing(settings.play_command_queueing));
+        let device = world
+            .get_resource::<AudioOutputDevice>()
+            .and_then(|device| device.name.clone());
+        let setup = ManagerSetup {
+            settings,
+            #[cfg(feature = "analysis")]
+            main_spectrum: world.remove_resource::<MainTrackSpectrum>().map(
+                |MainTrackSpectrum(spectrum)| {
+                    let tap = SpectrumTapBuilder::new(spectrum);
+                    (tap.settings, tap.shared)
+                },
+            ),
+            create: Box::new(move |setup, device| {
+                create_manager::<B>(setup, device, backend_settings())
+            }),
+            retry_at: Instant::now() + MANAGER_RETRY_INTERVAL,
+        };
+        let locked = settings.unlock_on_input;
+        let manager = if locked {
+            debug!("Waiting for user input to setup audio");
+            None
+        } else {
+            match (setup.create)(&setup, device) {
+                Ok(manager) => Some(manager),
+                Err(setup_error) => {
+                    warn!(
+                        "Failed to setup audio: {}, retrying every {:?}",
+                        setup_error, MANAGER_RETRY_INTERVAL
+                    );
+                    None
                 }
             }
+        };
+
+        let mut audio_output = Self::with_manager(manager);
+        audio_output.locked = locked;
+        #[cfg(feature = "analysis")]
+        {
+            audio_output.main_spectrum = setup
+                .main_spectrum
+                .as_ref()
+                .map(|(_, shared)| shared.clone());
         }
-        if let Some(channel_state) = self.channels.get_mut(channel) {
-            channel_state.paused = true;
-        } else {
-            let channel_state = ChannelState {
-                paused: true,
-                ..Default::default()
-            };
-            self.channels.insert(channel.clone(), channel_state);
+        if audio_output.manager.is_none() {
+            audio_output.setup = Some(setup);
         }
+        audio_output.max_audible_instances = settings.max_audible_instances;
+        audio_output.pan_law = settings.pan_law;
+        audio_output.start_ramp(settings.startup_ramp);
+
+        audio_output
     }
 
-    fn resume(
-        &mut self,
-        channel: &Channel,
-        audio_instances: &mut Assets<AudioInstance>,
-        tween: &Option<AudioTween>,
-    ) {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instances.iter_mut() {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    if instance.handle.state() == kira::sound::PlaybackState::Paused
-                        || instance.handle.state() == kira::sound::PlaybackState::Pausing
-                        || instance.handle.state() == kira::sound::PlaybackState::Stopping
-                    {
-                        if let Err(error) = instance.handle.resume(tween) {
-                            error!("Failed to resume instance: {:?}", error);
-                        }
-                    }
-                }
+    /// The backend of the audio manager, if it is a `B`
+    pub(crate) fn backend_mut<B: Backend + 'static>(&mut self) -> Option<&mut B> {
+        self.manager.as_mut()?.backend_any().downcast_mut()
+    }
+
+    /// Move the audio to the output device with the given name, or the default device
+    pub(crate) fn set_output_device(&mut self, device: Option<String>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(backend) = self.backend_mut::<OutputBackend>() {
+            backend.set_device(device);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = device;
+    }
+
+    /// Try to create the audio manager again, if it could not be set up so far
+    ///
+    /// Channel settings stay pending while there is no manager, so they are applied as soon as
+    /// it is available.
+    pub(crate) fn retry_setup(&mut self, device: Option<&str>, now: Instant) {
+        if self.locked {
+            return;
+        }
+        let Some(setup) = self.setup.as_mut() else {
+            return;
+        };
+        if now < setup.retry_at {
+            return;
+        }
+        setup.retry_at = now + MANAGER_RETRY_INTERVAL;
+        let startup_ramp = setup.settings.startup_ramp;
+        let manager = match (setup.create)(setup, device.map(str::to_owned)) {
+            Ok(manager) => manager,
+            Err(error) => {
+                debug!("Failed to setup audio again due to {}", error);
+                return;
+            }
+        };
+        info!("Audio output is available");
+        if self.disabled.is_some() {
+            if let Err(error) = manager.pause(kira::tween::Tween::default()) {
+                warn!("Failed to pause audio due to {:?}", error);
             }
         }
-        if let Some(channel_state) = self.channels.get_mut(channel) {
-            channel_state.paused = false;
-        } else {
-            self.channels
-                .insert(channel.clone(), ChannelState::default());
+        self.setup = None;
+        self.manager = Some(manager);
+        self.start_ramp(startup_ramp);
+    }
+
+    /// Whether the audio is still waiting for the first user input
+    pub(crate) fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Create the audio manager after the first user input
+    ///
+    /// Commands kept while the audio was locked run with the next audio systems. If creating the
+    /// manager fails, it is retried like a failed setup at startup.
+    pub(crate) fn unlock(&mut self, device: Option<&str>, now: Instant) {
+        if !std::mem::take(&mut self.locked) {
+            return;
+        }
+        if let Some(setup) = self.setup.as_mut() {
+            setup.retry_at = now;
         }
+        self.retry_setup(device, now);
     }
 
-    fn set_volume(
-        &mut self,
-        channel: &Channel,
-        audio_instances: &mut Assets<AudioInstance>,
-        volume: Volume,
-        tween: &Option<AudioTween>,
-    ) {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instances.iter_mut() {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    if let Err(error) = instance.handle.set_volume(volume, tween) {
-                        error!("Failed to set volume for instance: {:?}", error);
-                    }
+    #[cfg(test)]
+    pub(crate) fn new<B: Backend + 'static>(manager: Option<AudioManager<B>>) -> Self {
+        Self::with_manager(manager.map(|manager| Box::new(manager) as Box<dyn DynAudioManager>))
+    }
+
+    fn with_manager(manager: Option<Box<dyn DynAudioManager>>) -> Self {
+        Self {
+            manager,
+            setup: None,
+            locked: false,
+            instances: HashMap::default(),
+            channels: HashMap::default(),
+            tracks: HashMap::default(),
+            parents: HashMap::default(),
+            commands_to_retry: Vec::default(),
+            chains: Vec::default(),
+            dropped_instances: Vec::default(),
+            playback_rate_repairs: Vec::default(),
+            active_channels: HashSet::default(),
+            clocks: HashMap::default(),
+            new_clocks: Vec::default(),
+            max_instance_durations: HashMap::default(),
+            max_instances: HashMap::default(),
+            max_instances_per_source: HashMap::default(),
+            pan_law: PanLaw::default(),
+            pan_laws: HashMap::default(),
+            expiring_instances: Vec::default(),
+            tempos: HashMap::default(),
+            beats: HashMap::default(),
+            loop_positions: HashMap::default(),
+            scheduler: None,
+            scheduled_instances: Vec::default(),
+            intro_clocks: Vec::default(),
+            track_volumes: HashMap::default(),
+            virtual_instances: Vec::default(),
+            max_audible_instances: None,
+            changed_channels: Vec::default(),
+            executed_play_commands: Vec::default(),
+            disabled: None,
+            interrupted_channels: Vec::default(),
+            unfocused_channels: None,
+            solo: None,
+            muted_for_solo: Vec::default(),
+            event_commands: Vec::default(),
+            command_counts: CommandCounts::default(),
+            mono_sources: HashMap::default(),
+            meters: HashMap::default(),
+            captures: HashMap::default(),
+            #[cfg(feature = "analysis")]
+            spectrums: HashMap::default(),
+            #[cfg(feature = "analysis")]
+            main_spectrum: None,
+        }
+    }
+
+    /// Immediately silence the main track
+    fn silence(&mut self) {
+        let Some(manager) = self.manager.as_ref() else {
+            return;
+        };
+        if let Err(error) = manager.main_track().set_volume(
+            Volume::Amplitude(0.),
+            AudioTween::linear(Duration::ZERO).into(),
+        ) {
+            warn!("Failed to silence main track due to {:?}", error);
+        }
+    }
+
+    /// Pause or resume the audio backend
+    pub(crate) fn apply_global_audio(&mut self, global_audio: &GlobalAudio) {
+        let disabled = (!global_audio.is_enabled()).then(|| global_audio.disabled_playback());
+        if disabled.is_some() != self.disabled.is_some() {
+            if let Some(manager) = self.manager.as_ref() {
+                let result = if disabled.is_some() {
+                    manager.pause(kira::tween::Tween::default())
+                } else {
+                    manager.resume(kira::tween::Tween::default())
+                };
+                if let Err(error) = result {
+                    warn!("Failed to pause or resume audio due to {:?}", error);
+                    return;
                 }
             }
         }
-        if let Some(channel_state) = self.channels.get_mut(channel) {
-            channel_state.volume = volume;
-        } else {
-            let channel_state = ChannelState {
-                volume,
-                ..Default::default()
-            };
-            self.channels.insert(channel.clone(), channel_state);
-        }
+        self.disabled = disabled;
     }
 
-    fn set_panning(
+    /// Pause the given channels, unless they are paused already
+    pub(crate) fn begin_interruption(
         &mut self,
-        channel: &Channel,
+        channels: &[Channel],
         audio_instances: &mut Assets<AudioInstance>,
-        panning: f64,
-        tween: &Option<AudioTween>,
     ) {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instances.iter_mut() {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    if let Err(error) = instance.handle.set_panning(panning, tween) {
-                        error!("Failed to set panning for instance: {:?}", error);
-                    }
-                }
+        for channel in channels {
+            let paused = self
+                .channels
+                .get(channel)
+                .map_or(false, |state| state.paused);
+            if paused || self.interrupted_channels.contains(channel) {
+                continue;
             }
-        }
-        if let Some(channel_state) = self.channels.get_mut(channel) {
-            channel_state.panning = panning;
-        } else {
-            let channel_state = ChannelState {
-                panning,
-                ..Default::default()
-            };
-            self.channels.insert(channel.clone(), channel_state);
+            self.pause(channel, audio_instances, &None, None);
+            self.interrupted_channels.push(channel.clone());
         }
     }
 
-    fn set_playback_rate(
+    /// Forget the channels paused for an interruption and optionally resume them
+    pub(crate) fn end_interruption(
         &mut self,
-        channel: &Channel,
+        resume: bool,
         audio_instances: &mut Assets<AudioInstance>,
-        playback_rate: f64,
-        tween: &Option<AudioTween>,
     ) {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            let tween = map_tween(tween);
-            for instance in instances.iter_mut() {
-                if let Some(instance) = audio_instances.get_mut(instance.id()) {
-                    if let Err(error) = instance.handle.set_playback_rate(playback_rate, tween) {
-                        error!("Failed to set playback rate for instance: {:?}", error);
-                    }
-                }
+        for channel in std::mem::take(&mut self.interrupted_channels) {
+            if resume {
+                self.resume(&channel, audio_instances, &None, ResumeTargets::Paused);
             }
         }
-        if let Some(channel_state) = self.channels.get_mut(channel) {
-            channel_state.playback_rate = playback_rate;
-        } else {
-            let channel_state = ChannelState {
-                playback_rate,
-                ..Default::default()
-            };
-            self.channels.insert(channel.clone(), channel_state);
-        }
     }
 
-    fn play(
+    /// Returns `true` while the channels are paused because the app is not focused
+    pub(crate) fn is_paused_for_focus_loss(&self) -> bool {
+        self.unfocused_channels.is_some()
+    }
+
+    /// Pause all channels, except the kept ones and their parents, unless they are paused already
+    ///
+    /// Runs once when the app loses focus. Channels the game pauses itself in the meantime are
+    /// left paused once the focus returns.
+    pub(crate) fn pause_for_focus_loss(
         &mut self,
-        channel: &Channel,
-        partial_sound_settings: &PartialSoundSettings,
-        audio_source: &AudioSource,
-        instance_handle: Handle<AudioInstance>,
+        keep: &[Channel],
+        tween: &AudioTween,
         audio_instances: &mut Assets<AudioInstance>,
-    ) -> AudioCommandResult {
-        let mut sound = audio_source.sound.clone();
-        if let Some(channel_state) = self.channels.get(channel) {
-            channel_state.apply(&mut sound);
-            // This is reverted after pausing the sound handle.
-            // Otherwise the audio thread will start playing the sound before our pause command goes through.
-            if channel_state.paused {
-                sound.settings.playback_rate = kira::tween::Value::Fixed(PlaybackRate::Factor(0.0));
-            }
-        }
-        if partial_sound_settings.paused {
-            sound.settings.playback_rate = kira::tween::Value::Fixed(PlaybackRate::Factor(0.0));
-        }
-        partial_sound_settings.apply(&mut sound);
-        let sound_handle = self.manager.as_mut().unwrap().play(sound);
-        if let Err(error) = sound_handle {
-            warn!("Failed to play sound due to {:?}", error);
-            return AudioCommandResult::Ok;
-        }
-        let mut sound_handle = sound_handle.unwrap();
-        if let Some(channel_state) = self.channels.get(channel) {
-            if channel_state.paused {
-                if let Err(error) = sound_handle.pause(kira::tween::Tween::default()) {
-                    warn!(
-                        "Failed to pause instance (channel was paused) due to {:?}",
-                        error
-                    );
-                }
-                let playback_rate = partial_sound_settings
-                    .playback_rate
-                    .unwrap_or(channel_state.playback_rate);
-                if let Err(error) =
-                    sound_handle.set_playback_rate(playback_rate, kira::tween::Tween::default())
-                {
-                    error!("Failed to set playback rate for instance: {:?}", error);
-                }
-            }
-        }
-        if partial_sound_settings.paused {
-            if let Err(error) = sound_handle.pause(kira::tween::Tween::default()) {
-                warn!("Failed to pause instance due to {:?}", error);
-            }
-            let playback_rate = partial_sound_settings.playback_rate.unwrap_or(1.0);
-            if let Err(error) =
-                sound_handle.set_playback_rate(playback_rate, kira::tween::Tween::default())
+    ) {
+        let mut paused_channels: Vec<Channel> = vec![];
+        for channel in self.instances.keys().chain(self.channels.keys()) {
+            let paused = self
+                .channels
+                .get(channel)
+                .map_or(false, |state| state.paused);
+            if paused
+                || paused_channels.contains(channel)
+                || keep
+                    .iter()
+                    .any(|kept| self.is_within(kept, channel) || self.is_within(channel, kept))
             {
-                error!("Failed to set playback rate for instance: {:?}", error);
+                continue;
             }
+            paused_channels.push(channel.clone());
         }
-        audio_instances.insert(
-            &instance_handle,
-            AudioInstance {
-                handle: sound_handle,
-            },
-        );
-        if let Some(instance_states) = self.instances.get_mut(channel) {
-            instance_states.push(instance_handle);
-        } else {
-            self.instances
-                .insert(channel.clone(), vec![instance_handle]);
+        for channel in &paused_channels {
+            self.pause(channel, audio_instances, &Some(tween.clone()), None);
         }
-
-        AudioCommandResult::Ok
+        self.unfocused_channels = Some(paused_channels);
     }
 
-    pub(crate) fn play_channel<T: Resource>(
+    /// Resume the channels paused by [`pause_for_focus_loss`](Self::pause_for_focus_loss)
+    pub(crate) fn resume_after_focus_loss(
         &mut self,
-        audio_sources: &Assets<AudioSource>,
-        channel: &AudioChannel<T>,
+        tween: &AudioTween,
         audio_instances: &mut Assets<AudioInstance>,
     ) {
-        if self.manager.is_none() {
-            return;
-        }
-        let mut commands = channel.commands.write();
-        let len = commands.len();
-        let channel_id = TypeId::of::<T>();
-        let channel = Channel::Typed(channel_id);
-        let mut commands_to_retry = vec![];
-        let mut i = 0;
-        while i < len {
-            let audio_command = commands.pop_back().unwrap();
-            let result =
-                self.run_audio_command(&audio_command, audio_sources, audio_instances, &channel);
-            if let AudioCommand::Stop(_) = audio_command {
-                commands_to_retry.clear();
-            }
-            if let AudioCommandResult::Retry = result {
-                commands_to_retry.push(audio_command);
-            }
-            i += 1;
+        for channel in self.unfocused_channels.take().unwrap_or_default() {
+            self.resume(
+                &channel,
+                audio_instances,
+                &Some(tween.clone()),
+                ResumeTargets::Paused,
+            );
         }
-        commands_to_retry
-            .drain(..)
-            .for_each(|command| commands.push_front(command));
     }
 
-    pub(crate) fn play_dynamic_channels(
-        &mut self,
-        audio_sources: &Assets<AudioSource>,
-        channels: &DynamicAudioChannels,
-        audio_instances: &mut Assets<AudioInstance>,
-    ) {
-        if self.manager.is_none() {
-            return;
+    /// What to do with a play command before running it
+    ///
+    /// Returns `None` if the command can run.
+    fn hold_play_command(&self, frozen: bool) -> Option<AudioCommandResult> {
+        match self.disabled {
+            Some(DisabledPlayback::Drop) => Some(AudioCommandResult::Ok),
+            Some(DisabledPlayback::Buffer) => Some(AudioCommandResult::Retry),
+            None if frozen => Some(AudioCommandResult::Retry),
+            None => None,
         }
-        for (key, channel) in channels.channels.iter() {
-            let mut commands = channel.commands.write();
-            let len = commands.len();
-            let channel = Channel::Dynamic(key.clone());
-            let mut i = 0;
-            while i < len {
-                let audio_command = commands.pop_back().unwrap();
-                let result = self.run_audio_command(
-                    &audio_command,
-                    audio_sources,
-                    audio_instances,
-                    &channel,
-                );
-                if let AudioCommandResult::Retry = result {
-                    commands.push_front(audio_command);
-                }
-                i += 1;
+    }
+
+    /// Silence or fade in the main track like configured for the start of the app
+    fn start_ramp(&mut self, startup_ramp: StartupRamp) {
+        match startup_ramp {
+            StartupRamp::Immediate => (),
+            StartupRamp::FadeIn(duration) => {
+                self.silence();
+                self.ramp_up(duration);
             }
+            StartupRamp::Manual => self.silence(),
         }
     }
 
-    pub(crate) fn run_audio_command(
-        &mut self,
-        audio_command: &AudioCommand,
-        audio_sources: &Assets<AudioSource>,
-        audio_instances: &mut Assets<AudioInstance>,
-        channel: &Channel,
-    ) -> AudioCommandResult {
-        match audio_command {
-            AudioCommand::Play(play_args) => {
-                if let Some(audio_source) = audio_sources.get(&play_args.source) {
-                    self.play(
-                        channel,
-                        &play_args.settings,
-                        audio_source,
-                        play_args.instance_handle.clone(),
-                        audio_instances,
-                    )
-                } else {
-                    // audio source hasn't loaded yet. Add it back to the queue
-                    AudioCommandResult::Retry
-                }
-            }
-            AudioCommand::Stop(tween) => self.stop(channel, audio_instances, tween),
-            AudioCommand::Pause(tween) => {
-                self.pause(channel, audio_instances, tween);
-                AudioCommandResult::Ok
-            }
-            AudioCommand::Resume(tween) => {
-                self.resume(channel, audio_instances, tween);
-                AudioCommandResult::Ok
-            }
-            AudioCommand::SetVolume(volume, tween) => {
-                self.set_volume(channel, audio_instances, *volume, tween);
-                AudioCommandResult::Ok
-            }
-            AudioCommand::SetPanning(panning, tween) => {
-                self.set_panning(channel, audio_instances, *panning, tween);
-                AudioCommandResult::Ok
-            }
-            AudioCommand::SetPlaybackRate(playback_rate, tween) => {
-                self.set_playback_rate(channel, audio_instances, *playback_rate, tween);
-                AudioCommandResult::Ok
+    /// Fade the main track to full volume over the given duration
+    pub(crate) fn ramp_up(&mut self, duration: Duration) {
+        let Some(manager) = self.manager.as_ref() else {
+            // fade in once the manager could be set up
+            if let Some(setup) = self.setup.as_mut() {
+                setup.settings.startup_ramp = StartupRamp::FadeIn(duration);
             }
+            return;
+        };
+        if let Err(error) = manager
+            .main_track()
+            .set_volume(Volume::Amplitude(1.), AudioTween::linear(duration).into())
+        {
+            warn!("Failed to ramp up main track due to {:?}", error);
         }
     }
 
-    pub(crate) fn cleanup_stopped_instances(&mut self, instances: &mut Assets<AudioInstance>) {
-        for (_, handles) in self.instances.iter_mut() {
-            handles.retain(|handle| {
-                if let Some(instance) = instances.get(handle) {
-                    instance.handle.state() != kira::sound::PlaybackState::Stopped
-                } else {
-                    false
-                }
-            });
-        }
+    /// Levels of all channels with metering
+    pub(crate) fn meters(&self) -> &HashMap<Channel, Arc<SharedLevels>> {
+        &self.meters
     }
-}
 
-pub(crate) fn play_dynamic_channels(
-    mut audio_output: NonSendMut<AudioOutput>,
-    channels: Res<DynamicAudioChannels>,
-    audio_sources: Option<Res<Assets<AudioSource>>>,
-    mut audio_instances: ResMut<Assets<AudioInstance>>,
-) {
-    if let Some(audio_sources) = audio_sources {
-        audio_output.play_dynamic_channels(&audio_sources, &channels, &mut audio_instances);
-    };
-}
+    /// Captures of all channels with capturing
+    pub(crate) fn captures(&self) -> &HashMap<Channel, Arc<SharedCapture>> {
+        &self.captures
+    }
 
-pub(crate) fn play_audio_channel<T: Resource>(
-    mut audio_output: NonSendMut<AudioOutput>,
-    channel: Res<AudioChannel<T>>,
-    audio_sources: Option<Res<Assets<AudioSource>>>,
-    mut instances: ResMut<Assets<AudioInstance>>,
-) {
-    if let Some(audio_sources) = audio_sources {
-        audio_output.play_channel(&audio_sources, &channel, &mut instances);
-    };
-}
+    /// Spectrums of all channels that compute one
+    #[cfg(feature = "analysis")]
+    pub(crate) fn spectrums(&self) -> &HashMap<Channel, Arc<SharedSpectrum>> {
+        &self.spectrums
+    }
 
-pub(crate) fn cleanup_stopped_instances(
-    mut audio_output: NonSendMut<AudioOutput>,
-    mut instances: ResMut<Assets<AudioInstance>>,
-) {
-    audio_output.cleanup_stopped_instances(&mut instances);
-}
+    /// Spectrum of the main track, if it computes one
+    #[cfg(feature = "analysis")]
+    pub(crate) fn main_spectrum(&self) -> Option<&Arc<SharedSpectrum>> {
+        self.main_spectrum.as_ref()
+    }
 
-pub(crate) fn update_instance_states<T: Resource>(
-    audio_output: NonSend<AudioOutput>,
-    audio_instances: Res<Assets<AudioInstance>>,
-    mut channel: ResMut<AudioChannel<T>>,
-) {
-    if let Some(instances) = audio_output
-        .instances
-        .get(&Channel::Typed(TypeId::of::<T>()))
-    {
-        channel.states.clear();
-        for instance_handle in instances.iter() {
-            let state = audio_instances
-                .get(instance_handle)
-                .map(|instance| instance.state())
-                .unwrap_or(PlaybackState::Stopped);
-            channel.states.insert(instance_handle.id(), state);
-        }
+    /// The pan law for sounds of the given channel
+    fn pan_law(&self, channel: &Channel) -> PanLaw {
+        self.pan_laws.get(channel).copied().unwrap_or(self.pan_law)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::channel::AudioControl;
-    use crate::{Audio, AudioPlugin};
-    use bevy::asset::{AssetId, AssetPlugin};
-    use bevy::prelude::*;
-    use kira::manager::backend::mock::MockBackend;
-    use kira::manager::AudioManagerSettings;
-    use uuid::Uuid;
+    /// The given channel followed by all its parent channels
+    fn lineage<'a>(&'a self, channel: &'a Channel) -> impl Iterator<Item = &'a Channel> {
+        std::iter::successors(Some(channel), |channel| self.parents.get(*channel))
+    }
 
-    #[test]
-    fn keeps_order_of_commands_to_retry() {
-        // we only need this app to conveniently get a assets collection for `AudioSource`...
-        let mut app = App::new();
-        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
-        let audio_source_assets = app
-            .world_mut()
-            .remove_resource::<Assets<AudioSource>>()
-            .unwrap();
-        let mut audio_instance_assets = app
-            .world_mut()
-            .remove_resource::<Assets<AudioInstance>>()
-            .unwrap();
+    /// Is the channel the given ancestor or one of its sub-mixes?
+    fn is_within(&self, channel: &Channel, ancestor: &Channel) -> bool {
+        self.lineage(channel).any(|channel| channel == ancestor)
+    }
 
-        let mut audio_output = AudioOutput {
-            manager: AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
-            instances: HashMap::default(),
-            channels: HashMap::default(),
-        };
-        let audio_handle_one: Handle<AudioSource> =
-            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(1758302748397294)));
-        let audio_handle_two: Handle<AudioSource> =
-            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(2537024739048739)));
+    /// The channel volume combined with the volumes of all parent channels
+    fn effective_volume(&self, channel: &Channel) -> Volume {
+        Volume::Amplitude(
+            self.lineage(channel)
+                .filter_map(|channel| self.channels.get(channel))
+                .map(|channel_state| channel_state.volume.as_amplitude())
+                .product(),
+        )
+    }
 
-        let channel = AudioChannel::<Audio>::default();
-        channel.play(audio_handle_one.clone());
-        channel.play(audio_handle_two.clone());
+    /// Is the channel or any of its parent channels paused?
+    fn effectively_paused(&self, channel: &Channel) -> bool {
+        self.lineage(channel)
+            .filter_map(|channel| self.channels.get(channel))
+            .any(|channel_state| channel_state.paused)
+    }
 
-        audio_output.play_channel(&audio_source_assets, &channel, &mut audio_instance_assets);
+    /// Clock time of the scheduler clock after the given delay
+    fn scheduled_time(&mut self, delay: Duration) -> Option<ClockTime> {
+        if self.scheduler.is_none() {
+            let manager = self.manager.as_mut()?;
+            let scheduler = manager
+                .add_clock(ClockSpeed::TicksPerSecond(1000.))
+                .map_err(|error| warn!("Failed to add scheduler clock due to {:?}", error))
+                .ok()?;
+            if let Err(error) = scheduler.start() {
+                warn!("Failed to start scheduler clock due to {:?}", error);
+            }
+            self.scheduler = Some(scheduler);
+        }
 
-        let command_one = channel.commands.write().pop_back().unwrap();
-        match command_one {
-            AudioCommand::Play(settings) => {
-                assert_eq!(settings.source.id(), audio_handle_one.id())
+        self.scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.time() + delay.as_millis() as u64)
+    }
+
+    /// Kira tween for the given tween, starting after the given delay
+    fn delayed_tween(
+        &mut self,
+        tween: &Option<AudioTween>,
+        delay: Option<Duration>,
+    ) -> kira::tween::Tween {
+        let mut tween = map_tween(tween);
+        if let Some(start_time) = delay.and_then(|delay| self.scheduled_time(delay)) {
+            tween.start_time = StartTime::ClockTime(start_time);
+        }
+
+        tween
+    }
+
+    fn add_clock(&mut self, speed: ClockSpeed, clock: AssetId<AudioClock>) {
+        let Some(manager) = self.manager.as_mut() else {
+            return;
+        };
+        match manager.add_clock(speed) {
+            Ok(handle) => {
+                if let Err(error) = handle.start() {
+                    warn!("Failed to start clock due to {:?}", error);
+                }
+                self.clocks.insert(clock, Some(handle.id()));
+                self.new_clocks.push((clock, handle));
+            }
+            Err(error) => {
+                warn!("Failed to add clock due to {:?}", error);
+                self.clocks.insert(clock, None);
             }
-            _ => panic!("Wrong audio command"),
         }
-        let command_two = channel.commands.write().pop_back().unwrap();
-        match command_two {
-            AudioCommand::Play(settings) => {
-                assert_eq!(settings.source.id(), audio_handle_two.id())
+    }
+
+    /// Insert newly created clocks as assets and forget about clocks whose asset was removed
+    ///
+    /// Instances waiting for a clock are marked as started once their clock reached their start time.
+    pub(crate) fn update_clocks(
+        &mut self,
+        clock_assets: &mut Assets<AudioClock>,
+        audio_instances: &mut Assets<AudioInstance>,
+    ) {
+        for (clock, handle) in self.new_clocks.drain(..) {
+            clock_assets.insert(clock, AudioClock { handle });
+        }
+        for (clock, id) in self.clocks.iter_mut() {
+            if id.is_some() && !clock_assets.contains(*clock) {
+                *id = None;
             }
-            _ => panic!("Wrong audio command"),
         }
+        let scheduler = self.scheduler.as_ref();
+        let intro_clocks = &self.intro_clocks;
+        self.scheduled_instances.retain(|(handle, start_time)| {
+            let Some(instance) = audio_instances.get_mut(handle) else {
+                return false;
+            };
+            let ticks = match scheduler {
+                Some(scheduler) if scheduler.id() == start_time.clock => {
+                    Some(scheduler.time().ticks)
+                }
+                _ => intro_clocks
+                    .iter()
+                    .find(|clock| clock.id() == start_time.clock)
+                    .map(|clock| clock.time().ticks)
+                    .or_else(|| {
+                        clock_assets
+                            .iter()
+                            .find(|(_, clock)| clock.handle.id() == start_time.clock)
+                            .map(|(_, clock)| clock.ticks())
+                    }),
+            };
+            // Instances of removed clocks never start, so they stop waiting as well
+            if ticks.map_or(false, |ticks| ticks < start_time.ticks) {
+                return true;
+            }
+            instance.waiting = false;
+            false
+        });
+        self.intro_clocks.retain(|clock| clock.time().ticks < 1);
     }
 
-    #[test]
-    fn stop_command_removes_previous_play_commands() {
-        // we only need this app to conveniently get a assets collection for `AudioSource`...
-        let mut app = App::new();
-        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
-        let audio_source_assets = app
-            .world_mut()
-            .remove_resource::<Assets<AudioSource>>()
-            .unwrap();
-        let mut audio_instance_assets = app
-            .world_mut()
-            .remove_resource::<Assets<AudioInstance>>()
-            .unwrap();
+    /// Set the volume of the mixer track backing the channel
+    ///
+    /// Returns `false` if the channel has no mixer track yet or the command failed.
+    pub(crate) fn set_track_volume(
+        &mut self,
+        channel: &Channel,
+        volume: Volume,
+        tween: kira::tween::Tween,
+    ) -> bool {
+        let Some(Some(track)) = self.tracks.get_mut(channel) else {
+            return false;
+        };
+        match track.set_volume(volume, tween) {
+            Ok(()) => {
+                self.track_volumes.insert(channel.clone(), volume);
+                true
+            }
+            Err(error) => {
+                warn!("Failed to set volume of mixer track due to {:?}", error);
+                false
+            }
+        }
+    }
 
-        let mut audio_output = AudioOutput {
-            manager: AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
-            instances: HashMap::default(),
-            channels: HashMap::default(),
+    /// Set the volume of the send from the channel's mixer track to the mixer track of `send`
+    ///
+    /// Returns `false` if either channel has no mixer track or the channel does not send to `send`.
+    pub(crate) fn set_send_volume(
+        &mut self,
+        channel: &Channel,
+        send: &Channel,
+        volume: Volume,
+        tween: kira::tween::Tween,
+    ) -> bool {
+        let (Some(Some(track)), Some(Some(send_track))) =
+            (self.tracks.get(channel), self.tracks.get(send))
+        else {
+            return false;
         };
-        let audio_handle_one: Handle<AudioSource> =
-            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(13290473942075938)));
-        let audio_handle_two: Handle<AudioSource> =
-            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(243290473942075938)));
+        track.set_route(send_track.id(), volume, tween).is_ok()
+    }
 
-        let channel = AudioChannel::<Audio>::default();
-        channel.play(audio_handle_one);
-        channel.stop();
-        channel.play(audio_handle_two.clone());
+    /// The channel that the given instance plays on
+    pub(crate) fn channel_of(&self, instance: &Handle<AudioInstance>) -> Option<&Channel> {
+        self.instances
+            .iter()
+            .find(|(_, instances)| instances.contains(instance))
+            .map(|(channel, _)| channel)
+    }
+
+    fn stop(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        tween: &Option<AudioTween>,
+        delay: Option<Duration>,
+    ) -> AudioCommandResult {
+        let equal_power = tween
+            .as_ref()
+            .filter(|tween| tween.is_equal_power() && delay.is_none());
+        let kira_tween = self.delayed_tween(tween, delay);
+        if let Some(instances) = self.instances.get_mut(channel) {
+            for instance in instances {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    let result = match equal_power {
+                        Some(tween) => instance.stop_with_tween(tween),
+                        None => instance.stop_with_kira_tween(kira_tween),
+                    };
+                    match result {
+                        Err(CommandError::CommandQueueFull) => {
+                            return AudioCommandResult::Retry;
+                        }
+                        Err(error) => {
+                            error!("Failed to stop instance: {:?}", error);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        if delay.is_none() {
+            self.chains.retain(|chain| &chain.channel != channel);
+        }
 
-        audio_output.play_channel(&audio_source_assets, &channel, &mut audio_instance_assets);
+        AudioCommandResult::Ok
+    }
 
-        let command = channel.commands.write().pop_back().unwrap();
-        match command {
-            AudioCommand::Play(settings) => {
-                assert_eq!(settings.source.id(), audio_handle_two.id())
+    /// Stop the given instances of a crossfade and cancel chained sounds of their channel
+    fn fade_out(
+        &mut self,
+        channel: &Channel,
+        instances: &[Handle<AudioInstance>],
+        audio_instances: &mut Assets<AudioInstance>,
+        tween: &AudioTween,
+    ) {
+        for instance in instances {
+            if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                if let Err(error) = instance.stop_with_tween(tween) {
+                    error!("Failed to fade out instance: {:?}", error);
+                }
             }
-            _ => panic!("Wrong audio command"),
         }
-        assert!(channel.commands.write().pop_back().is_none());
+        self.chains.retain(|chain| &chain.channel != channel);
     }
+
+    fn pause(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        tween: &Option<AudioTween>,
+        delay: Option<Duration>,
+    ) {
+        let tween = self.delayed_tween(tween, delay);
+        for (instance_channel, instance_handles) in self.instances.iter() {
+            if !self.is_within(instance_channel, channel) {
+                continue;
+            }
+            for instance in instance_handles.iter() {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    if kira::sound::PlaybackState::Playing == instance.handle.state() {
+                        if let Err(error) = instance.handle.pause(tween) {
+                            error!("Failed to pause instance: {:?}", error);
+                        }
+                    }
+                }
+            }
+        }
+        if delay.is_some() {
+            return;
+        }
+        self.update_channel_state(channel, |state| state.paused = true);
+    }
+
+    /// Change the state of a channel and remember to send an event if it changed
+    fn update_channel_state(&mut self, channel: &Channel, update: impl FnOnce(&mut ChannelState)) {
+        let state = self.channels.entry(channel.clone()).or_default();
+        let previous = state.clone();
+        update(state);
+        if *state != previous && !self.changed_channels.contains(channel) {
+            self.changed_channels.push(channel.clone());
+        }
+    }
+
+    /// Events for all channels whose state changed since the last call
+    fn channel_settings_changes(&mut self) -> Vec<ChannelSettingsChanged> {
+        let channels = &self.channels;
+        self.changed_channels
+            .drain(..)
+            .filter_map(|channel| {
+                let settings = channels.get(&channel)?.clone();
+                Some(ChannelSettingsChanged { channel, settings })
+            })
+            .collect()
+    }
+
+    /// Events for all channels that ran their last pending play command since the last call
+    fn play_commands_executed(&mut self) -> impl Iterator<Item = PlayCommandsExecuted> + '_ {
+        self.executed_play_commands
+            .drain(..)
+            .map(|channel| PlayCommandsExecuted { channel })
+    }
+
+    fn resume(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        tween: &Option<AudioTween>,
+        targets: ResumeTargets,
+    ) {
+        self.update_channel_state(channel, |state| state.paused = false);
+        let tween = map_tween(tween);
+        for (instance_channel, instances) in self.instances.iter() {
+            // sub-mixes stay paused if they or another parent are paused themselves
+            if !self.is_within(instance_channel, channel)
+                || self.effectively_paused(instance_channel)
+            {
+                continue;
+            }
+            for instance in instances.iter() {
+                let Some(instance) = audio_instances.get_mut(instance.id()) else {
+                    continue;
+                };
+                let state = instance.handle.state();
+                let result = if instance.stopping || state == kira::sound::PlaybackState::Stopping {
+                    if targets == ResumeTargets::Paused {
+                        continue;
+                    }
+                    instance.cancel_stop(tween)
+                } else if state == kira::sound::PlaybackState::Paused
+                    || state == kira::sound::PlaybackState::Pausing
+                {
+                    instance.handle.resume(tween)
+                } else {
+                    continue;
+                };
+                if let Err(error) = result {
+                    error!("Failed to resume instance: {:?}", error);
+                }
+            }
+        }
+    }
+
+    fn set_volume(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        volume: Volume,
+        tween: &Option<AudioTween>,
+    ) {
+        self.update_channel_state(channel, |state| state.volume = volume);
+        let tween = map_tween(tween);
+        for (instance_channel, instances) in self.instances.iter() {
+            if !self.is_within(instance_channel, channel) {
+                continue;
+            }
+            let volume = self.effective_volume(instance_channel);
+            for instance in instances.iter() {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    if let Err(error) = instance.set_volume_with_kira_tween(volume, tween) {
+                        error!("Failed to set volume for instance: {:?}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_panning(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        panning: Panning,
+        tween: &Option<AudioTween>,
+    ) {
+        if let Some(instances) = self.instances.get_mut(channel) {
+            let tween = map_tween(tween);
+            for instance in instances.iter_mut() {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    if let Err(error) = instance.set_panning_with_kira_tween(panning, tween) {
+                        error!("Failed to set panning for instance: {:?}", error);
+                    }
+                }
+            }
+        }
+        self.update_channel_state(channel, |state| state.panning = panning);
+    }
+
+    fn seek(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        position: SeekPosition,
+    ) {
+        if let Some(instances) = self.instances.get_mut(channel) {
+            for instance in instances.iter_mut() {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    instance.seeks += 1;
+                    let result = match position {
+                        SeekPosition::To(position) => instance.handle.seek_to(position),
+                        SeekPosition::By(amount) => instance.handle.seek_by(amount),
+                    };
+                    if let Err(error) = result {
+                        error!("Failed to seek instance: {:?}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Mute all instances but the given one, after unmuting the instances muted for a previous solo
+    fn set_solo(
+        &mut self,
+        solo: Option<Handle<AudioInstance>>,
+        audio_instances: &mut Assets<AudioInstance>,
+    ) {
+        for handle in self.muted_for_solo.drain(..) {
+            if let Some(instance) = audio_instances.get_mut(&handle) {
+                instance.unmute(AudioTween::default());
+            }
+        }
+        if let Some(solo) = &solo {
+            for handle in self.instances.values().flatten() {
+                if handle == solo {
+                    continue;
+                }
+                let Some(instance) = audio_instances.get_mut(handle) else {
+                    continue;
+                };
+                if !instance.is_muted() {
+                    instance.mute(AudioTween::default());
+                    self.muted_for_solo.push(handle.clone());
+                }
+            }
+        }
+        self.solo = solo;
+    }
+
+    fn set_playback_rate(
+        &mut self,
+        channel: &Channel,
+        audio_instances: &mut Assets<AudioInstance>,
+        playback_rate: f64,
+        tween: &Option<AudioTween>,
+    ) {
+        if let Some(instances) = self.instances.get_mut(channel) {
+            let tween = map_tween(tween);
+            for instance in instances.iter_mut() {
+                if let Some(instance) = audio_instances.get_mut(instance.id()) {
+                    if let Err(error) = instance.handle.set_playback_rate(playback_rate, tween) {
+                        error!("Failed to set playback rate for instance: {:?}", error);
+                    }
+                }
+            }
+        }
+        self.update_channel_state(channel, |state| state.playback_rate = playback_rate);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        &mut self,
+        channel: &Channel,
+        partial_sound_settings: &PartialSoundSettings,
+        audio_source: PlayableSource,
+        source_id: UntypedAssetId,
+        instance_handle: Handle<AudioInstance>,
+        audio_instances: &mut Assets<AudioInstance>,
+        rate_limits: Option<&mut SoundRateLimits>,
+    ) -> AudioCommandResult {
+        let mut start_time = None;
+        match partial_sound_settings.start {
+            Some(ScheduledStart::Tick(clock, ticks)) => match self.clocks.get(&clock) {
+                Some(Some(clock)) => {
+                    start_time = Some(ClockTime {
+                        clock: *clock,
+                        ticks,
+                    })
+                }
+                Some(None) => warn!("The clock of a sound was removed, playing it right away"),
+                // the clock is created by a command of another channel that did not run yet
+                None => return AudioCommandResult::Retry,
+            },
+            Some(ScheduledStart::Delay(delay)) => start_time = self.scheduled_time(delay),
+            Some(ScheduledStart::Clock(time)) => start_time = Some(time),
+            None => (),
+        }
+        if let (Some(category), Some(rate_limits)) = (&partial_sound_settings.category, rate_limits)
+        {
+            let mut active = if rate_limits.counts_active(category) {
+                self.active_in_category(category, audio_instances)
+            } else {
+                0
+            };
+            let exclusivity = rate_limits.exclusivity(category);
+            match exclusivity {
+                Some(Exclusivity::Ignore) if active > 0 => {
+                    debug!("Dropped sound, because its exclusive category '{category}' is playing");
+                    return self.drop_sound(channel, instance_handle);
+                }
+                Some(Exclusivity::Queue) if active > 0 => return AudioCommandResult::Retry,
+                // The playing sounds are only stopped if the new one passes the other rules
+                Some(Exclusivity::Restart) => active = 0,
+                _ => (),
+            }
+            if !rate_limits.try_start(category, active, Instant::now(), fastrand::f32()) {
+                debug!("Dropped sound due to the limit or rule of category '{category}'");
+                return self.drop_sound(channel, instance_handle);
+            }
+            if exclusivity == Some(Exclusivity::Restart) {
+                self.stop_category(category, audio_instances);
+            }
+        }
+        if let Some(&(max_instances, stealing)) = self.max_instances_per_source.get(channel) {
+            let source = Some(source_id);
+            if !self.make_room(channel, source, max_instances, stealing, audio_instances) {
+                debug!("Dropped sound due to the instance limit of its source");
+                return self.drop_sound(channel, instance_handle);
+            }
+        }
+        if let Some(&(max_instances, stealing)) = self.max_instances.get(channel) {
+            if !self.make_room(channel, None, max_instances, stealing, audio_instances) {
+                debug!("Dropped sound due to the instance limit of its channel");
+                return self.drop_sound(channel, instance_handle);
+            }
+        }
+        let mut settings = audio_source.settings();
+        if let Some(start_time) = start_time {
+            settings.start_time = StartTime::ClockTime(start_time);
+        }
+        if let Some(channel_state) = self.channels.get(channel) {
+            channel_state.apply(&mut settings);
+        }
+        if self.parents.contains_key(channel) {
+            settings.volume = Value::Fixed(self.effective_volume(channel));
+        }
+        partial_sound_settings.apply(&mut settings);
+        if let Some((start, end)) = partial_sound_settings.random_start {
+            let start = match end.or_else(|| audio_source.duration()) {
+                Some(end) if end > start => start + fastrand::f64() * (end - start),
+                _ => start,
+            };
+            settings.playback_region.start = start.into();
+        }
+        let start_paused = partial_sound_settings.paused || self.effectively_paused(channel);
+        // Kira cannot start sounds paused. Until our pause command goes through, the audio thread
+        // would already play the sound, so it starts at playback rate zero instead.
+        // The rate is restored right after pausing the sound handle.
+        if let Some(Some(track)) = self.tracks.get(channel) {
+            settings.output_destination = track.into();
+        }
+        let playback_rate = settings.playback_rate;
+        let volume = settings.volume;
+        let pan_law = self.pan_law(channel);
+        let mut pan_gain = 1.;
+        if let Value::Fixed(panning) = settings.panning {
+            let backend_panning;
+            (backend_panning, pan_gain) = pan_law.backend_panning(Panning::from_unit(panning));
+            settings.panning = Value::Fixed(backend_panning);
+            match settings.volume {
+                Value::Fixed(volume) if pan_gain != 1. => {
+                    settings.volume =
+                        Value::Fixed(Volume::Amplitude(volume.as_amplitude() * pan_gain));
+                }
+                _ => (),
+            }
+        }
+        let looped = settings.loop_region.is_some();
+        if start_paused {
+            settings.playback_rate = Value::Fixed(PlaybackRate::Factor(0.0));
+        }
+        let equal_power_fade_in = partial_sound_settings
+            .fade_in
+            .as_ref()
+            .filter(|fade_in| fade_in.is_equal_power());
+        if equal_power_fade_in.is_some() {
+            settings.volume = Value::Fixed(Volume::Amplitude(0.));
+        }
+        let manager = self.manager.as_mut().unwrap();
+        let sound_handle = match audio_source {
+            PlayableSource::Static(source) => manager
+                .play_static(source.sound.with_settings(settings))
+                .map(InstanceHandle::Static)
+                .map_err(|error| warn!("Failed to play sound due to {:?}", error)),
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayableSource::Streaming(source) => {
+                if settings.reverse {
+                    warn!("Streaming sounds cannot be played in reverse");
+                }
+                match source.sound(&settings) {
+                    Ok(sound) => manager
+                        .play_streaming(sound)
+                        .map(InstanceHandle::Streaming)
+                        .map_err(|error| warn!("Failed to play sound due to {:?}", error)),
+                    Err(error) => {
+                        warn!("Failed to decode streaming sound due to {:?}", error);
+                        Err(())
+                    }
+                }
+            }
+        };
+        let Ok(mut sound_handle) = sound_handle else {
+            return AudioCommandResult::Ok;
+        };
+        if start_paused {
+            if let Err(error) = sound_handle.pause(kira::tween::Tween::default()) {
+                warn!("Failed to pause instance due to {:?}", error);
+            }
+            if let Err(error) =
+                sound_handle.set_playback_rate(playback_rate, kira::tween::Tween::default())
+            {
+                warn!(
+                    "Failed to restore playback rate of paused instance due to {:?}, retrying next frame",
+                    error
+                );
+                self.playback_rate_repairs
+                    .push((instance_handle.clone(), playback_rate));
+            }
+        }
+        let mut instance = AudioInstance::new(sound_handle, volume);
+        instance.pan_law = pan_law;
+        instance.pan_gain = pan_gain;
+        if let Some(fade_in) = equal_power_fade_in {
+            let start = start_time.map_or(StartTime::Immediate, StartTime::ClockTime);
+            if let Err(error) = instance.equal_power_fade_in(fade_in, start) {
+                warn!("Failed to fade in sound due to {:?}", error);
+            }
+        }
+        if self
+            .solo
+            .as_ref()
+            .map_or(false, |solo| solo != &instance_handle)
+        {
+            instance.mute(AudioTween::default());
+            self.muted_for_solo.push(instance_handle.clone());
+        }
+        instance.category = partial_sound_settings.category.clone();
+        instance.source = Some(source_id);
+        instance.looped = looped;
+        if let Some(start_time) = start_time {
+            instance.waiting = true;
+            self.scheduled_instances
+                .push((instance_handle.clone(), start_time));
+        }
+        audio_instances.insert(&instance_handle, instance);
+        if let Some((max_duration, fade)) = self.max_instance_durations.get(channel) {
+            self.expiring_instances.push((
+                instance_handle.clone(),
+                Instant::now() + *max_duration,
+                fade.clone(),
+            ));
+        }
+        if let Some(instance_states) = self.instances.get_mut(channel) {
+            instance_states.push(instance_handle);
+        } else {
+            self.instances
+                .insert(channel.clone(), vec![instance_handle]);
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    /// Stop sounds of the channel according to the stealing policy until another sound fits
+    ///
+    /// If a source is given, only sounds of that source are counted and stopped.
+    /// Returns `false` if the new sound should be dropped.
+    fn make_room(
+        &mut self,
+        channel: &Channel,
+        source: Option<UntypedAssetId>,
+        max_instances: usize,
+        stealing: VoiceStealing,
+        audio_instances: &mut Assets<AudioInstance>,
+    ) -> bool {
+        let mut playing: Vec<_> = self
+            .instances
+            .get(channel)
+            .into_iter()
+            .flatten()
+            .filter(|handle| {
+                audio_instances.get(*handle).map_or(false, |instance| {
+                    instance.is_alive()
+                        && !instance.stopping
+                        && source.map_or(true, |source| instance.source == Some(source))
+                })
+            })
+            .cloned()
+            .collect();
+        if playing.len() < max_instances {
+            return true;
+        }
+        match stealing {
+            VoiceStealing::RejectNew => return false,
+            // Instances are tracked in the order they started
+            VoiceStealing::StopOldest => (),
+            VoiceStealing::StopQuietest => playing.sort_by(|a, b| {
+                let volume = |handle: &Handle<AudioInstance>| {
+                    audio_instances
+                        .get(handle)
+                        .map_or(0., AudioInstance::audible_volume)
+                };
+                volume(a).total_cmp(&volume(b))
+            }),
+        }
+        let excess = playing.len() + 1 - max_instances;
+        for handle in playing.iter().take(excess) {
+            if let Some(instance) = audio_instances.get_mut(handle) {
+                if let Err(error) = instance.stop_with_kira_tween(kira::tween::Tween::default()) {
+                    warn!("Failed to stop sound for a new one due to {:?}", error);
+                }
+            }
+        }
+
+        max_instances > 0
+    }
+
+    /// Playing or paused sounds of the given category
+    fn in_category<'a>(
+        &'a self,
+        category: &'a str,
+        audio_instances: &'a Assets<AudioInstance>,
+    ) -> impl Iterator<Item = &'a Handle<AudioInstance>> {
+        self.instances.values().flatten().filter(move |handle| {
+            audio_instances.get(*handle).map_or(false, |instance| {
+                instance.category.as_deref() == Some(category)
+                    && !instance.stopping
+                    && !matches!(
+                        instance.handle.state(),
+                        kira::sound::PlaybackState::Stopped | kira::sound::PlaybackState::Stopping
+                    )
+            })
+        })
+    }
+
+    /// Number of playing or paused sounds of the given category
+    fn active_in_category(&self, category: &str, audio_instances: &Assets<AudioInstance>) -> usize {
+        self.in_category(category, audio_instances).count()
+    }
+
+    /// Stop all playing or paused sounds of the given category
+    fn stop_category(&self, category: &str, audio_instances: &mut Assets<AudioInstance>) {
+        let playing: Vec<_> = self
+            .in_category(category, audio_instances)
+            .cloned()
+            .collect();
+        for handle in playing {
+            if let Some(instance) = audio_instances.get_mut(&handle) {
+                if let Err(error) = instance.stop_with_kira_tween(kira::tween::Tween::default()) {
+                    warn!("Failed to stop sound for a new one due to {:?}", error);
+                }
+            }
+        }
+    }
+
+    fn count_command(&mut self, result: &AudioCommandResult) {
+        self.command_counts.processed += 1;
+        if let AudioCommandResult::Retry = result {
+            self.command_counts.retried += 1;
+        }
+    }
+
+    /// Take the command counts since they were last taken
+    pub(crate) fn take_command_counts(&mut self) -> CommandCounts {
+        std::mem::take(&mut self.command_counts)
+    }
+
+    /// Number of tracked instances per channel
+    pub(crate) fn instance_counts(&self) -> impl Iterator<Item = (&Channel, usize)> {
+        self.instances
+            .iter()
+            .map(|(channel, instances)| (channel, instances.len()))
+    }
+
+    /// Returns `true` if the audio backend could be initialized
+    pub(crate) fn is_available(&self) -> bool {
+        self.manager.is_some()
+    }
+
+    pub(crate) fn play_channel<T: Resource>(
+        &mut self,
+        audio_sources: &AudioSources,
+        channel: &AudioChannel<T>,
+        audio_instances: &mut Assets<AudioInstance>,
+        mut rate_limits: Option<&mut SoundRateLimits>,
+    ) {
+        if self.manager.is_none() {
+            // like sounds sent as events, sounds played without audio output are dropped,
+            // unless the output is only waiting for user input
+            if !self.locked {
+                channel.commands.keep_channel_state();
+            }
+            return;
+        }
+        let channel_id = TypeId::of::<T>();
+        if !self.apply_channel_settings(&Channel::Typed(channel_id), &channel.settings)
+            || channel.commands.is_empty()
+        {
+            return;
+        }
+        #[cfg(feature = "trace")]
+        let _span =
+            bevy::log::info_span!("audio_channel", channel = std::any::type_name::<T>()).entered();
+        let frozen = channel.commands.is_frozen();
+        let mut commands = channel.commands.write();
+        let len = commands.len();
+        let channel = Channel::Typed(channel_id);
+        let mut commands_to_retry = std::mem::take(&mut self.commands_to_retry);
+        let mut had_play_commands = false;
+        let mut i = 0;
+        while i < len {
+            let audio_command = commands.pop_back().unwrap();
+            had_play_commands |= audio_command.is_play();
+            let held = match audio_command {
+                AudioCommand::Play(_) | AudioCommand::PlayMany(_) => self.hold_play_command(frozen),
+                _ => None,
+            };
+            let result = match held {
+                Some(result) => result,
+                None => self.run_audio_command(
+                    &audio_command,
+                    audio_sources,
+                    audio_instances,
+                    &channel,
+                    rate_limits.as_deref_mut(),
+                ),
+            };
+            self.count_command(&result);
+            if let AudioCommand::Stop(_, None) = audio_command {
+                commands_to_retry.clear();
+            }
+            if let AudioCommandResult::Retry = result {
+                commands_to_retry.push(audio_command);
+            }
+            i += 1;
+        }
+        commands_to_retry
+            .drain(..)
+            .for_each(|command| commands.push_front(command));
+        self.commands_to_retry = commands_to_retry;
+        if had_play_commands && !commands.iter().any(AudioCommand::is_play) {
+            self.executed_play_commands.push(channel);
+        }
+    }
+
+    pub(crate) fn play_dynamic_channels(
+        &mut self,
+        audio_sources: &AudioSources,
+        channels: &DynamicAudioChannels,
+        audio_instances: &mut Assets<AudioInstance>,
+        mut rate_limits: Option<&mut SoundRateLimits>,
+    ) {
+        if self.manager.is_none() {
+            if !self.locked {
+                channels.removed.lock().clear();
+                for dynamic_channel in channels.channels.values() {
+                    dynamic_channel.commands.keep_channel_state();
+                }
+            }
+            return;
+        }
+        for (key, tween) in channels.removed.lock().drain(..) {
+            let channel = Channel::Dynamic(key);
+            self.stop(&channel, audio_instances, &tween, None);
+        }
+        self.remove_dynamic_channels(channels);
+        for (key, dynamic_channel) in channels.channels.iter() {
+            // most channels are idle in most frames, so skip them before cloning their key
+            if dynamic_channel.commands.is_empty() && !dynamic_channel.settings.is_pending() {
+                continue;
+            }
+            let channel = Channel::Dynamic(key.clone());
+            if !self.apply_channel_settings(&channel, &dynamic_channel.settings)
+                || dynamic_channel.commands.is_empty()
+            {
+                continue;
+            }
+            #[cfg(feature = "trace")]
+            let _span = bevy::log::info_span!("audio_channel", channel = %key).entered();
+            let frozen = dynamic_channel.commands.is_frozen();
+            let mut commands = dynamic_channel.commands.write();
+            let len = commands.len();
+            let mut had_play_commands = false;
+            let mut i = 0;
+            while i < len {
+                let audio_command = commands.pop_back().unwrap();
+                had_play_commands |= audio_command.is_play();
+                let held = match audio_command {
+                    AudioCommand::Play(_) | AudioCommand::PlayMany(_) => {
+                        self.hold_play_command(frozen)
+                    }
+                    _ => None,
+                };
+                let result = match held {
+                    Some(result) => result,
+                    None => self.run_audio_command(
+                        &audio_command,
+                        audio_sources,
+                        audio_instances,
+                        &channel,
+                        rate_limits.as_deref_mut(),
+                    ),
+                };
+                self.count_command(&result);
+                if let AudioCommandResult::Retry = result {
+                    commands.push_front(audio_command);
+                }
+                i += 1;
+            }
+            if had_play_commands && !commands.iter().any(AudioCommand::is_play) {
+                self.executed_play_commands.push(channel);
+            }
+        }
+    }
+
+    /// Free all state of dynamic channels that were removed
+    ///
+    /// The instances and mixer track of a removed channel are kept until its sounds stopped, so
+    /// fade outs are not cut short.
+    fn remove_dynamic_channels(&mut self, channels: &DynamicAudioChannels) {
+        let removed = |channel: &Channel| match channel {
+            Channel::Dynamic(key) => !channels.channels.contains_key(key),
+            Channel::Typed(_) => false,
+        };
+        self.instances
+            .retain(|channel, instances| !removed(channel) || !instances.is_empty());
+        let instances = &self.instances;
+        self.tracks
+            .retain(|channel, _| !removed(channel) || instances.contains_key(channel));
+        self.parents.retain(|channel, _| !removed(channel));
+        self.channels.retain(|channel, _| !removed(channel));
+        self.max_instance_durations
+            .retain(|channel, _| !removed(channel));
+        self.max_instances.retain(|channel, _| !removed(channel));
+        self.max_instances_per_source
+            .retain(|channel, _| !removed(channel));
+        self.pan_laws.retain(|channel, _| !removed(channel));
+        self.meters.retain(|channel, _| !removed(channel));
+        self.captures.retain(|channel, _| !removed(channel));
+        #[cfg(feature = "analysis")]
+        self.spectrums.retain(|channel, _| !removed(channel));
+        self.tempos.retain(|channel, _| !removed(channel));
+        self.track_volumes.retain(|channel, _| !removed(channel));
+    }
+
+    /// Create the mixer track of a channel, if its settings changed
+    ///
+    /// Returns `false` while the channel is waiting for its parent channel to be set up.
+    fn apply_channel_settings(
+        &mut self,
+        channel: &Channel,
+        pending_settings: &PendingChannelSettings,
+    ) -> bool {
+        let mut pending_settings = pending_settings.lock();
+        let Some(settings) = pending_settings.as_ref() else {
+            return true;
+        };
+        let parent_track = match &settings.parent {
+            Some(parent) => match self.tracks.get(parent) {
+                Some(parent_track) => parent_track.as_ref().map(TrackHandle::id),
+                None => return false,
+            },
+            None => None,
+        };
+        let mut send_tracks = Vec::with_capacity(settings.sends.len());
+        for (send, volume) in &settings.sends {
+            match self.tracks.get(send) {
+                Some(Some(send_track)) => send_tracks.push((send_track.id(), *volume)),
+                Some(None) => {}
+                None => return false,
+            }
+        }
+        let Some(manager) = self.manager.as_mut() else {
+            return true;
+        };
+        let mut settings = pending_settings.take().unwrap();
+        match &settings.parent {
+            Some(parent) => self.parents.insert(channel.clone(), parent.clone()),
+            None => self.parents.remove(channel),
+        };
+        match settings.tempo.take() {
+            Some(tempo) => self.tempos.insert(channel.clone(), tempo),
+            None => self.tempos.remove(channel),
+        };
+        match settings.max_instance_duration.take() {
+            Some(max_instance_duration) => self
+                .max_instance_durations
+                .insert(channel.clone(), max_instance_duration),
+            None => self.max_instance_durations.remove(channel),
+        };
+        match settings.max_instances.take() {
+            Some(max_instances) => self.max_instances.insert(channel.clone(), max_instances),
+            None => self.max_instances.remove(channel),
+        };
+        match settings.max_instances_per_source.take() {
+            Some(max_instances) => self
+                .max_instances_per_source
+                .insert(channel.clone(), max_instances),
+            None => self.max_instances_per_source.remove(channel),
+        };
+        match settings.pan_law.take() {
+            Some(pan_law) => self.pan_laws.insert(channel.clone(), pan_law),
+            None => self.pan_laws.remove(channel),
+        };
+        match settings.meter.take() {
+            Some(meter) => self.meters.insert(channel.clone(), meter),
+            None => self.meters.remove(channel),
+        };
+        match settings.capture.take() {
+            Some(capture) => self.captures.insert(channel.clone(), capture),
+            None => self.captures.remove(channel),
+        };
+        #[cfg(feature = "analysis")]
+        match settings.spectrum.take() {
+            Some(spectrum) => self.spectrums.insert(channel.clone(), spectrum),
+            None => self.spectrums.remove(channel),
+        };
+        let track = manager
+            .add_sub_track(settings.into_track_builder(parent_track, send_tracks))
+            .map_err(|error| {
+                warn!(
+                    "Failed to create mixer track for channel due to {:?}, playing its sounds on the main track",
+                    error
+                )
+            })
+            .ok();
+        self.tracks.insert(channel.clone(), track);
+
+        true
+    }
+
+    /// The source mixed down to mono, computed once per source
+    fn mono_source(&mut self, id: AssetId<AudioSource>, source: &AudioSource) -> AudioSource {
+        let frames = match self.mono_sources.get(&id) {
+            Some((original, mono)) if Arc::ptr_eq(original, &source.sound.frames) => mono.clone(),
+            _ => {
+                let stereo = source
+                    .sound
+                    .frames
+                    .iter()
+                    .any(|frame| frame.left != frame.right);
+                let mono = if stereo {
+                    warn!(
+                        "Mixing a stereo sound down to mono for spatial audio. Use mono sound files \
+                        to skip this step, or add `KeepStereo` to the emitter to keep them in stereo."
+                    );
+                    source.to_mono().sound.frames
+                } else {
+                    source.sound.frames.clone()
+                };
+                self.mono_sources
+                    .insert(id, (source.sound.frames.clone(), mono.clone()));
+                mono
+            }
+        };
+
+        AudioSource {
+            sound: StaticSoundData {
+                frames,
+                ..source.sound.clone()
+            },
+        }
+    }
+
+    /// Play the intro right away and the body on a clock that ticks when the intro ends
+    #[allow(clippy::too_many_arguments)]
+    fn play_with_intro(
+        &mut self,
+        channel: &Channel,
+        partial_sound_settings: &PartialSoundSettings,
+        intro: &IntroSound,
+        intro_source: &AudioSource,
+        body_source: PlayableSource,
+        body_id: UntypedAssetId,
+        instance_handle: Handle<AudioInstance>,
+        audio_instances: &mut Assets<AudioInstance>,
+        rate_limits: Option<&mut SoundRateLimits>,
+    ) -> AudioCommandResult {
+        let Some(manager) = self.manager.as_mut() else {
+            return AudioCommandResult::Ok;
+        };
+        let intro_duration = intro_source.sound.duration().as_secs_f64()
+            / partial_sound_settings.playback_rate.unwrap_or(1.).abs();
+        let clock = match manager.add_clock(ClockSpeed::SecondsPerTick(intro_duration)) {
+            Ok(clock) => clock,
+            Err(error) => {
+                warn!(
+                    "Failed to add intro clock due to {:?}, playing the loop body right away",
+                    error
+                );
+                return self.play(
+                    channel,
+                    partial_sound_settings,
+                    body_source,
+                    body_id,
+                    instance_handle,
+                    audio_instances,
+                    rate_limits,
+                );
+            }
+        };
+        let clock_id = clock.id();
+
+        let mut intro_settings = partial_sound_settings.clone();
+        intro_settings.loop_start = None;
+        intro_settings.loop_end = None;
+        intro_settings.start = Some(ScheduledStart::Clock(ClockTime {
+            clock: clock_id,
+            ticks: 0,
+        }));
+        let result = self.play(
+            channel,
+            &intro_settings,
+            PlayableSource::Static(intro_source),
+            intro.source.id().untyped(),
+            intro.instance_handle.clone(),
+            audio_instances,
+            rate_limits,
+        );
+        let Some(intro_instance) = audio_instances.get_mut(&intro.instance_handle) else {
+            // the intro was dropped by a rate limit or failed to play, and the body with it
+            if matches!(result, AudioCommandResult::Ok) {
+                return self.drop_sound(channel, instance_handle);
+            }
+            return result;
+        };
+        intro_instance.source_path = intro.source.path().map(|path| (path.clone(), false));
+
+        let mut body_settings = partial_sound_settings.clone();
+        body_settings.fade_in = None;
+        body_settings.category = None;
+        body_settings.start = Some(ScheduledStart::Clock(ClockTime {
+            clock: clock_id,
+            ticks: 1,
+        }));
+        let result = self.play(
+            channel,
+            &body_settings,
+            body_source,
+            body_id,
+            instance_handle,
+            audio_instances,
+            None,
+        );
+        if let Err(error) = clock.start() {
+            warn!("Failed to start intro clock due to {:?}", error);
+        }
+        self.intro_clocks.push(clock);
+
+        result
+    }
+
+    fn run_play_command(
+        &mut self,
+        play_args: &PlayAudioSettings,
+        audio_sources: &AudioSources,
+        audio_instances: &mut Assets<AudioInstance>,
+        channel: &Channel,
+        rate_limits: Option<&mut SoundRateLimits>,
+    ) -> AudioCommandResult {
+        if let Some(condition) = &play_args.condition {
+            if !condition.is_met() {
+                return AudioCommandResult::Retry;
+            }
+        }
+        if let Some(audio_source) = audio_sources.get(&play_args.source) {
+            let resolved = play_args.settings.resolve_variations();
+            let settings = resolved.as_ref().unwrap_or(&play_args.settings);
+            let mut intro_source = match &play_args.intro {
+                Some(intro) => match audio_sources.sources.get(&intro.source) {
+                    Some(intro_source) => Some((intro, intro_source)),
+                    // audio source hasn't loaded yet. Add it back to the queue
+                    None => return AudioCommandResult::Retry,
+                },
+                None => None,
+            };
+            let (mono_body, mono_intro);
+            let mut audio_source = audio_source;
+            if settings.mono {
+                if let (AudioSourceHandle::Static(handle), PlayableSource::Static(source)) =
+                    (&play_args.source, &audio_source)
+                {
+                    mono_body = self.mono_source(handle.id(), source);
+                    audio_source = PlayableSource::Static(&mono_body);
+                }
+                if let Some((intro, source)) = intro_source {
+                    mono_intro = self.mono_source(intro.source.id(), source);
+                    intro_source = Some((intro, &mono_intro));
+                }
+            }
+            let fading_out = match settings.crossfade {
+                Some(_) => self.instances.get(channel).cloned().unwrap_or_default(),
+                None => vec![],
+            };
+            let result = match intro_source {
+                Some((intro, intro_source)) => self.play_with_intro(
+                    channel,
+                    settings,
+                    intro,
+                    intro_source,
+                    audio_source,
+                    play_args.source.id(),
+                    play_args.instance_handle.clone(),
+                    audio_instances,
+                    rate_limits,
+                ),
+                None => self.play(
+                    channel,
+                    settings,
+                    audio_source,
+                    play_args.source.id(),
+                    play_args.instance_handle.clone(),
+                    audio_instances,
+                    rate_limits,
+                ),
+            };
+            if let (Some(tween), AudioCommandResult::Ok) = (&settings.crossfade, &result) {
+                self.fade_out(channel, &fading_out, audio_instances, tween);
+            }
+            if let (AudioSourceHandle::Static(source), Some(instance)) = (
+                &play_args.source,
+                audio_instances.get(&play_args.instance_handle),
+            ) {
+                let after = settings.stop_when_silent.filter(|_| instance.looped);
+                let reversed = settings.reverse == Some(true)
+                    || settings.playback_rate.map_or(false, |rate| rate < 0.);
+                if after.is_some() || (self.max_audible_instances.is_some() && !reversed) {
+                    self.virtual_instances.push(VirtualInstance {
+                        instance: play_args.instance_handle.clone(),
+                        channel: channel.clone(),
+                        source: source.clone(),
+                        settings: settings.clone(),
+                        after,
+                        silent_since: None,
+                        stopped_at: None,
+                    });
+                }
+            }
+            if let Some(instance) = audio_instances.get_mut(&play_args.instance_handle) {
+                instance.source_path = play_args
+                    .source
+                    .path()
+                    .map(|(path, streaming)| (path.clone(), streaming));
+            }
+            if matches!(result, AudioCommandResult::Ok) && !play_args.chain.is_empty() {
+                let previous_duration = match audio_source {
+                    PlayableSource::Static(source)
+                        if settings.loop_start.is_none()
+                            && source.sound.settings.loop_region.is_none() =>
+                    {
+                        Some(source.sound.duration().as_secs_f64())
+                    }
+                    _ => None,
+                };
+                self.chains.push(PendingChain {
+                    channel: channel.clone(),
+                    previous: play_args.instance_handle.clone(),
+                    previous_duration,
+                    finished_at: None,
+                    sounds: play_args.chain.iter().cloned().collect(),
+                });
+            }
+            result
+        } else {
+            // audio source hasn't loaded yet. Add it back to the queue
+            AudioCommandResult::Retry
+        }
+    }
+
+    pub(crate) fn run_audio_command(
+        &mut self,
+        audio_command: &AudioCommand,
+        audio_sources: &AudioSources,
+        audio_instances: &mut Assets<AudioInstance>,
+        channel: &Channel,
+        rate_limits: Option<&mut SoundRateLimits>,
+    ) -> AudioCommandResult {
+        #[cfg(feature = "trace")]
+        let _span = audio_command.span(channel).entered();
+        match audio_command {
+            AudioCommand::Play(play_args) => self.run_play_command(
+                play_args,
+                audio_sources,
+                audio_instances,
+                channel,
+                rate_limits,
+            ),
+            AudioCommand::PlayMany(sounds) => {
+                // the sounds start together, so wait until all of them are loaded
+                if sounds
+                    .iter()
+                    .any(|sound| audio_sources.get(&sound.source).is_none())
+                {
+                    return AudioCommandResult::Retry;
+                }
+                let mut rate_limits = rate_limits;
+                for sound in sounds {
+                    self.run_play_command(
+                        sound,
+                        audio_sources,
+                        audio_instances,
+                        channel,
+                        rate_limits.as_deref_mut(),
+                    );
+                }
+                AudioCommandResult::Ok
+            }
+            AudioCommand::AddClock(speed, clock) => {
+                self.add_clock(*speed, *clock);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::Stop(tween, delay) => self.stop(channel, audio_instances, tween, *delay),
+            AudioCommand::Pause(tween, delay) => {
+                // paused by the game, so regaining the focus should not resume it
+                if let Some(unfocused_channels) = self.unfocused_channels.as_mut() {
+                    unfocused_channels.retain(|unfocused| unfocused != channel);
+                }
+                self.pause(channel, audio_instances, tween, *delay);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::Resume(tween, targets) => {
+                self.resume(channel, audio_instances, tween, *targets);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::SetVolume(volume, tween) => {
+                self.set_volume(channel, audio_instances, *volume, tween);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::SetPanning(panning, tween) => {
+                self.set_panning(channel, audio_instances, *panning, tween);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::SetPlaybackRate(playback_rate, tween) => {
+                self.set_playback_rate(channel, audio_instances, *playback_rate, tween);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::Seek(position) => {
+                self.seek(channel, audio_instances, *position);
+                AudioCommandResult::Ok
+            }
+            AudioCommand::Solo(solo) => {
+                self.set_solo(solo.clone(), audio_instances);
+                AudioCommandResult::Ok
+            }
+        }
+    }
+
+    /// Run commands sent as events, after the ones from earlier frames that were not ready
+    pub(crate) fn run_event_commands(
+        &mut self,
+        commands: impl IntoIterator<Item = (Channel, AudioCommand)>,
+        audio_sources: &AudioSources,
+        audio_instances: &mut Assets<AudioInstance>,
+        mut rate_limits: Option<&mut SoundRateLimits>,
+    ) {
+        if self.manager.is_none() {
+            if self.locked {
+                self.event_commands.extend(commands);
+            }
+            return;
+        }
+        let mut pending = std::mem::take(&mut self.event_commands);
+        pending.extend(commands);
+        for (channel, audio_command) in pending {
+            let held = match audio_command {
+                AudioCommand::Play(_) | AudioCommand::PlayMany(_) => self.hold_play_command(false),
+                _ => None,
+            };
+            let result = match held {
+                Some(result) => result,
+                None => self.run_audio_command(
+                    &audio_command,
+                    audio_sources,
+                    audio_instances,
+                    &channel,
+                    rate_limits.as_deref_mut(),
+                ),
+            };
+            self.count_command(&result);
+            if let AudioCommand::Stop(_, None) = audio_command {
+                self.event_commands
+                    .retain(|(retried_channel, _)| retried_channel != &channel);
+            }
+            if let AudioCommandResult::Retry = result {
+                self.event_commands.push((channel, audio_command));
+            }
+        }
+    }
+
+    pub(crate) fn play_chained_sounds(
+        &mut self,
+        audio_sources: &Assets<AudioSource>,
+        audio_instances: &mut Assets<AudioInstance>,
+        mut rate_limits: Option<&mut SoundRateLimits>,
+        now: Instant,
+    ) {
+        if self.chains.is_empty() {
+            return;
+        }
+        let mut chains = std::mem::take(&mut self.chains);
+        chains.retain_mut(|chain| {
+            let Some(next) = chain.sounds.front() else {
+                return false;
+            };
+            let previous = audio_instances.get(&chain.previous);
+            let finished = previous.map_or(true, |instance| {
+                instance.handle.state() == kira::sound::PlaybackState::Stopped
+            });
+            let ready = match next.transition {
+                ChainTransition::Immediate => finished,
+                ChainTransition::Gap(gap) => {
+                    finished && now.duration_since(*chain.finished_at.get_or_insert(now)) >= gap
+                }
+                ChainTransition::Overlap(overlap) => {
+                    finished
+                        || match (previous, chain.previous_duration) {
+                            (Some(instance), Some(duration)) => {
+                                instance.handle.position() >= duration - overlap.as_secs_f64()
+                            }
+                            _ => false,
+                        }
+                }
+            };
+            if !ready {
+                return true;
+            }
+            let Some(source) = audio_sources.get(&next.source) else {
+                // audio source hasn't loaded yet. Try again next frame
+                return true;
+            };
+            let result = self.play(
+                &chain.channel,
+                &next.settings,
+                PlayableSource::Static(source),
+                next.source.id().untyped(),
+                next.instance_handle.clone(),
+                audio_instances,
+                rate_limits.as_deref_mut(),
+            );
+            if let AudioCommandResult::Retry = result {
+                // waiting for an exclusive category
+                return true;
+            }
+            let next = chain.sounds.pop_front().unwrap();
+            if let Some(instance) = audio_instances.get_mut(&next.instance_handle) {
+                instance.source_path = next.source.path().map(|path| (path.clone(), false));
+            }
+            chain.previous = next.instance_handle;
+            chain.previous_duration = (next.settings.loop_start.is_none()
+                && source.sound.settings.loop_region.is_none())
+            .then(|| source.sound.duration().as_secs_f64());
+            chain.finished_at = None;
+
+            !chain.sounds.is_empty()
+        });
+        self.chains = chains;
+    }
+
+    /// Stop sounds that are silent or over the voice cap, and restart them in phase once they
+    /// are audible and there is room again
+    pub(crate) fn virtualize_instances(
+        &mut self,
+        audio_sources: &Assets<AudioSource>,
+        audio_instances: &mut Assets<AudioInstance>,
+        now: Instant,
+    ) {
+        const SILENCE: f64 = 0.001;
+        if self.virtual_instances.is_empty() {
+            return;
+        }
+        let mut virtual_instances = std::mem::take(&mut self.virtual_instances);
+        virtual_instances.retain(|tracked| {
+            let Some(instance) = audio_instances.get_mut(&tracked.instance) else {
+                return false;
+            };
+            let (Some(position), Some(source)) =
+                (instance.virtualized, audio_sources.get(&tracked.source))
+            else {
+                return instance.is_alive();
+            };
+            if tracked.position(source, position, now).is_none() {
+                // the sound finished while it was stopped
+                instance.virtualized = None;
+                return false;
+            }
+
+            true
+        });
+
+        // Sounds that may play with their priority and volume, and whether they are stopped
+        let mut candidates = vec![];
+        for (index, tracked) in virtual_instances.iter_mut().enumerate() {
+            let Some(instance) = audio_instances.get_mut(&tracked.instance) else {
+                continue;
+            };
+            let volume = instance.audible_volume()
+                * self
+                    .track_volumes
+                    .get(&tracked.channel)
+                    .map_or(1., Volume::as_amplitude);
+            if volume > SILENCE {
+                tracked.silent_since = None;
+            } else {
+                tracked.silent_since.get_or_insert(now);
+            }
+            let silent_for_long = match (tracked.after, tracked.silent_since) {
+                (Some(after), Some(silent_since)) => now.duration_since(silent_since) >= after,
+                _ => false,
+            };
+            let stopped = instance.virtualized.is_some();
+            if silent_for_long {
+                if !stopped {
+                    tracked.virtualize(instance, now);
+                }
+                continue;
+            }
+            if stopped
+                || (!instance.stopping && matches!(instance.state(), PlaybackState::Playing { .. }))
+            {
+                candidates.push((index, tracked.settings.priority, volume, stopped));
+            }
+        }
+
+        let room = match self.max_audible_instances {
+            Some(max_audible_instances) => {
+                let candidate_ids: HashSet<_> = candidates
+                    .iter()
+                    .map(|(index, ..)| virtual_instances[*index].instance.id())
+                    .collect();
+                // Sounds that cannot be stopped and restarted still take up room
+                let fixed = self
+                    .instances
+                    .values()
+                    .flatten()
+                    .filter(|handle| !candidate_ids.contains(&handle.id()))
+                    .filter_map(|handle| audio_instances.get(handle))
+                    .filter(|instance| instance.virtualized.is_none() && instance.is_alive())
+                    .count();
+                max_audible_instances.saturating_sub(fixed)
+            }
+            None => usize::MAX,
+        };
+        // Higher priority first, then louder, then sounds that are already playing
+        candidates.sort_by(
+            |(_, priority_a, volume_a, stopped_a), (_, priority_b, volume_b, stopped_b)| {
+                priority_b
+                    .cmp(priority_a)
+                    .then(volume_b.total_cmp(volume_a))
+                    .then(stopped_a.cmp(stopped_b))
+            },
+        );
+        let mut restart = vec![];
+        for (rank, (index, _, _, stopped)) in candidates.into_iter().enumerate() {
+            match (rank < room, stopped) {
+                (true, true) => restart.push(index),
+                (false, false) => {
+                    let tracked = &mut virtual_instances[index];
+                    if let Some(instance) = audio_instances.get_mut(&tracked.instance) {
+                        tracked.virtualize(instance, now);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for index in restart {
+            let tracked = &mut virtual_instances[index];
+            let (Some(position), Some(source)) = (
+                audio_instances
+                    .get(&tracked.instance)
+                    .and_then(|instance| instance.virtualized),
+                audio_sources.get(&tracked.source),
+            ) else {
+                continue;
+            };
+            let Some(restart_position) = tracked.position(source, position, now) else {
+                continue;
+            };
+            let previous = audio_instances.remove(&tracked.instance).unwrap();
+            if let Some(instances) = self.instances.get_mut(&tracked.channel) {
+                instances.retain(|handle| handle != &tracked.instance);
+            }
+            let mut settings = tracked.settings.clone();
+            settings.start_position = Some(restart_position);
+            settings.random_start = None;
+            settings.start = None;
+            settings.fade_in = None;
+            settings.crossfade = None;
+            settings.category = None;
+            let mono;
+            let source = if settings.mono {
+                mono = self.mono_source(tracked.source.id(), source);
+                &mono
+            } else {
+                source
+            };
+            self.play(
+                &tracked.channel,
+                &settings,
+                PlayableSource::Static(source),
+                tracked.source.id().untyped(),
+                tracked.instance.clone(),
+                audio_instances,
+                None,
+            );
+            let Some(instance) = audio_instances.get_mut(&tracked.instance) else {
+                continue;
+            };
+            instance.restore(previous);
+            tracked.silent_since = None;
+            tracked.stopped_at = None;
+        }
+        self.virtual_instances = virtual_instances;
+    }
+
+    /// Report a sound that was dropped before it started as stopped
+    fn drop_sound(
+        &mut self,
+        channel: &Channel,
+        handle: Handle<AudioInstance>,
+    ) -> AudioCommandResult {
+        self.dropped_instances.push(AudioInstanceStopped {
+            handle,
+            channel: channel.clone(),
+        });
+
+        AudioCommandResult::Ok
+    }
+
+    /// Stop tracking instances that stopped or were removed and return them
+    ///
+    /// Sounds that were dropped before they started are returned as well.
+    pub(crate) fn cleanup_stopped_instances(
+        &mut self,
+        instances: &mut Assets<AudioInstance>,
+    ) -> Vec<AudioInstanceStopped> {
+        let mut stopped = std::mem::take(&mut self.dropped_instances);
+        for (channel, handles) in self.instances.iter_mut() {
+            handles.retain(|handle| {
+                let playing = instances.get(handle).map_or(false, AudioInstance::is_alive);
+                if !playing {
+                    stopped.push(AudioInstanceStopped {
+                        handle: handle.clone(),
+                        channel: channel.clone(),
+                    });
+                }
+
+                playing
+            });
+        }
+
+        stopped
+    }
+
+    /// Was the channel active when activity was last checked?
+    pub(crate) fn is_channel_active(&self, channel: &Channel) -> bool {
+        self.active_channels.contains(channel)
+    }
+
+    /// Find all channels that went from silent to active or back since the last call
+    pub(crate) fn channel_activity(
+        &mut self,
+        audio_instances: &Assets<AudioInstance>,
+    ) -> Vec<ChannelActivity> {
+        let mut activity = vec![];
+        let mut active_channels = HashSet::default();
+        for (channel, instances) in self.instances.iter() {
+            let active_instances = instances
+                .iter()
+                .filter_map(|instance| audio_instances.get(instance))
+                .filter(|instance| {
+                    matches!(
+                        instance.state(),
+                        PlaybackState::Playing { .. }
+                            | PlaybackState::Pausing { .. }
+                            | PlaybackState::Stopping { .. }
+                    )
+                })
+                .count();
+            if active_instances == 0 {
+                continue;
+            }
+            if !self.active_channels.contains(channel) {
+                activity.push(ChannelActivity {
+                    channel: channel.clone(),
+                    active_instances,
+                });
+            }
+            active_channels.insert(channel.clone());
+        }
+        for channel in self.active_channels.difference(&active_channels) {
+            activity.push(ChannelActivity {
+                channel: channel.clone(),
+                active_instances: 0,
+            });
+        }
+        self.active_channels = active_channels;
+
+        activity
+    }
+
+    /// Find all playing instances of channels with a tempo that reached a new beat
+    pub(crate) fn music_beats(
+        &mut self,
+        audio_instances: &Assets<AudioInstance>,
+        output_latency: Duration,
+    ) -> Vec<(MusicBeat, Option<MusicBar>)> {
+        let mut events = vec![];
+        let mut beats = HashMap::default();
+        for (channel, tempo) in self.tempos.iter() {
+            let Some(instances) = self.instances.get(channel) else {
+                continue;
+            };
+            for instance_handle in instances {
+                let Some(instance) = audio_instances.get(instance_handle) else {
+                    continue;
+                };
+                let position = match instance.state() {
+                    PlaybackState::Playing { position } => {
+                        Some(position - output_latency.as_secs_f64())
+                    }
+                    _ => None,
+                };
+                let Some(position) = position.filter(|position| *position >= 0.) else {
+                    if let Some(beat) = self.beats.get(&instance_handle.id()) {
+                        beats.insert(instance_handle.id(), *beat);
+                    }
+                    continue;
+                };
+                let beat = tempo.beat_at(position);
+                if self.beats.get(&instance_handle.id()) != Some(&beat) {
+                    events.push(tempo.beat_event(channel, instance_handle, beat));
+                }
+                beats.insert(instance_handle.id(), beat);
+            }
+        }
+        self.beats = beats;
+
+        events
+    }
+
+    /// Find all looping instances that wrapped from the end of their loop back to the start
+    pub(crate) fn loop_completions(
+        &mut self,
+        audio_instances: &Assets<AudioInstance>,
+    ) -> Vec<LoopCompleted> {
+        let mut events = vec![];
+        let mut loop_positions = HashMap::default();
+        for (channel, instances) in self.instances.iter() {
+            for instance_handle in instances {
+                let Some(instance) = audio_instances.get(instance_handle) else {
+                    continue;
+                };
+                if !instance.looped {
+                    continue;
+                }
+                let position = instance.handle.position();
+                let (last_position, mut loops, seeks) = self
+                    .loop_positions
+                    .get(&instance_handle.id())
+                    .copied()
+                    .unwrap_or((position, 0, instance.seeks));
+                if position < last_position && seeks == instance.seeks {
+                    loops += 1;
+                    events.push(LoopCompleted {
+                        instance: instance_handle.clone(),
+                        channel: channel.clone(),
+                        loops,
+                    });
+                }
+                loop_positions.insert(instance_handle.id(), (position, loops, instance.seeks));
+            }
+        }
+        self.loop_positions = loop_positions;
+
+        events
+    }
+
+    /// Stop instances that exceeded the maximum instance duration of their channel
+    pub(crate) fn stop_expired_instances(
+        &mut self,
+        instances: &mut Assets<AudioInstance>,
+        now: Instant,
+    ) {
+        self.expiring_instances.retain(|(handle, deadline, fade)| {
+            let Some(instance) = instances.get_mut(handle) else {
+                return false;
+            };
+            if instance.handle.state() == kira::sound::PlaybackState::Stopped {
+                return false;
+            }
+            if now < *deadline {
+                return true;
+            }
+            match instance.stop_with_tween(fade) {
+                Ok(()) => false,
+                Err(CommandError::CommandQueueFull) => true,
+                Err(error) => {
+                    error!("Failed to stop expired instance: {:?}", error);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Stop instances at the end of their equal power fade-out
+    pub(crate) fn stop_faded_out_instances(
+        &mut self,
+        instances: &mut Assets<AudioInstance>,
+        now: Instant,
+    ) {
+        for handle in self.instances.values().flatten() {
+            let faded_out = instances
+                .get(handle)
+                .and_then(|instance| instance.stop_at)
+                .map_or(false, |stop_at| stop_at <= now);
+            if !faded_out {
+                continue;
+            }
+            let instance = instances.get_mut(handle).unwrap();
+            match instance.stop_with_kira_tween(kira::tween::Tween::default()) {
+                Ok(()) => instance.stop_at = None,
+                Err(CommandError::CommandQueueFull) => (),
+                Err(error) => {
+                    error!("Failed to stop faded out instance: {:?}", error);
+                    instance.stop_at = None;
+                }
+            }
+        }
+    }
+
+    /// Re-issue playback rates that could not be restored after starting an instance paused
+    ///
+    /// Paused sounds are started at playback rate zero. If restoring the rate fails, the
+    /// instance would silently stay stuck at rate zero after resuming it.
+    pub(crate) fn repair_playback_rates(&mut self, instances: &mut Assets<AudioInstance>) {
+        self.playback_rate_repairs
+            .retain(|(handle, playback_rate)| {
+                let Some(instance) = instances.get_mut(handle) else {
+                    return false;
+                };
+                match instance
+                    .handle
+                    .set_playback_rate(*playback_rate, kira::tween::Tween::default())
+                {
+                    Err(CommandError::CommandQueueFull) => true,
+                    Err(error) => {
+                        error!("Failed to restore playback rate for instance: {:?}", error);
+                        false
+                    }
+                    Ok(()) => false,
+                }
+            });
+    }
+}
+
+pub(crate) fn retry_audio_setup(
+    mut audio_output: NonSendMut<AudioOutput>,
+    device: Res<AudioOutputDevice>,
+) {
+    audio_output.retry_setup(device.name.as_deref(), Instant::now());
+}
+
+pub(crate) fn play_dynamic_channels(
+    mut audio_output: NonSendMut<AudioOutput>,
+    channels: Res<DynamicAudioChannels>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    #[cfg(not(target_arch = "wasm32"))] streaming_sources: Option<
+        Res<Assets<StreamingAudioSource>>,
+    >,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut rate_limits: Option<ResMut<SoundRateLimits>>,
+) {
+    if let Some(audio_sources) = audio_sources {
+        let audio_sources = AudioSources {
+            sources: &audio_sources,
+            #[cfg(not(target_arch = "wasm32"))]
+            streaming_sources: streaming_sources.as_deref(),
+        };
+        audio_output.play_dynamic_channels(
+            &audio_sources,
+            &channels,
+            &mut audio_instances,
+            rate_limits.as_deref_mut(),
+        );
+    };
+}
+
+pub(crate) fn play_audio_channel<T: Resource>(
+    mut audio_output: NonSendMut<AudioOutput>,
+    channel: Res<AudioChannel<T>>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    #[cfg(not(target_arch = "wasm32"))] streaming_sources: Option<
+        Res<Assets<StreamingAudioSource>>,
+    >,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    mut rate_limits: Option<ResMut<SoundRateLimits>>,
+) {
+    if let Some(audio_sources) = audio_sources {
+        let audio_sources = AudioSources {
+            sources: &audio_sources,
+            #[cfg(not(target_arch = "wasm32"))]
+            streaming_sources: streaming_sources.as_deref(),
+        };
+        audio_output.play_channel(
+            &audio_sources,
+            &channel,
+            &mut instances,
+            rate_limits.as_deref_mut(),
+        );
+    };
+}
+
+/// The one-shot system playing the channel `T`, registered on first dispatch
+#[derive(Resource)]
+struct ChannelDispatch<T> {
+    system: SystemId,
+    _channel: PhantomData<fn() -> T>,
+}
+
+/// Exclusive system running the pending commands of the channel `T` right away
+///
+/// Channel commands normally run in [`PostUpdate`](bevy::app::PostUpdate). Run this system
+/// directly after a system queuing latency critical sounds, like gunshots in a competitive game,
+/// to send them to the audio thread without waiting for the rest of the frame.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Gunshots;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Gunshots>()
+///     .add_systems(Update, (shoot, dispatch_audio_channel::<Gunshots>).chain());
+///
+/// fn shoot(gunshots: Res<AudioChannel<Gunshots>>, asset_server: Res<AssetServer>) {
+///     gunshots.play(asset_server.load("sounds/gunshot.ogg"));
+/// }
+/// ```
+/// As an exclusive system, it cannot run in parallel with other systems.
+pub fn dispatch_audio_channel<T: Resource>(world: &mut World) {
+    let system = match world.get_resource::<ChannelDispatch<T>>() {
+        Some(dispatch) => dispatch.system,
+        None => {
+            let system = world.register_system(play_audio_channel::<T>);
+            world.insert_resource(ChannelDispatch::<T> {
+                system,
+                _channel: PhantomData,
+            });
+            system
+        }
+    };
+    if let Err(error) = world.run_system(system) {
+        warn!("Failed to dispatch audio channel due to {:?}", error);
+    }
+}
+
+pub(crate) fn play_chained_sounds(
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut rate_limits: Option<ResMut<SoundRateLimits>>,
+) {
+    if let Some(audio_sources) = audio_sources {
+        audio_output.play_chained_sounds(
+            &audio_sources,
+            &mut audio_instances,
+            rate_limits.as_deref_mut(),
+            Instant::now(),
+        );
+    }
+}
+
+pub(crate) fn virtualize_instances(
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    if let Some(audio_sources) = audio_sources {
+        audio_output.virtualize_instances(&audio_sources, &mut audio_instances, Instant::now());
+    }
+}
+
+pub(crate) fn apply_global_audio(
+    mut audio_output: NonSendMut<AudioOutput>,
+    global_audio: Res<GlobalAudio>,
+) {
+    audio_output.apply_global_audio(&global_audio);
+}
+
+pub(crate) fn ramp_up_audio(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut ramps: EventReader<RampUpAudio>,
+) {
+    if let Some(ramp) = ramps.read().last() {
+        audio_output.ramp_up(ramp.duration);
+    }
+}
+
+pub(crate) fn cleanup_stopped_instances(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    mut stopped: EventWriter<AudioInstanceStopped>,
+) {
+    audio_output.repair_playback_rates(&mut instances);
+    audio_output.stop_expired_instances(&mut instances, Instant::now());
+    audio_output.stop_faded_out_instances(&mut instances, Instant::now());
+    stopped.send_batch(audio_output.cleanup_stopped_instances(&mut instances));
+}
+
+pub(crate) fn emit_channel_activity(
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    mut events: EventWriter<ChannelActivity>,
+) {
+    events.send_batch(audio_output.channel_activity(&audio_instances));
+}
+
+pub(crate) fn emit_channel_settings_changes(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut events: EventWriter<ChannelSettingsChanged>,
+) {
+    events.send_batch(audio_output.channel_settings_changes());
+}
+
+pub(crate) fn emit_play_commands_executed(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut events: EventWriter<PlayCommandsExecuted>,
+) {
+    events.send_batch(audio_output.play_commands_executed());
+}
+
+pub(crate) fn emit_loop_completions(
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    mut events: EventWriter<LoopCompleted>,
+) {
+    events.send_batch(audio_output.loop_completions(&audio_instances));
+}
+
+/// Update the tracked states of a channel's instances in place
+///
+/// Changes from one kind of state to another are added to `changes`. Returns `true` if any
+/// state changed its kind or an instance was removed.
+fn update_states(
+    channel: &Channel,
+    instances: &[Handle<AudioInstance>],
+    audio_instances: &Assets<AudioInstance>,
+    states: &mut HashMap<AssetId<AudioInstance>, PlaybackState>,
+    changes: &mut Vec<PlaybackStateChanged>,
+) -> bool {
+    let mut changed = false;
+    for handle in instances {
+        let current = audio_instances
+            .get(handle)
+            .map(AudioInstance::state)
+            .unwrap_or(PlaybackState::Stopped);
+        let previous = states
+            .insert(handle.id(), current)
+            .unwrap_or(PlaybackState::Queued);
+        if !previous.is_same_kind(&current) {
+            changes.push(PlaybackStateChanged {
+                instance: handle.clone_weak(),
+                channel: channel.clone(),
+                previous,
+                current,
+            });
+            changed = true;
+        }
+    }
+    if states.len() > instances.len() {
+        states.retain(|id, previous| {
+            let kept = instances.iter().any(|handle| handle.id() == *id);
+            if !kept && !previous.is_same_kind(&PlaybackState::Stopped) {
+                changes.push(PlaybackStateChanged {
+                    instance: Handle::Weak(*id),
+                    channel: channel.clone(),
+                    previous: *previous,
+                    current: PlaybackState::Stopped,
+                });
+            }
+            kept
+        });
+        changed = true;
+    }
+
+    changed
+}
+
+pub(crate) fn update_instance_states<T: Resource>(
+    audio_output: NonSend<AudioOutput>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    mut channel: ResMut<AudioChannel<T>>,
+    mut events: EventWriter<PlaybackStateChanged>,
+    mut changes: Local<Vec<PlaybackStateChanged>>,
+) {
+    let channel_id = Channel::Typed(TypeId::of::<T>());
+    let Some(instances) = audio_output.instances.get(&channel_id) else {
+        return;
+    };
+    // positions change every frame, so only changed instances or kinds of states count as changes
+    let tracked = channel.bypass_change_detection();
+    let mut changed = update_states(
+        &channel_id,
+        instances,
+        &audio_instances,
+        &mut tracked.states,
+        &mut changes,
+    );
+    if tracked.instances != *instances {
+        tracked.instances.clone_from(instances);
+        changed = true;
+    }
+    tracked
+        .source_paths
+        .retain(|id, _| tracked.states.contains_key(id));
+    for handle in instances {
+        if tracked.source_paths.contains_key(&handle.id()) {
+            continue;
+        }
+        if let Some(source_path) = audio_instances
+            .get(handle)
+            .and_then(|instance| instance.source_path.clone())
+        {
+            tracked.source_paths.insert(handle.id(), source_path);
+        }
+    }
+    if changed {
+        channel.set_changed();
+    }
+    events.send_batch(changes.drain(..));
+}
+
+pub(crate) fn update_dynamic_instance_states(
+    audio_output: NonSend<AudioOutput>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    mut channels: ResMut<DynamicAudioChannels>,
+    mut events: EventWriter<PlaybackStateChanged>,
+    mut changes: Local<Vec<PlaybackStateChanged>>,
+) {
+    let mut changed = false;
+    for (key, tracked) in channels.bypass_change_detection().channels.iter_mut() {
+        let channel = Channel::Dynamic(key.clone());
+        let Some(instances) = audio_output.instances.get(&channel) else {
+            continue;
+        };
+        changed |= update_states(
+            &channel,
+            instances,
+            &audio_instances,
+            &mut tracked.states,
+            &mut changes,
+        );
+        if tracked.instances != *instances {
+            tracked.instances.clone_from(instances);
+            changed = true;
+        }
+    }
+    if changed {
+        channels.set_changed();
+    }
+    events.send_batch(changes.drain(..));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::channel::AudioControl;
+    use crate::instance::AudioInstanceAssetsExt;
+    use crate::rate_limit::CategoryRule;
+    use crate::{
+        Audio, AudioPlugin, ChainTransition, ChainedSoundSettings, ChannelSettings, MainTrack,
+        MockAudioPlugin, PlaySignal,
+    };
+    use bevy::asset::{AssetId, AssetPlugin};
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use kira::dsp::Frame;
+    use kira::manager::backend::mock::{MockBackend, MockBackendSettings};
+    use kira::manager::backend::Renderer;
+    use kira::sound::static_sound::StaticSoundData;
+    use kira::track::effect::reverb::ReverbBuilder;
+    use uuid::Uuid;
+
+    #[test]
+    fn keeps_order_of_commands_to_retry() {
+        // we only need this app to conveniently get a assets collection for `AudioSource`...
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
+        let audio_source_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioSource>>()
+            .unwrap();
+        let mut audio_instance_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioInstance>>()
+            .unwrap();
+
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let audio_handle_one: Handle<AudioSource> =
+            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(1758302748397294)));
+        let audio_handle_two: Handle<AudioSource> =
+            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(2537024739048739)));
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(audio_handle_one.clone());
+        channel.play(audio_handle_two.clone());
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        let command_one = channel.commands.write().pop_back().unwrap();
+        match command_one {
+            AudioCommand::Play(settings) => {
+                assert_eq!(settings.source, audio_handle_one.clone().into())
+            }
+            _ => panic!("Wrong audio command"),
+        }
+        let command_two = channel.commands.write().pop_back().unwrap();
+        match command_two {
+            AudioCommand::Play(settings) => {
+                assert_eq!(settings.source, audio_handle_two.clone().into())
+            }
+            _ => panic!("Wrong audio command"),
+        }
+    }
+
+    #[test]
+    fn missing_audio_output_drops_sounds_and_keeps_channel_settings() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
+        let audio_source_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioSource>>()
+            .unwrap();
+        let mut audio_instance_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioInstance>>()
+            .unwrap();
+
+        let mut audio_output = AudioOutput::new(None::<AudioManager<MockBackend>>);
+        audio_output.setup = Some(ManagerSetup {
+            settings: AudioSettings {
+                startup_ramp: StartupRamp::Manual,
+                ..default()
+            },
+            #[cfg(feature = "analysis")]
+            main_spectrum: None,
+            create: Box::new(|setup, device| {
+                create_manager::<MockBackend>(setup, device, MockBackendSettings::default())
+            }),
+            retry_at: Instant::now(),
+        });
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(Handle::default());
+        channel.set_volume(0.8);
+        channel.set_volume(0.5);
+        channel.pause();
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.ramp_up(Duration::from_secs(1));
+
+        // only the newest volume and the pause wait for the output
+        assert_eq!(channel.commands.len(), 2);
+        assert!(channel.settings.lock().is_some());
+        assert_eq!(
+            audio_output.setup.as_ref().unwrap().settings.startup_ramp,
+            StartupRamp::FadeIn(Duration::from_secs(1))
+        );
+
+        // retrying uses the backend the output was set up with
+        audio_output.retry_setup(None, Instant::now());
+        assert!(audio_output.is_available());
+        assert!(audio_output.backend_mut::<MockBackend>().is_some());
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(channel.commands.is_empty());
+        let state = &audio_output.channels[&Channel::typed::<Audio>()];
+        assert!(state.paused);
+        assert_eq!(state.volume, Volume::Amplitude(0.5));
+    }
+
+    #[test]
+    fn stop_command_removes_previous_play_commands() {
+        // we only need this app to conveniently get a assets collection for `AudioSource`...
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
+        let audio_source_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioSource>>()
+            .unwrap();
+        let mut audio_instance_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioInstance>>()
+            .unwrap();
+
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let audio_handle_one: Handle<AudioSource> =
+            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(13290473942075938)));
+        let audio_handle_two: Handle<AudioSource> =
+            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(243290473942075938)));
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(audio_handle_one);
+        channel.stop();
+        channel.play(audio_handle_two.clone());
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        let command = channel.commands.write().pop_back().unwrap();
+        match command {
+            AudioCommand::Play(settings) => {
+                assert_eq!(settings.source, audio_handle_two.clone().into())
+            }
+            _ => panic!("Wrong audio command"),
+        }
+        assert!(channel.commands.write().pop_back().is_none());
+    }
+
+    #[test]
+    fn processing_commands_does_not_allocate_per_frame() {
+        // we only need this app to conveniently get a assets collection for `AudioSource`...
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin));
+        let audio_source_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioSource>>()
+            .unwrap();
+        let mut audio_instance_assets = app
+            .world_mut()
+            .remove_resource::<Assets<AudioInstance>>()
+            .unwrap();
+
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let channel = AudioChannel::<Audio>::default();
+        let mut dynamic_channels = DynamicAudioChannels::default();
+        // the source is never loaded, so the commands are retried every frame
+        let never_loaded =
+            Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(8472093847502934)));
+        channel.play(never_loaded.clone());
+        dynamic_channels.create_channel("test").play(never_loaded);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.play_dynamic_channels(
+            &(&audio_source_assets).into(),
+            &dynamic_channels,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert_no_alloc::reset_violation_count();
+        assert_no_alloc::assert_no_alloc(|| {
+            for _ in 0..10 {
+                audio_output.play_channel(
+                    &(&audio_source_assets).into(),
+                    &channel,
+                    &mut audio_instance_assets,
+                    None,
+                );
+                audio_output.play_dynamic_channels(
+                    &(&audio_source_assets).into(),
+                    &dynamic_channels,
+                    &mut audio_instance_assets,
+                    None,
+                );
+                audio_output.cleanup_stopped_instances(&mut audio_instance_assets);
+            }
+        });
+        assert_eq!(assert_no_alloc::violation_count(), 0);
+        assert_eq!(channel.commands.len(), 1);
+        assert_eq!(dynamic_channels.channel("test").commands.len(), 1);
+    }
+
+    #[test]
+    fn frozen_channel_holds_back_play_commands() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.freeze();
+        let instance = channel.play(source).handle();
+        channel.set_volume(0.5);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert_eq!(channel.commands.len(), 1);
+        assert!(audio_instance_assets.get(&instance).is_none());
+
+        channel.unfreeze();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert!(channel.commands.is_empty());
+        assert!(audio_instance_assets.get(&instance).is_some());
+    }
+
+    #[test]
+    fn deferred_play_waits_for_condition() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let signal = PlaySignal::default();
+        let instance = channel.play(source).play_when(signal.clone()).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert_eq!(channel.commands.len(), 1);
+        assert!(audio_instance_assets.get(&instance).is_none());
+
+        signal.fire();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert!(channel.commands.is_empty());
+        assert!(audio_instance_assets.get(&instance).is_some());
+    }
+
+    #[test]
+    fn chained_sound_plays_after_previous_finished() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let first = channel
+            .play(source.clone())
+            .then_play(source, ChainTransition::Immediate)
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.play_chained_sounds(
+            &audio_source_assets,
+            &mut audio_instance_assets,
+            None,
+            Instant::now(),
+        );
+        assert_eq!(audio_instance_assets.len(), 1);
+
+        audio_instance_assets.remove(&first);
+        audio_output.play_chained_sounds(
+            &audio_source_assets,
+            &mut audio_instance_assets,
+            None,
+            Instant::now(),
+        );
+        assert_eq!(audio_instance_assets.len(), 1);
+        assert!(audio_output.chains.is_empty());
+    }
+
+    #[test]
+    fn chained_sound_plays_with_its_own_settings() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let first = channel
+            .play(source.clone())
+            .then_play(
+                source.clone(),
+                ChainedSoundSettings::new(ChainTransition::Immediate)
+                    .looped()
+                    .with_volume(0.5),
+            )
+            .then_play(source, ChainTransition::Immediate)
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let chained = &audio_output.chains[0].sounds[0].settings;
+        assert_eq!(chained.loop_start, Some(0.));
+        assert_eq!(chained.volume, Some(Volume::Amplitude(0.5)));
+
+        audio_instance_assets.remove(&first);
+        audio_output.play_chained_sounds(
+            &audio_source_assets,
+            &mut audio_instance_assets,
+            None,
+            Instant::now(),
+        );
+        assert_eq!(audio_output.chains[0].sounds.len(), 1);
+        assert!(audio_output.chains[0].previous_duration.is_none());
+    }
+
+    #[test]
+    fn stopping_channel_cancels_chained_sounds() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        channel
+            .play(source.clone())
+            .then_play(source, ChainTransition::Immediate);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert_eq!(audio_output.chains.len(), 1);
+
+        channel.stop();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_output.chains.is_empty());
+    }
+
+    #[test]
+    fn solo_mutes_all_other_sounds_until_cleared() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let solo = channel.play(source.clone()).handle();
+        let other = channel.play(source.clone()).handle();
+        channel.solo_instance(&solo);
+        let later = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let muted =
+            |handle: &Handle<AudioInstance>| audio_instance_assets.get(handle).unwrap().is_muted();
+        assert!(!muted(&solo));
+        assert!(muted(&other));
+        assert!(muted(&later));
+
+        channel.clear_solo();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(!audio_instance_assets.get(&other).unwrap().is_muted());
+        assert!(!audio_instance_assets.get(&later).unwrap().is_muted());
+    }
+
+    #[test]
+    fn interruptions_resume_only_the_channels_they_paused() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let music = Channel::typed::<MainTrack>();
+        let voice = Channel::dynamic("voice");
+        let paused = |audio_output: &AudioOutput, channel: &Channel| {
+            audio_output
+                .channels
+                .get(channel)
+                .map_or(false, |state| state.paused)
+        };
+
+        audio_output.pause(&voice, &mut audio_instance_assets, &None, None);
+        audio_output
+            .begin_interruption(&[music.clone(), voice.clone()], &mut audio_instance_assets);
+        assert!(paused(&audio_output, &music));
+        assert!(paused(&audio_output, &voice));
+
+        audio_output.end_interruption(true, &mut audio_instance_assets);
+        assert!(!paused(&audio_output, &music));
+        assert!(paused(&audio_output, &voice));
+    }
+
+    #[test]
+    fn paused_instance_plays_after_resume() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).paused().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_output.playback_rate_repairs.is_empty());
+
+        audio_instance_assets
+            .get_mut(&instance)
+            .unwrap()
+            .resume(AudioTween::default());
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        // the first call adds the sound, the second one runs its commands
+        backend.on_start_processing();
+        backend.on_start_processing();
+        for _ in 0..10 {
+            backend.process();
+        }
+        // publishes the new playback position
+        backend.on_start_processing();
+
+        let position = audio_instance_assets
+            .get(&instance)
+            .unwrap()
+            .handle
+            .position();
+        assert!(
+            position > 0.,
+            "paused instance is stuck at playback rate zero"
+        );
+    }
+
+    #[test]
+    fn resume_keeps_stopping_sounds_stopping_unless_resuming_all() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let paused = channel.play(source.clone()).paused().handle();
+        let stopping = channel.play(source).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_instance_assets
+            .get_mut(&stopping)
+            .unwrap()
+            .stop(AudioTween::linear(Duration::from_secs(10)));
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        backend.on_start_processing();
+
+        channel.resume();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        let state = |handle: &Handle<AudioInstance>| {
+            audio_instance_assets.get(handle).unwrap().handle.state()
+        };
+        assert_eq!(state(&paused), kira::sound::PlaybackState::Playing);
+        assert_eq!(state(&stopping), kira::sound::PlaybackState::Stopping);
+
+        channel.resume().resume_all();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        let instance = audio_instance_assets.get(&stopping).unwrap();
+        assert_eq!(instance.handle.state(), kira::sound::PlaybackState::Playing);
+        assert!(!instance.stopping);
+    }
+
+    #[test]
+    fn muted_instance_restores_latest_volume() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).with_volume(0.5).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let audio_instance = audio_instance_assets.get_mut(&instance).unwrap();
+        assert_eq!(audio_instance.volume, Value::Fixed(Volume::Amplitude(0.5)));
+
+        assert!(audio_instance.mute(AudioTween::default()).is_none());
+        assert!(audio_instance.is_muted());
+
+        channel.set_volume(0.8);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let audio_instance = audio_instance_assets.get_mut(&instance).unwrap();
+        assert!(audio_instance.is_muted());
+
+        assert!(audio_instance.unmute(AudioTween::default()).is_none());
+        assert!(!audio_instance.is_muted());
+        assert_eq!(audio_instance.volume, Value::Fixed(Volume::Amplitude(0.8)));
+    }
+
+    #[test]
+    fn play_many_starts_all_sounds_once_loaded() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let loaded = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let loading = Handle::<AudioSource>::Weak(AssetId::from(Uuid::new_v4()));
+
+        let channel = AudioChannel::<Audio>::default();
+        let instances = channel
+            .play_many([loaded.clone(), loading.clone()])
+            .looped()
+            .handles();
+        assert_eq!(channel.commands.len(), 1);
+        assert_eq!(channel.state(&instances[1]), PlaybackState::Queued);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_instance_assets.get(&instances[0]).is_none());
+
+        let source = audio_source_assets.get(&loaded).unwrap().clone();
+        audio_source_assets.insert(&loading, source);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        for instance in &instances {
+            assert!(audio_instance_assets.get(instance).unwrap().looped);
+        }
+        assert!(channel.commands.is_empty());
+    }
+
+    #[test]
+    fn play_commands_executed_is_sent_once_no_play_command_is_pending() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let loaded = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let loading = Handle::<AudioSource>::Weak(AssetId::from(Uuid::from_u128(4823094823)));
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(loaded.clone());
+        channel.play(loading.clone());
+        assert!(channel.has_pending_commands());
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(channel.has_pending_commands());
+        assert_eq!(audio_output.play_commands_executed().count(), 0);
+
+        audio_source_assets.insert(&loading, audio_source_assets.get(&loaded).unwrap().clone());
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(!channel.has_pending_commands());
+        assert_eq!(
+            audio_output.play_commands_executed().collect::<Vec<_>>(),
+            [PlayCommandsExecuted {
+                channel: Channel::typed::<Audio>()
+            }]
+        );
+
+        channel.set_volume(0.5);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert_eq!(audio_output.play_commands_executed().count(), 0);
+    }
+
+    /// A backend outside of kira, to check that the plugin works with any backend
+    struct TestBackend {
+        settings: TestBackendSettings,
+        renderer: Option<Renderer>,
+    }
+
+    #[derive(Clone, Default)]
+    struct TestBackendSettings {
+        sample_rate: u32,
+    }
+
+    impl Backend for TestBackend {
+        type Settings = TestBackendSettings;
+        type Error = ();
+
+        fn setup(settings: Self::Settings) -> Result<(Self, u32), Self::Error> {
+            let sample_rate = settings.sample_rate;
+            let backend = TestBackend {
+                settings,
+                renderer: None,
+            };
+            Ok((backend, sample_rate))
+        }
+
+        fn start(&mut self, renderer: Renderer) -> Result<(), Self::Error> {
+            self.renderer = Some(renderer);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn plays_through_user_defined_backend() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            AudioPlugin::<TestBackend>::default()
+                .with_backend_settings(TestBackendSettings { sample_rate: 1234 }),
+        ));
+        let source = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+            });
+
+        app.world().resource::<Audio>().play(source);
+        app.update();
+
+        assert_eq!(app.world().resource::<Assets<AudioInstance>>().len(), 1);
+        let mut audio_output = app.world_mut().non_send_resource_mut::<AudioOutput>();
+        let backend = audio_output.backend_mut::<TestBackend>().unwrap();
+        assert_eq!(backend.settings.sample_rate, 1234);
+        assert!(backend.renderer.is_some());
+    }
+
+    #[test]
+    fn dispatched_channel_plays_before_post_update() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), MockAudioPlugin));
+        let world = app.world_mut();
+        let source = world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+            });
+
+        for _ in 0..2 {
+            world.resource::<Audio>().play(source.clone());
+            world.run_system_once(dispatch_audio_channel::<MainTrack>);
+            assert!(!world.resource::<Audio>().has_pending_commands());
+        }
+        assert_eq!(world.resource::<Assets<AudioInstance>>().len(), 2);
+    }
+
+    #[test]
+    fn channel_settings_changes_are_reported_once_per_frame() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.set_volume(0.5);
+        channel.set_volume(0.25);
+        channel.pause();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        let changes = audio_output.channel_settings_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].channel, Channel::typed::<Audio>());
+        assert_eq!(changes[0].settings.volume(), Volume::Amplitude(0.25));
+        assert!(changes[0].settings.is_paused());
+        assert!(audio_output.channel_settings_changes().is_empty());
+
+        channel.set_volume(0.25);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_output.channel_settings_changes().is_empty());
+    }
+
+    #[test]
+    fn channel_activity_is_reported_on_changes() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source.clone()).handle();
+        channel.play(source);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        backend.on_start_processing();
+
+        assert_eq!(
+            audio_output.channel_activity(&audio_instance_assets),
+            vec![ChannelActivity {
+                channel: Channel::typed::<Audio>(),
+                active_instances: 2,
+            }]
+        );
+        assert!(audio_output
+            .channel_activity(&audio_instance_assets)
+            .is_empty());
+
+        audio_instance_assets.remove(&instance);
+        assert!(audio_output
+            .channel_activity(&audio_instance_assets)
+            .is_empty());
+
+        channel.stop();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        // let the stop tween finish
+        for _ in 0..20 {
+            backend.process();
+        }
+        backend.on_start_processing();
+
+        assert_eq!(
+            audio_output.channel_activity(&audio_instance_assets),
+            vec![ChannelActivity {
+                channel: Channel::typed::<Audio>(),
+                active_instances: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn sounds_wait_for_their_clock() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let mut clock_assets = Assets::<AudioClock>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let other_channel = AudioChannel::<MainTrack>::default();
+        let clock = other_channel.add_clock(ClockSpeed::TicksPerMinute(120.));
+        let instance = channel.play(source).start_at_tick(&clock, 4).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert_eq!(channel.commands.len(), 1);
+        assert!(audio_instance_assets.get(&instance).is_none());
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &other_channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert!(clock_assets.get(&clock).is_some());
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(channel.commands.is_empty());
+        assert!(audio_instance_assets.get(&instance).is_some());
+
+        clock_assets.remove(&clock);
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert_eq!(audio_output.clocks.get(&clock.id()), Some(&None));
+    }
+
+    #[test]
+    fn instances_exceeding_max_duration_are_stopped() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::with_settings(
+            ChannelSettings::default().max_instance_duration(Duration::from_secs(1)),
+        );
+        let instance = channel.play(source).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let now = Instant::now();
+        audio_output.stop_expired_instances(&mut audio_instance_assets, now);
+        assert_eq!(audio_output.expiring_instances.len(), 1);
+
+        audio_output
+            .stop_expired_instances(&mut audio_instance_assets, now + Duration::from_secs(2));
+        assert!(audio_output.expiring_instances.is_empty());
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        backend.on_start_processing();
+        assert!(matches!(
+            audio_instance_assets.get(&instance).unwrap().state(),
+            PlaybackState::Stopping { .. } | PlaybackState::Stopped
+        ));
+    }
+
+    #[test]
+    fn channels_with_tempo_report_beats() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel =
+            AudioChannel::<Audio>::with_settings(ChannelSettings::default().with_tempo(120., 4));
+        let instance = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        backend.on_start_processing();
+
+        // the beat was not heard yet
+        assert!(audio_output
+            .music_beats(&audio_instance_assets, Duration::from_secs(1))
+            .is_empty());
+        let events = audio_output.music_beats(&audio_instance_assets, Duration::ZERO);
+        assert_eq!(events.len(), 1);
+        let (beat, bar) = &events[0];
+        assert_eq!(beat.instance, instance);
+        assert_eq!(beat.beat, 0);
+        assert_eq!(bar.as_ref().map(|bar| bar.bar), Some(0));
+        assert!(audio_output
+            .music_beats(&audio_instance_assets, Duration::ZERO)
+            .is_empty());
+    }
+
+    #[test]
+    fn delayed_sounds_are_queued_until_they_start() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let mut clock_assets = Assets::<AudioClock>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel
+            .play(source)
+            .looped()
+            .delayed(Duration::from_millis(100))
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        backend.on_start_processing();
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert_eq!(
+            audio_instance_assets.state(&instance),
+            PlaybackState::Queued
+        );
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        for _ in 0..200 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert!(matches!(
+            audio_instance_assets.state(&instance),
+            PlaybackState::Playing { .. }
+        ));
+    }
+
+    #[test]
+    fn delayed_stop_lets_sounds_play_until_the_delay_passed() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).looped().handle();
+        channel.stop().after(Duration::from_millis(100));
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        backend.on_start_processing();
+        for _ in 0..50 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        // the sound is marked as stopping right away, but keeps playing until the delay passed
+        assert_eq!(
+            audio_instance_assets.state(&instance),
+            PlaybackState::Stopping { position: 0.05 }
+        );
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        for _ in 0..100 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        assert_eq!(
+            audio_instance_assets.state(&instance),
+            PlaybackState::Stopped
+        );
+    }
+
+    #[test]
+    fn seek_moves_all_sounds_of_the_channel() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let first = channel.play(source.clone()).looped().handle();
+        let second = channel.play(source).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+
+        channel.seek_to(0.05);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        for _ in 0..10 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        for instance in [first, second] {
+            let position = audio_instance_assets.state(&instance).position().unwrap();
+            assert!(position >= 0.05, "position {position} was not seeked");
+        }
+    }
+
+    #[test]
+    fn finished_instances_are_reported_on_cleanup() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        assert!(audio_output
+            .cleanup_stopped_instances(&mut audio_instance_assets)
+            .is_empty());
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        for _ in 0..200 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        assert_eq!(
+            audio_output.cleanup_stopped_instances(&mut audio_instance_assets),
+            vec![AudioInstanceStopped {
+                handle: instance,
+                channel: Channel::typed::<Audio>(),
+            }]
+        );
+        assert!(audio_output
+            .cleanup_stopped_instances(&mut audio_instance_assets)
+            .is_empty());
+    }
+
+    #[test]
+    fn looping_instances_report_completed_loops() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        for _ in 0..50 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        assert!(audio_output
+            .loop_completions(&audio_instance_assets)
+            .is_empty());
+
+        // the sound is 85ms long, so it wraps within the next 50ms
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        for _ in 0..50 {
+            backend.process();
+        }
+        backend.on_start_processing();
+        assert_eq!(
+            audio_output.loop_completions(&audio_instance_assets),
+            vec![LoopCompleted {
+                instance,
+                channel: Channel::typed::<Audio>(),
+                loops: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn crossfade_fades_out_previous_sounds() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let previous = channel.play(source.clone()).looped().handle();
+        let next = channel
+            .crossfade_to(source, AudioTween::linear(Duration::from_millis(100)))
+            .looped()
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        for _ in 0..10 {
+            backend.process();
+        }
+        backend.on_start_processing();
+
+        assert!(matches!(
+            audio_instance_assets.state(&previous),
+            PlaybackState::Stopping { .. }
+        ));
+        assert!(matches!(
+            audio_instance_assets.state(&next),
+            PlaybackState::Playing { .. }
+        ));
+    }
+
+    #[test]
+    fn equal_power_crossfade_stops_previous_sounds_once_faded_out() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let previous = channel.play(source.clone()).looped().handle();
+        let next = channel
+            .crossfade_to(source, AudioTween::equal_power(Duration::from_millis(100)))
+            .looped()
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let now = Instant::now();
+        audio_output.stop_faded_out_instances(&mut audio_instance_assets, now);
+        let fading_out = audio_instance_assets.get(&previous).unwrap();
+        assert!(fading_out.stopping);
+        assert!(fading_out.stop_at.is_some());
+        assert!(audio_instance_assets.get(&next).unwrap().stop_at.is_none());
+
+        audio_output
+            .stop_faded_out_instances(&mut audio_instance_assets, now + Duration::from_secs(1));
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        backend.process();
+        backend.on_start_processing();
+
+        assert!(audio_instance_assets
+            .get(&previous)
+            .unwrap()
+            .stop_at
+            .is_none());
+        assert!(!matches!(
+            audio_instance_assets.state(&previous),
+            PlaybackState::Playing { .. }
+        ));
+        assert!(matches!(
+            audio_instance_assets.state(&next),
+            PlaybackState::Playing { .. }
+        ));
+    }
+
+    #[test]
+    fn loop_body_starts_when_intro_ends() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let mut clock_assets = Assets::<AudioClock>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let body = channel
+            .play_with_intro(source.clone(), source.clone())
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let instances = audio_output.instances[&Channel::typed::<Audio>()].clone();
+        assert_eq!(instances.len(), 2);
+        let intro = instances[0].clone();
+        assert_eq!(instances[1], body);
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        for _ in 0..40 {
+            backend.process();
+        }
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert!(matches!(
+            audio_instance_assets.state(&intro),
+            PlaybackState::Playing { .. }
+        ));
+        assert_eq!(audio_instance_assets.state(&body), PlaybackState::Queued);
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        for _ in 0..100 {
+            backend.process();
+        }
+        // clocks publish their ticks when processing starts
+        backend.on_start_processing();
+        audio_output.update_clocks(&mut clock_assets, &mut audio_instance_assets);
+        assert_eq!(audio_instance_assets.state(&intro), PlaybackState::Stopped);
+        assert!(matches!(
+            audio_instance_assets.state(&body),
+            PlaybackState::Playing { .. }
+        ));
+        // the body picks up on the sample the intro ends, so it is 140ms minus the intro along
+        let intro_duration = audio_source_assets
+            .get(&source)
+            .unwrap()
+            .sound
+            .duration()
+            .as_secs_f64();
+        let body_position = audio_instance_assets.get(&body).unwrap().handle.position();
+        assert!((body_position - (0.14 - intro_duration)).abs() <= 0.001);
+        assert!(audio_output.intro_clocks.is_empty());
+    }
+
+    #[test]
+    fn silent_looped_sounds_stop_and_restart_in_phase() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel
+            .play(source)
+            .looped()
+            .with_volume(0.)
+            .stop_when_silent(Duration::ZERO)
+            .handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let now = Instant::now();
+        audio_output.virtualize_instances(&audio_source_assets, &mut audio_instance_assets, now);
+        assert_eq!(
+            audio_instance_assets.get(&instance).unwrap().virtualized,
+            Some(0.)
+        );
+        assert!(audio_output
+            .cleanup_stopped_instances(&mut audio_instance_assets)
+            .is_empty());
+
+        audio_instance_assets
+            .get_mut(&instance)
+            .unwrap()
+            .set_volume(1., AudioTween::default());
+        audio_output.virtualize_instances(
+            &audio_source_assets,
+            &mut audio_instance_assets,
+            now + Duration::from_millis(100),
+        );
+        let restarted = audio_instance_assets.get(&instance).unwrap();
+        assert_eq!(restarted.virtualized, None);
+        assert_eq!(restarted.volume, Value::Fixed(Volume::Amplitude(1.)));
+        assert_eq!(
+            audio_output.instances[&Channel::typed::<Audio>()],
+            vec![instance]
+        );
+    }
+
+    #[test]
+    fn sounds_over_the_voice_cap_are_virtualized_by_priority() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        audio_output.max_audible_instances = Some(1);
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let ambience = channel.play(source.clone()).looped().handle();
+        let music = channel.play(source).looped().with_priority(1).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let now = Instant::now();
+        audio_output.virtualize_instances(&audio_source_assets, &mut audio_instance_assets, now);
+        assert_eq!(
+            audio_instance_assets.get(&ambience).unwrap().virtualized,
+            Some(0.)
+        );
+        assert_eq!(audio_instance_assets.get(&music).unwrap().virtualized, None);
+
+        audio_instance_assets
+            .get_mut(&music)
+            .unwrap()
+            .stop(AudioTween::default());
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        // new sounds only receive their own commands, like the stop, once processing starts again
+        backend.on_start_processing();
+        backend.on_start_processing();
+        for _ in 0..50 {
+            backend.process();
+        }
+        audio_output.virtualize_instances(
+            &audio_source_assets,
+            &mut audio_instance_assets,
+            now + Duration::from_millis(50),
+        );
+        assert_eq!(
+            audio_instance_assets.get(&ambience).unwrap().virtualized,
+            None
+        );
+        assert!(audio_output.instances[&Channel::typed::<Audio>()].contains(&ambience));
+    }
+
+    #[test]
+    fn channels_limit_their_number_of_sounds() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::with_settings(
+            ChannelSettings::default().max_instances(2, VoiceStealing::RejectNew),
+        );
+        let sounds: Vec<_> = (0..3)
+            .map(|_| channel.play(source.clone()).looped().handle())
+            .collect();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_instance_assets.get(&sounds[1]).is_some());
+        assert!(audio_instance_assets.get(&sounds[2]).is_none());
+
+        let channel = AudioChannel::<MainTrack>::with_settings(
+            ChannelSettings::default().max_instances(2, VoiceStealing::StopQuietest),
+        );
+        let loud = channel.play(source.clone()).looped().handle();
+        let quiet = channel.play(source.clone()).with_volume(0.5).handle();
+        let new = channel.play(source.clone()).looped().handle();
+        let newest = channel.play(source).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_instance_assets.get(&quiet).unwrap().stopping);
+        assert!(audio_instance_assets.get(&loud).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&new).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&newest).unwrap().stopping);
+    }
+
+    #[test]
+    fn source_limit_only_stops_sounds_of_the_same_source() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let sound = StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap();
+        let impact = audio_source_assets.add(AudioSource {
+            sound: sound.clone(),
+        });
+        let other = audio_source_assets.add(AudioSource { sound });
+
+        let channel = AudioChannel::<MainTrack>::with_settings(
+            ChannelSettings::default().max_instances_per_source(2, VoiceStealing::StopOldest),
+        );
+        let oldest = channel.play(impact.clone()).looped().handle();
+        let different = channel.play(other).looped().handle();
+        let older = channel.play(impact.clone()).looped().handle();
+        let newest = channel.play(impact).looped().handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_instance_assets.get(&oldest).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&different).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&older).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&newest).unwrap().stopping);
+    }
+
+    #[test]
+    fn silenced_output_plays_after_ramp_up() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        audio_output.silence();
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(source).looped();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        backend.on_start_processing();
+        backend.on_start_processing();
+        let silent = (0..100).all(|_| backend.process() == Frame::ZERO);
+        assert!(silent);
+
+        audio_output.ramp_up(Duration::ZERO);
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        let audible = (0..100).any(|_| backend.process() != Frame::ZERO);
+        assert!(audible);
+    }
+
+    #[test]
+    fn paused_instance_with_playback_rate_waits_for_resume() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        channel.pause();
+        channel.set_playback_rate(2.0);
+        let instance = channel.play(source).with_playback_rate(1.5).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.set_sample_rate(1000);
+        // adds the sound, but only runs its pause command on the next call
+        backend.on_start_processing();
+        for _ in 0..10 {
+            backend.process();
+        }
+        backend.on_start_processing();
+
+        let position = audio_instance_assets
+            .get(&instance)
+            .unwrap()
+            .handle
+            .position();
+        assert_eq!(position, 0.);
+    }
+
+    #[test]
+    fn failed_playback_rate_restore_is_repaired() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        // fill the command queue of the instance, so restoring the playback rate fails
+        let audio_instance = audio_instance_assets.get_mut(&instance).unwrap();
+        while audio_instance.handle.pause(default()).is_ok() {}
+        audio_output
+            .playback_rate_repairs
+            .push((instance.clone(), Value::Fixed(PlaybackRate::Factor(1.0))));
+
+        audio_output.repair_playback_rates(&mut audio_instance_assets);
+        assert_eq!(audio_output.playback_rate_repairs.len(), 1);
+
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        backend.on_start_processing();
+        backend.on_start_processing();
+        audio_output.repair_playback_rates(&mut audio_instance_assets);
+        assert!(audio_output.playback_rate_repairs.is_empty());
+    }
+
+    #[test]
+    fn channels_play_through_their_own_track() {
+        // the reverb sizes its buffers for the sample rate the manager is created with
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend> {
+                backend_settings: MockBackendSettings { sample_rate: 1000 },
+                ..default()
+            })
+            .ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+
+        let channel = AudioChannel::<Audio>::with_settings(
+            ChannelSettings::default().with_effect(ReverbBuilder::new()),
+        );
+        channel.play(source.clone());
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(audio_output
+            .tracks
+            .contains_key(&Channel::Typed(TypeId::of::<Audio>())));
+
+        let mut dynamic_channels = DynamicAudioChannels::default();
+        dynamic_channels.create_channel("cave").play(source);
+        audio_output.play_dynamic_channels(
+            &(&audio_source_assets).into(),
+            &dynamic_channels,
+            &mut audio_instance_assets,
+            None,
+        );
+        let cave = Channel::Dynamic("cave".into());
+        assert!(audio_output.tracks.contains_key(&cave));
+
+        dynamic_channels.remove_channel("cave");
+        audio_output.play_dynamic_channels(
+            &(&audio_source_assets).into(),
+            &dynamic_channels,
+            &mut audio_instance_assets,
+            None,
+        );
+        // the track is kept until the sounds of the removed channel stopped
+        assert!(audio_output.tracks.contains_key(&cave));
+        let backend = audio_output.backend_mut::<MockBackend>().unwrap();
+        // new sounds only receive their own commands, like the stop, once processing starts again
+        backend.on_start_processing();
+        backend.on_start_processing();
+        for _ in 0..50 {
+            backend.process();
+        }
+        let stopped = audio_output.cleanup_stopped_instances(&mut audio_instance_assets);
+        assert!(stopped.iter().any(|stopped| stopped.channel == cave));
+        audio_output.play_dynamic_channels(
+            &(&audio_source_assets).into(),
+            &dynamic_channels,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(!audio_output.tracks.contains_key(&cave));
+        assert!(!audio_output.instances.contains_key(&cave));
+    }
+
+    #[test]
+    fn sub_mix_channels_inherit_volume_and_pause_from_parent() {
+        #[derive(Resource)]
+        struct Sfx;
+        #[derive(Resource)]
+        struct Footsteps;
+
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let parent = AudioChannel::<Sfx>::default();
+        let child = AudioChannel::<Footsteps>::with_settings(
+            ChannelSettings::default().with_parent::<Sfx>(),
+        );
+        let child_channel = Channel::Typed(TypeId::of::<Footsteps>());
+
+        child.set_volume(0.5);
+        child.play(source);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &child,
+            &mut audio_instance_assets,
+            None,
+        );
+        // the child waits for its parent to be set up
+        assert_eq!(child.commands.len(), 2);
+        assert!(!audio_output.tracks.contains_key(&child_channel));
+
+        parent.set_volume(0.5);
+        parent.pause();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &parent,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &child,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(child.commands.is_empty());
+        assert!(audio_output.tracks.contains_key(&child_channel));
+        assert_eq!(
+            audio_output.effective_volume(&child_channel).as_amplitude(),
+            0.25
+        );
+        assert!(audio_output.effectively_paused(&child_channel));
+
+        parent.resume();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &parent,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(!audio_output.effectively_paused(&child_channel));
+    }
+
+    #[test]
+    fn channels_send_to_other_channels() {
+        #[derive(Resource)]
+        struct Reverb;
+
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let reverb = AudioChannel::<Reverb>::with_settings(
+            ChannelSettings::default().with_effect(ReverbBuilder::new()),
+        );
+        let channel = AudioChannel::<Audio>::with_settings(
+            ChannelSettings::default().with_send::<Reverb>(0.),
+        );
+        let audio_channel = Channel::typed::<Audio>();
+        let reverb_channel = Channel::typed::<Reverb>();
+
+        let instance = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        // the channel waits for the channel it sends to
+        assert!(!audio_output.tracks.contains_key(&audio_channel));
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &reverb,
+            &mut audio_instance_assets,
+            None,
+        );
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert_eq!(audio_output.channel_of(&instance), Some(&audio_channel));
+        assert!(audio_output.set_send_volume(
+            &audio_channel,
+            &reverb_channel,
+            Volume::Amplitude(0.5),
+            AudioTween::default().into(),
+        ));
+        assert!(!audio_output.set_send_volume(
+            &reverb_channel,
+            &audio_channel,
+            Volume::Amplitude(0.5),
+            AudioTween::default().into(),
+        ));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn plays_streaming_sources() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let audio_source_assets = Assets::<AudioSource>::default();
+        let mut streaming_source_assets = Assets::<StreamingAudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = streaming_source_assets.add(StreamingAudioSource {
+            bytes: std::fs::read("assets/sounds/loop.ogg").unwrap().into(),
+            settings: default(),
+        });
+
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play_streaming(source).handle();
+        audio_output.play_channel(
+            &AudioSources {
+                sources: &audio_source_assets,
+                streaming_sources: Some(&streaming_source_assets),
+            },
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert!(channel.commands.is_empty());
+        assert!(matches!(
+            audio_instance_assets.get(&instance).unwrap().handle,
+            InstanceHandle::Streaming(_)
+        ));
+    }
+
+    #[test]
+    fn only_changes_in_the_kind_of_state_are_reported() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let channel = AudioChannel::<Audio>::default();
+        let instance = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        let channel_id = Channel::typed::<Audio>();
+        let instances = audio_output.instances.get(&channel_id).unwrap().clone();
+        let mut states = HashMap::default();
+        let mut changes = vec![];
+
+        assert!(update_states(
+            &channel_id,
+            &instances,
+            &audio_instance_assets,
+            &mut states,
+            &mut changes
+        ));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, PlaybackState::Queued);
+        assert!(matches!(changes[0].current, PlaybackState::Playing { .. }));
+
+        changes.clear();
+        assert!(!update_states(
+            &channel_id,
+            &instances,
+            &audio_instance_assets,
+            &mut states,
+            &mut changes
+        ));
+        assert!(changes.is_empty());
+
+        assert!(update_states(
+            &channel_id,
+            &[],
+            &audio_instance_assets,
+            &mut states,
+            &mut changes
+        ));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].instance, instance);
+        assert_eq!(changes[0].current, PlaybackState::Stopped);
+        assert!(states.is_empty());
+    }
+
+    #[test]
+    fn mono_sources_are_mixed_down_once() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let id = AssetId::from(Uuid::from_u128(84028493028475));
+        let source = AudioSource {
+            sound: StaticSoundData {
+                sample_rate: 100,
+                frames: vec![Frame::new(1., 0.), Frame::new(0., 1.)].into(),
+                settings: StaticSoundSettings::default(),
+            },
+        };
+
+        let first = audio_output.mono_source(id, &source);
+        let second = audio_output.mono_source(id, &source);
+
+        assert_eq!(first.sound.frames[0], Frame::from_mono(0.5));
+        assert!(Arc::ptr_eq(&first.sound.frames, &second.sound.frames));
+    }
+
+    #[test]
+    fn commands_are_counted_until_taken() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let loaded = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let channel = AudioChannel::<Audio>::default();
+        channel.play(loaded);
+        channel.play(Handle::default());
+        channel.set_volume(0.5);
+
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+
+        assert_eq!(
+            audio_output.take_command_counts(),
+            CommandCounts {
+                processed: 3,
+                retried: 1,
+            }
+        );
+        assert_eq!(audio_output.take_command_counts(), CommandCounts::default());
+        assert_eq!(
+            audio_output
+                .instance_counts()
+                .map(|(_, instances)| instances)
+                .sum::<usize>(),
+            1
+        );
+    }
+
+    #[test]
+    fn exclusive_categories_never_overlap() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let mut rate_limits = SoundRateLimits::default()
+            .with_rule(
+                "alarm",
+                CategoryRule::default().exclusive(Exclusivity::Restart),
+            )
+            .with_rule(
+                "bark",
+                CategoryRule::default().exclusive(Exclusivity::Ignore),
+            )
+            .with_rule(
+                "dialogue",
+                CategoryRule::default().exclusive(Exclusivity::Queue),
+            );
+        let channel = AudioChannel::<Audio>::default();
+        let play = |category: &'static str| {
+            channel
+                .play(source.clone())
+                .looped()
+                .with_category(category)
+                .handle()
+        };
+
+        let first_alarm = play("alarm");
+        let second_alarm = play("alarm");
+        let first_bark = play("bark");
+        let second_bark = play("bark");
+        let first_line = play("dialogue");
+        let second_line = play("dialogue");
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            Some(&mut rate_limits),
+        );
+
+        assert!(audio_instance_assets.get(&first_alarm).unwrap().stopping);
+        assert!(!audio_instance_assets.get(&second_alarm).unwrap().stopping);
+        assert!(audio_instance_assets.get(&first_bark).is_some());
+        assert!(audio_instance_assets.get(&second_bark).is_none());
+        assert!(audio_instance_assets.get(&first_line).is_some());
+        assert!(audio_instance_assets.get(&second_line).is_none());
+        assert_eq!(channel.commands.len(), 1);
+        let stopped = audio_output.cleanup_stopped_instances(&mut audio_instance_assets);
+        assert!(stopped.iter().any(|stopped| stopped.handle == second_bark));
+        assert!(!stopped.iter().any(|stopped| stopped.handle == second_line));
+
+        audio_instance_assets
+            .get_mut(&first_line)
+            .unwrap()
+            .stop(AudioTween::default());
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            Some(&mut rate_limits),
+        );
+        assert!(audio_instance_assets.get(&second_line).is_some());
+        assert!(channel.commands.is_empty());
+    }
+
+    #[test]
+    fn disabled_audio_drops_or_buffers_play_commands() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_source_assets = Assets::<AudioSource>::default();
+        let mut audio_instance_assets = Assets::<AudioInstance>::default();
+        let source = audio_source_assets.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let channel = AudioChannel::<Audio>::default();
+        let mut global_audio = GlobalAudio::default();
+
+        global_audio.set_enabled(false);
+        audio_output.apply_global_audio(&global_audio);
+        let dropped = channel.play(source.clone()).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(channel.commands.is_empty());
+        assert!(audio_instance_assets.get(&dropped).is_none());
+
+        global_audio.set_disabled_playback(DisabledPlayback::Buffer);
+        audio_output.apply_global_audio(&global_audio);
+        let buffered = channel.play(source).handle();
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert_eq!(channel.commands.len(), 1);
+
+        global_audio.set_enabled(true);
+        audio_output.apply_global_audio(&global_audio);
+        audio_output.play_channel(
+            &(&audio_source_assets).into(),
+            &channel,
+            &mut audio_instance_assets,
+            None,
+        );
+        assert!(channel.commands.is_empty());
+        assert!(audio_instance_assets.get(&buffered).is_some());
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: assert_no_alloc::AllocDisabler = assert_no_alloc::AllocDisabler;
 }
@@ -1,7 +1,10 @@
+use crate::PanLaw;
+use bevy::ecs::event::Event;
 use bevy::ecs::system::Resource;
 use bevy::utils::default;
-use kira::manager::backend::DefaultBackend;
+use kira::manager::backend::Backend;
 use kira::manager::{AudioManagerSettings, Capacities};
+use std::time::Duration;
 
 /// This resource is used to configure the audio backend at creation
 ///
@@ -18,6 +21,59 @@ pub struct AudioSettings {
     pub command_capacity: usize,
     /// The maximum number of sounds that can be playing at a time.
     pub sound_capacity: usize,
+    /// How the overall volume ramps up when the app starts
+    pub startup_ramp: StartupRamp,
+    /// When play commands are sent to the audio backend
+    pub play_command_queueing: PlayCommandQueueing,
+    /// The maximum number of sounds that are audible at a time, unlimited if `None`
+    ///
+    /// Sounds over the limit are virtualized: they are stopped in the audio backend, but keep
+    /// their position and report [`PlaybackState::Playing`](crate::PlaybackState::Playing).
+    /// Once there is room again, they restart where they would have been. Sounds with a lower
+    /// [priority](crate::PlayAudioCommand::with_priority) are virtualized first, then quieter
+    /// sounds. Streamed and reversed sounds cannot be virtualized, but count towards the limit.
+    ///
+    /// The limit is enforced once per frame, so keep
+    /// [`sound_capacity`](Self::sound_capacity) higher to leave room for the sounds started
+    /// in a single frame.
+    pub max_audible_instances: Option<usize>,
+    /// How the panning of sounds maps to the gains of the left and right speaker
+    ///
+    /// Applies to [`set_panning`](crate::AudioControl::set_panning) and spatial audio. Channels
+    /// can use another law with [`ChannelSettings::with_pan_law`](crate::ChannelSettings::with_pan_law).
+    pub pan_law: PanLaw,
+    /// Number of frames the audio is processed in at a time, the default of the device if `None`
+    ///
+    /// Smaller buffers lower the latency, but the audio crackles if a buffer cannot be filled in
+    /// time. The size is clamped to the range the device supports. Only used on desktop platforms.
+    pub buffer_size: Option<u32>,
+    /// Preferred sample rate of the output, the default of the device if `None`
+    ///
+    /// If the device does not support the sample rate, its default is used. Only used on
+    /// desktop platforms, and as the sample rate of the [`OfflineAudioPlugin`](crate::OfflineAudioPlugin).
+    pub sample_rate: Option<u32>,
+    /// Wait for the first user input before setting up the audio
+    ///
+    /// Browsers block audio until the user interacted with the page. While waiting, sounds and
+    /// other commands are kept and run once the audio is set up, which sends [`AudioUnlocked`](crate::AudioUnlocked).
+    /// Sounds played before the first input all start at that moment, so only play
+    /// sounds that should still be heard late, like music. Enabled by default on the web.
+    pub unlock_on_input: bool,
+    /// Pause the channels while the app is not focused
+    ///
+    /// This is the initial value of [`AudioFocusSettings::enabled`](crate::AudioFocusSettings::enabled),
+    /// which also configures the fade and the channels that keep playing.
+    pub pause_on_focus_loss: bool,
+}
+
+impl AudioSettings {
+    /// Names of the available output devices
+    ///
+    /// Choose one of them with [`AudioOutputDevice`](crate::AudioOutputDevice). The list is
+    /// always empty on the web.
+    pub fn output_devices() -> Vec<String> {
+        crate::device::output_devices()
+    }
 }
 
 impl Default for AudioSettings {
@@ -25,11 +81,185 @@ impl Default for AudioSettings {
         Self {
             command_capacity: 128,
             sound_capacity: 128,
+            startup_ramp: StartupRamp::default(),
+            play_command_queueing: PlayCommandQueueing::default(),
+            max_audible_instances: None,
+            pan_law: PanLaw::default(),
+            buffer_size: None,
+            sample_rate: None,
+            unlock_on_input: cfg!(target_arch = "wasm32"),
+            pause_on_focus_loss: false,
         }
     }
 }
 
-impl From<AudioSettings> for AudioManagerSettings<DefaultBackend> {
+/// How the overall volume ramps up when the app starts
+///
+/// Use this to keep the first sounds from playing at full volume before the player's saved
+/// volume settings are applied.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// App::new()
+///     .insert_resource(AudioSettings {
+///         startup_ramp: StartupRamp::Manual,
+///         ..default()
+///     })
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_systems(Startup, apply_saved_volumes);
+///
+/// fn apply_saved_volumes(audio: Res<Audio>, mut ramp: EventWriter<RampUpAudio>) {
+///     audio.set_volume(0.8);
+///     ramp.send(RampUpAudio {
+///         duration: Duration::from_millis(500),
+///     });
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StartupRamp {
+    /// Play at full volume right away
+    #[default]
+    Immediate,
+    /// Fade in from silence over the given duration
+    FadeIn(Duration),
+    /// Stay silent until a [`RampUpAudio`](crate::RampUpAudio) event is sent
+    Manual,
+}
+
+/// When a [`PlayAudioCommand`](crate::PlayAudioCommand) is sent to the audio backend
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// App::new()
+///     .insert_resource(AudioSettings {
+///         play_command_queueing: PlayCommandQueueing::Explicit,
+///         ..default()
+///     })
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_systems(Update, play_jump);
+///
+/// fn play_jump(audio: Res<Audio>, asset_server: Res<AssetServer>) {
+///     let mut command = audio.play(asset_server.load("sounds/jump.ogg"));
+///     command.with_volume(0.5);
+///     command.queue();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PlayCommandQueueing {
+    /// Queue the command when it is dropped, unless [`queue`](crate::PlayAudioCommand::queue)
+    /// was called before
+    #[default]
+    OnDrop,
+    /// Only queue the command when [`queue`](crate::PlayAudioCommand::queue) is called
+    ///
+    /// Dropping a command that was not queued logs a warning.
+    Explicit,
+}
+
+/// The [`PlayCommandQueueing`] of the app
+///
+/// Kept after the [`AudioSettings`] are consumed, so channels pick it up when they are registered.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub(crate) struct AppPlayCommandQueueing(pub(crate) PlayCommandQueueing);
+
+/// Resource to turn all audio off and on at run-time
+///
+/// While audio is disabled, the audio backend is paused, so it uses next to no CPU. Sounds that
+/// were playing continue where they were once audio is enabled again. This is meant for an
+/// "audio off" option in your settings menu, which should do more than setting the volume to zero.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn toggle_audio(keys: Res<ButtonInput<KeyCode>>, mut global_audio: ResMut<GlobalAudio>) {
+///     if keys.just_pressed(KeyCode::KeyM) {
+///         let enabled = global_audio.is_enabled();
+///         global_audio.set_enabled(!enabled);
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalAudio {
+    enabled: bool,
+    while_disabled: DisabledPlayback,
+}
+
+impl Default for GlobalAudio {
+    fn default() -> Self {
+        GlobalAudio {
+            enabled: true,
+            while_disabled: DisabledPlayback::default(),
+        }
+    }
+}
+
+impl GlobalAudio {
+    /// Turn all audio on or off
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns `true` unless audio was turned off
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Choose what happens to sounds that are played while audio is disabled
+    pub fn set_disabled_playback(&mut self, while_disabled: DisabledPlayback) {
+        self.while_disabled = while_disabled;
+    }
+
+    /// What happens to sounds that are played while audio is disabled
+    pub fn disabled_playback(&self) -> DisabledPlayback {
+        self.while_disabled
+    }
+}
+
+/// What happens to sounds that are played while audio is disabled, see [`GlobalAudio`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DisabledPlayback {
+    /// Drop the play commands, so the sounds never play
+    #[default]
+    Drop,
+    /// Keep the play commands queued and play the sounds once audio is enabled again
+    Buffer,
+}
+
+/// Resource with the time between the audio backend processing a sound and it being heard
+///
+/// Wireless headphones can add a few hundred milliseconds of latency. [`MusicBeat`](crate::MusicBeat),
+/// [`MusicBar`](crate::MusicBar), and [`BeatTick`](crate::BeatTick) events are delayed by the
+/// offset, so they line up with the heard beat. The latency of the output device is not
+/// reported by the audio backend, so offer a calibration option to your players and set the
+/// offset from it. Use it as well to judge the timing of player input against the music.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// fn apply_calibration(mut output_latency: ResMut<OutputLatency>) {
+///     output_latency.offset = Duration::from_millis(180);
+/// }
+/// ```
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct OutputLatency {
+    /// How much later sounds are heard than they are processed
+    pub offset: Duration,
+}
+
+/// Event to fade the overall volume to full volume over the given duration
+///
+/// See [`StartupRamp`] for an example.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RampUpAudio {
+    /// Duration of the fade
+    pub duration: Duration,
+}
+
+impl<B: Backend> From<AudioSettings> for AudioManagerSettings<B>
+where
+    B::Settings: Default,
+{
     fn from(settings: AudioSettings) -> Self {
         AudioManagerSettings {
             capacities: Capacities {
@@ -0,0 +1,133 @@
+//! Beat and bar events for channels playing music
+
+use crate::audio_output::AudioOutput;
+use crate::backend_settings::OutputLatency;
+use crate::channel::Channel;
+use crate::instance::AudioInstance;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::event::{Event, EventWriter};
+use bevy::ecs::system::{NonSendMut, Res};
+
+/// Event sent when a sound on a channel with a tempo reaches a new beat
+///
+/// Configure the tempo of a channel with [`with_tempo`](crate::ChannelSettings::with_tempo).
+/// Beats are derived from the playback position of each playing sound, so they follow pauses,
+/// seeking, and loops. At most one beat is sent per sound and frame. Beats are delayed by the
+/// [`OutputLatency`](crate::OutputLatency), so they line up with the heard beat.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn pulse_ui(mut beats: EventReader<MusicBeat>) {
+///     for beat in beats.read() {
+///         info!("Beat {} of the bar", beat.beat_in_bar + 1);
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct MusicBeat {
+    /// The channel of the sound
+    pub channel: Channel,
+    /// The sound that reached the beat
+    pub instance: Handle<AudioInstance>,
+    /// Index of the beat counted from the start of the sound
+    pub beat: u64,
+    /// Index of the beat in its bar, `0` being the first beat of a bar
+    pub beat_in_bar: u32,
+}
+
+/// Event sent when a sound on a channel with a tempo reaches a new bar
+///
+/// Sent together with the [`MusicBeat`] of the first beat in the bar.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct MusicBar {
+    /// The channel of the sound
+    pub channel: Channel,
+    /// The sound that reached the bar
+    pub instance: Handle<AudioInstance>,
+    /// Index of the bar counted from the start of the sound
+    pub bar: u64,
+}
+
+/// Tempo and time signature of a channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Tempo {
+    pub(crate) beats_per_minute: f64,
+    pub(crate) beats_per_bar: u32,
+}
+
+impl Tempo {
+    /// Index of the beat at the given position in seconds
+    pub(crate) fn beat_at(&self, position: f64) -> u64 {
+        (position.max(0.) * self.beats_per_minute / 60.).floor() as u64
+    }
+
+    pub(crate) fn beat_event(
+        &self,
+        channel: &Channel,
+        instance: &Handle<AudioInstance>,
+        beat: u64,
+    ) -> (MusicBeat, Option<MusicBar>) {
+        let beats_per_bar = u64::from(self.beats_per_bar.max(1));
+        let beat_in_bar = beat % beats_per_bar;
+        let bar = (beat_in_bar == 0).then(|| MusicBar {
+            channel: channel.clone(),
+            instance: instance.clone(),
+            bar: beat / beats_per_bar,
+        });
+
+        (
+            MusicBeat {
+                channel: channel.clone(),
+                instance: instance.clone(),
+                beat,
+                beat_in_bar: beat_in_bar as u32,
+            },
+            bar,
+        )
+    }
+}
+
+pub(crate) fn emit_music_beats(
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    output_latency: Res<OutputLatency>,
+    mut beats: EventWriter<MusicBeat>,
+    mut bars: EventWriter<MusicBar>,
+) {
+    for (beat, bar) in audio_output.music_beats(&audio_instances, output_latency.offset) {
+        beats.send(beat);
+        if let Some(bar) = bar {
+            bars.send(bar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainTrack;
+
+    #[test]
+    fn beats_and_bars_follow_the_tempo() {
+        let tempo = Tempo {
+            beats_per_minute: 120.,
+            beats_per_bar: 4,
+        };
+        let channel = Channel::typed::<MainTrack>();
+        let instance = Handle::<AudioInstance>::default();
+
+        assert_eq!(tempo.beat_at(0.), 0);
+        assert_eq!(tempo.beat_at(0.49), 0);
+        assert_eq!(tempo.beat_at(0.5), 1);
+        assert_eq!(tempo.beat_at(2.1), 4);
+
+        let (beat, bar) = tempo.beat_event(&channel, &instance, 4);
+        assert_eq!(beat.beat_in_bar, 0);
+        assert_eq!(bar.map(|bar| bar.bar), Some(1));
+
+        let (beat, bar) = tempo.beat_event(&channel, &instance, 6);
+        assert_eq!(beat.beat_in_bar, 2);
+        assert!(bar.is_none());
+    }
+}
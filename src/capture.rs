@@ -0,0 +1,255 @@
+//! Capture the recent output of channels for custom analysis or tooling
+
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use bevy::ecs::system::{NonSend, ResMut, Resource};
+use bevy::utils::HashMap;
+use kira::clock::clock_info::ClockInfoProvider;
+use kira::dsp::Frame;
+use kira::modulator::value_provider::ModulatorValueProvider;
+use kira::track::effect::{Effect, EffectBuilder};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Resource with the captures of all channels with [capturing](crate::ChannelSettings::with_capture)
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Voice;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     // about five seconds at 48 kHz
+///     .add_audio_channel_with_settings::<Voice>(ChannelSettings::default().with_capture(240_000))
+///     .add_systems(Update, stream_voice);
+///
+/// fn stream_voice(capture: Res<AudioCapture>, mut position: Local<usize>) {
+///     let Some(voice) = capture.get(&Channel::typed::<Voice>()) else {
+///         return;
+///     };
+///     let mut frames = vec![];
+///     *position = voice.read_since(*position, &mut frames);
+///     // send `frames` to the spectators
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct AudioCapture {
+    channels: HashMap<Channel, ChannelCapture>,
+}
+
+impl AudioCapture {
+    /// The capture of the given channel
+    pub fn get(&self, channel: &Channel) -> Option<&ChannelCapture> {
+        self.channels.get(channel)
+    }
+}
+
+/// Ring buffer with the most recent output of a channel
+///
+/// The buffer is written on the audio thread. Reading copies the frames out of it.
+#[derive(Clone)]
+pub struct ChannelCapture {
+    shared: Arc<SharedCapture>,
+}
+
+impl ChannelCapture {
+    /// Sample rate of the captured frames
+    pub fn sample_rate(&self) -> u32 {
+        self.shared.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Maximum number of frames kept in the buffer
+    pub fn capacity(&self) -> usize {
+        self.shared.left.len()
+    }
+
+    /// Number of frames captured since the channel was set up
+    ///
+    /// Use it as position for [`read_since`](Self::read_since).
+    pub fn frames_written(&self) -> usize {
+        self.shared.written.load(Ordering::Acquire)
+    }
+
+    /// Append the frames captured since the given position to `frames`
+    ///
+    /// Returns the position to continue reading from next time. Frames that were already
+    /// dropped from the buffer are skipped.
+    pub fn read_since(&self, position: usize, frames: &mut Vec<Frame>) -> usize {
+        let capacity = self.capacity();
+        let written = self.frames_written();
+        let start = position.clamp(written.saturating_sub(capacity), written);
+        let first = frames.len();
+        frames.extend((start..written).map(|index| {
+            let index = index % capacity;
+            Frame::new(
+                f32::from_bits(self.shared.left[index].load(Ordering::Relaxed)),
+                f32::from_bits(self.shared.right[index].load(Ordering::Relaxed)),
+            )
+        }));
+        // frames overwritten while copying are dropped
+        let overwritten = self
+            .frames_written()
+            .saturating_sub(capacity)
+            .saturating_sub(start)
+            .min(written - start);
+        frames.drain(first..first + overwritten);
+
+        written
+    }
+
+    /// Append the latest `count` frames to `frames`, or fewer if not that many are captured
+    pub fn read_latest(&self, count: usize, frames: &mut Vec<Frame>) {
+        self.read_since(self.frames_written().saturating_sub(count), frames);
+    }
+}
+
+/// Capture shared between the audio thread and the main thread
+pub(crate) struct SharedCapture {
+    left: Vec<AtomicU32>,
+    right: Vec<AtomicU32>,
+    written: AtomicUsize,
+    sample_rate: AtomicU32,
+}
+
+/// Effect passing the signal through unchanged while capturing it
+pub(crate) struct CaptureTap {
+    shared: Arc<SharedCapture>,
+}
+
+pub(crate) struct CaptureTapBuilder {
+    pub(crate) shared: Arc<SharedCapture>,
+}
+
+impl CaptureTapBuilder {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        CaptureTapBuilder {
+            shared: Arc::new(SharedCapture {
+                left: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+                right: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+                written: AtomicUsize::new(0),
+                sample_rate: AtomicU32::new(0),
+            }),
+        }
+    }
+}
+
+impl EffectBuilder for CaptureTapBuilder {
+    type Handle = ();
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        (
+            Box::new(CaptureTap {
+                shared: self.shared,
+            }),
+            (),
+        )
+    }
+}
+
+impl Effect for CaptureTap {
+    fn init(&mut self, sample_rate: u32) {
+        self.shared
+            .sample_rate
+            .store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn on_change_sample_rate(&mut self, sample_rate: u32) {
+        self.shared
+            .sample_rate
+            .store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn process(
+        &mut self,
+        input: Frame,
+        _dt: f64,
+        _clock_info_provider: &ClockInfoProvider,
+        _modulator_value_provider: &ModulatorValueProvider,
+    ) -> Frame {
+        let written = self.shared.written.load(Ordering::Relaxed);
+        let index = written % self.shared.left.len();
+        self.shared.left[index].store(input.left.to_bits(), Ordering::Relaxed);
+        self.shared.right[index].store(input.right.to_bits(), Ordering::Relaxed);
+        self.shared
+            .written
+            .store(written.wrapping_add(1), Ordering::Release);
+
+        input
+    }
+}
+
+pub(crate) fn update_audio_capture(
+    audio_output: NonSend<AudioOutput>,
+    mut audio_capture: ResMut<AudioCapture>,
+) {
+    let captures = audio_output.captures();
+    if audio_capture.channels.len() == captures.len()
+        && captures.iter().all(|(channel, shared)| {
+            audio_capture
+                .channels
+                .get(channel)
+                .map_or(false, |capture| Arc::ptr_eq(&capture.shared, shared))
+        })
+    {
+        return;
+    }
+    audio_capture.channels = captures
+        .iter()
+        .map(|(channel, shared)| {
+            (
+                channel.clone(),
+                ChannelCapture {
+                    shared: shared.clone(),
+                },
+            )
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kira::clock::clock_info::MockClockInfoProviderBuilder;
+    use kira::modulator::value_provider::MockModulatorValueProviderBuilder;
+
+    #[test]
+    fn captures_keep_the_most_recent_frames() {
+        let builder = CaptureTapBuilder::new(4);
+        let capture = ChannelCapture {
+            shared: builder.shared.clone(),
+        };
+        let (mut tap, _) = builder.build();
+        let clock_info_provider = MockClockInfoProviderBuilder::new(0).build();
+        let modulator_value_provider = MockModulatorValueProviderBuilder::new(0).build();
+        let mut process = |count: usize| {
+            for _ in 0..count {
+                let sample = capture.frames_written() as f32;
+                tap.process(
+                    Frame::from_mono(sample),
+                    1. / 48_000.,
+                    &clock_info_provider,
+                    &modulator_value_provider,
+                );
+            }
+        };
+
+        process(3);
+        let mut frames = vec![];
+        let position = capture.read_since(0, &mut frames);
+        assert_eq!(position, 3);
+        assert_eq!(frames, [0., 1., 2.].map(Frame::from_mono));
+
+        process(6);
+        frames.clear();
+        let position = capture.read_since(position, &mut frames);
+        assert_eq!(position, 9);
+        assert_eq!(frames, [5., 6., 7., 8.].map(Frame::from_mono));
+
+        frames.clear();
+        capture.read_latest(2, &mut frames);
+        assert_eq!(frames, [7., 8.].map(Frame::from_mono));
+    }
+}
@@ -1,26 +1,159 @@
 pub mod dynamic;
 pub mod typed;
 
-use crate::audio::{AudioCommand, FadeIn, FadeOut, PlayAudioCommand, TweenCommand};
+#[cfg(feature = "analysis")]
+use crate::analysis::{SharedSpectrum, SpectrumSettings, SpectrumTapBuilder};
+use crate::audio::{
+    combine_panning, AudioCommand, AudioTween, FadeIn, FadeOut, PanLaw, Panning, PlayAudioCommand,
+    PlayManyCommand, ResumeFadeIn, TweenCommand,
+};
+use crate::backend_settings::PlayCommandQueueing;
+use crate::beat::Tempo;
+use crate::capture::{CaptureTapBuilder, SharedCapture};
+use crate::channel::dynamic::ChannelKey;
+use crate::clock::AudioClock;
 use crate::instance::AudioInstance;
+use crate::metering::{MeterTapBuilder, SharedLevels};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::StreamingAudioSource;
 use crate::{AudioSource, PlaybackState};
-use bevy::asset::Handle;
-use kira::sound::static_sound::StaticSoundData;
+use bevy::asset::{AssetId, Handle};
+use bevy::ecs::event::Event;
+use bevy::ecs::system::Resource;
+use bevy::reflect::std_traits::ReflectDefault;
+use bevy::reflect::Reflect;
+use kira::clock::ClockSpeed;
+use kira::sound::static_sound::StaticSoundSettings;
+use kira::track::effect::EffectBuilder;
+use kira::track::{TrackBuilder, TrackId, TrackRoutes};
 use kira::tween::Value;
 use kira::Volume;
+use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
 use std::any::TypeId;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+/// Identifies a typed or dynamic audio channel
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Channel {
+    /// A typed channel, identified by the type of its marker resource
     Typed(TypeId),
-    Dynamic(String),
+    /// A dynamic channel, identified by its key
+    Dynamic(ChannelKey),
 }
 
-pub(crate) struct ChannelState {
+impl Channel {
+    /// The typed channel [`AudioChannel<T>`](crate::AudioChannel)
+    pub fn typed<T: Resource>() -> Self {
+        Channel::Typed(TypeId::of::<T>())
+    }
+
+    /// The dynamic channel with the given key
+    pub fn dynamic(key: impl Into<ChannelKey>) -> Self {
+        Channel::Dynamic(key.into())
+    }
+}
+
+/// Event sent when a channel starts or stops playing sounds
+///
+/// A channel is active while at least one of its sounds is playing, pausing, or stopping.
+/// The event is only sent when a channel goes from silent to active, or back to silent.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Voice;
+///
+/// fn show_subtitles(mut activity: EventReader<ChannelActivity>) {
+///     for event in activity.read() {
+///         if event.channel == Channel::typed::<Voice>() {
+///             info!("Voice channel active: {}", event.is_active());
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelActivity {
+    /// The channel that became active or silent
+    pub channel: Channel,
+    /// Number of active sounds in the channel, `0` if the channel became silent
+    pub active_instances: usize,
+}
+
+impl ChannelActivity {
+    /// Returns `true` if the channel became active
+    pub fn is_active(&self) -> bool {
+        self.active_instances > 0
+    }
+}
+
+/// Event sent when the volume, panning, playback rate, or pause state of a channel changed
+///
+/// The event is sent once per frame and channel, after the audio commands of the frame ran,
+/// no matter which system changed the channel. Use it to keep volume sliders and other UI in sync
+/// without polling.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// fn update_music_slider(mut changes: EventReader<ChannelSettingsChanged>) {
+///     for change in changes.read() {
+///         if change.channel == Channel::typed::<Music>() {
+///             info!("Music volume: {}", change.settings.volume().as_amplitude());
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct ChannelSettingsChanged {
+    /// The channel that changed
+    pub channel: Channel,
+    /// The new settings of the channel
+    pub settings: ChannelState,
+}
+
+/// Event sent when all play commands that were waiting on a channel ran
+///
+/// Play commands wait while their source is loading, for example. Once the event is sent, the
+/// sounds were handed to the audio backend. Use it together with
+/// [`has_pending_commands`](AudioControl::has_pending_commands) to only continue a loading flow
+/// once the queued audio started.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Cutscene;
+///
+/// fn start_cutscene_video(mut executed: EventReader<PlayCommandsExecuted>) {
+///     if executed
+///         .read()
+///         .any(|event| event.channel == Channel::typed::<Cutscene>())
+///     {
+///         info!("Cutscene audio started, starting the video");
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct PlayCommandsExecuted {
+    /// The channel that ran its play commands
+    pub channel: Channel,
+}
+
+/// Volume, panning, playback rate, and pause state of a channel
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelState {
     pub(crate) paused: bool,
     pub(crate) volume: Volume,
     pub(crate) playback_rate: f64,
-    pub(crate) panning: f64,
+    pub(crate) panning: Panning,
 }
 
 impl Default for ChannelState {
@@ -29,16 +162,288 @@ impl Default for ChannelState {
             paused: false,
             volume: 1.0.into(),
             playback_rate: 1.0,
-            panning: 0.5,
+            panning: Panning::CENTER,
         }
     }
 }
 
 impl ChannelState {
-    pub(crate) fn apply(&self, sound: &mut StaticSoundData) {
-        sound.settings.volume = Value::Fixed(self.volume);
-        sound.settings.playback_rate = self.playback_rate.into();
-        sound.settings.panning = Value::Fixed(self.panning);
+    /// The volume of the channel
+    pub fn volume(&self) -> Volume {
+        self.volume
+    }
+
+    /// The panning of the channel
+    pub fn panning(&self) -> Panning {
+        self.panning
+    }
+
+    /// The playback rate of the channel
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate
+    }
+
+    /// Returns `true` if the channel is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub(crate) fn apply(&self, settings: &mut StaticSoundSettings) {
+        settings.volume = Value::Fixed(self.volume);
+        settings.playback_rate = self.playback_rate.into();
+        combine_panning(settings, self.panning);
+    }
+}
+
+/// Settings for the mixer track backing a channel
+///
+/// Every channel plays its sounds through its own mixer track. Use these settings to add
+/// effects to all sounds of a channel, or to make the channel a sub-mix of another channel.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Sfx;
+///
+/// #[derive(Resource)]
+/// struct Footsteps;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel_with_settings::<Sfx>(
+///         ChannelSettings::default().with_effect(ReverbBuilder::new()),
+///     )
+///     .add_audio_channel_with_settings::<Footsteps>(ChannelSettings::default().with_parent::<Sfx>());
+/// ```
+#[derive(Default)]
+pub struct ChannelSettings {
+    track: TrackBuilder,
+    pub(crate) parent: Option<Channel>,
+    pub(crate) sends: Vec<(Channel, Volume)>,
+    pub(crate) max_instance_duration: Option<(Duration, AudioTween)>,
+    pub(crate) max_instances: Option<(usize, VoiceStealing)>,
+    pub(crate) max_instances_per_source: Option<(usize, VoiceStealing)>,
+    pub(crate) tempo: Option<Tempo>,
+    pub(crate) pan_law: Option<PanLaw>,
+    #[cfg(feature = "analysis")]
+    pub(crate) spectrum: Option<Arc<SharedSpectrum>>,
+    pub(crate) meter: Option<Arc<SharedLevels>>,
+    pub(crate) capture: Option<Arc<SharedCapture>>,
+}
+
+impl ChannelSettings {
+    /// Add an effect to the channel's mixer track
+    ///
+    /// Effects are applied in the order they are added.
+    pub fn with_effect(mut self, effect: impl EffectBuilder) -> Self {
+        self.track.add_effect(effect);
+
+        self
+    }
+
+    /// Make this channel a sub-mix of the typed channel `T`
+    ///
+    /// The volume, pause state, and effects of the parent channel also apply to this channel.
+    /// Sounds on this channel wait until the parent channel is set up, so the parent needs to be
+    /// added to the app as well.
+    pub fn with_parent<T: Resource>(mut self) -> Self {
+        self.parent = Some(Channel::Typed(TypeId::of::<T>()));
+
+        self
+    }
+
+    /// Make this channel a sub-mix of the dynamic channel with the given key
+    ///
+    /// See [`with_parent`](Self::with_parent).
+    pub fn with_dynamic_parent(mut self, key: impl Into<ChannelKey>) -> Self {
+        self.parent = Some(Channel::Dynamic(key.into()));
+
+        self
+    }
+
+    /// Additionally send the sound of this channel to the typed channel `T` with the given volume
+    ///
+    /// Sends are useful for shared effects like a reverb channel. The volume of the send can be
+    /// changed later, for example by [`DistanceReverb`](crate::prelude::DistanceReverb). Like a parent,
+    /// the channel `T` needs to be added to the app as well.
+    pub fn with_send<T: Resource>(mut self, volume: impl Into<Volume>) -> Self {
+        self.sends
+            .push((Channel::Typed(TypeId::of::<T>()), volume.into()));
+
+        self
+    }
+
+    /// Additionally send the sound of this channel to the dynamic channel with the given key
+    ///
+    /// See [`with_send`](Self::with_send).
+    pub fn with_dynamic_send(
+        mut self,
+        key: impl Into<ChannelKey>,
+        volume: impl Into<Volume>,
+    ) -> Self {
+        self.sends
+            .push((Channel::Dynamic(key.into()), volume.into()));
+
+        self
+    }
+
+    /// Stop sounds of this channel that play longer than the given duration
+    ///
+    /// This is a safety net against accidentally looped or run-away sounds. The duration counts
+    /// from the moment a sound started, including time it spent paused. Stopped sounds fade out
+    /// with the default [`AudioTween`]; use [`max_instance_duration_with_fade`](Self::max_instance_duration_with_fade)
+    /// for a custom fade.
+    pub fn max_instance_duration(self, duration: Duration) -> Self {
+        self.max_instance_duration_with_fade(duration, AudioTween::default())
+    }
+
+    /// Stop sounds of this channel that play longer than the given duration with the given fade
+    ///
+    /// See [`max_instance_duration`](Self::max_instance_duration).
+    pub fn max_instance_duration_with_fade(mut self, duration: Duration, fade: AudioTween) -> Self {
+        self.max_instance_duration = Some((duration, fade));
+
+        self
+    }
+
+    /// Limit the number of sounds playing at the same time on this channel
+    ///
+    /// When a new sound would exceed the limit, the given [`VoiceStealing`] policy decides
+    /// whether the new sound is dropped or another sound of the channel is stopped for it.
+    /// Sounds that are stopping already do not count towards the limit.
+    pub fn max_instances(mut self, max_instances: usize, stealing: VoiceStealing) -> Self {
+        self.max_instances = Some((max_instances, stealing));
+
+        self
+    }
+
+    /// Limit the number of sounds of the same source playing at the same time on this channel
+    ///
+    /// Identical sounds overlapping each other quickly muddy the mix, for example a burst of
+    /// the same impact sound. The limit applies to each source separately and in addition to
+    /// [`max_instances`](Self::max_instances). Sounds that are stopping already do not count
+    /// towards the limit.
+    pub fn max_instances_per_source(
+        mut self,
+        max_instances: usize,
+        stealing: VoiceStealing,
+    ) -> Self {
+        self.max_instances_per_source = Some((max_instances, stealing));
+
+        self
+    }
+
+    /// Compute the frequency spectrum of this channel for visualizers
+    ///
+    /// The spectrum is available in the [`AudioSpectrum`](crate::AudioSpectrum) resource.
+    #[cfg(feature = "analysis")]
+    pub fn with_spectrum(mut self, settings: SpectrumSettings) -> Self {
+        let tap = SpectrumTapBuilder::new(settings);
+        self.spectrum = Some(tap.shared.clone());
+        self.track.add_effect(tap);
+
+        self
+    }
+
+    /// Measure the peak and RMS levels of this channel
+    ///
+    /// The levels are measured over windows of 50 milliseconds and available in the
+    /// [`ChannelLevels`](crate::ChannelLevels) resource.
+    pub fn with_metering(mut self) -> Self {
+        let tap = MeterTapBuilder::new(Duration::from_millis(50));
+        self.meter = Some(tap.shared.clone());
+        self.track.add_effect(tap);
+
+        self
+    }
+
+    /// Keep the most recent output of this channel in a ring buffer of the given number of frames
+    ///
+    /// The frames are available through the [`AudioCapture`](crate::AudioCapture) resource, for
+    /// example to analyse them or to send them over the network. The buffer is allocated up
+    /// front, so pick the capacity for the sample rate of the output, e.g. `48_000` for one second
+    /// at 48 kHz.
+    pub fn with_capture(mut self, frames: usize) -> Self {
+        let tap = CaptureTapBuilder::new(frames);
+        self.capture = Some(tap.shared.clone());
+        self.track.add_effect(tap);
+
+        self
+    }
+
+    /// Use the given pan law for sounds of this channel instead of [`AudioSettings::pan_law`](crate::AudioSettings::pan_law)
+    pub fn with_pan_law(mut self, pan_law: PanLaw) -> Self {
+        self.pan_law = Some(pan_law);
+
+        self
+    }
+
+    /// Send [`MusicBeat`](crate::MusicBeat) and [`MusicBar`](crate::MusicBar) events for sounds of this channel
+    ///
+    /// All sounds of the channel are expected to start on the first beat of a bar.
+    pub fn with_tempo(mut self, beats_per_minute: f64, beats_per_bar: u32) -> Self {
+        self.tempo = Some(Tempo {
+            beats_per_minute,
+            beats_per_bar,
+        });
+
+        self
+    }
+
+    pub(crate) fn into_track_builder(
+        self,
+        parent_track: Option<TrackId>,
+        send_tracks: Vec<(TrackId, Volume)>,
+    ) -> TrackBuilder {
+        let routes = match parent_track {
+            Some(parent_track) => TrackRoutes::parent(parent_track),
+            None => TrackRoutes::new(),
+        };
+        let routes = send_tracks
+            .into_iter()
+            .fold(routes, |routes, (track, volume)| {
+                routes.with_route(track, volume)
+            });
+
+        self.track.routes(routes)
+    }
+}
+
+/// What happens when a channel reaches its [maximum number of sounds](ChannelSettings::max_instances)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[reflect(Debug, PartialEq, Default)]
+pub enum VoiceStealing {
+    /// Drop the new sound
+    #[default]
+    RejectNew,
+    /// Stop the sound that started first
+    StopOldest,
+    /// Stop the sound with the lowest volume
+    StopQuietest,
+}
+
+/// Channel settings that still need to be applied to the channel's mixer track
+pub(crate) struct PendingChannelSettings(Mutex<Option<ChannelSettings>>);
+
+impl PendingChannelSettings {
+    pub(crate) fn new(settings: ChannelSettings) -> Self {
+        PendingChannelSettings(Mutex::new(Some(settings)))
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Option<ChannelSettings>> {
+        self.0.lock()
+    }
+
+    /// Returns `true` if there are settings that were not applied yet
+    pub(crate) fn is_pending(&self) -> bool {
+        self.0.lock().is_some()
+    }
+}
+
+impl Default for PendingChannelSettings {
+    fn default() -> Self {
+        PendingChannelSettings::new(ChannelSettings::default())
     }
 }
 
@@ -54,7 +459,95 @@ pub trait AudioControl {
     ///     audio.play(asset_server.load("audio.mp3"));
     /// }
     /// ```
-    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand;
+    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand<'_>;
+
+    /// Play audio that is decoded while playing
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     audio.play_streaming(asset_server.load("music.ogg")).looped();
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_streaming(&self, audio_source: Handle<StreamingAudioSource>) -> PlayAudioCommand<'_>;
+
+    /// Play audio while fading out all sounds that are playing on the channel
+    ///
+    /// The new sound fades in with the given tween while the others fade out with it. Both
+    /// happen in a single command once the new sound starts, so sounds played before on the
+    /// same frame are faded out as well. Use an [equal power](AudioTween::equal_power) tween to
+    /// avoid the dip in loudness of linear crossfades.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// # use std::time::Duration;
+    ///
+    /// fn enter_boss_room(asset_server: Res<AssetServer>, music: Res<Audio>) {
+    ///     music
+    ///         .crossfade_to(
+    ///             asset_server.load("music/boss.ogg"),
+    ///             AudioTween::equal_power(Duration::from_secs(2)),
+    ///         )
+    ///         .looped();
+    /// }
+    /// ```
+    fn crossfade_to(
+        &self,
+        audio_source: Handle<AudioSource>,
+        tween: AudioTween,
+    ) -> PlayAudioCommand<'_>;
+
+    /// Play several sounds with the same settings at once
+    ///
+    /// All sounds are queued as a single command and start in the same frame, once all of them
+    /// are loaded. This is cheaper than calling [`play`](Self::play) for every sound, for example
+    /// when starting a bed of crowd chatter.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn start_crowd(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     let chatter = ["crowd/chatter_1.ogg", "crowd/chatter_2.ogg", "crowd/chatter_3.ogg"];
+    ///     let instances = audio
+    ///         .play_many(chatter.map(|path| asset_server.load(path)))
+    ///         .looped()
+    ///         .with_volume(0.3)
+    ///         .handles();
+    /// }
+    /// ```
+    fn play_many(
+        &self,
+        audio_sources: impl IntoIterator<Item = Handle<AudioSource>>,
+    ) -> PlayManyCommand<'_>;
+
+    /// Play an intro once, followed by a looping body without any gap
+    ///
+    /// The body is scheduled on a clock that ticks when the intro ends, so it starts on the exact
+    /// sample. The settings of the returned command apply to both sounds, while looping and fade
+    /// in only apply to the body and the intro respectively. The returned handle controls the
+    /// body; stopping the channel stops both.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn start_level_music(asset_server: Res<AssetServer>, music: Res<Audio>) {
+    ///     music.play_with_intro(
+    ///         asset_server.load("music/level_intro.ogg"),
+    ///         asset_server.load("music/level_loop.ogg"),
+    ///     );
+    /// }
+    /// ```
+    fn play_with_intro(
+        &self,
+        intro: Handle<AudioSource>,
+        loop_body: Handle<AudioSource>,
+    ) -> PlayAudioCommand<'_>;
 
     /// Stop all audio
     ///
@@ -66,7 +559,7 @@ pub trait AudioControl {
     ///     audio.stop();
     /// }
     /// ```
-    fn stop(&self) -> TweenCommand<FadeOut>;
+    fn stop(&self) -> TweenCommand<'_, FadeOut>;
 
     /// Pause all audio
     ///
@@ -78,10 +571,12 @@ pub trait AudioControl {
     ///     audio.pause();
     /// }
     /// ```
-    fn pause(&self) -> TweenCommand<FadeOut>;
+    fn pause(&self) -> TweenCommand<'_, FadeOut>;
 
     /// Resume all audio
     ///
+    /// Sounds that are fading out to stop keep stopping, unless the command is
+    /// [`resume_all`](TweenCommand::resume_all).
     /// ```
     /// # use bevy::prelude::*;
     /// # use bevy_kira_audio::prelude::*;
@@ -90,7 +585,7 @@ pub trait AudioControl {
     ///     audio.resume();
     /// }
     /// ```
-    fn resume(&self) -> TweenCommand<FadeIn>;
+    fn resume(&self) -> TweenCommand<'_, ResumeFadeIn>;
 
     /// Set the volume
     ///
@@ -105,7 +600,22 @@ pub trait AudioControl {
     ///     audio.set_volume(0.5);
     /// }
     /// ```
-    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<FadeIn>;
+    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<'_, FadeIn>;
+
+    /// Set the volume in decibels
+    ///
+    /// `0` dB is the original volume and every `-6` dB roughly halves the amplitude. Volumes of
+    /// `-60` dB and below are silent.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn my_system(audio: Res<Audio>) {
+    ///     audio.set_volume_db(-6.);
+    /// }
+    /// ```
+    fn set_volume_db(&self, decibels: f64) -> TweenCommand<'_, FadeIn>;
 
     /// Set panning
     ///
@@ -113,6 +623,8 @@ pub trait AudioControl {
     /// Values up to 1 pan to the right
     /// Values down to 0 pan to the left
     ///
+    /// Use [`Panning::from_signed`] to set the panning from `-1` (hard left) to `1` (hard right).
+    ///
     /// ```
     /// # use bevy::prelude::*;
     /// # use bevy_kira_audio::prelude::*;
@@ -121,11 +633,12 @@ pub trait AudioControl {
     ///     audio.set_panning(0.9);
     /// }
     /// ```
-    fn set_panning(&self, panning: f64) -> TweenCommand<FadeIn>;
+    fn set_panning(&self, panning: impl Into<Panning>) -> TweenCommand<'_, FadeIn>;
 
     /// Set playback rate
     ///
-    /// The default value is 1
+    /// The default value is 1. Negative rates play sounds backwards. The rate is clamped to
+    /// [`MAX_PLAYBACK_RATE`](crate::MAX_PLAYBACK_RATE) in either direction.
     ///
     /// ```
     /// # use bevy::prelude::*;
@@ -135,18 +648,247 @@ pub trait AudioControl {
     ///     audio.set_playback_rate(2.0);
     /// }
     /// ```
-    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<FadeIn>;
+    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<'_, FadeIn>;
+
+    /// Seek all sounds in the channel to the given position in seconds
+    ///
+    /// Looping sounds wrap the position into their loop region.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn restart_from_checkpoint(music: Res<Audio>) {
+    ///     music.seek_to(42.0);
+    /// }
+    /// ```
+    fn seek_to(&self, seconds: f64);
+
+    /// Seek all sounds in the channel by the given amount of seconds
+    ///
+    /// Negative values seek backwards.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn skip_ahead(music: Res<Audio>) {
+    ///     music.seek_by(10.0);
+    /// }
+    /// ```
+    fn seek_by(&self, seconds: f64);
+
+    /// Mute all other sounds to listen to the given one alone
+    ///
+    /// This is meant for debugging a sound in a busy mix. The solo applies to the sounds of all
+    /// channels, including sounds started while it is active. Muted sounds keep playing and keep
+    /// their volume, so [`clear_solo`](Self::clear_solo) restores the mix as it was.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    /// #[derive(Resource)]
+    /// struct Suspicious(Handle<AudioInstance>);
+    ///
+    /// fn toggle_solo(
+    ///     keys: Res<ButtonInput<KeyCode>>,
+    ///     suspicious: Res<Suspicious>,
+    ///     audio: Res<Audio>,
+    /// ) {
+    ///     if keys.just_pressed(KeyCode::F9) {
+    ///         audio.solo_instance(&suspicious.0);
+    ///     } else if keys.just_released(KeyCode::F9) {
+    ///         audio.clear_solo();
+    ///     }
+    /// }
+    /// ```
+    fn solo_instance(&self, instance: &Handle<AudioInstance>);
+
+    /// Unmute all sounds muted by [`solo_instance`](Self::solo_instance)
+    fn clear_solo(&self);
 
     /// Get state for a playback instance.
     fn state(&self, instance_handle: &Handle<AudioInstance>) -> PlaybackState;
 
+    /// Returns `true` while commands sent to this channel wait to be executed
+    ///
+    /// Commands run once per frame in [`PostUpdate`](bevy::app::PostUpdate). Play commands
+    /// keep waiting while their source is loading. A [`PlayCommandsExecuted`] event is sent once
+    /// the last waiting play command ran.
+    fn has_pending_commands(&self) -> bool;
+
     /// Returns `true` if there is any sound in this channel that is in the state `Playing`, `Pausing`, or `Stopping`
     ///
     /// If there are only `Stopped`, `Paused`, or `Queued` sounds, the method will return `false`.
     /// The same result is returned if there are no sounds in the channel at all.
     fn is_playing_sound(&self) -> bool;
+
+    /// The sounds of this channel in the order they started
+    ///
+    /// This contains all sounds that are playing, paused, or stopping. The list is updated once
+    /// per frame in [`PreUpdate`](bevy::app::PreUpdate), so sounds only show up in the frame
+    /// after they started playing.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn quiet_all_sounds(audio: Res<Audio>, mut audio_instances: ResMut<Assets<AudioInstance>>) {
+    ///     for handle in audio.instances() {
+    ///         if let Some(instance) = audio_instances.get_mut(handle) {
+    ///             instance.set_volume(0.2, AudioTween::default());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn instances(&self) -> &[Handle<AudioInstance>];
+
+    /// Add a clock ticking at the given speed
+    ///
+    /// The clock is not bound to this channel. Its asset is available once the audio systems ran.
+    /// See [`AudioClock`](crate::AudioClock) for an example.
+    fn add_clock(&self, speed: ClockSpeed) -> Handle<AudioClock>;
+
+    /// Hold back new sounds until the channel is unfrozen
+    ///
+    /// Sounds that are already playing are not affected. Play commands are kept in the queue
+    /// and executed once [`unfreeze`](Self::unfreeze) is called. All other commands, like changing
+    /// the volume, still execute.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn start_scene_transition(sfx: Res<Audio>) {
+    ///     sfx.freeze();
+    /// }
+    /// ```
+    fn freeze(&self);
+
+    /// Play all sounds held back since the channel was frozen
+    ///
+    /// See [`freeze`](Self::freeze)
+    fn unfreeze(&self);
+
+    /// Returns `true` if the channel is frozen
+    ///
+    /// See [`freeze`](Self::freeze)
+    fn is_frozen(&self) -> bool;
 }
 
 pub(crate) trait AudioCommandQue {
     fn que(&self, command: AudioCommand);
+
+    /// Whether play commands are only queued by [`PlayAudioCommand::queue`](crate::PlayAudioCommand::queue)
+    fn explicit_queueing(&self) -> bool {
+        false
+    }
+}
+
+/// Queue the creation of a new clock and return the handle of its future asset
+pub(crate) fn add_clock(que: &impl AudioCommandQue, speed: ClockSpeed) -> Handle<AudioClock> {
+    let asset_id = AssetId::from(uuid::Uuid::new_v4());
+    que.que(AudioCommand::AddClock(speed, asset_id));
+
+    Handle::Weak(asset_id)
+}
+
+/// Queue of audio commands for a single channel
+///
+/// Commands are pushed to the front and executed from the back. The number of queued commands
+/// is mirrored in an atomic, so the audio systems can skip idle channels without taking the lock.
+#[derive(Default)]
+pub(crate) struct CommandQueue {
+    commands: RwLock<VecDeque<AudioCommand>>,
+    len: AtomicUsize,
+    frozen: AtomicBool,
+    explicit_queueing: AtomicBool,
+}
+
+impl CommandQueue {
+    pub(crate) fn push(&self, command: AudioCommand) {
+        self.write().push_front(command);
+    }
+
+    /// Drop the sounds, but keep the newest command per kind of channel state
+    ///
+    /// Used while there is no audio output, so volume, panning, playback rate, and pause state
+    /// set in the meantime still apply once the output is back.
+    pub(crate) fn keep_channel_state(&self) {
+        let mut kept = [false; 4];
+        // the newest commands are at the front
+        self.write().retain(|command| {
+            let kind = match command {
+                AudioCommand::AddClock(..) => return true,
+                AudioCommand::SetVolume(..) => 0,
+                AudioCommand::SetPanning(..) => 1,
+                AudioCommand::SetPlaybackRate(..) => 2,
+                AudioCommand::Pause(..) | AudioCommand::Resume(..) => 3,
+                _ => return false,
+            };
+            !std::mem::replace(&mut kept[kind], true)
+        });
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_frozen(&self, frozen: bool) {
+        self.frozen.store(frozen, Ordering::Release);
+    }
+
+    pub(crate) fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_queueing(&self, queueing: PlayCommandQueueing) {
+        self.explicit_queueing
+            .store(queueing == PlayCommandQueueing::Explicit, Ordering::Release);
+    }
+
+    pub(crate) fn is_explicit_queueing(&self) -> bool {
+        self.explicit_queueing.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, VecDeque<AudioCommand>> {
+        self.commands.read()
+    }
+
+    pub(crate) fn write(&self) -> CommandQueueGuard<'_> {
+        CommandQueueGuard {
+            commands: self.commands.write(),
+            len: &self.len,
+        }
+    }
+}
+
+/// Write access to a [`CommandQueue`] that updates the queue length when dropped
+pub(crate) struct CommandQueueGuard<'a> {
+    commands: RwLockWriteGuard<'a, VecDeque<AudioCommand>>,
+    len: &'a AtomicUsize,
+}
+
+impl<'a> Deref for CommandQueueGuard<'a> {
+    type Target = VecDeque<AudioCommand>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.commands
+    }
+}
+
+impl<'a> DerefMut for CommandQueueGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.commands
+    }
+}
+
+impl<'a> Drop for CommandQueueGuard<'a> {
+    fn drop(&mut self) {
+        self.len.store(self.commands.len(), Ordering::Release);
+    }
 }
@@ -1,28 +1,46 @@
 use crate::audio::{
-    AudioCommand, FadeIn, FadeOut, PlayAudioCommand, PlayAudioSettings, TweenCommand,
-    TweenCommandKind,
+    valid_playback_rate, AudioCommand, AudioTween, FadeIn, FadeOut, IntroSound, PlayAudioCommand,
+    PlayManyCommand, ResumeFadeIn, ResumeTargets, SeekPosition, TweenCommand, TweenCommandKind,
 };
-use crate::channel::AudioCommandQue;
+use crate::backend_settings::PlayCommandQueueing;
+use crate::channel::{
+    add_clock, AudioCommandQue, ChannelSettings, CommandQueue, PendingChannelSettings,
+};
+use crate::clock::AudioClock;
 use crate::instance::AudioInstance;
-use crate::{AudioControl, AudioSource, PlaybackState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::StreamingAudioSource;
+use crate::{AudioControl, AudioSource, Panning, PlaybackState};
 use bevy::asset::{AssetId, Handle};
 use bevy::ecs::system::Resource;
 use bevy::utils::hashbrown::hash_map::Iter;
 use bevy::utils::HashMap;
+use kira::clock::ClockSpeed;
 use kira::Volume;
-use parking_lot::RwLock;
-use std::collections::VecDeque;
+use parking_lot::Mutex;
+use std::any::TypeId;
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 /// A dynamic channel to play and control audio
 #[derive(Default)]
 pub struct DynamicAudioChannel {
-    pub(crate) commands: RwLock<VecDeque<AudioCommand>>,
+    pub(crate) commands: CommandQueue,
     pub(crate) states: HashMap<AssetId<AudioInstance>, PlaybackState>,
+    pub(crate) instances: Vec<Handle<AudioInstance>>,
+    pub(crate) settings: PendingChannelSettings,
 }
 
 impl AudioCommandQue for DynamicAudioChannel {
     fn que(&self, command: AudioCommand) {
-        self.commands.write().push_front(command)
+        self.commands.push(command)
+    }
+
+    fn explicit_queueing(&self) -> bool {
+        self.commands.is_explicit_queueing()
     }
 }
 
@@ -35,10 +53,65 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.play(asset_server.load("audio.mp3"));
     /// }
     /// ```
-    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand {
+    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand<'_> {
+        PlayAudioCommand::new(audio_source, self)
+    }
+
+    /// Play audio that is decoded while playing
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     audio.play_streaming(asset_server.load("music.ogg")).looped();
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_streaming(&self, audio_source: Handle<StreamingAudioSource>) -> PlayAudioCommand<'_> {
         PlayAudioCommand::new(audio_source, self)
     }
 
+    /// Play audio while fading out all sounds that are playing on the channel
+    ///
+    /// See [`AudioControl::crossfade_to`].
+    fn crossfade_to(
+        &self,
+        audio_source: Handle<AudioSource>,
+        tween: AudioTween,
+    ) -> PlayAudioCommand<'_> {
+        let mut command = PlayAudioCommand::new(audio_source, self);
+        command.settings.fade_in = Some(tween.clone());
+        command.settings.crossfade = Some(tween);
+
+        command
+    }
+
+    /// Play several sounds with the same settings at once
+    ///
+    /// See [`AudioControl::play_many`].
+    fn play_many(
+        &self,
+        audio_sources: impl IntoIterator<Item = Handle<AudioSource>>,
+    ) -> PlayManyCommand<'_> {
+        PlayManyCommand::new(audio_sources, self)
+    }
+
+    /// Play an intro once, followed by a looping body without any gap
+    ///
+    /// See [`AudioControl::play_with_intro`].
+    fn play_with_intro(
+        &self,
+        intro: Handle<AudioSource>,
+        loop_body: Handle<AudioSource>,
+    ) -> PlayAudioCommand<'_> {
+        let mut command = PlayAudioCommand::new(loop_body, self);
+        command.looped();
+        command.intro = Some(IntroSound::new(intro));
+
+        command
+    }
+
     /// Stop all audio
     ///
     /// ```
@@ -49,7 +122,7 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.stop();
     /// }
     /// ```
-    fn stop(&self) -> TweenCommand<FadeOut> {
+    fn stop(&self) -> TweenCommand<'_, FadeOut> {
         TweenCommand::new(TweenCommandKind::Stop, self)
     }
 
@@ -63,7 +136,7 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.pause();
     /// }
     /// ```
-    fn pause(&self) -> TweenCommand<FadeOut> {
+    fn pause(&self) -> TweenCommand<'_, FadeOut> {
         TweenCommand::new(TweenCommandKind::Pause, self)
     }
 
@@ -77,8 +150,8 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.resume();
     /// }
     /// ```
-    fn resume(&self) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::Resume, self)
+    fn resume(&self) -> TweenCommand<'_, ResumeFadeIn> {
+        TweenCommand::new(TweenCommandKind::Resume(ResumeTargets::default()), self)
     }
 
     /// Set the volume
@@ -94,9 +167,16 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.set_volume(0.5);
     /// }
     /// ```
-    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<FadeIn> {
+    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<'_, FadeIn> {
         TweenCommand::new(TweenCommandKind::SetVolume(volume.into()), self)
     }
+
+    /// Set the volume in decibels
+    ///
+    /// See [`AudioControl::set_volume_db`].
+    fn set_volume_db(&self, decibels: f64) -> TweenCommand<'_, FadeIn> {
+        self.set_volume(Volume::Decibels(decibels))
+    }
     /// Set panning
     ///
     /// The default value is 0.5
@@ -111,8 +191,8 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.set_panning(0.9);
     /// }
     /// ```
-    fn set_panning(&self, panning: f64) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::SetPanning(panning), self)
+    fn set_panning(&self, panning: impl Into<Panning>) -> TweenCommand<'_, FadeIn> {
+        TweenCommand::new(TweenCommandKind::SetPanning(panning.into()), self)
     }
     /// Set playback rate
     ///
@@ -126,8 +206,33 @@ impl AudioControl for DynamicAudioChannel {
     ///     audio.set_playback_rate(2.0);
     /// }
     /// ```
-    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::SetPlaybackRate(playback_rate), self)
+    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<'_, FadeIn> {
+        TweenCommand::new(
+            TweenCommandKind::SetPlaybackRate(valid_playback_rate(playback_rate)),
+            self,
+        )
+    }
+
+    /// Seek all sounds in the channel to the given position in seconds
+    fn seek_to(&self, seconds: f64) {
+        self.que(AudioCommand::Seek(SeekPosition::To(seconds)));
+    }
+
+    /// Seek all sounds in the channel by the given amount of seconds
+    fn seek_by(&self, seconds: f64) {
+        self.que(AudioCommand::Seek(SeekPosition::By(seconds)));
+    }
+
+    /// Mute all other sounds to listen to the given one alone
+    ///
+    /// See [`AudioControl::solo_instance`].
+    fn solo_instance(&self, instance: &Handle<AudioInstance>) {
+        self.que(AudioCommand::Solo(Some(instance.clone())));
+    }
+
+    /// Unmute all sounds muted by [`solo_instance`](AudioControl::solo_instance)
+    fn clear_solo(&self) {
+        self.que(AudioCommand::Solo(None));
     }
 
     /// Get state for a playback instance.
@@ -140,11 +245,12 @@ impl AudioControl for DynamicAudioChannel {
                     .read()
                     .iter()
                     .find(|command| match command {
-                        AudioCommand::Play(PlayAudioSettings {
-                            instance_handle: handle,
-                            settings: _,
-                            source: _,
-                        }) => handle.id() == instance_handle.id(),
+                        AudioCommand::Play(settings) => {
+                            settings.instance_handle.id() == instance_handle.id()
+                        }
+                        AudioCommand::PlayMany(sounds) => sounds
+                            .iter()
+                            .any(|settings| settings.instance_handle.id() == instance_handle.id()),
                         _ => false,
                     })
                     .map(|_| PlaybackState::Queued)
@@ -152,6 +258,11 @@ impl AudioControl for DynamicAudioChannel {
             })
     }
 
+    /// Returns `true` while commands sent to this channel wait to be executed
+    fn has_pending_commands(&self) -> bool {
+        !self.commands.is_empty()
+    }
+
     /// Returns `true` if there is any sound in this channel that is in the state `Playing`, `Pausing`, or `Stopping`
     ///
     /// If there are only `Stopped`, `Paused`, or `Queued` sounds, the method will return `false`.
@@ -166,6 +277,191 @@ impl AudioControl for DynamicAudioChannel {
                 _ => playing,
             })
     }
+
+    /// The sounds of this channel in the order they started
+    ///
+    /// See [`AudioControl::instances`].
+    fn instances(&self) -> &[Handle<AudioInstance>] {
+        &self.instances
+    }
+
+    /// Add a clock ticking at the given speed
+    ///
+    /// See [`AudioClock`](crate::AudioClock) for an example.
+    fn add_clock(&self, speed: ClockSpeed) -> Handle<AudioClock> {
+        add_clock(self, speed)
+    }
+
+    /// Hold back new sounds until the channel is unfrozen
+    ///
+    /// Sounds that are already playing are not affected. Play commands are kept in the queue
+    /// and executed once [`unfreeze`](Self::unfreeze) is called. All other commands, like changing
+    /// the volume, still execute.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn start_scene_transition(sfx: Res<Audio>) {
+    ///     sfx.freeze();
+    /// }
+    /// ```
+    fn freeze(&self) {
+        self.commands.set_frozen(true);
+    }
+
+    /// Play all sounds held back since the channel was frozen
+    fn unfreeze(&self) {
+        self.commands.set_frozen(false);
+    }
+
+    /// Returns `true` if the channel is frozen
+    fn is_frozen(&self) -> bool {
+        self.commands.is_frozen()
+    }
+}
+
+/// Key of a dynamic audio channel
+///
+/// Keys are cheap to clone and compare. Besides string keys, you can use any hashable value,
+/// like an enum, as key. This avoids typos in string keys and allocating strings in hot paths.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Hash)]
+/// enum Level {
+///     Forest,
+///     Cave,
+/// }
+///
+/// impl From<Level> for ChannelKey {
+///     fn from(level: Level) -> Self {
+///         ChannelKey::hashed(&level)
+///     }
+/// }
+///
+/// fn play(mut channels: ResMut<DynamicAudioChannels>, asset_server: Res<AssetServer>) {
+///     channels
+///         .create_channel_keyed(Level::Cave)
+///         .play(asset_server.load("sounds/drip.ogg"));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub enum ChannelKey {
+    /// A string key
+    Name(Arc<str>),
+    /// A key derived from the hash of a value and its type
+    Hashed(TypeId, u64),
+}
+
+impl ChannelKey {
+    /// Create a key from the hash of the given value
+    ///
+    /// Values of different types never result in the same key.
+    pub fn hashed<K: Hash + 'static>(key: &K) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        ChannelKey::Hashed(TypeId::of::<K>(), hasher.finish())
+    }
+
+    /// Get the name of a string key
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ChannelKey::Name(name) => Some(name),
+            ChannelKey::Hashed(..) => None,
+        }
+    }
+}
+
+/// Borrowed form of a [`ChannelKey`], to look up channels by name without allocating
+#[derive(PartialEq, Eq, Hash)]
+enum ChannelKeyRef<'a> {
+    Name(&'a str),
+    Hashed(TypeId, u64),
+}
+
+/// Keys that can be compared to the keys of a channel map
+trait AsChannelKey {
+    fn key_ref(&self) -> ChannelKeyRef<'_>;
+}
+
+impl AsChannelKey for ChannelKey {
+    fn key_ref(&self) -> ChannelKeyRef<'_> {
+        match self {
+            ChannelKey::Name(name) => ChannelKeyRef::Name(name),
+            ChannelKey::Hashed(type_id, hash) => ChannelKeyRef::Hashed(*type_id, *hash),
+        }
+    }
+}
+
+impl AsChannelKey for &str {
+    fn key_ref(&self) -> ChannelKeyRef<'_> {
+        ChannelKeyRef::Name(self)
+    }
+}
+
+impl<'a> Borrow<dyn AsChannelKey + 'a> for ChannelKey {
+    fn borrow(&self) -> &(dyn AsChannelKey + 'a) {
+        self
+    }
+}
+
+impl PartialEq for dyn AsChannelKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.key_ref() == other.key_ref()
+    }
+}
+
+impl Eq for dyn AsChannelKey + '_ {}
+
+impl Hash for dyn AsChannelKey + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key_ref().hash(state);
+    }
+}
+
+impl PartialEq for ChannelKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.key_ref() == other.key_ref()
+    }
+}
+
+impl Eq for ChannelKey {}
+
+// Hashes like the borrowed key, so channels can be looked up by name
+impl Hash for ChannelKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key_ref().hash(state);
+    }
+}
+
+impl From<&str> for ChannelKey {
+    fn from(name: &str) -> Self {
+        ChannelKey::Name(name.into())
+    }
+}
+
+impl From<String> for ChannelKey {
+    fn from(name: String) -> Self {
+        ChannelKey::Name(name.into())
+    }
+}
+
+impl From<&ChannelKey> for ChannelKey {
+    fn from(key: &ChannelKey) -> Self {
+        key.clone()
+    }
+}
+
+impl Display for ChannelKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelKey::Name(name) => write!(f, "{name}"),
+            ChannelKey::Hashed(_, hash) => write!(f, "#{hash:x}"),
+        }
+    }
 }
 
 /// Resource to play and control audio in dynamic channels
@@ -174,38 +470,109 @@ impl AudioControl for DynamicAudioChannel {
 /// If that is not the case, typed channels are easier to use with Bevy's ECS.
 #[derive(Resource, Default)]
 pub struct DynamicAudioChannels {
-    pub(crate) channels: HashMap<String, DynamicAudioChannel>,
+    pub(crate) channels: HashMap<ChannelKey, DynamicAudioChannel>,
+    /// Removed channels whose sounds still need to be stopped, with the fade out to use
+    pub(crate) removed: Mutex<Vec<(ChannelKey, Option<AudioTween>)>>,
+    pub(crate) queueing: PlayCommandQueueing,
 }
 
 impl DynamicAudioChannels {
+    /// Use the [`PlayCommandQueueing`] of the app for all current and future channels
+    pub(crate) fn set_queueing(&mut self, queueing: PlayCommandQueueing) {
+        self.queueing = queueing;
+        for channel in self.channels.values() {
+            channel.commands.set_queueing(queueing);
+        }
+    }
+
     /// Creates and returns an audio channel for the given key
     ///
     /// If there already is a channel with the given key, it will be stopped and removed.
     pub fn create_channel(&mut self, key: &str) -> &DynamicAudioChannel {
-        if self.is_channel(key) {
-            self.remove_channel(key);
+        self.create_channel_keyed(key)
+    }
+
+    /// Creates and returns an audio channel for the given [`ChannelKey`]
+    ///
+    /// If there already is a channel with the given key, it will be stopped and removed.
+    pub fn create_channel_keyed(&mut self, key: impl Into<ChannelKey>) -> &DynamicAudioChannel {
+        self.create_channel_with_settings(key, ChannelSettings::default())
+    }
+
+    /// Creates and returns an audio channel for the given [`ChannelKey`] with the given settings
+    ///
+    /// If there already is a channel with the given key, it will be stopped and removed.
+    pub fn create_channel_with_settings(
+        &mut self,
+        key: impl Into<ChannelKey>,
+        settings: ChannelSettings,
+    ) -> &DynamicAudioChannel {
+        let key = key.into();
+        if self.is_channel_keyed(&key) {
+            self.remove_channel_keyed(&key);
         }
-        self.channels
-            .insert(key.to_owned(), DynamicAudioChannel::default());
-        self.channels
-            .get(key)
-            .expect("Failed to retrieve dynamic audio channel")
+        let channel = self
+            .channels
+            .entry(key)
+            .or_insert_with(|| DynamicAudioChannel {
+                settings: PendingChannelSettings::new(settings),
+                ..Default::default()
+            });
+        channel.commands.set_queueing(self.queueing);
+
+        channel
     }
 
     /// Remove the channel behind the given key
     ///
-    /// All audio in the channel will be stopped before it is removed.
+    /// All audio in the channel will be stopped and commands that did not run yet are dropped.
+    /// The mixer track and all state of the channel are freed once its sounds stopped.
     /// This method will do nothing if there is no channel for the given key.
     pub fn remove_channel(&mut self, key: &str) {
-        if let Some(channel) = self.get_channel(key) {
-            channel.stop();
+        if let Some((key, _)) = self.channels.remove_entry(&key as &dyn AsChannelKey) {
+            self.removed.get_mut().push((key, None));
+        }
+    }
+
+    /// Remove the channel behind the given [`ChannelKey`]
+    ///
+    /// See [`remove_channel`](Self::remove_channel).
+    pub fn remove_channel_keyed(&mut self, key: impl Into<ChannelKey>) {
+        self.remove(key.into(), None);
+    }
+
+    /// Remove the channel behind the given key after fading out its sounds
+    ///
+    /// See [`remove_channel`](Self::remove_channel).
+    pub fn remove_channel_with_fade(&mut self, key: &str, tween: AudioTween) {
+        self.remove_channel_keyed_with_fade(key, tween);
+    }
+
+    /// Remove the channel behind the given [`ChannelKey`] after fading out its sounds
+    ///
+    /// See [`remove_channel`](Self::remove_channel).
+    pub fn remove_channel_keyed_with_fade(
+        &mut self,
+        key: impl Into<ChannelKey>,
+        tween: AudioTween,
+    ) {
+        self.remove(key.into(), Some(tween));
+    }
+
+    fn remove(&mut self, key: ChannelKey, tween: Option<AudioTween>) {
+        if self.channels.remove(&key).is_some() {
+            self.removed.get_mut().push((key, tween));
         }
-        self.channels.remove(key);
     }
 
     /// Checks if there is a channel available for the given key.
     pub fn is_channel(&self, key: &str) -> bool {
-        self.channels.contains_key(key)
+        self.channels.contains_key(&key as &dyn AsChannelKey)
+    }
+
+    /// Checks if there is a channel available for the given [`ChannelKey`].
+    pub fn is_channel_keyed(&self, key: impl Into<ChannelKey>) -> bool {
+        self.channels.contains_key(&key.into())
     }
 
     /// Get a channel to play and control audio in
@@ -215,25 +582,43 @@ impl DynamicAudioChannels {
     /// If you aren't sure that there is one, you can check with [`is_channel`](Self::is_channel),
     /// or use [`get_channel`](Self::get_channel) instead.
     pub fn channel(&self, key: &str) -> &DynamicAudioChannel {
-        self.channels.get(key).unwrap_or_else(|| {
-            panic!("Attempting to access dynamic audio channel '{key:?}', which doesn't exist.")
+        self.get_channel(key).unwrap_or_else(|| {
+            panic!("Attempting to access dynamic audio channel '{key}', which doesn't exist.")
+        })
+    }
+
+    /// Get a channel to play and control audio in
+    ///
+    /// # Panics
+    /// This method will panic if there is no channel for the given [`ChannelKey`].
+    /// If you aren't sure that there is one, you can check with [`is_channel_keyed`](Self::is_channel_keyed),
+    /// or use [`get_channel_keyed`](Self::get_channel_keyed) instead.
+    pub fn channel_keyed(&self, key: impl Into<ChannelKey>) -> &DynamicAudioChannel {
+        let key = key.into();
+        self.channels.get(&key).unwrap_or_else(|| {
+            panic!("Attempting to access dynamic audio channel '{key}', which doesn't exist.")
         })
     }
 
     /// Get a channel to play and control audio in
     pub fn get_channel(&self, key: &str) -> Option<&DynamicAudioChannel> {
-        self.channels.get(key)
+        self.channels.get(&key as &dyn AsChannelKey)
+    }
+
+    /// Get a channel to play and control audio in
+    pub fn get_channel_keyed(&self, key: impl Into<ChannelKey>) -> Option<&DynamicAudioChannel> {
+        self.channels.get(&key.into())
     }
 
     /// An iterator over the keys and dynamic audio channels
-    pub fn iter(&self) -> Iter<String, DynamicAudioChannel> {
+    pub fn iter(&self) -> Iter<'_, ChannelKey, DynamicAudioChannel> {
         self.channels.iter()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::channel::dynamic::DynamicAudioChannels;
+    use crate::channel::dynamic::{ChannelKey, DynamicAudioChannels};
     use crate::channel::*;
     use bevy::asset::AssetId;
     use uuid::Uuid;
@@ -269,10 +654,15 @@ mod tests {
         let instance_handle =
             Handle::<AudioInstance>::Weak(AssetId::from(Uuid::from_u128(43290473942075938)));
         audio.create_channel("test");
-        audio.channels.get_mut("test").unwrap().states.insert(
-            instance_handle.id(),
-            PlaybackState::Pausing { position: 42. },
-        );
+        audio
+            .channels
+            .get_mut(&ChannelKey::from("test"))
+            .unwrap()
+            .states
+            .insert(
+                instance_handle.id(),
+                PlaybackState::Pausing { position: 42. },
+            );
 
         assert_eq!(
             audio.channel("test").state(&instance_handle),
@@ -284,24 +674,91 @@ mod tests {
     fn finds_playing_sound() {
         let mut audio = DynamicAudioChannels::default();
         audio.create_channel("test");
-        audio.channels.get_mut("test").unwrap().states.insert(
-            AssetId::from(Uuid::from_u128(143290473942075938)),
-            PlaybackState::Queued,
-        );
-        audio.channels.get_mut("test").unwrap().states.insert(
-            AssetId::from(Uuid::from_u128(243290473942075938)),
-            PlaybackState::Paused { position: 42. },
-        );
-        audio.channels.get_mut("test").unwrap().states.insert(
-            AssetId::from(Uuid::from_u128(343290473942075938)),
-            PlaybackState::Stopped,
-        );
+        audio
+            .channels
+            .get_mut(&ChannelKey::from("test"))
+            .unwrap()
+            .states
+            .insert(
+                AssetId::from(Uuid::from_u128(143290473942075938)),
+                PlaybackState::Queued,
+            );
+        audio
+            .channels
+            .get_mut(&ChannelKey::from("test"))
+            .unwrap()
+            .states
+            .insert(
+                AssetId::from(Uuid::from_u128(243290473942075938)),
+                PlaybackState::Paused { position: 42. },
+            );
+        audio
+            .channels
+            .get_mut(&ChannelKey::from("test"))
+            .unwrap()
+            .states
+            .insert(
+                AssetId::from(Uuid::from_u128(343290473942075938)),
+                PlaybackState::Stopped,
+            );
         assert!(!audio.channel("test").is_playing_sound());
 
-        audio.channels.get_mut("test").unwrap().states.insert(
-            AssetId::from(Uuid::from_u128(43290473942075938)),
-            PlaybackState::Playing { position: 42. },
-        );
+        audio
+            .channels
+            .get_mut(&ChannelKey::from("test"))
+            .unwrap()
+            .states
+            .insert(
+                AssetId::from(Uuid::from_u128(43290473942075938)),
+                PlaybackState::Playing { position: 42. },
+            );
         assert!(audio.channel("test").is_playing_sound());
     }
+
+    #[test]
+    fn hashed_keys_differ_between_types() {
+        #[derive(Hash)]
+        enum Level {
+            Forest,
+        }
+        #[derive(Hash)]
+        enum Area {
+            Forest,
+        }
+
+        assert_eq!(
+            ChannelKey::hashed(&Level::Forest),
+            ChannelKey::hashed(&Level::Forest)
+        );
+        assert_ne!(
+            ChannelKey::hashed(&Level::Forest),
+            ChannelKey::hashed(&Area::Forest)
+        );
+    }
+
+    #[test]
+    fn string_and_keyed_api_refer_to_same_channel() {
+        let mut audio = DynamicAudioChannels::default();
+        audio.create_channel("test");
+
+        assert!(audio.is_channel_keyed(ChannelKey::from("test")));
+        assert!(audio.get_channel_keyed(String::from("test")).is_some());
+    }
+
+    #[test]
+    fn names_find_channels_created_with_owned_keys() {
+        #[derive(Hash)]
+        struct Test;
+
+        let mut audio = DynamicAudioChannels::default();
+        audio.create_channel_keyed(String::from("test"));
+        audio.create_channel_keyed(ChannelKey::hashed(&Test));
+
+        assert!(audio.is_channel("test"));
+        assert!(audio.get_channel("test").is_some());
+        assert!(!audio.is_channel("other"));
+        audio.remove_channel("test");
+        assert!(!audio.is_channel_keyed("test"));
+        assert!(audio.is_channel_keyed(ChannelKey::hashed(&Test)));
+    }
 }
@@ -1,16 +1,21 @@
 use crate::audio::{
-    AudioCommand, FadeIn, FadeOut, PlayAudioCommand, PlayAudioSettings, TweenCommand,
-    TweenCommandKind,
+    valid_playback_rate, AudioCommand, AudioTween, FadeIn, FadeOut, IntroSound, PlayAudioCommand,
+    PlayManyCommand, ResumeFadeIn, ResumeTargets, SeekPosition, TweenCommand, TweenCommandKind,
 };
-use crate::channel::AudioCommandQue;
+use crate::backend_settings::AppPlayCommandQueueing;
+use crate::channel::{
+    add_clock, AudioCommandQue, ChannelSettings, CommandQueue, PendingChannelSettings,
+};
+use crate::clock::AudioClock;
 use crate::instance::AudioInstance;
-use crate::{AudioControl, AudioSource, PlaybackState};
-use bevy::asset::{AssetId, Handle};
-use bevy::ecs::system::Resource;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::StreamingAudioSource;
+use crate::{AudioControl, AudioSource, Panning, PlaybackBookmark, PlaybackState};
+use bevy::asset::{AssetId, AssetPath, AssetServer, Handle};
+use bevy::ecs::system::{Res, Resource};
 use bevy::utils::HashMap;
+use kira::clock::ClockSpeed;
 use kira::Volume;
-use parking_lot::RwLock;
-use std::collections::VecDeque;
 use std::marker::PhantomData;
 
 /// Channel to play and control audio
@@ -19,24 +24,103 @@ use std::marker::PhantomData;
 /// By default, there is only the [`AudioChannel<MainTrack>`](crate::Audio) channel.
 #[derive(Resource)]
 pub struct AudioChannel<T> {
-    pub(crate) commands: RwLock<VecDeque<AudioCommand>>,
+    pub(crate) commands: CommandQueue,
     pub(crate) states: HashMap<AssetId<AudioInstance>, PlaybackState>,
+    pub(crate) instances: Vec<Handle<AudioInstance>>,
+    pub(crate) source_paths: HashMap<AssetId<AudioInstance>, (AssetPath<'static>, bool)>,
+    pub(crate) settings: PendingChannelSettings,
     _marker: PhantomData<T>,
 }
 
 impl<T> Default for AudioChannel<T> {
     fn default() -> Self {
+        AudioChannel::<T>::with_settings(ChannelSettings::default())
+    }
+}
+
+impl<T> AudioChannel<T> {
+    pub(crate) fn with_settings(settings: ChannelSettings) -> Self {
         AudioChannel::<T> {
             commands: Default::default(),
             states: Default::default(),
+            instances: Default::default(),
+            source_paths: Default::default(),
+            settings: PendingChannelSettings::new(settings),
             _marker: PhantomData,
         }
     }
 }
 
+impl<T> AudioChannel<T> {
+    /// Get a bookmark of the sound playing in this channel
+    ///
+    /// This is meant for channels playing one sound at a time, like a soundtrack. Playing sounds
+    /// are preferred over paused ones. Returns `None` if no sound loaded from an asset path is
+    /// playing or paused.
+    pub fn playback_bookmark(&self) -> Option<PlaybackBookmark> {
+        self.states
+            .iter()
+            .filter_map(|(id, state)| match state {
+                PlaybackState::Playing { position } => Some((id, *position, true)),
+                PlaybackState::Paused { position } | PlaybackState::Pausing { position } => {
+                    Some((id, *position, false))
+                }
+                _ => None,
+            })
+            .filter_map(|(id, position, playing)| {
+                self.source_paths
+                    .get(id)
+                    .map(|(source, streaming)| (playing, source, position, *streaming))
+            })
+            .max_by_key(|(playing, ..)| *playing)
+            .map(|(_, source, position, streaming)| PlaybackBookmark {
+                source: source.clone(),
+                position,
+                streaming,
+            })
+    }
+
+    /// Play the bookmarked sound from its bookmarked position
+    ///
+    /// See [`PlaybackBookmark`] for an example.
+    pub fn resume_from_bookmark(
+        &self,
+        bookmark: &PlaybackBookmark,
+        asset_server: &AssetServer,
+    ) -> PlayAudioCommand<'_> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut command = if bookmark.streaming {
+            self.play_streaming(asset_server.load(bookmark.source.clone()))
+        } else {
+            self.play(asset_server.load(bookmark.source.clone()))
+        };
+        #[cfg(target_arch = "wasm32")]
+        let mut command = self.play(asset_server.load(bookmark.source.clone()));
+        command.start_from(bookmark.position);
+
+        command
+    }
+}
+
+/// Use the [`PlayCommandQueueing`](crate::PlayCommandQueueing) of the app for the channel
+///
+/// Runs at startup, since channels can be registered before the audio plugin is added.
+pub(crate) fn apply_play_command_queueing<T: Resource>(
+    channel: Res<AudioChannel<T>>,
+    queueing: Option<Res<AppPlayCommandQueueing>>,
+) {
+    if let Some(queueing) = queueing {
+        channel.commands.set_queueing(queueing.0);
+    }
+}
+
 impl<T> AudioCommandQue for AudioChannel<T> {
     fn que(&self, command: AudioCommand) {
-        self.commands.write().push_front(command)
+        self.commands.push(command)
+    }
+
+    fn explicit_queueing(&self) -> bool {
+        self.commands.is_explicit_queueing()
     }
 }
 
@@ -51,10 +135,65 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.play(asset_server.load("audio.mp3"));
     /// }
     /// ```
-    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand {
+    fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand<'_> {
+        PlayAudioCommand::new(audio_source, self)
+    }
+
+    /// Play audio that is decoded while playing
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     audio.play_streaming(asset_server.load("music.ogg")).looped();
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_streaming(&self, audio_source: Handle<StreamingAudioSource>) -> PlayAudioCommand<'_> {
         PlayAudioCommand::new(audio_source, self)
     }
 
+    /// Play audio while fading out all sounds that are playing on the channel
+    ///
+    /// See [`AudioControl::crossfade_to`].
+    fn crossfade_to(
+        &self,
+        audio_source: Handle<AudioSource>,
+        tween: AudioTween,
+    ) -> PlayAudioCommand<'_> {
+        let mut command = PlayAudioCommand::new(audio_source, self);
+        command.settings.fade_in = Some(tween.clone());
+        command.settings.crossfade = Some(tween);
+
+        command
+    }
+
+    /// Play several sounds with the same settings at once
+    ///
+    /// See [`AudioControl::play_many`].
+    fn play_many(
+        &self,
+        audio_sources: impl IntoIterator<Item = Handle<AudioSource>>,
+    ) -> PlayManyCommand<'_> {
+        PlayManyCommand::new(audio_sources, self)
+    }
+
+    /// Play an intro once, followed by a looping body without any gap
+    ///
+    /// See [`AudioControl::play_with_intro`].
+    fn play_with_intro(
+        &self,
+        intro: Handle<AudioSource>,
+        loop_body: Handle<AudioSource>,
+    ) -> PlayAudioCommand<'_> {
+        let mut command = PlayAudioCommand::new(loop_body, self);
+        command.looped();
+        command.intro = Some(IntroSound::new(intro));
+
+        command
+    }
+
     /// Stop all audio
     ///
     /// ```
@@ -65,7 +204,7 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.stop();
     /// }
     /// ```
-    fn stop(&self) -> TweenCommand<FadeOut> {
+    fn stop(&self) -> TweenCommand<'_, FadeOut> {
         TweenCommand::new(TweenCommandKind::Stop, self)
     }
 
@@ -79,7 +218,7 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.pause();
     /// }
     /// ```
-    fn pause(&self) -> TweenCommand<FadeOut> {
+    fn pause(&self) -> TweenCommand<'_, FadeOut> {
         TweenCommand::new(TweenCommandKind::Pause, self)
     }
 
@@ -93,8 +232,8 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.resume();
     /// }
     /// ```
-    fn resume(&self) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::Resume, self)
+    fn resume(&self) -> TweenCommand<'_, ResumeFadeIn> {
+        TweenCommand::new(TweenCommandKind::Resume(ResumeTargets::default()), self)
     }
 
     /// Set the volume
@@ -109,10 +248,17 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.set_volume(0.5);
     /// }
     /// ```
-    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<FadeIn> {
+    fn set_volume(&self, volume: impl Into<Volume>) -> TweenCommand<'_, FadeIn> {
         TweenCommand::new(TweenCommandKind::SetVolume(volume.into()), self)
     }
 
+    /// Set the volume in decibels
+    ///
+    /// See [`AudioControl::set_volume_db`].
+    fn set_volume_db(&self, decibels: f64) -> TweenCommand<'_, FadeIn> {
+        self.set_volume(Volume::Decibels(decibels))
+    }
+
     /// Set panning
     ///
     /// The default value is 0.5
@@ -127,8 +273,8 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.set_panning(0.9);
     /// }
     /// ```
-    fn set_panning(&self, panning: f64) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::SetPanning(panning), self)
+    fn set_panning(&self, panning: impl Into<Panning>) -> TweenCommand<'_, FadeIn> {
+        TweenCommand::new(TweenCommandKind::SetPanning(panning.into()), self)
     }
 
     /// Set playback rate
@@ -143,8 +289,33 @@ impl<T> AudioControl for AudioChannel<T> {
     ///     audio.set_playback_rate(2.0);
     /// }
     /// ```
-    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<FadeIn> {
-        TweenCommand::new(TweenCommandKind::SetPlaybackRate(playback_rate), self)
+    fn set_playback_rate(&self, playback_rate: f64) -> TweenCommand<'_, FadeIn> {
+        TweenCommand::new(
+            TweenCommandKind::SetPlaybackRate(valid_playback_rate(playback_rate)),
+            self,
+        )
+    }
+
+    /// Seek all sounds in the channel to the given position in seconds
+    fn seek_to(&self, seconds: f64) {
+        self.que(AudioCommand::Seek(SeekPosition::To(seconds)));
+    }
+
+    /// Seek all sounds in the channel by the given amount of seconds
+    fn seek_by(&self, seconds: f64) {
+        self.que(AudioCommand::Seek(SeekPosition::By(seconds)));
+    }
+
+    /// Mute all other sounds to listen to the given one alone
+    ///
+    /// See [`AudioControl::solo_instance`].
+    fn solo_instance(&self, instance: &Handle<AudioInstance>) {
+        self.que(AudioCommand::Solo(Some(instance.clone())));
+    }
+
+    /// Unmute all sounds muted by [`solo_instance`](AudioControl::solo_instance)
+    fn clear_solo(&self) {
+        self.que(AudioCommand::Solo(None));
     }
 
     /// Get state for a playback instance.
@@ -157,11 +328,12 @@ impl<T> AudioControl for AudioChannel<T> {
                     .read()
                     .iter()
                     .find(|command| match command {
-                        AudioCommand::Play(PlayAudioSettings {
-                            instance_handle: handle,
-                            settings: _,
-                            source: _,
-                        }) => handle.id() == instance_handle.id(),
+                        AudioCommand::Play(settings) => {
+                            settings.instance_handle.id() == instance_handle.id()
+                        }
+                        AudioCommand::PlayMany(sounds) => sounds
+                            .iter()
+                            .any(|settings| settings.instance_handle.id() == instance_handle.id()),
                         _ => false,
                     })
                     .map(|_| PlaybackState::Queued)
@@ -169,6 +341,11 @@ impl<T> AudioControl for AudioChannel<T> {
             })
     }
 
+    /// Returns `true` while commands sent to this channel wait to be executed
+    fn has_pending_commands(&self) -> bool {
+        !self.commands.is_empty()
+    }
+
     /// Returns `true` if there is any sound in this channel that is in the state `Playing`, `Pausing`, or `Stopping`
     ///
     /// If there are only `Stopped`, `Paused`, or `Queued` sounds, the method will return `false`.
@@ -183,13 +360,55 @@ impl<T> AudioControl for AudioChannel<T> {
                 _ => playing,
             })
     }
+
+    /// The sounds of this channel in the order they started
+    ///
+    /// See [`AudioControl::instances`].
+    fn instances(&self) -> &[Handle<AudioInstance>] {
+        &self.instances
+    }
+
+    /// Add a clock ticking at the given speed
+    ///
+    /// See [`AudioClock`](crate::AudioClock) for an example.
+    fn add_clock(&self, speed: ClockSpeed) -> Handle<AudioClock> {
+        add_clock(self, speed)
+    }
+
+    /// Hold back new sounds until the channel is unfrozen
+    ///
+    /// Sounds that are already playing are not affected. Play commands are kept in the queue
+    /// and executed once [`unfreeze`](Self::unfreeze) is called. All other commands, like changing
+    /// the volume, still execute.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::prelude::*;
+    ///
+    /// fn start_scene_transition(sfx: Res<Audio>) {
+    ///     sfx.freeze();
+    /// }
+    /// ```
+    fn freeze(&self) {
+        self.commands.set_frozen(true);
+    }
+
+    /// Play all sounds held back since the channel was frozen
+    fn unfreeze(&self) {
+        self.commands.set_frozen(false);
+    }
+
+    /// Returns `true` if the channel is frozen
+    fn is_frozen(&self) -> bool {
+        self.commands.is_frozen()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::channel::typed::AudioChannel;
     use crate::channel::*;
-    use crate::Audio;
+    use crate::{Audio, PlaybackBookmark};
     use bevy::asset::{AssetId, Handle};
     use uuid::Uuid;
 
@@ -226,6 +445,36 @@ mod test {
         );
     }
 
+    #[test]
+    fn bookmark_prefers_playing_sounds() {
+        let mut audio = AudioChannel::<Audio>::default();
+        assert_eq!(audio.playback_bookmark(), None);
+
+        let paused = Uuid::from_u128(43290473942075938).into();
+        audio
+            .states
+            .insert(paused, PlaybackState::Paused { position: 3. });
+        audio
+            .source_paths
+            .insert(paused, ("sounds/paused.ogg".into(), false));
+        let playing = Uuid::from_u128(432952340473942075938).into();
+        audio
+            .states
+            .insert(playing, PlaybackState::Playing { position: 42. });
+        audio
+            .source_paths
+            .insert(playing, ("sounds/playing.ogg".into(), true));
+
+        assert_eq!(
+            audio.playback_bookmark(),
+            Some(PlaybackBookmark {
+                source: "sounds/playing.ogg".into(),
+                position: 42.,
+                streaming: true,
+            })
+        );
+    }
+
     #[test]
     fn finds_playing_sound() {
         let mut audio = AudioChannel::<Audio>::default();
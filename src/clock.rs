@@ -0,0 +1,97 @@
+//! Clocks to start sounds in sync with a beat
+
+use crate::audio_output::AudioOutput;
+use crate::instance::{AudioCommandError, AudioInstance};
+use crate::AudioTween;
+use bevy::asset::{Asset, Assets};
+use bevy::ecs::change_detection::{NonSendMut, ResMut};
+use kira::clock::{ClockHandle, ClockSpeed};
+
+/// Asset for controlling a clock
+///
+/// Create clocks with [`add_clock`](crate::AudioControl::add_clock) and start sounds on a tick of
+/// the clock with [`start_at_tick`](crate::PlayAudioCommand::start_at_tick). New clocks start
+/// ticking right away. The clock is removed from the audio backend when its asset is removed.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Beat(Handle<AudioClock>);
+///
+/// fn setup(mut commands: Commands, audio: Res<Audio>) {
+///     let clock = audio.add_clock(ClockSpeed::TicksPerMinute(120.));
+///     commands.insert_resource(Beat(clock));
+/// }
+///
+/// fn play_on_next_beat(
+///     beat: Res<Beat>,
+///     clocks: Res<Assets<AudioClock>>,
+///     audio: Res<Audio>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     if let Some(clock) = clocks.get(&beat.0) {
+///         audio
+///             .play(asset_server.load("sounds/drum.ogg"))
+///             .start_at_tick(&beat.0, clock.ticks() + 1);
+///     }
+/// }
+/// ```
+#[derive(Asset, bevy::reflect::TypePath)]
+pub struct AudioClock {
+    pub(crate) handle: ClockHandle,
+}
+
+impl AudioClock {
+    /// Start or resume the clock
+    pub fn start(&mut self) -> Option<AudioCommandError> {
+        self.handle
+            .start()
+            .err()
+            .map(|kira_error| kira_error.into())
+    }
+
+    /// Pause the clock
+    pub fn pause(&mut self) -> Option<AudioCommandError> {
+        self.handle
+            .pause()
+            .err()
+            .map(|kira_error| kira_error.into())
+    }
+
+    /// Stop the clock and reset it to tick zero
+    pub fn stop(&mut self) -> Option<AudioCommandError> {
+        self.handle.stop().err().map(|kira_error| kira_error.into())
+    }
+
+    /// Set the speed of the clock with the given easing
+    pub fn set_speed(&mut self, speed: ClockSpeed, tween: AudioTween) -> Option<AudioCommandError> {
+        self.handle
+            .set_speed(speed, tween.into())
+            .err()
+            .map(|kira_error| kira_error.into())
+    }
+
+    /// Returns `true` if the clock is ticking
+    pub fn is_ticking(&self) -> bool {
+        self.handle.ticking()
+    }
+
+    /// Number of ticks since the clock was started
+    pub fn ticks(&self) -> u64 {
+        self.handle.time().ticks
+    }
+
+    /// Progress towards the next tick from `0.0` to `1.0`
+    pub fn fractional_position(&self) -> f64 {
+        self.handle.fractional_position()
+    }
+}
+
+pub(crate) fn update_audio_clocks(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut clocks: ResMut<Assets<AudioClock>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    audio_output.update_clocks(&mut clocks, &mut audio_instances);
+}
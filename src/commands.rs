@@ -0,0 +1,330 @@
+//! Play sounds through [`Commands`] without accessing the channel resources
+
+use crate::audio::PlayAudioCommand;
+use crate::channel::AudioControl;
+use crate::source::AudioCue;
+use crate::spatial::{AudioEmitter, KeepStereo};
+use crate::{Audio, AudioInstance, AudioSource, DynamicAudioChannels};
+use bevy::asset::{AssetServer, Assets, Handle, LoadState};
+use bevy::ecs::system::{EntityCommands, Resource};
+use bevy::ecs::world::Command;
+use bevy::log::warn;
+use bevy::prelude::{Commands, Entity, World};
+
+type ConfigureSound = Box<dyn FnOnce(&mut PlayAudioCommand<'_>) + Send>;
+
+/// Extension trait to play sounds on the [`Audio`] channel through [`Commands`]
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Sounds {
+///     hit: Handle<AudioSource>,
+/// }
+///
+/// #[derive(Event)]
+/// struct Hit;
+///
+/// fn play_hit_sound(mut hits: EventReader<Hit>, sounds: Res<Sounds>, mut commands: Commands) {
+///     for _ in hits.read() {
+///         commands.play_sound_with(sounds.hit.clone(), |sound| {
+///             sound.with_volume(0.5);
+///         });
+///     }
+/// }
+/// ```
+pub trait AudioCommandsExt {
+    /// Play the sound on the [`Audio`] channel
+    fn play_sound(&mut self, source: Handle<AudioSource>) -> &mut Self;
+
+    /// Play the sound on the [`Audio`] channel with settings applied by the given function
+    fn play_sound_with(
+        &mut self,
+        source: Handle<AudioSource>,
+        configure: impl FnOnce(&mut PlayAudioCommand<'_>) + Send + 'static,
+    ) -> &mut Self;
+
+    /// Play the [`AudioCue`] on its channel with its settings
+    ///
+    /// If the cue is not loaded yet, it plays once it is.
+    fn play_cue(&mut self, cue: Handle<AudioCue>) -> &mut Self;
+}
+
+impl AudioCommandsExt for Commands<'_, '_> {
+    fn play_sound(&mut self, source: Handle<AudioSource>) -> &mut Self {
+        self.add(PlaySound {
+            source,
+            configure: None,
+            emitter: None,
+        });
+
+        self
+    }
+
+    fn play_sound_with(
+        &mut self,
+        source: Handle<AudioSource>,
+        configure: impl FnOnce(&mut PlayAudioCommand<'_>) + Send + 'static,
+    ) -> &mut Self {
+        self.add(PlaySound {
+            source,
+            configure: Some(Box::new(configure)),
+            emitter: None,
+        });
+
+        self
+    }
+
+    fn play_cue(&mut self, cue: Handle<AudioCue>) -> &mut Self {
+        self.add(PlayCue { cue, emitter: None });
+
+        self
+    }
+}
+
+/// Plays the sound and adds its instance to the entity's [`AudioEmitter`]
+///
+/// An [`AudioEmitter`] is inserted, if the entity does not have one yet. The sound is mixed down
+/// to mono, unless the entity has a [`KeepStereo`] component.
+impl AudioCommandsExt for EntityCommands<'_> {
+    fn play_sound(&mut self, source: Handle<AudioSource>) -> &mut Self {
+        let emitter = self.id();
+        self.commands().add(PlaySound {
+            source,
+            configure: None,
+            emitter: Some(emitter),
+        });
+
+        self
+    }
+
+    fn play_sound_with(
+        &mut self,
+        source: Handle<AudioSource>,
+        configure: impl FnOnce(&mut PlayAudioCommand<'_>) + Send + 'static,
+    ) -> &mut Self {
+        let emitter = self.id();
+        self.commands().add(PlaySound {
+            source,
+            configure: Some(Box::new(configure)),
+            emitter: Some(emitter),
+        });
+
+        self
+    }
+
+    fn play_cue(&mut self, cue: Handle<AudioCue>) -> &mut Self {
+        let emitter = self.id();
+        self.commands().add(PlayCue {
+            cue,
+            emitter: Some(emitter),
+        });
+
+        self
+    }
+}
+
+struct PlaySound {
+    source: Handle<AudioSource>,
+    configure: Option<ConfigureSound>,
+    emitter: Option<Entity>,
+}
+
+impl Command for PlaySound {
+    fn apply(self, world: &mut World) {
+        let Some(audio) = world.get_resource::<Audio>() else {
+            warn!("Failed to play sound, because the AudioPlugin was not added");
+            return;
+        };
+        let mut command = audio.play(self.source);
+        if let Some(emitter) = self.emitter {
+            play_in_mono(world, emitter, &mut command);
+        }
+        if let Some(configure) = self.configure {
+            configure(&mut command);
+        }
+        let instance = command.queue();
+        drop(command);
+
+        if let Some(emitter) = self.emitter {
+            add_to_emitter(world, emitter, instance);
+        }
+    }
+}
+
+struct PlayCue {
+    cue: Handle<AudioCue>,
+    emitter: Option<Entity>,
+}
+
+/// Cues that are played once they are loaded
+#[derive(Resource, Default)]
+pub(crate) struct PendingCues(Vec<PlayCue>);
+
+impl Command for PlayCue {
+    fn apply(self, world: &mut World) {
+        let Some(cues) = world.get_resource::<Assets<AudioCue>>() else {
+            warn!("Failed to play cue, because the AudioPlugin was not added");
+            return;
+        };
+        let Some(cue) = cues.get(&self.cue).cloned() else {
+            let failed = world
+                .get_resource::<AssetServer>()
+                .and_then(|asset_server| asset_server.get_load_state(&self.cue))
+                .map_or(false, |state| matches!(state, LoadState::Failed(_)));
+            if failed {
+                warn!("Failed to play cue, because it could not be loaded");
+            } else {
+                world.resource_mut::<PendingCues>().0.push(self);
+            }
+            return;
+        };
+
+        let instance = match &cue.channel {
+            Some(key) => {
+                let Some(channel) = world
+                    .get_resource::<DynamicAudioChannels>()
+                    .and_then(|channels| channels.get_channel_keyed(key))
+                else {
+                    warn!("Failed to play cue, because there is no dynamic channel '{key}'");
+                    return;
+                };
+                play_cue_on(channel, &cue, world, self.emitter)
+            }
+            None => play_cue_on(world.resource::<Audio>(), &cue, world, self.emitter),
+        };
+
+        if let Some(emitter) = self.emitter {
+            add_to_emitter(world, emitter, instance);
+        }
+    }
+}
+
+fn play_cue_on(
+    channel: &impl AudioControl,
+    cue: &AudioCue,
+    world: &World,
+    emitter: Option<Entity>,
+) -> Handle<AudioInstance> {
+    let mut command = channel.play(cue.source.clone());
+    if let Some(emitter) = emitter {
+        play_in_mono(world, emitter, &mut command);
+    }
+    cue.settings.configure(&mut command);
+
+    command.queue()
+}
+
+/// Mix sounds of emitters down to mono, unless the entity keeps them in stereo
+fn play_in_mono(world: &World, emitter: Entity, command: &mut PlayAudioCommand<'_>) {
+    if world.get::<KeepStereo>(emitter).is_none() {
+        command.mono();
+    }
+}
+
+/// Add the instance to the entity's [`AudioEmitter`], inserting one if needed
+fn add_to_emitter(world: &mut World, emitter: Entity, instance: Handle<AudioInstance>) {
+    let Some(mut entity) = world.get_entity_mut(emitter) else {
+        warn!("Failed to add sound to the emitter {emitter:?}, because it does not exist");
+        return;
+    };
+    match entity.get_mut::<AudioEmitter>() {
+        Some(mut emitter) => emitter.instances.push(instance),
+        None => {
+            entity.insert(AudioEmitter {
+                instances: vec![instance],
+            });
+        }
+    }
+}
+
+pub(crate) fn play_pending_cues(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<PendingCues>().0);
+    for cue in pending {
+        cue.apply(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioCommand;
+    use bevy::ecs::world::CommandQueue;
+
+    #[test]
+    fn entity_sounds_are_added_to_the_emitter() {
+        let mut world = World::new();
+        world.insert_resource(Audio::default());
+        let entity = world.spawn_empty().id();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.play_sound(Handle::default());
+        commands
+            .entity(entity)
+            .play_sound_with(Handle::default(), |sound| {
+                sound.looped();
+            });
+        queue.apply(&mut world);
+
+        assert_eq!(world.resource::<Audio>().commands.len(), 2);
+        let emitter = world.get::<AudioEmitter>(entity).unwrap();
+        assert_eq!(emitter.instances.len(), 1);
+    }
+
+    #[test]
+    fn emitter_sounds_are_mono_unless_kept_stereo() {
+        let mut world = World::new();
+        world.insert_resource(Audio::default());
+        let mono = world.spawn_empty().id();
+        let stereo = world.spawn(KeepStereo).id();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.entity(mono).play_sound(Handle::default());
+        commands.entity(stereo).play_sound(Handle::default());
+        queue.apply(&mut world);
+
+        let modes: Vec<bool> = world
+            .resource::<Audio>()
+            .commands
+            .read()
+            .iter()
+            .rev()
+            .map(|command| match command {
+                AudioCommand::Play(play_args) => play_args.settings.mono,
+                _ => panic!("Wrong audio command"),
+            })
+            .collect();
+        assert_eq!(modes, vec![true, false]);
+    }
+
+    #[test]
+    fn cues_play_once_they_are_loaded() {
+        let mut world = World::new();
+        world.insert_resource(Audio::default());
+        world.init_resource::<Assets<AudioCue>>();
+        world.init_resource::<PendingCues>();
+        let cue = world.resource::<Assets<AudioCue>>().reserve_handle();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.play_cue(cue.clone());
+        queue.apply(&mut world);
+        assert_eq!(world.resource::<PendingCues>().0.len(), 1);
+        assert!(world.resource::<Audio>().commands.is_empty());
+
+        world.resource_mut::<Assets<AudioCue>>().insert(
+            &cue,
+            AudioCue {
+                source: Handle::default(),
+                channel: None,
+                settings: Default::default(),
+            },
+        );
+        play_pending_cues(&mut world);
+        assert!(world.resource::<PendingCues>().0.is_empty());
+        assert_eq!(world.resource::<Audio>().commands.len(), 1);
+    }
+}
@@ -0,0 +1,102 @@
+//! Run conditions to only run systems while audio is available or playing
+
+use crate::audio::PlaybackState;
+use crate::audio_output::AudioOutput;
+use crate::channel::typed::AudioChannel;
+use crate::channel::AudioControl;
+use crate::AudioInstance;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::{IntoSystem, NonSend, ReadOnlySystem, Res, Resource};
+
+/// Run condition that is `true` if the audio backend could be initialized
+///
+/// Without an audio device, all play commands are dropped. Setting up the backend is retried
+/// every few seconds, so this can become `true` later, for example when a headset is plugged in.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # fn setup_music_menu() {}
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_systems(Startup, setup_music_menu.run_if(audio_output_available()));
+/// ```
+pub fn audio_output_available() -> impl ReadOnlySystem<In = (), Out = bool> {
+    IntoSystem::into_system(|audio_output: Option<NonSend<AudioOutput>>| {
+        audio_output.map_or(false, |audio_output| audio_output.is_available())
+    })
+}
+
+/// Run condition that is `true` while the channel plays a sound
+///
+/// Sounds that are pausing or stopping count as playing, see [`AudioControl::is_playing_sound`].
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// # fn sync_to_beat() {}
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Music>()
+///     .add_systems(Update, sync_to_beat.run_if(channel_is_playing::<Music>()));
+/// ```
+pub fn channel_is_playing<T: Resource>() -> impl FnMut(Option<Res<AudioChannel<T>>>) -> bool {
+    |channel: Option<Res<AudioChannel<T>>>| {
+        channel.map_or(false, |channel| channel.is_playing_sound())
+    }
+}
+
+/// Run condition that is `true` while the given instance plays
+///
+/// Queued, paused, and stopped instances do not count as playing.
+pub fn instance_is_playing(
+    instance: Handle<AudioInstance>,
+) -> impl FnMut(Res<Assets<AudioInstance>>) -> bool {
+    move |audio_instances: Res<Assets<AudioInstance>>| {
+        audio_instances.get(&instance).map_or(false, |instance| {
+            matches!(instance.state(), PlaybackState::Playing { .. })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MainTrack;
+    use bevy::ecs::system::{RunSystemOnce, System};
+    use bevy::prelude::World;
+    use kira::manager::backend::mock::MockBackend;
+    use kira::manager::AudioManager;
+
+    #[test]
+    fn audio_output_is_unavailable_without_a_manager() {
+        let mut world = World::new();
+        let mut condition = audio_output_available();
+        condition.initialize(&mut world);
+        assert!(!condition.run((), &mut world));
+
+        world.insert_non_send_resource(AudioOutput::new(None::<AudioManager<MockBackend>>));
+        assert!(!condition.run((), &mut world));
+    }
+
+    #[test]
+    fn channel_is_playing_while_a_sound_plays() {
+        let mut world = World::new();
+        assert!(!world.run_system_once(channel_is_playing::<MainTrack>()));
+
+        let mut channel = AudioChannel::<MainTrack>::default();
+        let instance = Handle::<AudioInstance>::default();
+        channel
+            .states
+            .insert(instance.id(), PlaybackState::Paused { position: 1. });
+        world.insert_resource(channel);
+        assert!(!world.run_system_once(channel_is_playing::<MainTrack>()));
+
+        world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .states
+            .insert(instance.id(), PlaybackState::Playing { position: 1. });
+        assert!(world.run_system_once(channel_is_playing::<MainTrack>()));
+    }
+}
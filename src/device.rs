@@ -0,0 +1,55 @@
+//! Choose the output device that the audio plays on
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend;
+
+use crate::audio_output::AudioOutput;
+use bevy::ecs::system::{NonSendMut, Res, Resource};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use backend::{output_devices, ConfigPreferences};
+#[cfg(not(target_arch = "wasm32"))]
+pub use backend::{OutputBackend, OutputBackendSettings};
+/// The kira backend that the [`AudioPlugin`](crate::AudioPlugin) plays through by default
+#[cfg(target_arch = "wasm32")]
+pub type OutputBackend = kira::manager::backend::DefaultBackend;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn output_devices() -> Vec<String> {
+    Vec::new()
+}
+
+/// Resource choosing the output device that the audio plays on
+///
+/// Insert it before adding the [`AudioPlugin`](crate::AudioPlugin) to start on the chosen
+/// device, or change it at any time to move the audio to another device. Sounds keep playing
+/// while moving. If the chosen device is not available, the audio plays on the default device
+/// of the system.
+///
+/// Choosing a device is only supported on desktop platforms.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn select_device(mut device: ResMut<AudioOutputDevice>, mut index: Local<usize>) {
+///     let devices = AudioSettings::output_devices();
+///     if devices.is_empty() {
+///         return;
+///     }
+///     *index = (*index + 1) % devices.len();
+///     device.name = Some(devices[*index].clone());
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AudioOutputDevice {
+    /// Name of the device as listed by [`AudioSettings::output_devices`](crate::AudioSettings::output_devices)
+    ///
+    /// `None` plays on the default device and follows it when it changes.
+    pub name: Option<String>,
+}
+
+pub(crate) fn switch_output_device(
+    mut audio_output: NonSendMut<AudioOutput>,
+    device: Res<AudioOutputDevice>,
+) {
+    audio_output.set_output_device(device.name.clone());
+}
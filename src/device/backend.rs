@@ -0,0 +1,336 @@
+//! Cpal backend that can move the audio to another output device
+//!
+//! This follows the cpal backend of kira, which always plays on the default device.
+
+use bevy::log::warn;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{
+    BufferSize, Device, SampleRate, Stream, StreamConfig, StreamError, SupportedBufferSize,
+};
+use kira::manager::backend::cpal::Error;
+use kira::manager::backend::{Backend, Renderer};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the stream is checked for device changes and disconnections
+const CHECK_STREAM_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Settings of the [`OutputBackend`], taken from the [`AudioSettings`](crate::AudioSettings)
+/// and the [`AudioOutputDevice`](crate::AudioOutputDevice)
+#[derive(Default)]
+pub struct OutputBackendSettings {
+    /// Name of the device to play on, the default device if `None`
+    pub(crate) device: Option<String>,
+    pub(crate) preferences: ConfigPreferences,
+}
+
+/// Stream configuration to use instead of the defaults of the device
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ConfigPreferences {
+    /// Frames per buffer, clamped to the range supported by the device
+    pub(crate) buffer_size: Option<u32>,
+    /// Only used if the device supports it
+    pub(crate) sample_rate: Option<u32>,
+}
+
+/// State shared with the thread managing the stream
+#[derive(Default)]
+struct StreamControl {
+    /// Device to move to, `Some(None)` for the default device
+    requested_device: Mutex<Option<Option<String>>>,
+    should_drop: AtomicBool,
+}
+
+/// The kira backend that the [`AudioPlugin`](crate::AudioPlugin) plays through by default
+///
+/// It plays on the cpal output device chosen with [`AudioOutputDevice`](crate::AudioOutputDevice)
+/// and moves to another device without interrupting sounds.
+pub struct OutputBackend {
+    /// Device and config to start the stream with
+    setup: Option<(Device, StreamConfig)>,
+    device: Option<String>,
+    preferences: ConfigPreferences,
+    control: Arc<StreamControl>,
+}
+
+impl OutputBackend {
+    /// Move the audio to the device with the given name, or the default device
+    pub(crate) fn set_device(&mut self, device: Option<String>) {
+        *self.control.requested_device.lock() = Some(device);
+    }
+}
+
+impl Backend for OutputBackend {
+    type Settings = OutputBackendSettings;
+    type Error = Error;
+
+    fn setup(settings: Self::Settings) -> Result<(Self, u32), Self::Error> {
+        let (device, config) = device_and_config(settings.device.as_deref(), settings.preferences)?;
+        warn_if_missing(settings.device.as_deref(), &device);
+        let sample_rate = config.sample_rate.0;
+        if settings
+            .preferences
+            .sample_rate
+            .map_or(false, |preferred| preferred != sample_rate)
+        {
+            warn!(
+                "Audio output device does not support the preferred sample rate, using {} Hz",
+                sample_rate
+            );
+        }
+
+        Ok((
+            OutputBackend {
+                setup: Some((device, config)),
+                device: settings.device,
+                preferences: settings.preferences,
+                control: Arc::default(),
+            },
+            sample_rate,
+        ))
+    }
+
+    fn start(&mut self, renderer: Renderer) -> Result<(), Self::Error> {
+        let (device, config) = self
+            .setup
+            .take()
+            .expect("Cannot start the audio backend multiple times");
+        let control = self.control.clone();
+        let chosen_device = self.device.clone();
+        let preferences = self.preferences;
+        // cpal streams cannot be sent to other threads on all platforms
+        std::thread::spawn(move || {
+            let mut stream_manager = StreamManager {
+                renderer: Arc::new(Mutex::new(Some(renderer))),
+                stream: None,
+                stream_error: Arc::default(),
+                chosen_device,
+                preferences,
+                device_name: device_name(&device),
+                sample_rate: config.sample_rate.0,
+                failing: false,
+            };
+            if let Err(error) = stream_manager.start_stream(&device, &config) {
+                warn!("Failed to start audio stream due to {:?}", error);
+            }
+            loop {
+                std::thread::sleep(CHECK_STREAM_INTERVAL);
+                if control.should_drop.load(Ordering::SeqCst) {
+                    break;
+                }
+                stream_manager.check_stream(&control);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl Drop for OutputBackend {
+    fn drop(&mut self) {
+        self.control.should_drop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Starts the cpal stream and restarts it on another device if needed
+struct StreamManager {
+    /// The renderer while no stream is running
+    renderer: Arc<Mutex<Option<Renderer>>>,
+    stream: Option<Stream>,
+    stream_error: Arc<Mutex<Option<StreamError>>>,
+    /// Name of the device to play on, the default device if `None`
+    chosen_device: Option<String>,
+    preferences: ConfigPreferences,
+    device_name: String,
+    sample_rate: u32,
+    /// Restarting the stream failed the last time it was tried
+    failing: bool,
+}
+
+impl StreamManager {
+    fn check_stream(&mut self, control: &StreamControl) {
+        if let Some(device) = control.requested_device.lock().take() {
+            if device != self.chosen_device {
+                self.chosen_device = device;
+                self.restart_stream();
+                return;
+            }
+        }
+        // kira only restarts on disconnects, but a stream that reported an error is often silent
+        let failed = self.stream_error.lock().take().is_some();
+        if failed || self.stream.is_none() {
+            self.restart_stream();
+            return;
+        }
+        // Disabled on macos due to audio artifacts when querying devices while playing,
+        // see https://github.com/tesselode/kira/issues/38
+        #[cfg(not(target_os = "macos"))]
+        if let Ok((device, config)) =
+            device_and_config(self.chosen_device.as_deref(), ConfigPreferences::default())
+        {
+            // a preferred sample rate is kept, even if the default of the device changes
+            let sample_rate_changed =
+                self.preferences.sample_rate.is_none() && config.sample_rate.0 != self.sample_rate;
+            if device_name(&device) != self.device_name || sample_rate_changed {
+                self.restart_stream();
+            }
+        }
+    }
+
+    fn restart_stream(&mut self) {
+        self.stop_stream();
+        let result = device_and_config(self.chosen_device.as_deref(), self.preferences).and_then(
+            |(device, config)| {
+                warn_if_missing(self.chosen_device.as_deref(), &device);
+                self.start_stream(&device, &config)
+            },
+        );
+        // restarting is retried until it works, only warn about the first failure
+        if let Err(error) = &result {
+            if !self.failing {
+                warn!("Failed to restart audio stream due to {:?}", error);
+            }
+        }
+        self.failing = result.is_err();
+    }
+
+    fn start_stream(&mut self, device: &Device, config: &StreamConfig) -> Result<(), Error> {
+        let mut renderer = self
+            .renderer
+            .lock()
+            .take()
+            .expect("The renderer is owned by a running stream");
+        let sample_rate = config.sample_rate.0;
+        if sample_rate != self.sample_rate {
+            renderer.on_change_sample_rate(sample_rate);
+        }
+        self.device_name = device_name(device);
+        self.sample_rate = sample_rate;
+        let mut renderer = RendererWrapper {
+            renderer: Some(renderer),
+            slot: self.renderer.clone(),
+        };
+        let stream_error = self.stream_error.clone();
+        let channels = config.channels;
+        // if building or playing the stream fails, dropping it returns the renderer
+        let stream = device.build_output_stream(
+            config,
+            move |data: &mut [f32], _| process_renderer(&mut renderer, data, channels),
+            move |error| *stream_error.lock() = Some(error),
+            None,
+        )?;
+        stream.play()?;
+        self.stream = Some(stream);
+
+        Ok(())
+    }
+
+    fn stop_stream(&mut self) {
+        self.stream = None;
+        self.stream_error.lock().take();
+    }
+}
+
+/// Puts the renderer back into its slot when the stream owning it is dropped
+struct RendererWrapper {
+    renderer: Option<Renderer>,
+    slot: Arc<Mutex<Option<Renderer>>>,
+}
+
+impl Drop for RendererWrapper {
+    fn drop(&mut self) {
+        *self.slot.lock() = self.renderer.take();
+    }
+}
+
+fn process_renderer(wrapper: &mut RendererWrapper, data: &mut [f32], channels: u16) {
+    let Some(renderer) = wrapper.renderer.as_mut() else {
+        return;
+    };
+    renderer.on_start_processing();
+    for frame in data.chunks_exact_mut(channels as usize) {
+        let out = renderer.process();
+        if channels == 1 {
+            frame[0] = (out.left + out.right) / 2.;
+        } else {
+            frame[0] = out.left;
+            frame[1] = out.right;
+            // silence additional channels instead of leaving garbage in them
+            for channel in frame.iter_mut().skip(2) {
+                *channel = 0.;
+            }
+        }
+    }
+}
+
+/// The device with the given name, or the default device, and its config
+fn device_and_config(
+    name: Option<&str>,
+    preferences: ConfigPreferences,
+) -> Result<(Device, StreamConfig), Error> {
+    let host = cpal::default_host();
+    let device = match name.and_then(|name| find_device(&host, name)) {
+        Some(device) => device,
+        None => host
+            .default_output_device()
+            .ok_or(Error::NoDefaultOutputDevice)?,
+    };
+    let default_config = device.default_output_config()?;
+    let mut config = default_config.config();
+    if let Some(sample_rate) = preferences.sample_rate {
+        let supported = device
+            .supported_output_configs()
+            .map_or(false, |mut ranges| {
+                ranges.any(|range| {
+                    range.channels() == config.channels
+                        && range.sample_format() == default_config.sample_format()
+                        && (range.min_sample_rate().0..=range.max_sample_rate().0)
+                            .contains(&sample_rate)
+                })
+            });
+        if supported {
+            config.sample_rate = SampleRate(sample_rate);
+        }
+    }
+    if let Some(buffer_size) = preferences.buffer_size {
+        config.buffer_size = BufferSize::Fixed(match default_config.buffer_size() {
+            SupportedBufferSize::Range { min, max } => buffer_size.clamp(*min, *max),
+            SupportedBufferSize::Unknown => buffer_size,
+        });
+    }
+
+    Ok((device, config))
+}
+
+fn find_device(host: &cpal::Host, name: &str) -> Option<Device> {
+    host.output_devices().ok()?.find(|device| {
+        device
+            .name()
+            .map_or(false, |device_name| device_name == name)
+    })
+}
+
+fn warn_if_missing(chosen: Option<&str>, device: &Device) {
+    if let Some(chosen) = chosen {
+        let name = device_name(device);
+        if name != chosen {
+            warn!("Audio output device '{chosen}' is not available, playing on '{name}' instead");
+        }
+    }
+}
+
+fn device_name(device: &Device) -> String {
+    device
+        .name()
+        .unwrap_or_else(|_| "device name unavailable".to_owned())
+}
+
+/// Names of the available output devices
+pub(crate) fn output_devices() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.map(|device| device_name(&device)).collect())
+        .unwrap_or_default()
+}
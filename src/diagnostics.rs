@@ -0,0 +1,140 @@
+//! Diagnostics of the audio output for [`LogDiagnosticsPlugin`](bevy::diagnostic::LogDiagnosticsPlugin) and performance overlays
+
+use crate::audio_output::AudioOutput;
+use crate::channel::dynamic::ChannelKey;
+use crate::channel::Channel;
+use crate::AudioSystemSet;
+use bevy::app::{App, Plugin, PostUpdate};
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::ecs::system::{Local, NonSendMut, Res, ResMut, Resource};
+use bevy::prelude::IntoSystemConfigs;
+use bevy::utils::{get_short_name, HashMap};
+use std::any::TypeId;
+
+/// Adds diagnostics of the audio output
+///
+/// Next to the [overall diagnostics](AudioDiagnosticsPlugin::INSTANCES), every channel gets a
+/// diagnostic with the number of its instances at `audio/channels/<name>/instances`. Typed
+/// channels are named after their type, dynamic channels after their key.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::diagnostic::LogDiagnosticsPlugin;
+/// # use bevy_kira_audio::prelude::*;
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_plugins((AudioDiagnosticsPlugin, LogDiagnosticsPlugin::default()));
+/// ```
+#[derive(Default)]
+pub struct AudioDiagnosticsPlugin;
+
+impl AudioDiagnosticsPlugin {
+    /// Number of instances tracked in all channels
+    pub const INSTANCES: DiagnosticPath = DiagnosticPath::const_new("audio/instances");
+    /// Number of channel commands that were processed in a frame
+    pub const PROCESSED_COMMANDS: DiagnosticPath =
+        DiagnosticPath::const_new("audio/processed_commands");
+    /// Number of channel commands that could not run yet and are retried next frame
+    ///
+    /// A growing backlog usually means that sounds are played before their source is loaded.
+    pub const RETRIED_COMMANDS: DiagnosticPath =
+        DiagnosticPath::const_new("audio/retried_commands");
+}
+
+impl Plugin for AudioDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::INSTANCES))
+            .register_diagnostic(Diagnostic::new(Self::PROCESSED_COMMANDS))
+            .register_diagnostic(Diagnostic::new(Self::RETRIED_COMMANDS))
+            .init_resource::<TypedChannelNames>()
+            .add_systems(
+                PostUpdate,
+                update_audio_diagnostics
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            );
+    }
+}
+
+/// Type names of the typed channels, to name their diagnostics
+#[derive(Resource, Default)]
+pub(crate) struct TypedChannelNames(pub(crate) HashMap<TypeId, &'static str>);
+
+fn channel_name(channel: &Channel, typed_names: &TypedChannelNames) -> String {
+    let name = match channel {
+        Channel::Typed(id) => typed_names
+            .0
+            .get(id)
+            .map_or_else(|| format!("{id:?}"), |name| get_short_name(name)),
+        Channel::Dynamic(ChannelKey::Name(name)) => name.to_string(),
+        Channel::Dynamic(ChannelKey::Hashed(_, hash)) => format!("{hash:x}"),
+    };
+    // slashes would add components to the path and empty components are not allowed
+    let name = name.replace('/', "_");
+    if name.is_empty() {
+        "_".to_owned()
+    } else {
+        name
+    }
+}
+
+fn update_audio_diagnostics(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut diagnostics: Diagnostics,
+    mut store: ResMut<DiagnosticsStore>,
+    typed_names: Res<TypedChannelNames>,
+    mut channel_paths: Local<HashMap<Channel, DiagnosticPath>>,
+) {
+    let counts = audio_output.take_command_counts();
+    diagnostics.add_measurement(&AudioDiagnosticsPlugin::PROCESSED_COMMANDS, || {
+        counts.processed as f64
+    });
+    diagnostics.add_measurement(&AudioDiagnosticsPlugin::RETRIED_COMMANDS, || {
+        counts.retried as f64
+    });
+
+    let mut total = 0;
+    for (channel, instances) in audio_output.instance_counts() {
+        total += instances;
+        let path = channel_paths.entry(channel.clone()).or_insert_with(|| {
+            DiagnosticPath::new(format!(
+                "audio/channels/{}/instances",
+                channel_name(channel, &typed_names)
+            ))
+        });
+        if store.get(path).is_none() {
+            store.add(Diagnostic::new(path.clone()));
+        }
+        diagnostics.add_measurement(path, || instances as f64);
+    }
+    diagnostics.add_measurement(&AudioDiagnosticsPlugin::INSTANCES, || total as f64);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MainTrack;
+
+    #[test]
+    fn channels_are_named_after_their_type_or_key() {
+        let mut typed_names = TypedChannelNames::default();
+        typed_names.0.insert(
+            TypeId::of::<MainTrack>(),
+            std::any::type_name::<MainTrack>(),
+        );
+
+        assert_eq!(
+            channel_name(&Channel::typed::<MainTrack>(), &typed_names),
+            "MainTrack"
+        );
+        assert_eq!(
+            channel_name(&Channel::Dynamic("ambience/cave".into()), &typed_names),
+            "ambience_cave"
+        );
+        assert_eq!(
+            channel_name(&Channel::Dynamic("".into()), &typed_names),
+            "_"
+        );
+    }
+}
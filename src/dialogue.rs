@@ -0,0 +1,299 @@
+//! Queue voice lines on a channel, so they never talk over each other
+
+use crate::audio::{AudioTween, PlaybackState};
+use crate::channel::typed::AudioChannel;
+use crate::channel::{AudioControl, Channel};
+use crate::instance::AudioInstance;
+use crate::{AudioSource, MainTrack};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::event::{Event, EventWriter};
+use bevy::ecs::system::{Commands, Res, ResMut, Resource, SystemId};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A voice line to queue on a [`DialogueAudio`]
+#[derive(Clone, Debug)]
+pub struct DialogueLine {
+    source: Handle<AudioSource>,
+    priority: i32,
+    subtitle: Option<String>,
+    on_finished: Option<SystemId>,
+}
+
+impl DialogueLine {
+    /// A line with priority `0` and without subtitle
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        DialogueLine {
+            source,
+            priority: 0,
+            subtitle: None,
+            on_finished: None,
+        }
+    }
+
+    /// Set the priority of the line
+    ///
+    /// Lines with a higher priority are played first, and interrupt a playing line with a lower
+    /// priority. Lines with the same priority play in the order they were queued.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Set the subtitle that is sent with [`DialogueLineStarted`]
+    pub fn with_subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+
+        self
+    }
+
+    /// Run the given one-shot system once the line finished or was interrupted
+    ///
+    /// Register the system with [`World::register_system`](bevy::ecs::world::World::register_system).
+    pub fn on_finished(mut self, system: SystemId) -> Self {
+        self.on_finished = Some(system);
+
+        self
+    }
+}
+
+/// Identifies a line queued on a [`DialogueAudio`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DialogueLineId(u64);
+
+/// Resource playing voice lines one after another on the channel [`AudioChannel<T>`]
+///
+/// Queued lines wait until the current line finished. A line with a higher priority than the
+/// current line interrupts it with a short fade out. Every started line sends a
+/// [`DialogueLineStarted`] event with its subtitle, and every finished or interrupted line a
+/// [`DialogueLineFinished`] event.
+///
+/// Lines are played while the resource exists.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Voice;
+///
+/// fn greet(mut dialogue: ResMut<DialogueAudio<Voice>>, asset_server: Res<AssetServer>) {
+///     dialogue.queue(
+///         DialogueLine::new(asset_server.load("voice/greeting.ogg"))
+///             .with_subtitle("Welcome, traveller!"),
+///     );
+/// }
+///
+/// fn warn(mut dialogue: ResMut<DialogueAudio<Voice>>, asset_server: Res<AssetServer>) {
+///     dialogue.queue(
+///         DialogueLine::new(asset_server.load("voice/watch_out.ogg"))
+///             .with_subtitle("Watch out!")
+///             .with_priority(10),
+///     );
+/// }
+///
+/// fn show_subtitles(mut started: EventReader<DialogueLineStarted>) {
+///     for line in started.read() {
+///         if let Some(subtitle) = &line.subtitle {
+///             info!("{subtitle}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct DialogueAudio<T = MainTrack> {
+    queue: VecDeque<(DialogueLineId, DialogueLine)>,
+    current: Option<CurrentLine>,
+    interrupt_fade: AudioTween,
+    skip: bool,
+    next_id: u64,
+    _marker: PhantomData<T>,
+}
+
+struct CurrentLine {
+    id: DialogueLineId,
+    line: DialogueLine,
+    instance: Handle<AudioInstance>,
+}
+
+impl<T> Default for DialogueAudio<T> {
+    fn default() -> Self {
+        DialogueAudio {
+            queue: VecDeque::default(),
+            current: None,
+            interrupt_fade: AudioTween::linear(Duration::from_millis(150)),
+            skip: false,
+            next_id: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> DialogueAudio<T> {
+    /// Set the fade out of interrupted and skipped lines
+    ///
+    /// The default is a linear fade over 150 milliseconds.
+    pub fn with_interrupt_fade(mut self, fade: AudioTween) -> Self {
+        self.interrupt_fade = fade;
+
+        self
+    }
+
+    /// Queue the line to play once all lines with the same or a higher priority finished
+    pub fn queue(&mut self, line: DialogueLine) -> DialogueLineId {
+        let id = DialogueLineId(self.next_id);
+        self.next_id += 1;
+        self.queue.push_back((id, line));
+
+        id
+    }
+
+    /// Fade out the current line and continue with the next one
+    pub fn skip(&mut self) {
+        self.skip = true;
+    }
+
+    /// Remove all lines that did not start yet
+    ///
+    /// Removed lines send no events and do not run their systems.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// The line that is playing, if any
+    pub fn current_line(&self) -> Option<(DialogueLineId, &DialogueLine)> {
+        self.current
+            .as_ref()
+            .map(|current| (current.id, &current.line))
+    }
+
+    /// The instance of the line that is playing, if any
+    pub fn current_instance(&self) -> Option<&Handle<AudioInstance>> {
+        self.current.as_ref().map(|current| &current.instance)
+    }
+
+    /// Returns `true` while a line plays
+    pub fn is_speaking(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Number of lines waiting to play
+    pub fn queued_lines(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Index of the line to play next, the first one with the highest priority
+    fn next_line(&self) -> Option<usize> {
+        let mut next: Option<usize> = None;
+        for (index, (_, line)) in self.queue.iter().enumerate() {
+            if next.map_or(true, |next| line.priority > self.queue[next].1.priority) {
+                next = Some(index);
+            }
+        }
+
+        next
+    }
+
+    /// Does a queued line have a higher priority than the current line?
+    fn interrupts_current(&self) -> bool {
+        let Some(current) = &self.current else {
+            return false;
+        };
+
+        self.next_line().map_or(false, |next| {
+            self.queue[next].1.priority > current.line.priority
+        })
+    }
+}
+
+/// Event sent when a [`DialogueAudio`] starts a line
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct DialogueLineStarted {
+    /// The channel of the dialogue
+    pub channel: Channel,
+    /// The line that started
+    pub line: DialogueLineId,
+    /// Subtitle of the line
+    pub subtitle: Option<String>,
+}
+
+/// Event sent when a line of a [`DialogueAudio`] finished or was interrupted
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct DialogueLineFinished {
+    /// The channel of the dialogue
+    pub channel: Channel,
+    /// The line that finished
+    pub line: DialogueLineId,
+    /// `true` if the line was interrupted by a line with a higher priority, or skipped
+    pub interrupted: bool,
+}
+
+pub(crate) fn run_dialogue_audio<T: Resource>(
+    mut dialogue: ResMut<DialogueAudio<T>>,
+    channel: Res<AudioChannel<T>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut started: EventWriter<DialogueLineStarted>,
+    mut finished: EventWriter<DialogueLineFinished>,
+    mut commands: Commands,
+) {
+    let skip = std::mem::take(&mut dialogue.skip);
+    if let Some(current) = &dialogue.current {
+        let interrupted = skip || dialogue.interrupts_current();
+        if !interrupted && channel.state(&current.instance) != PlaybackState::Stopped {
+            return;
+        }
+        let current = dialogue.current.take().unwrap();
+        if interrupted {
+            if let Some(instance) = audio_instances.get_mut(&current.instance) {
+                instance.stop(dialogue.interrupt_fade.clone());
+            }
+        }
+        finished.send(DialogueLineFinished {
+            channel: Channel::typed::<T>(),
+            line: current.id,
+            interrupted,
+        });
+        if let Some(system) = current.line.on_finished {
+            commands.run_system(system);
+        }
+    }
+    let Some(next) = dialogue.next_line() else {
+        return;
+    };
+    let (id, line) = dialogue.queue.remove(next).unwrap();
+    started.send(DialogueLineStarted {
+        channel: Channel::typed::<T>(),
+        line: id,
+        subtitle: line.subtitle.clone(),
+    });
+    let instance = channel.play(line.source.clone()).handle();
+    dialogue.current = Some(CurrentLine { id, line, instance });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_priorities_play_first_and_interrupt() {
+        let mut dialogue = DialogueAudio::<MainTrack>::default();
+        dialogue.queue(DialogueLine::new(Handle::default()));
+        let urgent = dialogue.queue(DialogueLine::new(Handle::default()).with_priority(5));
+        dialogue.queue(DialogueLine::new(Handle::default()).with_priority(5));
+
+        assert_eq!(dialogue.next_line(), Some(1));
+        assert_eq!(dialogue.queue[1].0, urgent);
+
+        let (id, line) = dialogue.queue.remove(0).unwrap();
+        dialogue.current = Some(CurrentLine {
+            id,
+            line,
+            instance: Handle::default(),
+        });
+        assert!(dialogue.interrupts_current());
+
+        dialogue.clear();
+        assert!(!dialogue.interrupts_current());
+    }
+}
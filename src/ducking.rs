@@ -0,0 +1,371 @@
+//! Lower the volume of channels while other channels play sounds
+
+use crate::audio::AudioTween;
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use bevy::ecs::system::{NonSendMut, ResMut, Resource};
+use bevy::utils::{HashMap, Instant};
+use kira::Volume;
+use std::time::Duration;
+
+/// Configuration for ducking one channel while another channel plays sounds
+///
+/// Register it with [`add_channel_ducking`](crate::AudioApp::add_channel_ducking) or as a rule of
+/// a [`DuckingMatrix`].
+/// As soon as any sound plays on the trigger channel, the volume of the target channel's mixer
+/// track tweens to [`volume`](Self::with_volume) using the attack tween. Once the trigger channel
+/// has been silent for the hold duration, the volume tweens back using the release tween.
+///
+/// Ducking is applied on top of the target channel's own volume.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// #[derive(Resource)]
+/// struct Voice;
+///
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Voice>()
+///     .add_audio_channel::<Music>()
+///     .add_channel_ducking::<Voice, Music>(
+///         DuckChannel::default()
+///             .with_volume(0.3)
+///             .with_hold(Duration::from_millis(500)),
+///     );
+/// ```
+#[derive(Clone)]
+pub struct DuckChannel {
+    volume: Volume,
+    attack: AudioTween,
+    hold: Duration,
+    release: AudioTween,
+}
+
+impl Default for DuckChannel {
+    fn default() -> Self {
+        DuckChannel {
+            volume: Volume::Amplitude(0.25),
+            attack: AudioTween::linear(Duration::from_millis(100)),
+            hold: Duration::from_millis(250),
+            release: AudioTween::linear(Duration::from_millis(500)),
+        }
+    }
+}
+
+impl DuckChannel {
+    /// Set the volume of the target channel while it is ducked
+    ///
+    /// The default is an amplitude of `0.25`.
+    pub fn with_volume(mut self, volume: impl Into<Volume>) -> Self {
+        self.volume = volume.into();
+
+        self
+    }
+
+    /// Set the tween used to lower the volume of the target channel
+    pub fn with_attack(mut self, attack: AudioTween) -> Self {
+        self.attack = attack;
+
+        self
+    }
+
+    /// Set how long the target channel stays ducked after the trigger channel went silent
+    pub fn with_hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+
+        self
+    }
+
+    /// Set the tween used to restore the volume of the target channel
+    pub fn with_release(mut self, release: AudioTween) -> Self {
+        self.release = release;
+
+        self
+    }
+}
+
+/// Resource ducking channels while other channels play sounds
+///
+/// Every rule ducks a set of target channels while its trigger channel plays sounds, using the
+/// settings of a [`DuckChannel`]. All rules are evaluated together: if several triggers duck the
+/// same target, the target is lowered to the quietest of their volumes instead of the rules
+/// fighting over it. The volume is restored with the release of the rule that was ducking the
+/// target last.
+///
+/// [`add_channel_ducking`](crate::AudioApp::add_channel_ducking) adds rules to this resource.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Voice;
+///
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// #[derive(Resource)]
+/// struct Sfx;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Voice>()
+///     .add_audio_channel::<Music>()
+///     .add_audio_channel::<Sfx>()
+///     .insert_resource(
+///         DuckingMatrix::default()
+///             .with_rule(
+///                 Channel::typed::<Voice>(),
+///                 [Channel::typed::<Music>(), Channel::typed::<Sfx>()],
+///                 DuckChannel::default().with_volume(Volume::Decibels(-12.)),
+///             )
+///             .with_rule(
+///                 Channel::typed::<Sfx>(),
+///                 [Channel::typed::<Music>()],
+///                 DuckChannel::default().with_volume(Volume::Decibels(-3.)),
+///             ),
+///     );
+/// ```
+#[derive(Resource, Default)]
+pub struct DuckingMatrix {
+    rules: Vec<DuckingRule>,
+    /// Volume and ducking rule of all targets that are ducked
+    ducked: HashMap<Channel, (Volume, usize)>,
+}
+
+struct DuckingRule {
+    trigger: Channel,
+    targets: Vec<Channel>,
+    settings: DuckChannel,
+    last_active: Option<Instant>,
+}
+
+/// A volume change of a target channel
+struct DuckingChange {
+    target: Channel,
+    ducked: Option<(Volume, usize)>,
+    tween: AudioTween,
+}
+
+impl DuckingMatrix {
+    /// Duck the target channels while sounds play on the trigger channel
+    pub fn with_rule(
+        mut self,
+        trigger: Channel,
+        targets: impl IntoIterator<Item = Channel>,
+        ducking: DuckChannel,
+    ) -> Self {
+        self.add_rule(trigger, targets, ducking);
+
+        self
+    }
+
+    /// Duck the target channels while sounds play on the trigger channel
+    pub fn add_rule(
+        &mut self,
+        trigger: Channel,
+        targets: impl IntoIterator<Item = Channel>,
+        ducking: DuckChannel,
+    ) {
+        self.rules.push(DuckingRule {
+            trigger,
+            targets: targets.into_iter().collect(),
+            settings: ducking,
+            last_active: None,
+        });
+    }
+
+    /// Returns `true` if the channel is currently ducked
+    pub fn is_ducked(&self, channel: &Channel) -> bool {
+        self.ducked.contains_key(channel)
+    }
+
+    /// Find all targets whose volume needs to change, given which channels are active at `now`
+    fn changes(
+        &mut self,
+        is_active: impl Fn(&Channel) -> bool,
+        now: Instant,
+    ) -> Vec<DuckingChange> {
+        let mut wanted: HashMap<Channel, (Volume, usize)> = HashMap::default();
+        for (index, rule) in self.rules.iter_mut().enumerate() {
+            if !rule.should_duck(is_active(&rule.trigger), now) {
+                continue;
+            }
+            let volume = rule.settings.volume;
+            for target in &rule.targets {
+                let quieter = wanted.get(target).map_or(true, |(wanted_volume, _)| {
+                    volume.as_amplitude() < wanted_volume.as_amplitude()
+                });
+                if quieter {
+                    wanted.insert(target.clone(), (volume, index));
+                }
+            }
+        }
+
+        let mut changes = vec![];
+        for (target, &(volume, index)) in wanted.iter() {
+            let current = self.ducked.get(target);
+            match current {
+                Some((current_volume, _))
+                    if current_volume.as_amplitude() == volume.as_amplitude() =>
+                {
+                    continue
+                }
+                Some((current_volume, current_index))
+                    if current_volume.as_amplitude() < volume.as_amplitude() =>
+                {
+                    changes.push(DuckingChange {
+                        target: target.clone(),
+                        ducked: Some((volume, index)),
+                        tween: self.rules[*current_index].settings.release.clone(),
+                    })
+                }
+                _ => changes.push(DuckingChange {
+                    target: target.clone(),
+                    ducked: Some((volume, index)),
+                    tween: self.rules[index].settings.attack.clone(),
+                }),
+            }
+        }
+        for (target, (_, index)) in self.ducked.iter() {
+            if !wanted.contains_key(target) {
+                changes.push(DuckingChange {
+                    target: target.clone(),
+                    ducked: None,
+                    tween: self.rules[*index].settings.release.clone(),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+impl DuckingRule {
+    /// Register whether the trigger channel is active at `now` and return whether the targets should be ducked
+    fn should_duck(&mut self, active: bool, now: Instant) -> bool {
+        if active {
+            self.last_active = Some(now);
+            return true;
+        }
+
+        self.last_active.map_or(false, |last_active| {
+            now.duration_since(last_active) < self.settings.hold
+        })
+    }
+}
+
+pub(crate) fn duck_channels(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut matrix: ResMut<DuckingMatrix>,
+) {
+    let changes = matrix.changes(
+        |channel| audio_output.is_channel_active(channel),
+        Instant::now(),
+    );
+    if changes.is_empty() {
+        return;
+    }
+    for change in changes {
+        let volume = change
+            .ducked
+            .map_or(Volume::Amplitude(1.0), |(volume, _)| volume);
+        // Keep the old state if the target track is not ready yet, so the change is retried
+        if !audio_output.set_track_volume(&change.target, volume, change.tween.into()) {
+            continue;
+        }
+        match change.ducked {
+            Some(ducked) => matrix.ducked.insert(change.target, ducked),
+            None => matrix.ducked.remove(&change.target),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target_volumes(matrix: &DuckingMatrix) -> HashMap<Channel, f64> {
+        matrix
+            .ducked
+            .iter()
+            .map(|(target, (volume, _))| (target.clone(), volume.as_amplitude()))
+            .collect()
+    }
+
+    fn apply(matrix: &mut DuckingMatrix, active: &[&str], now: Instant) {
+        let changes = matrix.changes(
+            |channel| active.iter().any(|key| *channel == Channel::dynamic(*key)),
+            now,
+        );
+        for change in changes {
+            match change.ducked {
+                Some(ducked) => matrix.ducked.insert(change.target, ducked),
+                None => matrix.ducked.remove(&change.target),
+            };
+        }
+    }
+
+    #[test]
+    fn ducks_while_active_and_for_the_hold_duration() {
+        let mut matrix = DuckingMatrix::default().with_rule(
+            Channel::dynamic("voice"),
+            [Channel::dynamic("music")],
+            DuckChannel::default().with_hold(Duration::from_millis(200)),
+        );
+        let now = Instant::now();
+
+        apply(&mut matrix, &[], now);
+        assert!(!matrix.is_ducked(&Channel::dynamic("music")));
+        apply(&mut matrix, &["voice"], now);
+        assert!(matrix.is_ducked(&Channel::dynamic("music")));
+        apply(&mut matrix, &["voice"], now + Duration::from_millis(500));
+        apply(&mut matrix, &[], now + Duration::from_millis(600));
+        assert!(matrix.is_ducked(&Channel::dynamic("music")));
+        apply(&mut matrix, &[], now + Duration::from_millis(700));
+        assert!(!matrix.is_ducked(&Channel::dynamic("music")));
+    }
+
+    #[test]
+    fn the_quietest_active_rule_wins() {
+        let mut matrix = DuckingMatrix::default()
+            .with_rule(
+                Channel::dynamic("voice"),
+                [Channel::dynamic("music"), Channel::dynamic("sfx")],
+                DuckChannel::default()
+                    .with_volume(0.25)
+                    .with_hold(Duration::ZERO),
+            )
+            .with_rule(
+                Channel::dynamic("sfx"),
+                [Channel::dynamic("music")],
+                DuckChannel::default()
+                    .with_volume(0.5)
+                    .with_hold(Duration::ZERO),
+            );
+        let now = Instant::now();
+
+        apply(&mut matrix, &["sfx"], now);
+        assert_eq!(
+            target_volumes(&matrix),
+            HashMap::from([(Channel::dynamic("music"), 0.5)])
+        );
+        apply(&mut matrix, &["sfx", "voice"], now);
+        assert_eq!(
+            target_volumes(&matrix),
+            HashMap::from([
+                (Channel::dynamic("music"), 0.25),
+                (Channel::dynamic("sfx"), 0.25)
+            ])
+        );
+        apply(&mut matrix, &["sfx"], now + Duration::from_millis(1));
+        assert_eq!(
+            target_volumes(&matrix),
+            HashMap::from([(Channel::dynamic("music"), 0.5)])
+        );
+    }
+}
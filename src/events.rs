@@ -0,0 +1,193 @@
+//! Control channels through events instead of the channel resources
+
+use crate::audio::{AudioCommand, AudioTween, PlayAudioCommand};
+use crate::audio_output::AudioOutput;
+use crate::channel::{AudioCommandQue, Channel};
+use crate::rate_limit::SoundRateLimits;
+use crate::source::AudioCueSettings;
+use crate::{AudioInstance, AudioSource};
+use bevy::asset::{AssetId, Assets, Handle};
+use bevy::ecs::event::{Event, EventReader};
+use bevy::prelude::{NonSendMut, Res, ResMut};
+use kira::Volume;
+use std::cell::RefCell;
+use uuid::Uuid;
+
+/// Event to play a sound on a channel
+///
+/// Events are an alternative to the channel resources that is easy to record, replay, and
+/// send between plugins. They are processed after the commands of the channel resources.
+/// Within a frame, [`StopChannelEvent`]s run first, then [`SetChannelVolumeEvent`]s, and
+/// [`PlaySoundEvent`]s last.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn play_jump(mut events: EventWriter<PlaySoundEvent>, asset_server: Res<AssetServer>) {
+///     events.send(
+///         PlaySoundEvent::new(asset_server.load("sounds/jump.ogg"), Channel::typed::<MainTrack>())
+///             .with_settings(AudioCueSettings {
+///                 volume: Some(0.5),
+///                 ..default()
+///             }),
+///     );
+/// }
+/// ```
+#[derive(Event, Clone, Debug)]
+pub struct PlaySoundEvent {
+    /// The sound to play
+    pub source: Handle<AudioSource>,
+    /// The channel to play the sound on
+    pub channel: Channel,
+    /// Handle of the instance the sound plays as
+    pub instance: Handle<AudioInstance>,
+    /// Settings of the sound
+    pub settings: AudioCueSettings,
+}
+
+impl PlaySoundEvent {
+    /// Play the sound on the channel with default settings
+    pub fn new(source: Handle<AudioSource>, channel: Channel) -> Self {
+        PlaySoundEvent {
+            source,
+            channel,
+            instance: Handle::Weak(AssetId::from(Uuid::new_v4())),
+            settings: AudioCueSettings::default(),
+        }
+    }
+
+    /// Play the sound with the given settings
+    pub fn with_settings(mut self, settings: AudioCueSettings) -> Self {
+        self.settings = settings;
+
+        self
+    }
+}
+
+/// Event to stop all sounds of a channel
+///
+/// See [`PlaySoundEvent`] for the order of events.
+#[derive(Event, Clone, Debug)]
+pub struct StopChannelEvent {
+    /// The channel to stop
+    pub channel: Channel,
+    /// Fade the sounds out, instead of stopping them right away
+    pub fade_out: Option<AudioTween>,
+}
+
+/// Event to set the volume of a channel
+///
+/// See [`PlaySoundEvent`] for the order of events.
+#[derive(Event, Clone, Debug)]
+pub struct SetChannelVolumeEvent {
+    /// The channel to change
+    pub channel: Channel,
+    /// The new volume of the channel
+    pub volume: Volume,
+    /// Tween to the new volume, instead of setting it right away
+    pub tween: Option<AudioTween>,
+}
+
+/// Queue holding the command of a single play command
+#[derive(Default)]
+struct CommandSlot(RefCell<Option<AudioCommand>>);
+
+impl AudioCommandQue for CommandSlot {
+    fn que(&self, command: AudioCommand) {
+        *self.0.borrow_mut() = Some(command);
+    }
+}
+
+fn play_command(event: &PlaySoundEvent) -> AudioCommand {
+    let slot = CommandSlot::default();
+    let mut command = PlayAudioCommand::new(event.source.clone(), &slot);
+    command.instance_handle = event.instance.clone();
+    event.settings.configure(&mut command);
+    command.queue();
+    drop(command);
+
+    slot.0.into_inner().unwrap()
+}
+
+pub(crate) fn run_audio_events(
+    mut play_events: EventReader<PlaySoundEvent>,
+    mut stop_events: EventReader<StopChannelEvent>,
+    mut volume_events: EventReader<SetChannelVolumeEvent>,
+    mut audio_output: NonSendMut<AudioOutput>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut rate_limits: Option<ResMut<SoundRateLimits>>,
+) {
+    let Some(audio_sources) = audio_sources else {
+        return;
+    };
+    let stops = stop_events.read().map(|event| {
+        (
+            event.channel.clone(),
+            AudioCommand::Stop(event.fade_out.clone(), None),
+        )
+    });
+    let volumes = volume_events.read().map(|event| {
+        (
+            event.channel.clone(),
+            AudioCommand::SetVolume(event.volume, event.tween.clone()),
+        )
+    });
+    let plays = play_events
+        .read()
+        .map(|event| (event.channel.clone(), play_command(event)));
+    audio_output.run_event_commands(
+        stops.chain(volumes).chain(plays),
+        &(&*audio_sources).into(),
+        &mut audio_instances,
+        rate_limits.as_deref_mut(),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MainTrack;
+    use bevy::utils::default;
+    use kira::manager::backend::mock::MockBackend;
+    use kira::manager::{AudioManager, AudioManagerSettings};
+    use kira::sound::static_sound::StaticSoundData;
+
+    #[test]
+    fn stop_events_cancel_plays_waiting_for_their_source() {
+        let mut audio_output = AudioOutput::new(
+            AudioManager::new(AudioManagerSettings::<MockBackend>::default()).ok(),
+        );
+        let mut audio_sources = Assets::<AudioSource>::default();
+        let mut audio_instances = Assets::<AudioInstance>::default();
+        let loaded = audio_sources.add(AudioSource {
+            sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+        });
+        let channel = Channel::typed::<MainTrack>();
+        let playing = PlaySoundEvent::new(loaded, channel.clone());
+        let waiting = PlaySoundEvent::new(Handle::default(), channel.clone());
+
+        audio_output.run_event_commands(
+            [&playing, &waiting].map(|event| (channel.clone(), play_command(event))),
+            &(&audio_sources).into(),
+            &mut audio_instances,
+            None,
+        );
+        assert!(audio_instances.get(&playing.instance).is_some());
+
+        audio_output.run_event_commands(
+            [(channel.clone(), AudioCommand::Stop(None, None))],
+            &(&audio_sources).into(),
+            &mut audio_instances,
+            None,
+        );
+        let source = audio_sources.get(&playing.source).unwrap().clone();
+        audio_sources.insert(&waiting.source, source);
+        audio_output.run_event_commands(
+            std::iter::empty(),
+            &(&audio_sources).into(),
+            &mut audio_instances,
+            None,
+        );
+        assert!(audio_instances.get(&waiting.instance).is_none());
+    }
+}
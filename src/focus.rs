@@ -0,0 +1,198 @@
+//! Pause the audio while the app is in the background
+
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use crate::{AudioInstance, AudioTween, ChannelKey};
+use bevy::asset::Assets;
+use bevy::ecs::entity::{Entity, EntityHashSet};
+use bevy::ecs::event::EventReader;
+use bevy::ecs::system::{Local, NonSendMut, Query, Res, ResMut, Resource};
+use bevy::window::{Window, WindowOccluded};
+use std::time::Duration;
+
+/// Resource to pause the channels while no window of the app is focused
+///
+/// Channels fade out when the last window loses focus or is minimized, and fade back in once a
+/// window is focused again. Kept channels, and the channels they are a sub-mix of, play on.
+/// Enable it at startup with [`AudioSettings::pause_on_focus_loss`](crate::AudioSettings::pause_on_focus_loss),
+/// or toggle [`enabled`](Self::enabled) at any time, for example from an options menu.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Music>()
+///     .insert_resource(
+///         AudioFocusSettings::default()
+///             .with_enabled(true)
+///             .keep_channel::<Music>(),
+///     );
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct AudioFocusSettings {
+    /// Pause the channels while the app is not focused
+    ///
+    /// Disabling it while the channels are paused resumes them.
+    pub enabled: bool,
+    /// Fade of pausing and resuming the channels
+    pub fade: AudioTween,
+    pub(crate) keep: Vec<Channel>,
+}
+
+impl Default for AudioFocusSettings {
+    fn default() -> Self {
+        AudioFocusSettings {
+            enabled: false,
+            fade: AudioTween::linear(Duration::from_millis(250)),
+            keep: Vec::new(),
+        }
+    }
+}
+
+impl AudioFocusSettings {
+    /// Set whether the channels pause while the app is not focused
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Keep the typed channel [`AudioChannel<T>`](crate::AudioChannel) playing without focus
+    pub fn keep_channel<T: Resource>(self) -> Self {
+        self.keep(Channel::typed::<T>())
+    }
+
+    /// Keep the dynamic channel with the given key playing without focus
+    pub fn keep_dynamic_channel(self, key: impl Into<ChannelKey>) -> Self {
+        self.keep(Channel::dynamic(key))
+    }
+
+    /// Keep the given channel playing without focus
+    pub fn keep(mut self, channel: Channel) -> Self {
+        if !self.keep.contains(&channel) {
+            self.keep.push(channel);
+        }
+
+        self
+    }
+}
+
+pub(crate) fn pause_unfocused_audio(
+    settings: Res<AudioFocusSettings>,
+    windows: Query<(Entity, &Window)>,
+    mut occlusions: EventReader<WindowOccluded>,
+    mut occluded: Local<EntityHashSet>,
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    for event in occlusions.read() {
+        if event.occluded {
+            occluded.insert(event.window);
+        } else {
+            occluded.remove(&event.window);
+        }
+    }
+    // without windows, there is no focus to lose
+    let unfocused = !windows.is_empty()
+        && !windows
+            .iter()
+            .any(|(entity, window)| window.focused && !occluded.contains(&entity));
+    let paused = audio_output.is_paused_for_focus_loss();
+    if settings.enabled && unfocused {
+        if !paused {
+            audio_output.pause_for_focus_loss(&settings.keep, &settings.fade, &mut audio_instances);
+        }
+    } else if paused {
+        audio_output.resume_after_focus_loss(&settings.fade, &mut audio_instances);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Audio, AudioApp, AudioChannel, AudioControl, AudioSettings, AudioSource, MockAudioExt,
+        MockAudioPlugin, PlaybackState,
+    };
+    use bevy::asset::AssetPlugin;
+    use bevy::prelude::*;
+    use bevy::window::WindowPlugin;
+    use kira::sound::static_sound::StaticSoundData;
+
+    #[derive(Resource)]
+    struct Music;
+
+    #[test]
+    fn channels_pause_without_focus_except_kept_ones() {
+        let mut app = App::new();
+        app.insert_resource(AudioSettings {
+            pause_on_focus_loss: true,
+            ..default()
+        })
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            WindowPlugin::default(),
+            MockAudioPlugin,
+        ))
+        .add_audio_channel::<Music>();
+        let settings = app.world().resource::<AudioFocusSettings>().clone();
+        assert!(settings.enabled);
+        app.insert_resource(settings.keep_channel::<Music>());
+        let source = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+            });
+        let effect = app
+            .world()
+            .resource::<Audio>()
+            .play(source.clone())
+            .looped()
+            .handle();
+        let music = app
+            .world()
+            .resource::<AudioChannel<Music>>()
+            .play(source)
+            .looped()
+            .handle();
+        app.update();
+
+        let mut windows = app.world_mut().query::<&mut Window>();
+        windows.single_mut(app.world_mut()).focused = false;
+        app.update();
+        app.advance_audio(Duration::from_secs(1));
+        assert!(matches!(
+            app.audio_instance_state(&effect),
+            PlaybackState::Paused { .. }
+        ));
+        assert!(matches!(
+            app.audio_instance_state(&music),
+            PlaybackState::Playing { .. }
+        ));
+
+        windows.single_mut(app.world_mut()).focused = true;
+        app.update();
+        app.advance_audio(Duration::from_secs(1));
+        assert!(matches!(
+            app.audio_instance_state(&effect),
+            PlaybackState::Playing { .. }
+        ));
+
+        // channels the game pauses while unfocused stay paused
+        windows.single_mut(app.world_mut()).focused = false;
+        app.update();
+        app.world().resource::<Audio>().pause();
+        app.update();
+        windows.single_mut(app.world_mut()).focused = true;
+        app.update();
+        app.advance_audio(Duration::from_secs(1));
+        assert!(matches!(
+            app.audio_instance_state(&effect),
+            PlaybackState::Paused { .. }
+        ));
+    }
+}
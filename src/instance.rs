@@ -1,14 +1,214 @@
-use crate::{AudioTween, PlaybackState};
-use bevy::asset::{Asset, Assets, Handle};
+use crate::audio::valid_playback_rate;
+use crate::channel::Channel;
+use crate::{AudioTween, PanLaw, Panning, PlaybackState};
+use bevy::asset::{Asset, AssetPath, Assets, Handle, UntypedAssetId};
+use bevy::ecs::event::Event;
+use bevy::log::warn;
+use bevy::utils::Instant;
 use kira::sound::static_sound::StaticSoundHandle;
-use kira::tween::Value;
-use kira::{CommandError, Volume};
+use kira::sound::PlaybackRate;
+#[cfg(not(target_arch = "wasm32"))]
+use kira::sound::{streaming::StreamingSoundHandle, FromFileError};
+use kira::tween::{Tween, Value};
+use kira::{CommandError, StartTime, Volume};
+use std::borrow::Cow;
 use thiserror::Error;
 
 #[derive(Asset, bevy::reflect::TypePath)]
 /// Asset for direct audio control
 pub struct AudioInstance {
-    pub(crate) handle: StaticSoundHandle,
+    pub(crate) handle: InstanceHandle,
+    pub(crate) volume: Value<Volume>,
+    muted: bool,
+    /// Asset path of the played source and whether it is streamed
+    pub(crate) source_path: Option<(AssetPath<'static>, bool)>,
+    /// Asset id of the played source
+    pub(crate) source: Option<UntypedAssetId>,
+    /// The instance waits for its scheduled start
+    pub(crate) waiting: bool,
+    /// Category the sound was played with
+    pub(crate) category: Option<Cow<'static, str>>,
+    /// The sound was played with a loop region
+    pub(crate) looped: bool,
+    /// Number of seek commands sent for the sound
+    pub(crate) seeks: u64,
+    /// Position of a sound that was stopped while silent and will restart once audible
+    pub(crate) virtualized: Option<f64>,
+    /// A stop command was sent for the sound
+    pub(crate) stopping: bool,
+    /// End of an equal power fade-out, after which the sound is stopped
+    pub(crate) stop_at: Option<Instant>,
+    /// Pan law of the channel the sound plays on
+    pub(crate) pan_law: PanLaw,
+    /// Volume factor of the pan law at the current panning
+    pub(crate) pan_gain: f64,
+}
+
+/// Kira handle of a playing sound
+pub(crate) enum InstanceHandle {
+    Static(StaticSoundHandle),
+    #[cfg(not(target_arch = "wasm32"))]
+    Streaming(StreamingSoundHandle<FromFileError>),
+}
+
+macro_rules! with_handle {
+    ($instance_handle:expr, $handle:ident => $call:expr) => {
+        match $instance_handle {
+            InstanceHandle::Static($handle) => $call,
+            #[cfg(not(target_arch = "wasm32"))]
+            InstanceHandle::Streaming($handle) => $call,
+        }
+    };
+}
+
+impl InstanceHandle {
+    pub(crate) fn state(&self) -> kira::sound::PlaybackState {
+        with_handle!(self, handle => handle.state())
+    }
+
+    pub(crate) fn position(&self) -> f64 {
+        with_handle!(self, handle => handle.position())
+    }
+
+    pub(crate) fn pause(&mut self, tween: Tween) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.pause(tween))
+    }
+
+    pub(crate) fn resume(&mut self, tween: Tween) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.resume(tween))
+    }
+
+    pub(crate) fn stop(&mut self, tween: Tween) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.stop(tween))
+    }
+
+    pub(crate) fn set_volume(
+        &mut self,
+        volume: impl Into<Value<Volume>>,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.set_volume(volume, tween))
+    }
+
+    pub(crate) fn set_playback_rate(
+        &mut self,
+        playback_rate: impl Into<Value<PlaybackRate>>,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.set_playback_rate(playback_rate, tween))
+    }
+
+    pub(crate) fn set_panning(
+        &mut self,
+        panning: impl Into<Value<f64>>,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.set_panning(panning, tween))
+    }
+
+    pub(crate) fn seek_to(&mut self, position: f64) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.seek_to(position))
+    }
+
+    pub(crate) fn seek_by(&mut self, amount: f64) -> Result<(), CommandError> {
+        with_handle!(self, handle => handle.seek_by(amount))
+    }
+}
+
+impl From<&InstanceHandle> for PlaybackState {
+    fn from(handle: &InstanceHandle) -> Self {
+        PlaybackState::from_kira(handle.state(), handle.position())
+    }
+}
+
+/// Event sent when a sound stopped and is no longer tracked by its channel
+///
+/// This includes sounds that finished playing, were stopped, or whose [`AudioInstance`] asset
+/// was removed. Sounds dropped before they started, for example by
+/// [`SoundRateLimits`](crate::SoundRateLimits) or an instance limit, are reported as well.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Intro(Handle<AudioInstance>);
+///
+/// fn play_after_intro(
+///     mut stopped: EventReader<AudioInstanceStopped>,
+///     intro: Res<Intro>,
+///     audio: Res<Audio>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     if stopped.read().any(|event| event.handle == intro.0) {
+///         audio.play(asset_server.load("sounds/loop.ogg")).looped();
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct AudioInstanceStopped {
+    /// The sound that stopped
+    pub handle: Handle<AudioInstance>,
+    /// The channel the sound played on
+    pub channel: Channel,
+}
+
+/// Event sent each time a looping sound wraps from the end of its loop back to the start
+///
+/// Wraps are detected from the playback position of sounds playing forward. Seeking does not
+/// count as completing a loop.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn switch_variation(
+///     mut loops: EventReader<LoopCompleted>,
+///     mut instances: ResMut<Assets<AudioInstance>>,
+/// ) {
+///     for event in loops.read() {
+///         if event.loops == 4 {
+///             if let Some(instance) = instances.get_mut(&event.instance) {
+///                 instance.stop(AudioTween::default());
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct LoopCompleted {
+    /// The looping sound
+    pub instance: Handle<AudioInstance>,
+    /// The channel the sound plays on
+    pub channel: Channel,
+    /// Number of loops the sound completed so far
+    pub loops: u64,
+}
+
+/// Event sent when a sound goes from one kind of [`PlaybackState`] to another
+///
+/// Changes of the playback position alone are not reported. Sounds that are played are first
+/// reported as changing from [`PlaybackState::Queued`], and sounds that are cleaned up as
+/// changing to [`PlaybackState::Stopped`].
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn log_paused_sounds(mut changes: EventReader<PlaybackStateChanged>) {
+///     for change in changes.read() {
+///         if let PlaybackState::Paused { position } = change.current {
+///             info!("Sound {:?} paused at {position}s", change.instance);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct PlaybackStateChanged {
+    /// The sound that changed its state
+    pub instance: Handle<AudioInstance>,
+    /// The channel the sound plays on
+    pub channel: Channel,
+    /// The state before the change
+    pub previous: PlaybackState,
+    /// The new state
+    pub current: PlaybackState,
 }
 
 /// Errors that can occur when directly controlling audio
@@ -33,6 +233,35 @@ impl From<CommandError> for AudioCommandError {
 }
 
 impl AudioInstance {
+    pub(crate) fn new(handle: InstanceHandle, volume: Value<Volume>) -> Self {
+        AudioInstance {
+            handle,
+            volume,
+            muted: false,
+            source_path: None,
+            source: None,
+            waiting: false,
+            category: None,
+            looped: false,
+            seeks: 0,
+            virtualized: None,
+            stopping: false,
+            stop_at: None,
+            pan_law: PanLaw::default(),
+            pan_gain: 1.,
+        }
+    }
+
+    /// The volume to send to kira, including the gain of the pan law
+    fn backend_volume(&self) -> Value<Volume> {
+        match self.volume {
+            Value::Fixed(volume) if self.pan_gain != 1. => {
+                Value::Fixed(Volume::Amplitude(volume.as_amplitude() * self.pan_gain))
+            }
+            volume => volume,
+        }
+    }
+
     /// Pause the audio instance with the given easing
     pub fn pause(&mut self, tween: AudioTween) -> Option<AudioCommandError> {
         self.handle
@@ -51,42 +280,216 @@ impl AudioInstance {
 
     /// Stop the audio instance with the given easing
     pub fn stop(&mut self, tween: AudioTween) -> Option<AudioCommandError> {
-        self.handle
-            .stop(tween.into())
+        self.stop_with_tween(&tween)
             .err()
             .map(|kira_error| kira_error.into())
     }
 
+    /// Stop the audio instance, fading out the volume of [equal power](crate::AudioEasing::EqualPower) tweens
+    ///
+    /// Kira fades stopping sounds in decibels, so equal power fade-outs tween the amplitude of
+    /// the instance instead and the sound is stopped once the fade finished.
+    pub(crate) fn stop_with_tween(&mut self, tween: &AudioTween) -> Result<(), CommandError> {
+        if !tween.is_equal_power() {
+            return self.stop_with_kira_tween(tween.into());
+        }
+        self.virtualized = None;
+        self.handle
+            .set_volume(Volume::Amplitude(0.), tween.equal_power_fade_out())?;
+        self.stopping = true;
+        self.stop_at = Some(Instant::now() + tween.duration());
+
+        Ok(())
+    }
+
+    /// Fade in the volume of a sound that was started silent
+    pub(crate) fn equal_power_fade_in(
+        &mut self,
+        tween: &AudioTween,
+        start_time: StartTime,
+    ) -> Result<(), CommandError> {
+        let volume = match self.backend_volume() {
+            // amplitudes are interpolated linearly, so the easing shapes the amplitude
+            Value::Fixed(volume) => Value::Fixed(Volume::Amplitude(volume.as_amplitude())),
+            volume => volume,
+        };
+        self.handle.set_volume(
+            volume,
+            Tween {
+                start_time,
+                ..tween.equal_power_fade_in()
+            },
+        )
+    }
+
+    pub(crate) fn stop_with_kira_tween(&mut self, tween: Tween) -> Result<(), CommandError> {
+        self.virtualized = None;
+        self.handle.stop(tween)?;
+        self.stopping = true;
+
+        Ok(())
+    }
+
+    /// Resume a sound that is stopping, cancelling its fade-out
+    pub(crate) fn cancel_stop(&mut self, tween: Tween) -> Result<(), CommandError> {
+        if self.stop_at.is_some() {
+            // equal power fade-outs only tween the volume, so the sound is still playing
+            if !self.muted {
+                self.handle.set_volume(self.backend_volume(), tween)?;
+            }
+            self.stop_at = None;
+        } else {
+            self.handle.resume(tween)?;
+        }
+        self.stopping = false;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the sound is playing or will play again once audible
+    pub(crate) fn is_alive(&self) -> bool {
+        self.virtualized.is_some() || self.handle.state() != kira::sound::PlaybackState::Stopped
+    }
+
+    /// Take over the state of the instance that this restarted sound replaces
+    pub(crate) fn restore(&mut self, previous: AudioInstance) {
+        self.source_path = previous.source_path;
+        self.category = previous.category;
+        self.seeks = previous.seeks;
+        let tween = Tween {
+            duration: std::time::Duration::ZERO,
+            ..Default::default()
+        };
+        if let Err(error) = self.set_volume_with_kira_tween(previous.volume, tween) {
+            warn!(
+                "Failed to restore volume of restarted sound due to {:?}",
+                error
+            );
+        }
+    }
+
+    /// The volume of the sound, `0` while it is muted
+    pub(crate) fn audible_volume(&self) -> f64 {
+        match (self.muted, self.volume) {
+            (true, _) => 0.,
+            (false, Value::Fixed(volume)) => volume.as_amplitude(),
+            // Volumes following a modulator are assumed to be audible
+            (false, _) => 1.,
+        }
+    }
+
     /// Get the state of the audio instance
+    ///
+    /// Instances waiting for a scheduled start are [`PlaybackState::Queued`]. Sounds that were
+    /// stopped while silent (see [`stop_when_silent`](crate::PlayAudioCommand::stop_when_silent))
+    /// or over the voice cap (see
+    /// [`AudioSettings::max_audible_instances`](crate::AudioSettings::max_audible_instances))
+    /// are [`PlaybackState::Playing`] at the position they stopped at.
     pub fn state(&self) -> PlaybackState {
+        if let Some(position) = self.virtualized {
+            return PlaybackState::Playing { position };
+        }
+        if self.waiting && self.handle.state() == kira::sound::PlaybackState::Playing {
+            return PlaybackState::Queued;
+        }
+
         (&self.handle).into()
     }
 
     /// Set the volume of the audio instance
     ///
     /// Default is `1.0`
+    /// While the instance is muted, the volume is only applied once it gets unmuted.
     pub fn set_volume(
         &mut self,
         volume: impl Into<Value<Volume>>,
         tween: AudioTween,
     ) -> Option<AudioCommandError> {
-        self.handle
-            .set_volume(volume, tween.into())
+        self.set_volume_with_kira_tween(volume, tween.into())
             .err()
             .map(|kira_error| kira_error.into())
     }
 
+    pub(crate) fn set_volume_with_kira_tween(
+        &mut self,
+        volume: impl Into<Value<Volume>>,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        self.volume = volume.into();
+        if self.muted || self.stop_at.is_some() {
+            return Ok(());
+        }
+        self.handle.set_volume(self.backend_volume(), tween)
+    }
+
+    pub(crate) fn set_panning_with_kira_tween(
+        &mut self,
+        panning: Panning,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        let (backend_panning, pan_gain) = self.pan_law.backend_panning(panning);
+        self.handle.set_panning(backend_panning, tween)?;
+        if pan_gain == self.pan_gain {
+            return Ok(());
+        }
+        self.pan_gain = pan_gain;
+        if self.muted {
+            return Ok(());
+        }
+        self.handle.set_volume(self.backend_volume(), tween)
+    }
+
+    /// Silence the audio instance without losing its volume
+    ///
+    /// The instance keeps playing. Volume changes while muted are remembered and
+    /// applied by [`unmute`](Self::unmute).
+    pub fn mute(&mut self, tween: AudioTween) -> Option<AudioCommandError> {
+        if self.muted {
+            return None;
+        }
+        if let Err(kira_error) = self.handle.set_volume(Volume::Amplitude(0.0), tween.into()) {
+            return Some(kira_error.into());
+        }
+        self.muted = true;
+
+        None
+    }
+
+    /// Restore the volume the audio instance had before it was muted
+    pub fn unmute(&mut self, tween: AudioTween) -> Option<AudioCommandError> {
+        if !self.muted {
+            return None;
+        }
+        if self.stop_at.is_some() {
+            // the sound stays silent while fading out
+            self.muted = false;
+            return None;
+        }
+        if let Err(kira_error) = self.handle.set_volume(self.backend_volume(), tween.into()) {
+            return Some(kira_error.into());
+        }
+        self.muted = false;
+
+        None
+    }
+
+    /// Returns `true` if the audio instance is muted
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
     /// Sets the playback rate of the sound.
     ///
     /// Changing the playback rate will change both the speed
-    /// and pitch of the sound.
+    /// and pitch of the sound. Negative rates play the sound backwards.
+    /// The rate is clamped to [`MAX_PLAYBACK_RATE`](crate::MAX_PLAYBACK_RATE) in either direction.
     pub fn set_playback_rate(
         &mut self,
         playback_rate: f64,
         tween: AudioTween,
     ) -> Option<AudioCommandError> {
         self.handle
-            .set_playback_rate(playback_rate, tween.into())
+            .set_playback_rate(valid_playback_rate(playback_rate), tween.into())
             .err()
             .map(|kira_error| kira_error.into())
     }
@@ -96,15 +499,21 @@ impl AudioInstance {
     /// `0.0` is hard left,
     /// `0.5` is center (default)
     /// `1.0` is hard right.
-    pub fn set_panning(&mut self, panning: f64, tween: AudioTween) -> Option<AudioCommandError> {
-        self.handle
-            .set_panning(panning, tween.into())
+    ///
+    /// Use [`Panning::from_signed`] to set the panning from `-1` (hard left) to `1` (hard right).
+    pub fn set_panning(
+        &mut self,
+        panning: impl Into<Panning>,
+        tween: AudioTween,
+    ) -> Option<AudioCommandError> {
+        self.set_panning_with_kira_tween(panning.into(), tween.into())
             .err()
             .map(|kira_error| kira_error.into())
     }
 
     /// Sets the playback position to the specified time in seconds.
     pub fn seek_to(&mut self, position: f64) -> Option<AudioCommandError> {
+        self.seeks += 1;
         self.handle
             .seek_to(position)
             .err()
@@ -113,6 +522,7 @@ impl AudioInstance {
 
     /// Moves the playback position by the specified amount of time in seconds.
     pub fn seek_by(&mut self, amount: f64) -> Option<AudioCommandError> {
+        self.seeks += 1;
         self.handle
             .seek_by(amount)
             .err()
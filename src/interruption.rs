@@ -0,0 +1,107 @@
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use crate::{AudioInstance, ChannelKey};
+use bevy::asset::Assets;
+use bevy::ecs::event::{Event, EventReader};
+use bevy::prelude::{NonSendMut, Res, ResMut, Resource};
+
+/// Event for interruptions of the app's audio by the OS
+///
+/// Send it from your platform code, for example when a phone call comes in or headphones are
+/// unplugged. With the `mobile` feature, the plugin sends it when the app is suspended and
+/// resumed. On [`Began`](Self::Began), the channels in [`AudioInterruptionSettings`] are paused.
+/// Read the event to react to interruptions in your game, for example by opening the pause menu.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioInterruption {
+    /// The app lost audio focus
+    Began,
+    /// The interruption is over
+    Ended {
+        /// Resume the channels that were paused for the interruption
+        ///
+        /// Headphones being unplugged, for example, should not resume playback on their own.
+        resume: bool,
+    },
+}
+
+/// Resource to choose which channels are paused during an [`AudioInterruption`]
+///
+/// By default, no channel is paused.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel::<Music>()
+///     .insert_resource(
+///         AudioInterruptionSettings::default()
+///             .pause_channel::<MainTrack>()
+///             .pause_channel::<Music>(),
+///     );
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AudioInterruptionSettings {
+    pub(crate) channels: Vec<Channel>,
+}
+
+impl AudioInterruptionSettings {
+    /// Pause the typed channel [`AudioChannel<T>`](crate::AudioChannel) during interruptions
+    pub fn pause_channel<T: Resource>(self) -> Self {
+        self.pause(Channel::typed::<T>())
+    }
+
+    /// Pause the dynamic channel with the given key during interruptions
+    pub fn pause_dynamic_channel(self, key: impl Into<ChannelKey>) -> Self {
+        self.pause(Channel::dynamic(key))
+    }
+
+    /// Pause the given channel during interruptions
+    pub fn pause(mut self, channel: Channel) -> Self {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+
+        self
+    }
+}
+
+pub(crate) fn handle_audio_interruptions(
+    mut interruptions: EventReader<AudioInterruption>,
+    settings: Res<AudioInterruptionSettings>,
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    for interruption in interruptions.read() {
+        match interruption {
+            AudioInterruption::Began => {
+                audio_output.begin_interruption(&settings.channels, &mut audio_instances)
+            }
+            AudioInterruption::Ended { resume } => {
+                audio_output.end_interruption(*resume, &mut audio_instances)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mobile")]
+pub(crate) fn send_lifecycle_interruptions(
+    mut lifecycle: EventReader<bevy::window::AppLifecycle>,
+    mut interruptions: bevy::ecs::event::EventWriter<AudioInterruption>,
+) {
+    use bevy::window::AppLifecycle;
+
+    for event in lifecycle.read() {
+        match event {
+            AppLifecycle::WillSuspend => {
+                interruptions.send(AudioInterruption::Began);
+            }
+            AppLifecycle::WillResume => {
+                interruptions.send(AudioInterruption::Ended { resume: true });
+            }
+            _ => (),
+        }
+    }
+}
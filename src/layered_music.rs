@@ -0,0 +1,266 @@
+//! Adaptive music made of stems that fade in and out with game parameters
+
+use crate::audio::AudioTween;
+use crate::channel::typed::AudioChannel;
+use crate::channel::AudioControl;
+use crate::clock::AudioClock;
+use crate::instance::AudioInstance;
+use crate::{AudioSource, MainTrack};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::utils::HashMap;
+use kira::clock::ClockSpeed;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Resource playing stems of a piece of music in sync on the channel [`AudioChannel<T>`]
+///
+/// All layers loop and start on the same tick of a clock, so they stay locked to each other
+/// as long as the stems have the same length. The volume of every layer follows a named
+/// parameter that you set from your game logic, for example the intensity of a fight.
+///
+/// Pausing and resuming the channel keeps the layers in sync. Seeking or pausing single
+/// layers does not.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn start_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let mut music = LayeredMusic::<MainTrack>::new()
+///         .with_layer(MusicLayer::new(asset_server.load("music/pads.ogg"), "calm"))
+///         .with_layer(MusicLayer::new(asset_server.load("music/drums.ogg"), "danger"))
+///         .with_parameter("calm", 1.);
+///     music.play();
+///     commands.insert_resource(music);
+/// }
+///
+/// fn enemy_spotted(mut music: ResMut<LayeredMusic>) {
+///     music.set_parameter("danger", 1.);
+/// }
+/// ```
+#[derive(Resource)]
+pub struct LayeredMusic<T = MainTrack> {
+    layers: Vec<MusicLayer>,
+    parameters: HashMap<String, f32>,
+    fade: AudioTween,
+    playing: bool,
+    /// The parameters or layers changed since the volumes were last applied
+    changed: bool,
+    /// The instance of each layer, while the music is playing
+    instances: Vec<Handle<AudioInstance>>,
+    clock: Option<Handle<AudioClock>>,
+    _marker: PhantomData<T>,
+}
+
+/// A stem of [`LayeredMusic`]
+#[derive(Clone)]
+pub struct MusicLayer {
+    source: Handle<AudioSource>,
+    parameter: String,
+    curve: Option<Arc<dyn Fn(f32) -> f32 + Send + Sync>>,
+}
+
+impl MusicLayer {
+    /// Create a layer playing the given stem with the volume of the given parameter
+    pub fn new(source: Handle<AudioSource>, parameter: impl Into<String>) -> Self {
+        MusicLayer {
+            source,
+            parameter: parameter.into(),
+            curve: None,
+        }
+    }
+
+    /// Compute the volume of the layer from its parameter with a custom curve
+    ///
+    /// By default, the value of the parameter is the volume.
+    pub fn with_curve(mut self, curve: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        self.curve = Some(Arc::new(curve));
+
+        self
+    }
+
+    /// The name of the parameter controlling the volume of the layer
+    pub fn parameter(&self) -> &str {
+        &self.parameter
+    }
+
+    /// The volume between `0` and `1` for the given parameter value
+    pub fn volume(&self, value: f32) -> f32 {
+        match &self.curve {
+            Some(curve) => curve(value),
+            None => value,
+        }
+        .clamp(0., 1.)
+    }
+}
+
+impl<T> Default for LayeredMusic<T> {
+    fn default() -> Self {
+        LayeredMusic {
+            layers: vec![],
+            parameters: HashMap::default(),
+            fade: AudioTween::default(),
+            playing: false,
+            changed: false,
+            instances: vec![],
+            clock: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> LayeredMusic<T> {
+    /// Create stopped music without layers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer
+    ///
+    /// Layers can only be added while the music is stopped.
+    pub fn with_layer(mut self, layer: MusicLayer) -> Self {
+        self.layers.push(layer);
+
+        self
+    }
+
+    /// Set the initial value of a parameter
+    pub fn with_parameter(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.set_parameter(name, value);
+
+        self
+    }
+
+    /// Set the tween used when the volume of a layer changes
+    pub fn with_fade(mut self, fade: AudioTween) -> Self {
+        self.fade = fade;
+
+        self
+    }
+
+    /// Set the value of a parameter
+    ///
+    /// Parameters that were never set have the value `0`.
+    pub fn set_parameter(&mut self, name: impl Into<String>, value: f32) {
+        let name = name.into();
+        if self.parameters.get(&name) != Some(&value) {
+            self.parameters.insert(name, value);
+            self.changed = true;
+        }
+    }
+
+    /// The value of a parameter
+    pub fn parameter(&self, name: &str) -> f32 {
+        self.parameters.get(name).copied().unwrap_or_default()
+    }
+
+    /// All layers in the order they were added
+    pub fn layers(&self) -> &[MusicLayer] {
+        &self.layers
+    }
+
+    /// Start all layers in sync
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Fade out and stop all layers
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Returns `true` if the music is playing
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The instances of all layers in the order of the layers, while the music is playing
+    pub fn instances(&self) -> &[Handle<AudioInstance>] {
+        &self.instances
+    }
+
+    /// The current volume of every layer
+    fn volumes(&self) -> impl Iterator<Item = f32> + '_ {
+        self.layers
+            .iter()
+            .map(|layer| layer.volume(self.parameter(&layer.parameter)))
+    }
+}
+
+pub(crate) fn run_layered_music<T: Resource>(
+    mut music: ResMut<LayeredMusic<T>>,
+    channel: Res<AudioChannel<T>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut clocks: ResMut<Assets<AudioClock>>,
+) {
+    let started = !music.instances.is_empty();
+    if !music.playing {
+        if started {
+            let fade = music.fade.clone();
+            for instance in music.instances.drain(..) {
+                if let Some(instance) = audio_instances.get_mut(&instance) {
+                    instance.stop(fade.clone());
+                }
+            }
+            if let Some(clock) = music.clock.take() {
+                clocks.remove(&clock);
+            }
+        }
+        return;
+    }
+    if !started {
+        if music.layers.is_empty() {
+            return;
+        }
+        // All layers wait for the first tick of a new clock, so they start on the same sample
+        let clock = channel.add_clock(ClockSpeed::TicksPerSecond(100.));
+        let instances: Vec<_> = music
+            .volumes()
+            .zip(music.layers.iter())
+            .map(|(volume, layer)| {
+                channel
+                    .play(layer.source.clone())
+                    .looped()
+                    .with_volume(volume as f64)
+                    .start_at_tick(&clock, 1)
+                    .handle()
+            })
+            .collect();
+        music.instances = instances;
+        music.clock = Some(clock);
+        music.changed = false;
+        return;
+    }
+    if !music.changed {
+        return;
+    }
+    music.changed = false;
+    let music = music.into_inner();
+    for (volume, instance) in music.volumes().zip(music.instances.iter()) {
+        if let Some(instance) = audio_instances.get_mut(instance) {
+            instance.set_volume(volume as f64, music.fade.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_volumes_follow_their_parameters() {
+        let mut music = LayeredMusic::<MainTrack>::new()
+            .with_layer(MusicLayer::new(Handle::default(), "calm"))
+            .with_layer(
+                MusicLayer::new(Handle::default(), "danger").with_curve(|danger| danger * 2.),
+            )
+            .with_parameter("calm", 0.5);
+
+        assert_eq!(music.volumes().collect::<Vec<_>>(), vec![0.5, 0.]);
+        music.set_parameter("danger", 0.25);
+        assert!(music.changed);
+        assert_eq!(music.volumes().collect::<Vec<_>>(), vec![0.5, 0.5]);
+        music.set_parameter("danger", 3.);
+        assert_eq!(music.volumes().collect::<Vec<_>>(), vec![0.5, 1.]);
+    }
+}
@@ -34,42 +34,169 @@
 #![forbid(unsafe_code)]
 #![warn(unused_imports, missing_docs)]
 
+#[cfg(feature = "analysis")]
+mod analysis;
 mod audio;
 mod audio_output;
 mod backend_settings;
+mod beat;
+mod capture;
 mod channel;
+mod clock;
+mod commands;
+mod conditions;
+mod device;
+mod diagnostics;
+mod dialogue;
+mod ducking;
+mod events;
+mod focus;
 mod instance;
+mod interruption;
+mod layered_music;
+mod macros;
+mod metering;
+mod mock;
+mod offline;
+mod playlist;
+mod quality;
+mod rate_limit;
+mod sequencer;
 mod source;
 mod spatial;
+mod timeline;
+mod transport;
+mod unlock;
 
+#[cfg(feature = "analysis")]
+pub use analysis::{AudioSpectrum, MainTrackSpectrum, Spectrum, SpectrumSettings};
 pub use audio::{
-    AudioApp, AudioEasing, AudioTween, FadeIn, FadeOut, PlayAudioCommand, PlaybackState,
-    TweenCommand,
+    AudioApp, AudioEasing, AudioTween, ChainTransition, ChainedSoundSettings, FadeIn, FadeOut,
+    PanLaw, Panning, PlayAudioCommand, PlayCondition, PlayManyCommand, PlaySignal,
+    PlaybackBookmark, PlaybackState, ResumeFadeIn, TweenCommand, MAX_PLAYBACK_RATE,
 };
-pub use backend_settings::AudioSettings;
+pub use audio_output::dispatch_audio_channel;
+pub use backend_settings::{
+    AudioSettings, DisabledPlayback, GlobalAudio, OutputLatency, PlayCommandQueueing, RampUpAudio,
+    StartupRamp,
+};
+pub use beat::{MusicBar, MusicBeat};
 use bevy::app::{PostUpdate, PreUpdate};
 use bevy::asset::AssetApp;
-pub use channel::AudioControl;
-pub use source::AudioSource;
+pub use capture::{AudioCapture, ChannelCapture};
+pub use channel::{
+    AudioControl, Channel, ChannelActivity, ChannelSettings, ChannelSettingsChanged, ChannelState,
+    PlayCommandsExecuted, VoiceStealing,
+};
+pub use clock::AudioClock;
+pub use commands::AudioCommandsExt;
+pub use conditions::{audio_output_available, channel_is_playing, instance_is_playing};
+#[cfg(not(target_arch = "wasm32"))]
+pub use device::OutputBackendSettings;
+pub use device::{AudioOutputDevice, OutputBackend};
+pub use diagnostics::AudioDiagnosticsPlugin;
+pub use dialogue::{
+    DialogueAudio, DialogueLine, DialogueLineFinished, DialogueLineId, DialogueLineStarted,
+};
+pub use ducking::{DuckChannel, DuckingMatrix};
+pub use events::{PlaySoundEvent, SetChannelVolumeEvent, StopChannelEvent};
+pub use focus::AudioFocusSettings;
+pub use interruption::{AudioInterruption, AudioInterruptionSettings};
+pub use layered_music::{LayeredMusic, MusicLayer};
+pub use metering::{ChannelLevels, Levels};
+pub use mock::{MockAudioExt, MockAudioPlugin};
+pub use offline::{OfflineAudio, OfflineAudioPlugin, OfflineBackend, OfflineBackendSettings};
+pub use playlist::{AudioPlaylist, PlaylistRepeat};
+pub use quality::AudioQuality;
+pub use rate_limit::{CategoryRule, Exclusivity, SoundRateLimits};
+pub use sequencer::{SequencerStep, StepPattern, StepSequencer};
+#[cfg(not(target_arch = "wasm32"))]
+pub use source::StreamingAudioSource;
+pub use source::{
+    AudioCue, AudioCueSettings, AudioLoaderSettings, AudioSource, AudioSourceConversions,
+};
 use spatial::cleanup_stopped_spatial_instances;
+pub use timeline::{AudioTimeline, TimelineClock, TimelineEvent, TimelinePlayer};
+pub use transport::{BeatTick, Transport, TransportPosition};
+pub use unlock::AudioUnlocked;
 
 /// Most commonly used types
 pub mod prelude {
+    #[doc(hidden)]
+    #[cfg(feature = "analysis")]
+    pub use crate::analysis::{AudioSpectrum, MainTrackSpectrum, Spectrum, SpectrumSettings};
     #[doc(hidden)]
     pub use crate::audio::{
-        AudioApp, AudioEasing, AudioTween, FadeIn, FadeOut, PlayAudioCommand, PlaybackState,
-        TweenCommand,
+        AudioApp, AudioEasing, AudioTween, ChainTransition, ChainedSoundSettings, FadeIn, FadeOut,
+        PanLaw, Panning, PlayAudioCommand, PlayCondition, PlayManyCommand, PlaySignal,
+        PlaybackBookmark, PlaybackState, ResumeFadeIn, TweenCommand,
+    };
+    #[doc(hidden)]
+    pub use crate::audio_output::dispatch_audio_channel;
+    #[doc(hidden)]
+    pub use crate::backend_settings::{
+        AudioSettings, DisabledPlayback, GlobalAudio, OutputLatency, PlayCommandQueueing,
+        RampUpAudio, StartupRamp,
     };
     #[doc(hidden)]
-    pub use crate::backend_settings::AudioSettings;
+    pub use crate::beat::{MusicBar, MusicBeat};
     #[doc(hidden)]
-    pub use crate::channel::dynamic::{DynamicAudioChannel, DynamicAudioChannels};
+    pub use crate::capture::{AudioCapture, ChannelCapture};
+    #[doc(hidden)]
+    pub use crate::channel::dynamic::{ChannelKey, DynamicAudioChannel, DynamicAudioChannels};
     #[doc(hidden)]
     pub use crate::channel::typed::AudioChannel;
     #[doc(hidden)]
-    pub use crate::channel::AudioControl;
+    pub use crate::channel::{
+        AudioControl, Channel, ChannelActivity, ChannelSettings, ChannelSettingsChanged,
+        ChannelState, PlayCommandsExecuted, VoiceStealing,
+    };
+    #[doc(hidden)]
+    pub use crate::clock::AudioClock;
+    #[doc(hidden)]
+    pub use crate::commands::AudioCommandsExt;
+    #[doc(hidden)]
+    pub use crate::conditions::{audio_output_available, channel_is_playing, instance_is_playing};
+    #[doc(hidden)]
+    pub use crate::device::AudioOutputDevice;
+    #[doc(hidden)]
+    pub use crate::diagnostics::AudioDiagnosticsPlugin;
+    #[doc(hidden)]
+    pub use crate::dialogue::{
+        DialogueAudio, DialogueLine, DialogueLineFinished, DialogueLineId, DialogueLineStarted,
+    };
+    #[doc(hidden)]
+    pub use crate::ducking::{DuckChannel, DuckingMatrix};
+    #[doc(hidden)]
+    pub use crate::events::{PlaySoundEvent, SetChannelVolumeEvent, StopChannelEvent};
+    #[doc(hidden)]
+    pub use crate::focus::AudioFocusSettings;
+    #[doc(hidden)]
+    pub use crate::instance::{
+        AudioCommandError, AudioInstance, AudioInstanceAssetsExt, AudioInstanceStopped,
+        LoopCompleted, PlaybackStateChanged,
+    };
+    #[doc(hidden)]
+    pub use crate::interruption::{AudioInterruption, AudioInterruptionSettings};
+    #[doc(hidden)]
+    pub use crate::layered_music::{LayeredMusic, MusicLayer};
+    #[doc(hidden)]
+    pub use crate::metering::{ChannelLevels, Levels};
+    #[doc(hidden)]
+    pub use crate::mock::{MockAudioExt, MockAudioPlugin};
+    #[doc(hidden)]
+    pub use crate::offline::{OfflineAudio, OfflineAudioPlugin};
+    #[doc(hidden)]
+    pub use crate::playlist::{AudioPlaylist, PlaylistRepeat};
+    #[doc(hidden)]
+    pub use crate::quality::AudioQuality;
+    #[doc(hidden)]
+    pub use crate::rate_limit::{CategoryRule, Exclusivity, SoundRateLimits};
     #[doc(hidden)]
-    pub use crate::instance::{AudioCommandError, AudioInstance, AudioInstanceAssetsExt};
+    pub use crate::sequencer::{SequencerStep, StepPattern, StepSequencer};
+    #[doc(hidden)]
+    #[cfg(feature = "settings_loader")]
+    pub use crate::source::cue_loader::*;
     #[doc(hidden)]
     #[cfg(feature = "flac")]
     pub use crate::source::flac_loader::*;
@@ -83,26 +210,68 @@ pub mod prelude {
     #[cfg(feature = "settings_loader")]
     pub use crate::source::settings_loader::*;
     #[doc(hidden)]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::source::streaming_loader::*;
+    #[doc(hidden)]
+    #[cfg(feature = "settings_loader")]
+    pub use crate::source::timeline_loader::*;
+    #[doc(hidden)]
     #[cfg(feature = "wav")]
     pub use crate::source::wav_loader::*;
     #[doc(hidden)]
-    pub use crate::source::AudioSource;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::source::StreamingAudioSource;
+    #[doc(hidden)]
+    pub use crate::source::{
+        AudioCue, AudioCueSettings, AudioLoaderSettings, AudioSource, AudioSourceConversions,
+    };
     #[doc(hidden)]
-    pub use crate::spatial::{AudioEmitter, AudioReceiver, SpatialAudio};
+    pub use crate::spatial::{
+        AttenuationModel, AudioEmitter, AudioListener, AudioReceiver, DespawnBehavior,
+        DistanceReverb, Doppler, KeepStereo, SpatialAudio,
+    };
     #[doc(hidden)]
-    pub use crate::{Audio, AudioPlugin, MainTrack};
+    pub use crate::timeline::{AudioTimeline, TimelineClock, TimelineEvent, TimelinePlayer};
+    #[doc(hidden)]
+    pub use crate::transport::{BeatTick, Transport, TransportPosition};
+    #[doc(hidden)]
+    pub use crate::unlock::AudioUnlocked;
+    #[doc(hidden)]
+    pub use crate::{audio_channels, Audio, AudioPlugin, MainTrack};
     pub use kira::{
+        clock::ClockSpeed,
         dsp::Frame,
         sound::{
             static_sound::{StaticSoundData, StaticSoundSettings},
             FromFileError, Sound, SoundData,
         },
+        track::effect::reverb::ReverbBuilder,
         Volume,
     };
 }
 
-use crate::audio_output::{cleanup_stopped_instances, play_dynamic_channels, AudioOutput};
+use crate::audio_output::{
+    apply_global_audio, cleanup_stopped_instances, emit_channel_activity,
+    emit_channel_settings_changes, emit_loop_completions, emit_play_commands_executed,
+    play_chained_sounds, play_dynamic_channels, ramp_up_audio, retry_audio_setup,
+    update_dynamic_instance_states, virtualize_instances, AudioOutput, BackendSettingsFn,
+};
 
+use crate::backend_settings::AppPlayCommandQueueing;
+use crate::beat::emit_music_beats;
+use crate::capture::update_audio_capture;
+use crate::clock::update_audio_clocks;
+use crate::commands::{play_pending_cues, PendingCues};
+use crate::device::switch_output_device;
+use crate::ducking::duck_channels;
+use crate::events::run_audio_events;
+use crate::focus::pause_unfocused_audio;
+use crate::interruption::handle_audio_interruptions;
+use crate::metering::update_channel_levels;
+use crate::quality::{activity_update_due, spatial_update_due};
+#[cfg(feature = "settings_loader")]
+use crate::source::cue_loader::AudioCueLoader;
+use crate::source::finish_audio_source_conversions;
 #[cfg(feature = "flac")]
 use crate::source::flac_loader::FlacLoader;
 #[cfg(feature = "mp3")]
@@ -111,15 +280,36 @@ use crate::source::mp3_loader::Mp3Loader;
 use crate::source::ogg_loader::OggLoader;
 #[cfg(feature = "settings_loader")]
 use crate::source::settings_loader::SettingsLoader;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::streaming_loader::StreamingAudioLoader;
+#[cfg(feature = "settings_loader")]
+use crate::source::timeline_loader::AudioTimelineLoader;
 #[cfg(feature = "wav")]
 use crate::source::wav_loader::WavLoader;
-use crate::spatial::{run_spatial_audio, SpatialAudio};
-use bevy::prelude::{resource_exists, App, IntoSystemConfigs, Plugin, Resource, SystemSet};
+use crate::spatial::{run_distance_reverb, run_doppler, run_spatial_audio, SpatialAudio};
+use crate::timeline::run_timeline_players;
+use crate::transport::update_transport;
+use crate::unlock::unlock_audio_on_input;
+use bevy::ecs::event::Events;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::{
+    resource_exists, resource_exists_and_changed, App, IntoSystemConfigs, Plugin, Resource,
+    SystemSet,
+};
+use bevy::window::WindowOccluded;
+pub use channel::dynamic::ChannelKey;
 pub use channel::dynamic::DynamicAudioChannel;
 pub use channel::dynamic::DynamicAudioChannels;
 pub use channel::typed::AudioChannel;
 pub use instance::AudioInstance;
 pub use instance::AudioInstanceAssetsExt;
+pub use instance::AudioInstanceStopped;
+pub use instance::LoopCompleted;
+pub use instance::PlaybackStateChanged;
+use kira::manager::backend::Backend;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// A Bevy plugin for audio
 ///
@@ -149,14 +339,94 @@ pub use instance::AudioInstanceAssetsExt;
 /// #     events.send(AppExit::Success);
 /// # }
 /// ```
-#[derive(Default)]
-pub struct AudioPlugin;
+///
+/// The audio plays through the [`OutputBackend`]. Any other kira [`Backend`] can be used instead,
+/// for example kira's mock backend in tests:
+/// ```
+/// # use bevy_kira_audio::prelude::*;
+/// # use bevy::prelude::*;
+/// use kira::manager::backend::mock::{MockBackend, MockBackendSettings};
+///
+/// App::new().add_plugins((
+///     MinimalPlugins,
+///     AssetPlugin::default(),
+///     AudioPlugin::<MockBackend>::default()
+///         .with_backend_settings(MockBackendSettings { sample_rate: 48_000 }),
+/// ));
+/// ```
+/// Choosing the [`AudioOutputDevice`] and the buffer size or sample rate in the [`AudioSettings`]
+/// only works with the [`OutputBackend`].
+pub struct AudioPlugin<B: Backend = OutputBackend> {
+    backend_settings: Option<BackendSettingsFn<B>>,
+    _backend: PhantomData<fn() -> B>,
+}
+
+/// The [`AudioPlugin`] playing through the [`OutputBackend`]
+///
+/// Unit structs cannot have type parameters, and the default of a type parameter does not apply
+/// when naming the type in an expression. This constant shares the name of the plugin type to
+/// keep `add_plugins(AudioPlugin)` working.
+#[allow(non_upper_case_globals)]
+pub const AudioPlugin: AudioPlugin = AudioPlugin {
+    backend_settings: None,
+    _backend: PhantomData,
+};
+
+impl<B: Backend> Default for AudioPlugin<B> {
+    fn default() -> Self {
+        AudioPlugin {
+            backend_settings: None,
+            _backend: PhantomData,
+        }
+    }
+}
+
+impl<B: Backend> AudioPlugin<B> {
+    /// Create the backend with the given settings instead of their default
+    ///
+    /// The settings are cloned every time the audio manager is created, for example again after
+    /// the output device was lost.
+    pub fn with_backend_settings(mut self, settings: B::Settings) -> Self
+    where
+        B::Settings: Clone + Send + Sync + 'static,
+    {
+        self.backend_settings = Some(Arc::new(move || settings.clone()));
+        self
+    }
+}
 
-impl Plugin for AudioPlugin {
+impl<B> Plugin for AudioPlugin<B>
+where
+    B: Backend + 'static,
+    B::Settings: Default + 'static,
+    B::Error: Debug,
+{
     fn build(&self, app: &mut App) {
-        app.init_non_send_resource::<AudioOutput>()
+        let world = app.world_mut();
+        if world
+            .get_resource::<AudioSettings>()
+            .map_or(false, |settings| settings.pause_on_focus_loss)
+        {
+            world
+                .get_resource_or_insert_with(AudioFocusSettings::default)
+                .enabled = true;
+        }
+        let backend_settings = self
+            .backend_settings
+            .clone()
+            .unwrap_or_else(|| Arc::new(B::Settings::default));
+        let audio_output = AudioOutput::with_backend::<B>(world, backend_settings);
+        app.insert_non_send_resource(audio_output)
             .init_asset::<AudioSource>()
-            .init_asset::<AudioInstance>();
+            .init_asset::<AudioInstance>()
+            .init_asset::<AudioClock>()
+            .init_asset::<AudioCue>()
+            .init_asset::<AudioTimeline>();
+
+        // Registered before the static loaders, so that those stay the default for untyped loads
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_asset::<StreamingAudioSource>()
+            .init_asset_loader::<StreamingAudioLoader>();
 
         #[cfg(feature = "mp3")]
         app.init_asset_loader::<Mp3Loader>();
@@ -168,17 +438,163 @@ impl Plugin for AudioPlugin {
         app.init_asset_loader::<FlacLoader>();
 
         #[cfg(feature = "settings_loader")]
-        app.init_asset_loader::<SettingsLoader>();
+        app.init_asset_loader::<SettingsLoader>()
+            .init_asset_loader::<AudioCueLoader>()
+            .init_asset_loader::<AudioTimelineLoader>();
 
+        let queueing = app.world().resource::<AppPlayCommandQueueing>().0;
+        app.world_mut()
+            .get_resource_or_insert_with(DynamicAudioChannels::default)
+            .set_queueing(queueing);
         app.init_resource::<DynamicAudioChannels>()
+            .init_resource::<GlobalAudio>()
+            .init_resource::<AudioQuality>()
+            .init_resource::<OutputLatency>()
+            .init_resource::<AudioInterruptionSettings>()
+            .init_resource::<AudioFocusSettings>()
+            .init_resource::<PendingCues>()
+            .init_resource::<AudioSourceConversions>()
+            .init_resource::<ChannelLevels>()
+            .init_resource::<AudioCapture>()
+            .init_resource::<AudioOutputDevice>()
+            .register_type::<AudioTween>()
+            .register_type::<Panning>()
+            .register_type::<PanLaw>()
+            .register_type::<VoiceStealing>()
+            .add_event::<ChannelActivity>()
+            .add_event::<ChannelSettingsChanged>()
+            .add_event::<PlayCommandsExecuted>()
+            .add_event::<AudioInstanceStopped>()
+            .add_event::<LoopCompleted>()
+            .add_event::<PlaybackStateChanged>()
+            .add_event::<BeatTick>()
+            .add_event::<MusicBeat>()
+            .add_event::<MusicBar>()
+            .add_event::<RampUpAudio>()
+            .add_event::<AudioInterruption>()
+            .add_event::<AudioUnlocked>()
+            .add_event::<PlaySoundEvent>()
+            .add_event::<StopChannelEvent>()
+            .add_event::<SetChannelVolumeEvent>()
+            .add_event::<DialogueLineStarted>()
+            .add_event::<DialogueLineFinished>()
+            .add_systems(PostUpdate, ramp_up_audio)
+            .add_systems(PreUpdate, finish_audio_source_conversions)
+            .add_systems(PreUpdate, update_channel_levels)
+            .add_systems(PreUpdate, update_audio_capture)
+            .add_systems(PreUpdate, retry_audio_setup)
+            .add_systems(
+                PreUpdate,
+                unlock_audio_on_input
+                    .before(retry_audio_setup)
+                    .run_if(resource_exists::<Events<KeyboardInput>>),
+            )
+            .add_systems(
+                PreUpdate,
+                switch_output_device.run_if(resource_exists_and_changed::<AudioOutputDevice>),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_global_audio
+                    .before(AudioSystemSet::PlayDynamicChannels)
+                    .before(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                play_pending_cues
+                    .before(AudioSystemSet::PlayDynamicChannels)
+                    .before(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                run_timeline_players
+                    .before(AudioSystemSet::PlayDynamicChannels)
+                    .before(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                handle_audio_interruptions
+                    .before(AudioSystemSet::PlayDynamicChannels)
+                    .before(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                pause_unfocused_audio
+                    .before(AudioSystemSet::PlayDynamicChannels)
+                    .before(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<Events<WindowOccluded>>),
+            )
             .add_systems(
                 PostUpdate,
                 play_dynamic_channels.in_set(AudioSystemSet::PlayDynamicChannels),
             )
+            .add_systems(
+                PostUpdate,
+                run_audio_events
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                play_chained_sounds
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
             .add_systems(
                 PreUpdate,
                 cleanup_stopped_instances.in_set(AudioSystemSet::InstanceCleanup),
             )
+            .add_systems(
+                PreUpdate,
+                update_dynamic_instance_states.after(AudioSystemSet::InstanceCleanup),
+            )
+            .add_systems(
+                PostUpdate,
+                update_audio_clocks
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                emit_channel_settings_changes
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                emit_play_commands_executed
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                virtualize_instances
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels),
+            )
+            .add_systems(
+                PostUpdate,
+                duck_channels
+                    .after(AudioSystemSet::PlayDynamicChannels)
+                    .after(AudioSystemSet::PlayTypedChannels)
+                    .run_if(resource_exists::<DuckingMatrix>),
+            )
+            .add_systems(
+                PostUpdate,
+                update_transport
+                    .after(update_audio_clocks)
+                    .run_if(resource_exists::<Transport>),
+            )
+            .add_systems(
+                PreUpdate,
+                (emit_music_beats, emit_loop_completions).after(AudioSystemSet::InstanceCleanup),
+            )
+            .add_systems(
+                PreUpdate,
+                emit_channel_activity
+                    .after(AudioSystemSet::InstanceCleanup)
+                    .run_if(activity_update_due),
+            )
             .add_audio_channel::<MainTrack>()
             .add_systems(
                 PreUpdate,
@@ -188,8 +604,22 @@ impl Plugin for AudioPlugin {
             )
             .add_systems(
                 PostUpdate,
-                run_spatial_audio.run_if(resource_exists::<SpatialAudio>),
+                (run_spatial_audio, run_doppler, run_distance_reverb)
+                    .run_if(resource_exists::<SpatialAudio>)
+                    .run_if(spatial_update_due),
             );
+
+        #[cfg(feature = "analysis")]
+        app.init_resource::<AudioSpectrum>()
+            .add_systems(PreUpdate, analysis::update_audio_spectrum);
+
+        #[cfg(feature = "mobile")]
+        app.add_systems(
+            PostUpdate,
+            interruption::send_lifecycle_interruptions
+                .before(handle_audio_interruptions)
+                .run_if(resource_exists::<bevy::ecs::event::Events<bevy::window::AppLifecycle>>),
+        );
     }
 }
 
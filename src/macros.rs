@@ -0,0 +1,81 @@
+/// Declare multiple typed audio channels and a plugin registering them
+///
+/// Every channel gets a marker type and can optionally be configured with an initial
+/// `volume`, `panning`, `playback_rate`, or `paused` state. Add the generated plugin
+/// after the [`AudioPlugin`](crate::AudioPlugin) to register all channels.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::asset::AssetPlugin;
+/// use bevy_kira_audio::prelude::*;
+///
+/// audio_channels! {
+///     /// All audio channels of the game
+///     pub struct GameAudioChannels {
+///         /// Background music
+///         pub Music { volume: 0.5 },
+///         pub Sfx { volume: 0.8, panning: 0.4 },
+///         pub Ui,
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut app = App::new();
+/// app.add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_plugins(GameAudioChannels);
+/// # assert!(app.world().contains_resource::<AudioChannel<Music>>());
+/// # assert!(app.world().contains_resource::<AudioChannel<Ui>>());
+/// # }
+///
+/// fn play_music(music: Res<AudioChannel<Music>>, asset_server: Res<AssetServer>) {
+///     music.play(asset_server.load("sounds/loop.ogg")).looped();
+/// }
+/// ```
+#[macro_export]
+macro_rules! audio_channels {
+    (
+        $(#[$plugin_meta:meta])*
+        $plugin_vis:vis struct $plugin:ident {
+            $(
+                $(#[$channel_meta:meta])*
+                $channel_vis:vis $channel:ident $({ $($setting:ident : $value:expr),* $(,)? })?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$plugin_meta])*
+        $plugin_vis struct $plugin;
+
+        $(
+            $(#[$channel_meta])*
+            #[derive(::bevy::prelude::Resource)]
+            $channel_vis struct $channel;
+        )*
+
+        impl ::bevy::app::Plugin for $plugin {
+            fn build(&self, app: &mut ::bevy::app::App) {
+                $(
+                    $crate::AudioApp::add_audio_channel::<$channel>(app);
+                    #[allow(unused_variables)]
+                    let channel = app.world().resource::<$crate::AudioChannel<$channel>>();
+                    $($(
+                        $crate::audio_channels!(@setting channel, $setting, $value);
+                    )*)?
+                )*
+            }
+        }
+    };
+    (@setting $channel:ident, volume, $value:expr) => {
+        $crate::AudioControl::set_volume($channel, $value);
+    };
+    (@setting $channel:ident, panning, $value:expr) => {
+        $crate::AudioControl::set_panning($channel, $value);
+    };
+    (@setting $channel:ident, playback_rate, $value:expr) => {
+        $crate::AudioControl::set_playback_rate($channel, $value);
+    };
+    (@setting $channel:ident, paused, $value:expr) => {
+        if $value {
+            $crate::AudioControl::pause($channel);
+        }
+    };
+}
@@ -0,0 +1,198 @@
+//! Peak and RMS levels of channels for VU meters and loudness based mixing
+
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use bevy::ecs::system::{NonSend, ResMut, Resource};
+use bevy::utils::HashMap;
+use kira::clock::clock_info::ClockInfoProvider;
+use kira::dsp::Frame;
+use kira::modulator::value_provider::ModulatorValueProvider;
+use kira::track::effect::{Effect, EffectBuilder};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Resource with the latest levels of all channels with [metering](crate::ChannelSettings::with_metering)
+///
+/// The levels are measured on the audio thread and copied into this resource once per frame.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel_with_settings::<Music>(ChannelSettings::default().with_metering())
+///     .add_systems(Update, draw_vu_meter);
+///
+/// fn draw_vu_meter(levels: Res<ChannelLevels>) {
+///     if let Some(levels) = levels.get(&Channel::typed::<Music>()) {
+///         info!("Music at {:.1} dB", levels.rms_db());
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct ChannelLevels {
+    channels: HashMap<Channel, Levels>,
+}
+
+impl ChannelLevels {
+    /// The levels of the given channel
+    pub fn get(&self, channel: &Channel) -> Option<Levels> {
+        self.channels.get(channel).copied()
+    }
+}
+
+/// Levels of a channel over the last measurement window
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Levels {
+    /// Highest absolute sample value, `1` is full scale
+    pub peak: f32,
+    /// Root mean square of the samples, `1` is full scale
+    pub rms: f32,
+}
+
+impl Levels {
+    /// The peak level in decibels relative to full scale
+    pub fn peak_db(&self) -> f32 {
+        20. * self.peak.log10()
+    }
+
+    /// The RMS level in decibels relative to full scale
+    pub fn rms_db(&self) -> f32 {
+        20. * self.rms.log10()
+    }
+}
+
+/// Levels shared between the audio thread and the main thread
+#[derive(Default)]
+pub(crate) struct SharedLevels {
+    peak: AtomicU32,
+    rms: AtomicU32,
+}
+
+impl SharedLevels {
+    fn read(&self) -> Levels {
+        Levels {
+            peak: f32::from_bits(self.peak.load(Ordering::Relaxed)),
+            rms: f32::from_bits(self.rms.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Effect passing the signal through unchanged while measuring its levels
+pub(crate) struct MeterTap {
+    shared: Arc<SharedLevels>,
+    window: f64,
+    elapsed: f64,
+    peak: f32,
+    squares: f32,
+    samples: u32,
+}
+
+pub(crate) struct MeterTapBuilder {
+    pub(crate) shared: Arc<SharedLevels>,
+    window: Duration,
+}
+
+impl MeterTapBuilder {
+    pub(crate) fn new(window: Duration) -> Self {
+        MeterTapBuilder {
+            shared: Arc::default(),
+            window,
+        }
+    }
+}
+
+impl EffectBuilder for MeterTapBuilder {
+    type Handle = ();
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        let tap = MeterTap {
+            shared: self.shared,
+            window: self.window.as_secs_f64().max(f64::EPSILON),
+            elapsed: 0.,
+            peak: 0.,
+            squares: 0.,
+            samples: 0,
+        };
+
+        (Box::new(tap), ())
+    }
+}
+
+impl Effect for MeterTap {
+    fn process(
+        &mut self,
+        input: Frame,
+        dt: f64,
+        _clock_info_provider: &ClockInfoProvider,
+        _modulator_value_provider: &ModulatorValueProvider,
+    ) -> Frame {
+        self.peak = self.peak.max(input.left.abs()).max(input.right.abs());
+        self.squares += (input.left * input.left + input.right * input.right) / 2.;
+        self.samples += 1;
+        self.elapsed += dt;
+        if self.elapsed >= self.window {
+            self.elapsed -= self.window;
+            let rms = (self.squares / self.samples as f32).sqrt();
+            self.shared
+                .peak
+                .store(self.peak.to_bits(), Ordering::Relaxed);
+            self.shared.rms.store(rms.to_bits(), Ordering::Relaxed);
+            self.peak = 0.;
+            self.squares = 0.;
+            self.samples = 0;
+        }
+
+        input
+    }
+}
+
+pub(crate) fn update_channel_levels(
+    audio_output: NonSend<AudioOutput>,
+    mut channel_levels: ResMut<ChannelLevels>,
+) {
+    let meters = audio_output.meters();
+    channel_levels
+        .channels
+        .retain(|channel, _| meters.contains_key(channel));
+    for (channel, shared) in meters {
+        channel_levels
+            .channels
+            .insert(channel.clone(), shared.read());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kira::clock::clock_info::MockClockInfoProviderBuilder;
+    use kira::modulator::value_provider::MockModulatorValueProviderBuilder;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn tap_measures_peak_and_rms_of_its_input() {
+        let builder = MeterTapBuilder::new(Duration::from_millis(100));
+        let shared = builder.shared.clone();
+        let (mut tap, _) = builder.build();
+        let sample_rate = 1000;
+        let clock_info_provider = MockClockInfoProviderBuilder::new(0).build();
+        let modulator_value_provider = MockModulatorValueProviderBuilder::new(0).build();
+        for index in 0..sample_rate {
+            let sample = 0.5 * (2. * PI * 50. * index as f32 / sample_rate as f32).sin();
+            tap.process(
+                Frame::from_mono(sample),
+                1. / sample_rate as f64,
+                &clock_info_provider,
+                &modulator_value_provider,
+            );
+        }
+
+        let levels = shared.read();
+        assert!((levels.peak - 0.5).abs() < 1e-3);
+        assert!((levels.rms - 0.5 / 2_f32.sqrt()).abs() < 0.01);
+        assert!((levels.peak_db() + 6.02).abs() < 0.01);
+    }
+}
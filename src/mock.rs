@@ -0,0 +1,168 @@
+//! Test audio logic without a sound card
+
+use crate::audio_output::AudioOutput;
+use crate::offline::{FrameCounter, BLOCK_SIZE};
+use crate::{AudioInstance, AudioPlugin, AudioSettings, PlaybackState};
+use bevy::app::{App, Last, Plugin};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::{NonSendMut, Res, ResMut, Resource};
+use bevy::ecs::world::World;
+use bevy::time::Time;
+use kira::manager::backend::mock::MockBackend;
+use std::time::Duration;
+
+/// Plays the audio of the app through kira's [`MockBackend`]
+///
+/// Add this plugin instead of the [`AudioPlugin`] to unit test audio logic without a sound card.
+/// The audio advances by the delta of [`Time`] at the end of every frame, so with a manual
+/// [`TimeUpdateStrategy`](bevy::time::TimeUpdateStrategy) it runs in lockstep with the app.
+/// Use [`MockAudioExt`] to advance the audio in between and to check the state of instances.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::time::TimeUpdateStrategy;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// let mut app = App::new();
+/// app.add_plugins((MinimalPlugins, AssetPlugin::default(), MockAudioPlugin))
+///     .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(10)));
+/// let handle = app.world().resource::<Audio>().play(Handle::default()).handle();
+/// app.update();
+///
+/// // the source never loads
+/// assert_eq!(app.audio_instance_state(&handle), PlaybackState::Stopped);
+/// ```
+/// The audio renders at [`MockAudioPlugin::DEFAULT_SAMPLE_RATE`], unless another
+/// [`AudioSettings::sample_rate`] is set.
+#[derive(Default)]
+pub struct MockAudioPlugin;
+
+impl MockAudioPlugin {
+    /// Sample rate of the mock backend if none is set in the [`AudioSettings`]
+    ///
+    /// Every rendered frame advances the sounds by one millisecond.
+    pub const DEFAULT_SAMPLE_RATE: u32 = 1000;
+}
+
+impl Plugin for MockAudioPlugin {
+    fn build(&self, app: &mut App) {
+        let mut settings = app
+            .world()
+            .get_resource::<AudioSettings>()
+            .copied()
+            .unwrap_or_default();
+        let sample_rate = *settings
+            .sample_rate
+            .get_or_insert(Self::DEFAULT_SAMPLE_RATE);
+        app.insert_resource(settings)
+            .add_plugins(AudioPlugin::<MockBackend>::default())
+            .insert_resource(MockAudioClock(FrameCounter::new(sample_rate)))
+            .add_systems(Last, advance_mock_audio);
+    }
+}
+
+/// Converts durations to the number of frames to render on the mock backend
+#[derive(Resource)]
+struct MockAudioClock(FrameCounter);
+
+/// Helpers for apps and worlds using the [`MockAudioPlugin`]
+pub trait MockAudioExt {
+    /// Advance the audio by the given duration, independent of the app's [`Time`]
+    ///
+    /// The audio systems only see the changes with the next update, for example to send
+    /// [`AudioInstanceStopped`](crate::AudioInstanceStopped) events.
+    fn advance_audio(&mut self, duration: Duration);
+
+    /// The state of the given instance, [`PlaybackState::Stopped`] if it does not exist
+    fn audio_instance_state(&self, instance: &Handle<AudioInstance>) -> PlaybackState;
+}
+
+impl MockAudioExt for World {
+    fn advance_audio(&mut self, duration: Duration) {
+        let count = self.resource_mut::<MockAudioClock>().0.frames(duration);
+        render_mock_frames(&mut self.non_send_resource_mut::<AudioOutput>(), count);
+    }
+
+    fn audio_instance_state(&self, instance: &Handle<AudioInstance>) -> PlaybackState {
+        self.get_resource::<Assets<AudioInstance>>()
+            .and_then(|instances| instances.get(instance))
+            .map_or(PlaybackState::Stopped, AudioInstance::state)
+    }
+}
+
+impl MockAudioExt for App {
+    fn advance_audio(&mut self, duration: Duration) {
+        self.world_mut().advance_audio(duration);
+    }
+
+    fn audio_instance_state(&self, instance: &Handle<AudioInstance>) -> PlaybackState {
+        self.world().audio_instance_state(instance)
+    }
+}
+
+fn render_mock_frames(audio_output: &mut AudioOutput, count: usize) {
+    let Some(backend) = audio_output.backend_mut::<MockBackend>() else {
+        return;
+    };
+    let mut rendered = 0;
+    while rendered < count {
+        backend.on_start_processing();
+        let block = BLOCK_SIZE.min(count - rendered);
+        for _ in 0..block {
+            backend.process();
+        }
+        rendered += block;
+    }
+    if count > 0 {
+        // sounds publish their position when processing starts
+        backend.on_start_processing();
+    }
+}
+
+fn advance_mock_audio(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut clock: ResMut<MockAudioClock>,
+    time: Res<Time>,
+) {
+    let count = clock.0.frames(time.delta());
+    render_mock_frames(&mut audio_output, count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Audio, AudioControl, AudioInstanceStopped, AudioSource};
+    use bevy::asset::AssetPlugin;
+    use bevy::ecs::event::Events;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use kira::sound::static_sound::StaticSoundData;
+
+    #[test]
+    fn sounds_advance_with_the_app_and_manually() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), MockAudioPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                20,
+            )));
+        let sound = StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap();
+        let duration = sound.duration();
+        let source = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource { sound });
+        let instance = app.world().resource::<Audio>().play(source).handle();
+        // the first update starts the sound, but has no time delta
+        app.update();
+        app.update();
+        let PlaybackState::Playing { position } = app.audio_instance_state(&instance) else {
+            panic!("The sound should be playing");
+        };
+        assert!((position - 0.02).abs() < 1e-3);
+
+        app.advance_audio(duration);
+        assert_eq!(app.audio_instance_state(&instance), PlaybackState::Stopped);
+        app.update();
+        let stopped = app.world().resource::<Events<AudioInstanceStopped>>();
+        assert_eq!(stopped.len(), 1);
+    }
+}
@@ -0,0 +1,288 @@
+//! Render the audio into a buffer instead of playing it on a device
+
+use crate::audio_output::AudioOutput;
+use crate::AudioPlugin;
+use bevy::app::{App, Last, Plugin};
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::time::Time;
+use kira::dsp::Frame;
+use kira::manager::backend::{Backend, Renderer};
+use parking_lot::Mutex;
+use std::convert::Infallible;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of frames rendered between processing the commands sent to the renderer
+pub(crate) const BLOCK_SIZE: usize = 512;
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// Plays the audio of the app through the [`OfflineBackend`]
+///
+/// Add this plugin instead of the [`AudioPlugin`]. Nothing is played on a device, the audio is
+/// rendered into the [`OfflineAudio`] resource instead. By default, every frame renders the
+/// delta of [`Time`], so with a manual [`TimeUpdateStrategy`](bevy::time::TimeUpdateStrategy)
+/// the app runs faster than realtime and the audio stays in sync with the rendered frames.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::time::TimeUpdateStrategy;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// let mut app = App::new();
+/// app.add_plugins((MinimalPlugins, AssetPlugin::default(), OfflineAudioPlugin))
+///     .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+///         1. / 60.,
+///     )));
+/// for _ in 0..60 {
+///     app.update();
+/// }
+/// // or write to a `File`
+/// let mut wav = vec![];
+/// app.world()
+///     .resource::<OfflineAudio>()
+///     .write_wav(&mut wav)
+///     .unwrap();
+/// ```
+/// The sample rate is taken from [`AudioSettings::sample_rate`](crate::AudioSettings::sample_rate).
+#[derive(Default)]
+pub struct OfflineAudioPlugin;
+
+impl Plugin for OfflineAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(AudioPlugin::<OfflineBackend>::default());
+        let offline_audio = app
+            .world_mut()
+            .non_send_resource_mut::<AudioOutput>()
+            .backend_mut::<OfflineBackend>()
+            .map(|backend| OfflineAudio::new(backend.renderer.clone(), backend.sample_rate))
+            .expect("The offline backend can always be set up");
+        app.insert_resource(offline_audio)
+            .add_systems(Last, render_frame_duration);
+    }
+}
+
+/// Settings of the [`OfflineBackend`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OfflineBackendSettings {
+    /// The sample rate to render at
+    pub sample_rate: u32,
+}
+
+impl Default for OfflineBackendSettings {
+    fn default() -> Self {
+        OfflineBackendSettings {
+            sample_rate: 48_000,
+        }
+    }
+}
+
+/// kira backend that renders on demand instead of playing on a device
+///
+/// Use it through the [`OfflineAudioPlugin`].
+pub struct OfflineBackend {
+    sample_rate: u32,
+    /// The renderer once the backend was started
+    renderer: Arc<Mutex<Option<Renderer>>>,
+}
+
+impl Backend for OfflineBackend {
+    type Settings = OfflineBackendSettings;
+    type Error = Infallible;
+
+    fn setup(settings: Self::Settings) -> Result<(Self, u32), Self::Error> {
+        Ok((
+            OfflineBackend {
+                sample_rate: settings.sample_rate,
+                renderer: Arc::default(),
+            },
+            settings.sample_rate,
+        ))
+    }
+
+    fn start(&mut self, renderer: Renderer) -> Result<(), Self::Error> {
+        *self.renderer.lock() = Some(renderer);
+
+        Ok(())
+    }
+}
+
+/// Resource with the audio rendered by the [`OfflineAudioPlugin`]
+///
+/// Render more audio with [`render`](Self::render), or let every frame render its time delta
+/// with [`follow_time`](Self::follow_time).
+#[derive(Resource)]
+pub struct OfflineAudio {
+    /// Render the delta of [`Time`] at the end of every frame
+    ///
+    /// Disable it to only render manually, for example in tests.
+    pub follow_time: bool,
+    renderer: Arc<Mutex<Option<Renderer>>>,
+    frame_counter: FrameCounter,
+    frames: Vec<Frame>,
+}
+
+impl OfflineAudio {
+    fn new(renderer: Arc<Mutex<Option<Renderer>>>, sample_rate: u32) -> Self {
+        OfflineAudio {
+            follow_time: true,
+            renderer,
+            frame_counter: FrameCounter::new(sample_rate),
+            frames: vec![],
+        }
+    }
+
+    /// Sample rate of the rendered frames
+    pub fn sample_rate(&self) -> u32 {
+        self.frame_counter.sample_rate
+    }
+
+    /// Render the audio for the given duration
+    pub fn render(&mut self, duration: Duration) {
+        let count = self.frame_counter.frames(duration);
+        self.render_frames(count);
+    }
+
+    /// Render the given number of frames
+    pub fn render_frames(&mut self, count: usize) {
+        let mut renderer = self.renderer.lock();
+        let Some(renderer) = renderer.as_mut() else {
+            return;
+        };
+        self.frames.reserve(count);
+        let mut rendered = 0;
+        while rendered < count {
+            renderer.on_start_processing();
+            let block = BLOCK_SIZE.min(count - rendered);
+            self.frames.extend((0..block).map(|_| renderer.process()));
+            rendered += block;
+        }
+    }
+
+    /// All frames rendered so far
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Take the frames rendered so far, for example to stream them to an encoder
+    pub fn take_frames(&mut self) -> Vec<Frame> {
+        std::mem::take(&mut self.frames)
+    }
+
+    /// Write the frames rendered so far as stereo WAV with 32 bit float samples
+    pub fn write_wav(&self, mut writer: impl Write) -> io::Result<()> {
+        const CHANNELS: u16 = 2;
+        const BITS_PER_SAMPLE: u16 = 32;
+        const IEEE_FLOAT: u16 = 3;
+        let sample_rate = self.sample_rate();
+        let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+        let data_size = u32::try_from(self.frames.len() * block_align as usize)
+            .ok()
+            .filter(|size| *size <= u32::MAX - 36)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Too long for WAV"))?;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_size).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16_u32.to_le_bytes())?;
+        writer.write_all(&IEEE_FLOAT.to_le_bytes())?;
+        writer.write_all(&CHANNELS.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        for frame in &self.frames {
+            writer.write_all(&frame.left.to_le_bytes())?;
+            writer.write_all(&frame.right.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+}
+
+/// Converts durations to numbers of frames, without drifting over many conversions
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrameCounter {
+    sample_rate: u32,
+    /// Part of a frame left from earlier conversions, in nanoseconds times the sample rate
+    remainder: u128,
+}
+
+impl FrameCounter {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        FrameCounter {
+            sample_rate,
+            remainder: 0,
+        }
+    }
+
+    /// Number of frames to render for the given duration
+    pub(crate) fn frames(&mut self, duration: Duration) -> usize {
+        let scaled = duration.as_nanos() * self.sample_rate as u128 + self.remainder;
+        self.remainder = scaled % NANOS_PER_SECOND;
+
+        (scaled / NANOS_PER_SECOND) as usize
+    }
+}
+
+fn render_frame_duration(mut offline_audio: ResMut<OfflineAudio>, time: Res<Time>) {
+    if offline_audio.follow_time {
+        offline_audio.render(time.delta());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Audio, AudioControl, AudioSettings, AudioSource};
+    use bevy::asset::{AssetPlugin, Assets};
+    use bevy::prelude::*;
+    use kira::sound::static_sound::StaticSoundData;
+
+    #[test]
+    fn played_sounds_are_rendered_on_demand() {
+        let mut app = App::new();
+        app.insert_resource(AudioSettings {
+            sample_rate: Some(44_100),
+            ..default()
+        })
+        .add_plugins((MinimalPlugins, AssetPlugin::default(), OfflineAudioPlugin));
+        app.world_mut().resource_mut::<OfflineAudio>().follow_time = false;
+        let source = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+            });
+        app.world().resource::<Audio>().play(source);
+        app.update();
+
+        let mut offline_audio = app.world_mut().resource_mut::<OfflineAudio>();
+        assert_eq!(offline_audio.sample_rate(), 44_100);
+        assert!(offline_audio.frames().is_empty());
+        offline_audio.render(Duration::from_millis(50));
+        offline_audio.render(Duration::from_millis(50));
+        assert_eq!(offline_audio.frames().len(), 4410);
+        assert!(offline_audio
+            .frames()
+            .iter()
+            .any(|frame| frame.left != 0. || frame.right != 0.));
+    }
+
+    #[test]
+    fn wav_contains_all_rendered_frames() {
+        let mut offline_audio = OfflineAudio::new(Arc::default(), 48_000);
+        offline_audio.frames = vec![Frame::new(0.5, -0.5); 3];
+        let mut wav = vec![];
+        offline_audio.write_wav(&mut wav).unwrap();
+
+        assert_eq!(wav.len(), 44 + 3 * 8);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[4..8], &(36_u32 + 24).to_le_bytes());
+        assert_eq!(&wav[24..28], &48_000_u32.to_le_bytes());
+        assert_eq!(&wav[40..44], &24_u32.to_le_bytes());
+        assert_eq!(&wav[44..48], &0.5_f32.to_le_bytes());
+        assert_eq!(&wav[48..52], &(-0.5_f32).to_le_bytes());
+    }
+}
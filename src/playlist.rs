@@ -0,0 +1,295 @@
+//! Play a list of tracks one after another
+
+use crate::audio::{AudioEasing, AudioTween, PlaybackState};
+use crate::channel::typed::AudioChannel;
+use crate::channel::AudioControl;
+use crate::instance::AudioInstance;
+use crate::{AudioSource, MainTrack};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::{Res, ResMut, Resource};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// What an [`AudioPlaylist`] does after its last track
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlaylistRepeat {
+    /// Stop after the last track
+    #[default]
+    Off,
+    /// Start over with the first track
+    All,
+    /// Repeat the current track forever
+    One,
+}
+
+/// Resource playing a list of tracks on the channel [`AudioChannel<T>`]
+///
+/// Tracks are played one after another. The next track fades in while the current one fades
+/// out, starting the crossfade duration before the current track ends. Tracks of looping
+/// sources never end on their own; use [`skip`](Self::skip) to advance.
+///
+/// The playlist plays while the resource exists.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// #[derive(Resource)]
+/// struct Music;
+///
+/// fn start_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let mut playlist = AudioPlaylist::<Music>::new([
+///         asset_server.load("music/one.ogg"),
+///         asset_server.load("music/two.ogg"),
+///         asset_server.load("music/three.ogg"),
+///     ])
+///     .with_crossfade(AudioTween::equal_power(Duration::from_secs(3)))
+///     .with_repeat(PlaylistRepeat::All)
+///     .shuffled();
+///     playlist.play();
+///     commands.insert_resource(playlist);
+/// }
+/// ```
+#[derive(Resource)]
+pub struct AudioPlaylist<T = MainTrack> {
+    tracks: Vec<Handle<AudioSource>>,
+    /// Indices into `tracks` in the order they are played
+    order: Vec<usize>,
+    /// Index into `order` of the current track
+    position: usize,
+    crossfade: AudioTween,
+    repeat: PlaylistRepeat,
+    shuffle: bool,
+    playing: bool,
+    skip: bool,
+    current: Option<Handle<AudioInstance>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> AudioPlaylist<T> {
+    /// Create a stopped playlist with the given tracks
+    pub fn new(tracks: impl IntoIterator<Item = Handle<AudioSource>>) -> Self {
+        let tracks: Vec<_> = tracks.into_iter().collect();
+        AudioPlaylist {
+            order: (0..tracks.len()).collect(),
+            tracks,
+            position: 0,
+            crossfade: AudioTween::default().with_easing(AudioEasing::EqualPower),
+            repeat: PlaylistRepeat::default(),
+            shuffle: false,
+            playing: false,
+            skip: false,
+            current: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the tween used to fade between tracks
+    ///
+    /// The default is a short [equal power](AudioEasing::EqualPower) tween.
+    pub fn with_crossfade(mut self, crossfade: AudioTween) -> Self {
+        self.crossfade = crossfade;
+
+        self
+    }
+
+    /// Set what happens after the last track
+    pub fn with_repeat(mut self, repeat: PlaylistRepeat) -> Self {
+        self.repeat = repeat;
+
+        self
+    }
+
+    /// Play the tracks in random order
+    pub fn shuffled(mut self) -> Self {
+        self.set_shuffle(true);
+
+        self
+    }
+
+    /// Set what happens after the last track
+    pub fn set_repeat(&mut self, repeat: PlaylistRepeat) {
+        self.repeat = repeat;
+    }
+
+    /// Play the tracks in random or in the given order
+    ///
+    /// The order of the remaining tracks changes right away. With [`PlaylistRepeat::All`],
+    /// shuffled tracks are shuffled again before starting over.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+        let current = self.order.get(self.position).copied();
+        self.order = (0..self.tracks.len()).collect();
+        if shuffle {
+            fastrand::shuffle(&mut self.order);
+        }
+        // Keep the current track current
+        if let Some(current) = current {
+            self.position = self
+                .order
+                .iter()
+                .position(|&track| track == current)
+                .unwrap();
+        }
+    }
+
+    /// Start playing the current track
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Fade out the current track and stop the playlist
+    ///
+    /// Calling [`play`](Self::play) afterward starts the current track from the beginning.
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Crossfade to the next track
+    pub fn skip(&mut self) {
+        self.skip = true;
+    }
+
+    /// Returns `true` if the playlist is playing
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The track that is playing or plays next
+    pub fn current_track(&self) -> Option<&Handle<AudioSource>> {
+        self.order
+            .get(self.position)
+            .map(|&track| &self.tracks[track])
+    }
+
+    /// The sound of the current track, if it is playing
+    pub fn current_instance(&self) -> Option<&Handle<AudioInstance>> {
+        self.current.as_ref()
+    }
+
+    /// All tracks of the playlist in their original order
+    pub fn tracks(&self) -> &[Handle<AudioSource>] {
+        &self.tracks
+    }
+
+    /// Move to the next track
+    ///
+    /// Returns `false` if the playlist ended.
+    fn advance(&mut self) -> bool {
+        if self.repeat == PlaylistRepeat::One && !self.skip {
+            return true;
+        }
+        self.position += 1;
+        if self.position < self.order.len() {
+            return true;
+        }
+        self.position = 0;
+        if self.shuffle {
+            fastrand::shuffle(&mut self.order);
+        }
+
+        self.repeat != PlaylistRepeat::Off
+    }
+
+    /// Decide whether the current track needs to fade out for the next one
+    fn track_ends(&self, state: PlaybackState, source: Option<&AudioSource>) -> bool {
+        match state {
+            PlaybackState::Queued => false,
+            PlaybackState::Stopped => true,
+            _ => source
+                .and_then(|source| state.time_remaining(source))
+                .map_or(false, |remaining| {
+                    remaining <= self.crossfade.duration().max(Duration::from_millis(1))
+                }),
+        }
+    }
+}
+
+pub(crate) fn run_audio_playlist<T: Resource>(
+    mut playlist: ResMut<AudioPlaylist<T>>,
+    channel: Res<AudioChannel<T>>,
+    audio_sources: Res<Assets<AudioSource>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    if !playlist.playing {
+        if let Some(current) = playlist.current.take() {
+            if let Some(instance) = audio_instances.get_mut(&current) {
+                instance.stop(playlist.crossfade.clone());
+            }
+        }
+        playlist.skip = false;
+        return;
+    }
+    if let Some(current) = playlist.current.clone() {
+        let source = playlist
+            .current_track()
+            .and_then(|track| audio_sources.get(track));
+        if !playlist.skip && !playlist.track_ends(channel.state(&current), source) {
+            return;
+        }
+        if let Some(instance) = audio_instances.get_mut(&current) {
+            instance.stop(playlist.crossfade.clone());
+        }
+        playlist.current = None;
+        let next = playlist.advance();
+        playlist.skip = false;
+        if !next {
+            playlist.playing = false;
+            return;
+        }
+    }
+    let Some(track) = playlist.current_track().cloned() else {
+        playlist.playing = false;
+        return;
+    };
+    let crossfade = playlist.crossfade.clone();
+    playlist.current = Some(channel.play(track).fade_in(crossfade).handle());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_tracks(repeat: PlaylistRepeat) -> AudioPlaylist {
+        AudioPlaylist::new([Handle::default(), Handle::default(), Handle::default()])
+            .with_repeat(repeat)
+    }
+
+    #[test]
+    fn stops_after_last_track_without_repeat() {
+        let mut playlist = three_tracks(PlaylistRepeat::Off);
+
+        assert!(playlist.advance());
+        assert!(playlist.advance());
+        assert!(!playlist.advance());
+    }
+
+    #[test]
+    fn repeat_modes_keep_playing() {
+        let mut playlist = three_tracks(PlaylistRepeat::All);
+        for _ in 0..3 {
+            assert!(playlist.advance());
+        }
+        assert_eq!(playlist.position, 0);
+
+        let mut playlist = three_tracks(PlaylistRepeat::One);
+        assert!(playlist.advance());
+        assert_eq!(playlist.position, 0);
+        playlist.skip();
+        assert!(playlist.advance());
+        assert_eq!(playlist.position, 1);
+    }
+
+    #[test]
+    fn shuffling_keeps_the_current_track() {
+        let mut playlist = three_tracks(PlaylistRepeat::All);
+        playlist.advance();
+        let current = playlist.order[playlist.position];
+
+        playlist.set_shuffle(true);
+        assert_eq!(playlist.order[playlist.position], current);
+        let mut order = playlist.order.clone();
+        order.sort();
+        assert_eq!(order, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::{Local, Res, Resource};
+use bevy::time::Time;
+use std::time::Duration;
+
+/// Resource to trade audio fidelity for CPU time and battery at run-time
+///
+/// The default is full quality. Switch to [`AudioQuality::low_power`] when the OS reports
+/// low-power mode, and back when it ends.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn on_low_power_mode(mut quality: ResMut<AudioQuality>) {
+///     *quality = AudioQuality::low_power();
+/// }
+/// ```
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AudioQuality {
+    /// Minimum time between updates of spatial audio, Doppler, and distance reverb
+    ///
+    /// With [`Duration::ZERO`] they update every frame.
+    pub spatial_update_interval: Duration,
+    /// Minimum time between checks for [`ChannelActivity`](crate::ChannelActivity)
+    ///
+    /// With [`Duration::ZERO`] channels are checked every frame.
+    pub activity_update_interval: Duration,
+    /// Apply the Doppler effect to emitters with a [`Doppler`](crate::prelude::Doppler) component
+    ///
+    /// Turning it off resets the playback rate of their sounds.
+    pub doppler: bool,
+}
+
+impl Default for AudioQuality {
+    fn default() -> Self {
+        AudioQuality {
+            spatial_update_interval: Duration::ZERO,
+            activity_update_interval: Duration::ZERO,
+            doppler: true,
+        }
+    }
+}
+
+impl AudioQuality {
+    /// Settings for devices running on battery
+    ///
+    /// Spatial audio updates ten times a second, channel activity four times a second,
+    /// and the Doppler effect is off.
+    pub fn low_power() -> Self {
+        AudioQuality {
+            spatial_update_interval: Duration::from_millis(100),
+            activity_update_interval: Duration::from_millis(250),
+            doppler: false,
+        }
+    }
+}
+
+/// Returns `true` once at least `interval` passed since it last returned `true`
+fn interval_elapsed(interval: Duration, delta: Duration, elapsed: &mut Duration) -> bool {
+    *elapsed += delta;
+    if *elapsed < interval {
+        return false;
+    }
+    *elapsed = Duration::ZERO;
+
+    true
+}
+
+pub(crate) fn spatial_update_due(
+    quality: Res<AudioQuality>,
+    time: Res<Time>,
+    mut elapsed: Local<Duration>,
+) -> bool {
+    interval_elapsed(quality.spatial_update_interval, time.delta(), &mut elapsed)
+}
+
+pub(crate) fn activity_update_due(
+    quality: Res<AudioQuality>,
+    time: Res<Time>,
+    mut elapsed: Local<Duration>,
+) -> bool {
+    interval_elapsed(quality.activity_update_interval, time.delta(), &mut elapsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn updates_once_per_interval() {
+        let interval = Duration::from_millis(100);
+        let frame = Duration::from_millis(16);
+        let mut elapsed = Duration::ZERO;
+
+        let updates: Vec<bool> = (0..14)
+            .map(|_| interval_elapsed(interval, frame, &mut elapsed))
+            .collect();
+
+        assert_eq!(updates.iter().filter(|update| **update).count(), 2);
+        assert!(updates[6]);
+        assert!(updates[13]);
+    }
+
+    #[test]
+    fn zero_interval_updates_every_frame() {
+        let mut elapsed = Duration::ZERO;
+
+        assert!((0..5).all(|_| interval_elapsed(Duration::ZERO, Duration::ZERO, &mut elapsed)));
+    }
+}
@@ -0,0 +1,361 @@
+//! Limit and control when sounds of a category can start
+
+use crate::audio::PlayCondition;
+use bevy::ecs::system::Resource;
+use bevy::log::warn;
+use bevy::utils::{HashMap, Instant};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Resource to limit how many sounds of a category can start per second
+///
+/// Assign a category to a sound with [`with_category`](crate::PlayAudioCommand::with_category).
+/// Play commands exceeding the limit of their category are dropped before they reach the audio
+/// backend. Their instance handles report [`PlaybackState::Stopped`](crate::PlaybackState::Stopped)
+/// and an [`AudioInstanceStopped`](crate::AudioInstanceStopped) event is sent for them.
+/// Sounds without a category, or with a category that has neither a limit
+/// nor a rule, are never dropped.
+///
+/// Categories can additionally get a [`CategoryRule`] to control their triggering logic in data,
+/// for example from a loaded config file. Rules can require named conditions, which are registered
+/// on this resource by the game.
+///
+/// If this resource is not added to the ECS, no sounds are rate limited.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// fn setup(mut commands: Commands) {
+///     let in_combat = Arc::new(AtomicBool::new(false));
+///     commands.insert_resource(
+///         SoundRateLimits::default()
+///             .with_limit("ui", 10)
+///             .with_limit("impact", 20)
+///             .with_rule(
+///                 "battle_cry",
+///                 CategoryRule::default()
+///                     .with_probability(0.3)
+///                     .with_min_interval(Duration::from_secs(5))
+///                     .with_max_simultaneous(1)
+///                     .requires("in_combat"),
+///             )
+///             .with_condition("in_combat", move || in_combat.load(Ordering::Relaxed)),
+///     );
+/// }
+///
+/// fn on_hit(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     audio
+///         .play(asset_server.load("sounds/hit.ogg"))
+///         .with_category("impact");
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct SoundRateLimits {
+    limits: HashMap<String, u32>,
+    started: HashMap<String, VecDeque<Instant>>,
+    rules: HashMap<String, CategoryRule>,
+    conditions: HashMap<String, PlayCondition>,
+    last_started: HashMap<String, Instant>,
+}
+
+/// Rules deciding whether a sound of a category starts
+///
+/// Register rules for a category with [`SoundRateLimits::with_rule`].
+/// Sounds failing any of the rules are dropped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CategoryRule {
+    probability: f32,
+    min_interval: Option<Duration>,
+    max_simultaneous: Option<usize>,
+    exclusivity: Option<Exclusivity>,
+    conditions: Vec<String>,
+}
+
+/// What happens to a new sound of an exclusive category while another one is playing
+///
+/// Set the policy of a category with [`CategoryRule::exclusive`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exclusivity {
+    /// Stop the playing sounds of the category and start the new one
+    Restart,
+    /// Drop the new sound
+    Ignore,
+    /// Start the new sound once the playing one finished
+    ///
+    /// Waiting sounds keep the order they were played in.
+    Queue,
+}
+
+impl Default for CategoryRule {
+    fn default() -> Self {
+        CategoryRule {
+            probability: 1.,
+            min_interval: None,
+            max_simultaneous: None,
+            exclusivity: None,
+            conditions: vec![],
+        }
+    }
+}
+
+impl CategoryRule {
+    /// Set the chance of a sound to start from `0.0` (never) to `1.0` (always)
+    ///
+    /// The default is `1.0`.
+    pub fn with_probability(mut self, probability: f32) -> Self {
+        self.probability = probability;
+
+        self
+    }
+
+    /// Set the minimal time between the starts of two sounds of the category
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = Some(min_interval);
+
+        self
+    }
+
+    /// Set the maximum number of sounds of the category playing at the same time
+    pub fn with_max_simultaneous(mut self, max_simultaneous: usize) -> Self {
+        self.max_simultaneous = Some(max_simultaneous);
+
+        self
+    }
+
+    /// Never play two sounds of the category at the same time
+    ///
+    /// The policy decides what happens to a sound that is played while another one of the
+    /// category is still playing. Useful for dialogue lines or alarms that must not overlap
+    /// with themselves.
+    pub fn exclusive(mut self, policy: Exclusivity) -> Self {
+        self.exclusivity = Some(policy);
+
+        self
+    }
+
+    /// Only start sounds while the named condition is met
+    ///
+    /// Register the condition with [`SoundRateLimits::with_condition`]. Conditions that are not
+    /// registered are never met.
+    pub fn requires(mut self, condition: impl Into<String>) -> Self {
+        self.conditions.push(condition.into());
+
+        self
+    }
+}
+
+impl SoundRateLimits {
+    /// Limit the given category to at most `max_per_second` started sounds per second
+    pub fn with_limit(mut self, category: impl Into<String>, max_per_second: u32) -> Self {
+        self.set_limit(category, max_per_second);
+
+        self
+    }
+
+    /// Limit the given category to at most `max_per_second` started sounds per second
+    ///
+    /// This overwrites any previous limit of the category.
+    pub fn set_limit(&mut self, category: impl Into<String>, max_per_second: u32) {
+        self.limits.insert(category.into(), max_per_second);
+    }
+
+    /// Remove the limit of the given category
+    pub fn remove_limit(&mut self, category: &str) {
+        self.limits.remove(category);
+        self.started.remove(category);
+    }
+
+    /// Get the limit of the given category in started sounds per second
+    pub fn limit(&self, category: &str) -> Option<u32> {
+        self.limits.get(category).copied()
+    }
+
+    /// Set the rule of the given category
+    pub fn with_rule(mut self, category: impl Into<String>, rule: CategoryRule) -> Self {
+        self.set_rule(category, rule);
+
+        self
+    }
+
+    /// Set the rule of the given category
+    ///
+    /// This overwrites any previous rule of the category.
+    pub fn set_rule(&mut self, category: impl Into<String>, rule: CategoryRule) {
+        self.rules.insert(category.into(), rule);
+    }
+
+    /// Remove the rule of the given category
+    pub fn remove_rule(&mut self, category: &str) {
+        self.rules.remove(category);
+        self.last_started.remove(category);
+    }
+
+    /// Get the rule of the given category
+    pub fn rule(&self, category: &str) -> Option<&CategoryRule> {
+        self.rules.get(category)
+    }
+
+    /// Register a named condition that rules can require
+    pub fn with_condition(
+        mut self,
+        name: impl Into<String>,
+        condition: impl Into<PlayCondition>,
+    ) -> Self {
+        self.set_condition(name, condition);
+
+        self
+    }
+
+    /// Register a named condition that rules can require
+    ///
+    /// This overwrites any previous condition with the same name.
+    pub fn set_condition(&mut self, name: impl Into<String>, condition: impl Into<PlayCondition>) {
+        self.conditions.insert(name.into(), condition.into());
+    }
+
+    /// Remove the named condition
+    pub fn remove_condition(&mut self, name: &str) {
+        self.conditions.remove(name);
+    }
+
+    /// Does the given category need the number of its playing sounds to decide whether a sound may start?
+    pub(crate) fn counts_active(&self, category: &str) -> bool {
+        self.rules.get(category).map_or(false, |rule| {
+            rule.max_simultaneous.is_some() || rule.exclusivity.is_some()
+        })
+    }
+
+    /// The exclusivity policy of the given category
+    pub(crate) fn exclusivity(&self, category: &str) -> Option<Exclusivity> {
+        self.rules.get(category).and_then(|rule| rule.exclusivity)
+    }
+
+    /// Register a sound start at `now` and return whether the sound may start
+    ///
+    /// `active` is the number of playing sounds of the category and `roll` a random number
+    /// between `0.0` and `1.0` to check the probability of the category against.
+    pub(crate) fn try_start(
+        &mut self,
+        category: &str,
+        active: usize,
+        now: Instant,
+        roll: f32,
+    ) -> bool {
+        if let Some(rule) = self.rules.get(category) {
+            for name in &rule.conditions {
+                match self.conditions.get(name) {
+                    Some(condition) if condition.is_met() => (),
+                    Some(_) => return false,
+                    None => {
+                        warn!(
+                            "Sound category '{category}' requires the unknown condition '{name}'"
+                        );
+                        return false;
+                    }
+                }
+            }
+            if let (Some(min_interval), Some(last_started)) =
+                (rule.min_interval, self.last_started.get(category))
+            {
+                if now.duration_since(*last_started) < min_interval {
+                    return false;
+                }
+            }
+            if rule
+                .max_simultaneous
+                .map_or(false, |max_simultaneous| active >= max_simultaneous)
+            {
+                return false;
+            }
+            if roll >= rule.probability {
+                return false;
+            }
+        }
+        if let Some(&limit) = self.limits.get(category) {
+            if !self.started.contains_key(category) {
+                self.started
+                    .insert(category.to_owned(), VecDeque::default());
+            }
+            let started = self.started.get_mut(category).unwrap();
+            while let Some(start) = started.front() {
+                if now.duration_since(*start) < Duration::from_secs(1) {
+                    break;
+                }
+                started.pop_front();
+            }
+            if started.len() >= limit as usize {
+                return false;
+            }
+            started.push_back(now);
+        }
+        if self.rules.contains_key(category) {
+            match self.last_started.get_mut(category) {
+                Some(last_started) => *last_started = now,
+                None => {
+                    self.last_started.insert(category.to_owned(), now);
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn drops_sounds_over_the_limit() {
+        let mut limits = SoundRateLimits::default().with_limit("ui", 2);
+        let now = Instant::now();
+
+        assert!(limits.try_start("ui", 0, now, 0.));
+        assert!(limits.try_start("ui", 0, now, 0.));
+        assert!(!limits.try_start("ui", 0, now + Duration::from_millis(500), 0.));
+        assert!(limits.try_start("ui", 0, now + Duration::from_secs(1), 0.));
+    }
+
+    #[test]
+    fn sounds_without_limit_always_start() {
+        let mut limits = SoundRateLimits::default().with_limit("ui", 0);
+        let now = Instant::now();
+
+        assert!(!limits.try_start("ui", 0, now, 0.));
+        for _ in 0..100 {
+            assert!(limits.try_start("impact", 100, now, 0.99));
+        }
+    }
+
+    #[test]
+    fn rules_control_when_sounds_start() {
+        let in_combat = Arc::new(AtomicBool::new(false));
+        let condition = in_combat.clone();
+        let mut limits = SoundRateLimits::default()
+            .with_rule(
+                "battle_cry",
+                CategoryRule::default()
+                    .with_probability(0.5)
+                    .with_min_interval(Duration::from_secs(2))
+                    .with_max_simultaneous(1)
+                    .requires("in_combat"),
+            )
+            .with_rule("taunt", CategoryRule::default().requires("unknown"))
+            .with_condition("in_combat", move || condition.load(Ordering::Relaxed));
+        let now = Instant::now();
+
+        assert!(!limits.try_start("battle_cry", 0, now, 0.));
+        in_combat.store(true, Ordering::Relaxed);
+        assert!(!limits.try_start("battle_cry", 0, now, 0.7));
+        assert!(!limits.try_start("battle_cry", 1, now, 0.));
+        assert!(limits.try_start("battle_cry", 0, now, 0.2));
+        assert!(!limits.try_start("battle_cry", 0, now + Duration::from_secs(1), 0.));
+        assert!(limits.try_start("battle_cry", 0, now + Duration::from_secs(2), 0.));
+        assert!(!limits.try_start("taunt", 0, now, 0.));
+    }
+}
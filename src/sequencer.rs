@@ -0,0 +1,380 @@
+//! Play patterns of sounds in sync with a clock
+
+use crate::channel::typed::AudioChannel;
+use crate::channel::AudioControl;
+use crate::clock::AudioClock;
+use crate::transport::Transport;
+use crate::{AudioSource, MainTrack};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::utils::HashMap;
+use kira::Volume;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// Resource playing patterns of sounds on the channel [`AudioChannel<T>`] driven by a clock
+///
+/// Every step of the sequencer lasts [`ticks_per_step`](Self::with_ticks_per_step) ticks of the
+/// clock. Sounds are scheduled one step ahead with
+/// [`start_at_tick`](crate::PlayAudioCommand::start_at_tick), so they start on the exact tick.
+/// Patterns can have different lengths and repeat independently of each other.
+///
+/// The sequencer runs while the resource exists and its clock is ticking.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Drums;
+///
+/// fn start_drums(
+///     mut commands: Commands,
+///     drums: Res<AudioChannel<Drums>>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     let clock = drums.add_clock(ClockSpeed::TicksPerMinute(480.));
+///     let kick = asset_server.load("sounds/kick.ogg");
+///     let hat = asset_server.load("sounds/hat.ogg");
+///     commands.insert_resource(
+///         StepSequencer::<Drums>::new(clock)
+///             .with_ticks_per_step(2)
+///             .with_swing(0.5)
+///             .with_pattern("kick", StepPattern::new(4).with_step(0, SequencerStep::new(kick)))
+///             .with_pattern(
+///                 "hat",
+///                 StepPattern::new(2).with_step(1, SequencerStep::new(hat).with_probability(0.8)),
+///             ),
+///     );
+/// }
+/// ```
+#[derive(Resource)]
+pub struct StepSequencer<T = MainTrack> {
+    clock: Handle<AudioClock>,
+    ticks_per_step: u64,
+    swing: f64,
+    patterns: HashMap<String, StepPattern>,
+    next_step: Option<u64>,
+    _marker: PhantomData<T>,
+}
+
+/// A repeating sequence of steps of a [`StepSequencer`]
+#[derive(Clone, Debug, Default)]
+pub struct StepPattern {
+    steps: Vec<Option<SequencerStep>>,
+}
+
+/// A sound played on a step of a [`StepPattern`]
+#[derive(Clone, Debug)]
+pub struct SequencerStep {
+    source: Handle<AudioSource>,
+    probability: f32,
+    volume: Option<Volume>,
+    category: Option<Cow<'static, str>>,
+}
+
+impl<T> StepSequencer<T> {
+    /// Create a sequencer without patterns driven by the given clock
+    ///
+    /// Create a clock with [`add_clock`](crate::AudioControl::add_clock).
+    pub fn new(clock: Handle<AudioClock>) -> Self {
+        StepSequencer {
+            clock,
+            ticks_per_step: 1,
+            swing: 0.,
+            patterns: HashMap::default(),
+            next_step: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a sequencer without patterns driven by the clock of the given transport
+    ///
+    /// The sequencer plays the given number of steps per beat of the transport. If the ticks
+    /// of a beat cannot be divided evenly, steps are rounded down to whole ticks.
+    pub fn from_transport(transport: &Transport, steps_per_beat: u32) -> Self {
+        Self::new(transport.clock().clone())
+            .with_ticks_per_step((transport.ticks_per_beat() / steps_per_beat.max(1)) as u64)
+    }
+
+    /// Set the number of clock ticks per step
+    ///
+    /// The default is one tick per step. More ticks per step allow for finer swing.
+    pub fn with_ticks_per_step(mut self, ticks_per_step: u64) -> Self {
+        self.ticks_per_step = ticks_per_step.max(1);
+
+        self
+    }
+
+    /// Delay every second step by the given fraction of a step
+    ///
+    /// See [`set_swing`](Self::set_swing).
+    pub fn with_swing(mut self, swing: f64) -> Self {
+        self.set_swing(swing);
+
+        self
+    }
+
+    /// Delay every second step by the given fraction of a step
+    ///
+    /// The swing is between `0.0` (no swing) and `1.0` and rounded to whole clock ticks.
+    pub fn set_swing(&mut self, swing: f64) {
+        self.swing = swing.clamp(0., 1.);
+    }
+
+    /// Add a pattern with the given name
+    pub fn with_pattern(mut self, name: impl Into<String>, pattern: StepPattern) -> Self {
+        self.set_pattern(name, pattern);
+
+        self
+    }
+
+    /// Add a pattern with the given name
+    ///
+    /// This overwrites any previous pattern with the same name. The new pattern continues at the
+    /// current step of the sequencer.
+    pub fn set_pattern(&mut self, name: impl Into<String>, pattern: StepPattern) {
+        self.patterns.insert(name.into(), pattern);
+    }
+
+    /// Remove the pattern with the given name
+    pub fn remove_pattern(&mut self, name: &str) -> Option<StepPattern> {
+        self.patterns.remove(name)
+    }
+
+    /// Get the pattern with the given name
+    pub fn pattern(&self, name: &str) -> Option<&StepPattern> {
+        self.patterns.get(name)
+    }
+
+    /// Get the pattern with the given name mutably
+    pub fn pattern_mut(&mut self, name: &str) -> Option<&mut StepPattern> {
+        self.patterns.get_mut(name)
+    }
+
+    /// The clock driving the sequencer
+    pub fn clock(&self) -> &Handle<AudioClock> {
+        &self.clock
+    }
+
+    fn step_tick(&self, step: u64) -> u64 {
+        let swing = if step % 2 == 1 {
+            (self.swing * self.ticks_per_step as f64).round() as u64
+        } else {
+            0
+        };
+
+        step * self.ticks_per_step + swing
+    }
+
+    /// Collect all steps starting within the next step after the given clock tick
+    ///
+    /// `roll` returns random numbers between `0.0` and `1.0` to check the probability of steps.
+    pub(crate) fn due_steps(
+        &mut self,
+        ticks: u64,
+        mut roll: impl FnMut() -> f32,
+    ) -> Vec<(SequencerStep, u64)> {
+        let horizon = ticks + self.ticks_per_step;
+        // The clock was stopped or restarted since the last steps were scheduled
+        if self.next_step.map_or(true, |next_step| {
+            next_step * self.ticks_per_step > horizon + self.ticks_per_step
+        }) {
+            self.next_step = Some(ticks / self.ticks_per_step + 1);
+        }
+        let mut step = self.next_step.unwrap();
+        let mut due = vec![];
+        while step * self.ticks_per_step <= horizon {
+            let tick = self.step_tick(step);
+            if tick >= ticks {
+                for pattern in self.patterns.values() {
+                    if let Some(sequencer_step) = pattern.get(step) {
+                        if roll() < sequencer_step.probability {
+                            due.push((sequencer_step.clone(), tick));
+                        }
+                    }
+                }
+            }
+            step += 1;
+        }
+        self.next_step = Some(step);
+
+        due
+    }
+}
+
+impl StepPattern {
+    /// Create an empty pattern with the given number of steps
+    pub fn new(length: usize) -> Self {
+        StepPattern {
+            steps: vec![None; length],
+        }
+    }
+
+    /// Play the given step at the given index
+    pub fn with_step(mut self, index: usize, step: SequencerStep) -> Self {
+        self.set_step(index, step);
+
+        self
+    }
+
+    /// Play the given step at the given index
+    ///
+    /// Indices outside of the pattern are ignored.
+    pub fn set_step(&mut self, index: usize, step: SequencerStep) {
+        if let Some(slot) = self.steps.get_mut(index) {
+            *slot = Some(step);
+        }
+    }
+
+    /// Remove the step at the given index
+    pub fn clear_step(&mut self, index: usize) {
+        if let Some(slot) = self.steps.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    /// Number of steps in the pattern
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the pattern has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    fn get(&self, step: u64) -> Option<&SequencerStep> {
+        if self.steps.is_empty() {
+            return None;
+        }
+        self.steps[(step % self.steps.len() as u64) as usize].as_ref()
+    }
+}
+
+impl SequencerStep {
+    /// Play the given audio source on this step
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        SequencerStep {
+            source,
+            probability: 1.,
+            volume: None,
+            category: None,
+        }
+    }
+
+    /// Set the chance of the step to play from `0.0` (never) to `1.0` (always)
+    ///
+    /// The default is `1.0`.
+    pub fn with_probability(mut self, probability: f32) -> Self {
+        self.probability = probability;
+
+        self
+    }
+
+    /// Set the volume of the sound
+    pub fn with_volume(mut self, volume: impl Into<Volume>) -> Self {
+        self.volume = Some(volume.into());
+
+        self
+    }
+
+    /// Assign the sound to a category
+    ///
+    /// See [`SoundRateLimits`](crate::SoundRateLimits) for rules per category.
+    pub fn with_category(mut self, category: impl Into<Cow<'static, str>>) -> Self {
+        self.category = Some(category.into());
+
+        self
+    }
+}
+
+pub(crate) fn run_step_sequencer<T: Resource>(
+    mut sequencer: ResMut<StepSequencer<T>>,
+    clocks: Res<Assets<AudioClock>>,
+    channel: Res<AudioChannel<T>>,
+) {
+    let Some(clock) = clocks.get(&sequencer.clock) else {
+        return;
+    };
+    if !clock.is_ticking() {
+        return;
+    }
+    let clock_handle = sequencer.clock.clone();
+    for (step, tick) in sequencer.due_steps(clock.ticks(), fastrand::f32) {
+        let mut command = channel.play(step.source);
+        command.start_at_tick(&clock_handle, tick);
+        if let Some(volume) = step.volume {
+            command.with_volume(volume);
+        }
+        if let Some(category) = step.category {
+            command.with_category(category);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequencer() -> StepSequencer {
+        StepSequencer::new(Handle::default())
+            .with_ticks_per_step(4)
+            .with_pattern(
+                "beat",
+                StepPattern::new(2)
+                    .with_step(0, SequencerStep::new(Handle::default()))
+                    .with_step(
+                        1,
+                        SequencerStep::new(Handle::default()).with_probability(0.5),
+                    ),
+            )
+    }
+
+    #[test]
+    fn schedules_steps_one_step_ahead() {
+        let mut sequencer = sequencer();
+
+        let ticks: Vec<u64> = sequencer
+            .due_steps(0, || 0.)
+            .into_iter()
+            .map(|(_, tick)| tick)
+            .collect();
+        assert_eq!(ticks, vec![4]);
+        assert!(sequencer.due_steps(1, || 0.).is_empty());
+        let ticks: Vec<u64> = sequencer
+            .due_steps(4, || 0.)
+            .into_iter()
+            .map(|(_, tick)| tick)
+            .collect();
+        assert_eq!(ticks, vec![8]);
+    }
+
+    #[test]
+    fn swing_delays_every_second_step() {
+        let mut sequencer = sequencer().with_swing(0.5);
+
+        assert_eq!(sequencer.due_steps(0, || 0.)[0].1, 6);
+        assert_eq!(sequencer.due_steps(4, || 0.)[0].1, 8);
+    }
+
+    #[test]
+    fn steps_play_with_their_probability() {
+        let mut sequencer = sequencer();
+
+        assert!(sequencer.due_steps(0, || 0.7).is_empty());
+        assert_eq!(sequencer.due_steps(4, || 0.7).len(), 1);
+    }
+
+    #[test]
+    fn restarted_clock_restarts_the_sequence() {
+        let mut sequencer = sequencer();
+
+        sequencer.due_steps(40, || 0.);
+        let ticks: Vec<u64> = sequencer
+            .due_steps(0, || 0.)
+            .into_iter()
+            .map(|(_, tick)| tick)
+            .collect();
+        assert_eq!(ticks, vec![4]);
+    }
+}
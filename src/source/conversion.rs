@@ -0,0 +1,84 @@
+use crate::AudioSource;
+use bevy::asset::{AssetId, Assets, Handle};
+use bevy::ecs::system::{ResMut, Resource};
+use bevy::tasks::AsyncComputeTaskPool;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Resource to convert audio sources off the main thread
+///
+/// The converted source is added to the assets once its conversion is done.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct VoiceLine {
+///     original: Handle<AudioSource>,
+///     mono: Option<Handle<AudioSource>>,
+/// }
+///
+/// fn prepare_voice_line(
+///     mut voice_line: ResMut<VoiceLine>,
+///     sources: Res<Assets<AudioSource>>,
+///     mut conversions: ResMut<AudioSourceConversions>,
+/// ) {
+///     if voice_line.mono.is_none() {
+///         voice_line.mono = conversions.convert(&sources, &voice_line.original, AudioSource::to_mono);
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct AudioSourceConversions {
+    /// Slots the running conversions put their result into
+    ///
+    /// Tasks of the single threaded task pool cannot be polled for their output, so the output
+    /// is passed through the slot on every configuration.
+    tasks: Vec<(AssetId<AudioSource>, ConversionSlot)>,
+}
+
+/// Where a running conversion puts the converted source
+type ConversionSlot = Arc<Mutex<Option<AudioSource>>>;
+
+impl AudioSourceConversions {
+    /// Convert the source on the async compute task pool
+    ///
+    /// Returns the handle of the converted source, or `None` if the source is not loaded yet.
+    pub fn convert(
+        &mut self,
+        sources: &Assets<AudioSource>,
+        source: &Handle<AudioSource>,
+        conversion: impl FnOnce(&AudioSource) -> AudioSource + Send + 'static,
+    ) -> Option<Handle<AudioSource>> {
+        let source = sources.get(source)?.clone();
+        let handle = sources.reserve_handle();
+        let slot = Arc::new(Mutex::new(None));
+        let task_slot = slot.clone();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                *task_slot.lock() = Some(conversion(&source));
+            })
+            .detach();
+        self.tasks.push((handle.id(), slot));
+
+        Some(handle)
+    }
+
+    /// Returns `true` while conversions are running
+    pub fn is_converting(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+}
+
+pub(crate) fn finish_audio_source_conversions(
+    mut conversions: ResMut<AudioSourceConversions>,
+    mut sources: ResMut<Assets<AudioSource>>,
+) {
+    conversions.tasks.retain(|(id, slot)| {
+        let Some(converted) = slot.lock().take() else {
+            return true;
+        };
+        sources.insert(*id, converted);
+
+        false
+    });
+}
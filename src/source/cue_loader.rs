@@ -0,0 +1,92 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::source::{AudioCue, AudioCueSettings};
+
+/// Asset loader for `.audio.ron` files describing an [`AudioCue`]
+#[derive(Default)]
+pub struct AudioCueLoader;
+
+/// Content of a `.audio.ron` file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CueFile {
+    /// Asset path of the sound
+    source: String,
+    /// Key of the dynamic channel to play the sound on
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    settings: AudioCueSettings,
+}
+
+/// Possible errors that can be produced by [`AudioCueLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AudioCueLoaderError {
+    /// An [IO Error](std::io::Error)
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON Error](serde_ron::error::SpannedError)
+    #[error("Could not parse RON: {0}")]
+    RonError(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AudioCueLoader {
+    type Asset = AudioCue;
+    type Settings = ();
+    type Error = AudioCueLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let cue: CueFile = ron::de::from_bytes(&bytes)?;
+
+        Ok(AudioCue {
+            source: load_context.load(cue.source),
+            channel: cue.channel.map(Into::into),
+            settings: cue.settings,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["audio.ron"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cue_files_default_to_the_main_channel() {
+        let cue: CueFile = ron::de::from_str(
+            r#"(
+                source: "sounds/shot.ogg",
+                settings: (
+                    volume: Some(0.8),
+                    pitch_variation: Some((0.9, 1.1)),
+                ),
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(cue.source, "sounds/shot.ogg");
+        assert_eq!(cue.channel, None);
+        assert_eq!(
+            cue.settings,
+            AudioCueSettings {
+                volume: Some(0.8),
+                pitch_variation: Some((0.9, 1.1)),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,5 +1,8 @@
 //! Asset loaders for commonly used audio file formats
 
+mod conversion;
+#[cfg(feature = "settings_loader")]
+pub mod cue_loader;
 #[cfg(feature = "flac")]
 pub mod flac_loader;
 #[cfg(feature = "mp3")]
@@ -8,12 +11,33 @@ pub mod mp3_loader;
 pub mod ogg_loader;
 #[cfg(feature = "settings_loader")]
 pub mod settings_loader;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod streaming_loader;
+#[cfg(feature = "settings_loader")]
+pub mod timeline_loader;
 #[cfg(feature = "wav")]
 pub mod wav_loader;
 
-use bevy::asset::Asset;
+use crate::audio::PlayAudioCommand;
+use crate::ChannelKey;
+use bevy::asset::{Asset, Handle};
 use bevy::reflect::TypePath;
-use kira::sound::static_sound::StaticSoundData;
+pub(crate) use conversion::finish_audio_source_conversions;
+pub use conversion::AudioSourceConversions;
+use kira::dsp::{interpolate_frame, Frame};
+use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+#[cfg(not(target_arch = "wasm32"))]
+use kira::sound::{
+    streaming::{StreamingSoundData, StreamingSoundSettings},
+    FromFileError,
+};
+use kira::sound::{EndPosition, PlaybackPosition, Region};
+use kira::tween::Value;
+use kira::Volume;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{io::Cursor, sync::Arc};
 
 /// A source of audio data
 #[derive(Clone, Asset, TypePath)]
@@ -21,3 +45,433 @@ pub struct AudioSource {
     /// The Kira sound making up this `AudioSource`
     pub sound: StaticSoundData,
 }
+
+impl AudioSource {
+    /// The decoded frames of the source
+    ///
+    /// Kira decodes every sound into stereo frames. The frames of mono files have the same
+    /// sample on both channels.
+    pub fn frames(&self) -> &[Frame] {
+        &self.sound.frames
+    }
+
+    /// The sample rate of the [frames](Self::frames)
+    pub fn sample_rate(&self) -> u32 {
+        self.sound.sample_rate
+    }
+
+    /// Number of channels of the source, `1` if all frames have the same sample on both channels
+    ///
+    /// This checks all frames, cache the result for long sounds.
+    pub fn channel_count(&self) -> u16 {
+        if self.frames().iter().all(|frame| frame.left == frame.right) {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// The lowest and highest sample in each of the given number of equally long parts of the source
+    ///
+    /// Both channels are mixed into one. Draw a line between the two values of each part to
+    /// render the waveform, for example in an editor.
+    pub fn waveform(&self, parts: usize) -> Vec<(f32, f32)> {
+        let frames = self.frames();
+        if frames.is_empty() {
+            return vec![(0., 0.); parts];
+        }
+        (0..parts)
+            .map(|part| {
+                let start = part * frames.len() / parts;
+                let end = ((part + 1) * frames.len() / parts).max(start + 1);
+                frames[start..end.min(frames.len())]
+                    .iter()
+                    .map(|frame| (frame.left + frame.right) / 2.)
+                    .fold((f32::MAX, f32::MIN), |(low, high), sample| {
+                        (low.min(sample), high.max(sample))
+                    })
+            })
+            .collect()
+    }
+
+    /// A copy of this source with the given sample rate
+    ///
+    /// Positions in samples of the playback and loop regions are converted to seconds. There is
+    /// no low-pass filter, so strong downsampling can add aliasing. Converting long sounds takes
+    /// a while, use [`AudioSourceConversions`](crate::AudioSourceConversions) to convert them off
+    /// the main thread.
+    pub fn resampled(&self, sample_rate: u32) -> AudioSource {
+        let sample_rate = sample_rate.max(1);
+        let data = &self.sound;
+        let frames = &data.frames;
+        let step = data.sample_rate as f64 / sample_rate as f64;
+        let length = (frames.len() as f64 / step).round() as usize;
+        let frame = |index: isize| {
+            let last = frames.len() as isize - 1;
+            if last < 0 {
+                return Frame::ZERO;
+            }
+            frames[index.clamp(0, last) as usize]
+        };
+        let resampled: Vec<Frame> = (0..length)
+            .map(|index| {
+                let position = index as f64 * step;
+                let current = position as isize;
+                interpolate_frame(
+                    frame(current - 1),
+                    frame(current),
+                    frame(current + 1),
+                    frame(current + 2),
+                    position.fract() as f32,
+                )
+            })
+            .collect();
+        let mut settings = data.settings;
+        settings.playback_region = seconds_region(settings.playback_region, data.sample_rate);
+        settings.loop_region = settings
+            .loop_region
+            .map(|region| seconds_region(region, data.sample_rate));
+
+        AudioSource {
+            sound: StaticSoundData {
+                sample_rate,
+                frames: resampled.into(),
+                settings,
+            },
+        }
+    }
+
+    /// A copy of this source with both channels mixed into one
+    ///
+    /// Spatial audio pans sounds between the speakers, which works best for mono sounds.
+    pub fn to_mono(&self) -> AudioSource {
+        let frames: Vec<Frame> = self
+            .sound
+            .frames
+            .iter()
+            .map(|frame| Frame::from_mono((frame.left + frame.right) / 2.))
+            .collect();
+
+        AudioSource {
+            sound: StaticSoundData {
+                frames: frames.into(),
+                ..self.sound.clone()
+            },
+        }
+    }
+}
+
+/// The region with positions in samples converted to seconds
+fn seconds_region(region: Region, sample_rate: u32) -> Region {
+    let seconds = |position: PlaybackPosition| match position {
+        PlaybackPosition::Samples(samples) => {
+            PlaybackPosition::Seconds(samples as f64 / sample_rate as f64)
+        }
+        PlaybackPosition::Seconds(seconds) => PlaybackPosition::Seconds(seconds),
+    };
+
+    Region {
+        start: seconds(region.start),
+        end: match region.end {
+            EndPosition::Custom(end) => EndPosition::Custom(seconds(end)),
+            EndPosition::EndOfAudio => EndPosition::EndOfAudio,
+        },
+    }
+}
+
+/// Default sound settings baked into an [`AudioSource`] when loading it
+///
+/// These are the settings of the OGG, MP3, WAV, and FLAC loaders and can be configured per
+/// asset in its `.meta` file:
+/// ```ron
+/// (
+///     meta_format_version: "1.0",
+///     asset: Load(
+///         loader: "bevy_kira_audio::source::ogg_loader::OggLoader",
+///         settings: (
+///             loop_start: Some(0.0),
+///             volume: Some(0.5),
+///         ),
+///     ),
+/// )
+/// ```
+/// Settings of the channel and the play command are applied on top of these.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioLoaderSettings {
+    /// Loop the sound, starting from the given position in seconds
+    pub loop_start: Option<f64>,
+    /// Loop the sound, ending at the given position in seconds
+    pub loop_end: Option<f64>,
+    /// Amplitude multiplier
+    pub volume: Option<f64>,
+    /// The panning of the sound, where 0 is hard left and 1 is hard right
+    pub panning: Option<f64>,
+    /// Whether the sound should play in reverse
+    pub reverse: bool,
+}
+
+impl From<&AudioLoaderSettings> for StaticSoundSettings {
+    fn from(settings: &AudioLoaderSettings) -> Self {
+        let mut static_sound_settings = StaticSoundSettings::new().reverse(settings.reverse);
+        if let Some(loop_start) = settings.loop_start {
+            static_sound_settings
+                .loop_region
+                .get_or_insert_with(Default::default)
+                .start = loop_start.into();
+        }
+        if let Some(loop_end) = settings.loop_end {
+            static_sound_settings
+                .loop_region
+                .get_or_insert_with(Default::default)
+                .end = EndPosition::Custom(loop_end.into());
+        }
+        if let Some(volume) = settings.volume {
+            static_sound_settings.volume = Value::Fixed(Volume::Amplitude(volume));
+        }
+        if let Some(panning) = settings.panning {
+            static_sound_settings.panning = Value::Fixed(panning);
+        }
+
+        static_sound_settings
+    }
+}
+
+/// A sound bundled with the settings to play it with
+///
+/// Play cues with [`AudioCommandsExt::play_cue`](crate::AudioCommandsExt::play_cue). With the
+/// `settings_loader` feature, cues can be loaded from `.audio.ron` files, so sound design data
+/// does not need to live in code:
+/// ```ron
+/// (
+///     source: "sounds/shot.ogg",
+///     channel: Some("sfx"),
+///     settings: (
+///         volume: Some(0.8),
+///         pitch_variation: Some((0.9, 1.1)),
+///     ),
+/// )
+/// ```
+#[derive(Clone, Debug, Asset, TypePath)]
+pub struct AudioCue {
+    /// The sound to play
+    #[dependency]
+    pub source: Handle<AudioSource>,
+    /// Key of the dynamic channel to play the sound on
+    ///
+    /// If `None`, the sound is played on the [`Audio`](crate::Audio) channel.
+    pub channel: Option<ChannelKey>,
+    /// Settings applied to every play of the cue
+    pub settings: AudioCueSettings,
+}
+
+/// Settings of an [`AudioCue`]
+///
+/// Every setting maps to the method of [`PlayAudioCommand`] with the same name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioCueSettings {
+    /// Amplitude multiplier
+    pub volume: Option<f64>,
+    /// The playback rate, changing both the speed and the pitch of the sound
+    pub playback_rate: Option<f64>,
+    /// The panning of the sound, where 0 is hard left and 1 is hard right
+    pub panning: Option<f64>,
+    /// Loop the whole sound, unless a loop region is set
+    pub looped: bool,
+    /// Loop the sound, starting from the given position in seconds
+    pub loop_start: Option<f64>,
+    /// Loop the sound, ending at the given position in seconds
+    pub loop_end: Option<f64>,
+    /// Position in seconds to start the sound from
+    pub start_position: Option<f64>,
+    /// Duration of a linear fade-in in seconds
+    pub fade_in: Option<f64>,
+    /// Bounds of the random factor for the playback rate
+    pub pitch_variation: Option<(f64, f64)>,
+    /// Bounds of the random factor for the volume
+    pub volume_variation: Option<(f64, f64)>,
+    /// Sounds with a higher priority keep playing when the voice cap is reached
+    pub priority: i32,
+}
+
+impl AudioCueSettings {
+    pub(crate) fn configure(&self, command: &mut PlayAudioCommand<'_>) {
+        if let Some(volume) = self.volume {
+            command.with_volume(volume);
+        }
+        if let Some(playback_rate) = self.playback_rate {
+            command.with_playback_rate(playback_rate);
+        }
+        if let Some(panning) = self.panning {
+            command.with_panning(panning);
+        }
+        if self.looped {
+            command.looped();
+        }
+        if let Some(loop_start) = self.loop_start {
+            command.loop_from(loop_start);
+        }
+        if let Some(loop_end) = self.loop_end {
+            command.loop_until(loop_end);
+        }
+        if let Some(start_position) = self.start_position {
+            command.start_from(start_position);
+        }
+        if let Some(fade_in) = self.fade_in {
+            command.linear_fade_in(Duration::from_secs_f64(fade_in.max(0.)));
+        }
+        if let Some((min, max)) = self.pitch_variation {
+            command.with_pitch_variation(min..=max);
+        }
+        if let Some((min, max)) = self.volume_variation {
+            command.with_volume_variation(min..=max);
+        }
+        command.with_priority(self.priority);
+    }
+}
+
+/// A source of audio data that is decoded while it plays
+///
+/// In contrast to [`AudioSource`], the audio file is not decoded into memory when loading it.
+/// This saves a lot of memory for long music tracks at the cost of decoding while playing.
+/// Load any supported audio file as streaming source by requesting this asset type:
+/// `asset_server.load::<StreamingAudioSource>("music.ogg")`.
+///
+/// Streaming sounds cannot be played in reverse and are not supported on wasm.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Asset, TypePath)]
+pub struct StreamingAudioSource {
+    /// The encoded audio file
+    pub bytes: Arc<[u8]>,
+    /// The Kira settings used for every playback of this source
+    pub settings: StreamingSoundSettings,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StreamingAudioSource {
+    /// Settings of this source as settings for a static sound
+    pub(crate) fn static_settings(&self) -> StaticSoundSettings {
+        StaticSoundSettings::new()
+            .start_time(self.settings.start_time)
+            .playback_region(self.settings.playback_region)
+            .loop_region(self.settings.loop_region)
+            .volume(self.settings.volume)
+            .playback_rate(self.settings.playback_rate)
+            .panning(self.settings.panning)
+            .output_destination(self.settings.output_destination)
+            .fade_in_tween(self.settings.fade_in_tween)
+    }
+
+    /// Create a new decoder for this source to play with the given settings
+    pub(crate) fn sound(
+        &self,
+        settings: &StaticSoundSettings,
+    ) -> Result<StreamingSoundData<FromFileError>, FromFileError> {
+        let settings = StreamingSoundSettings::new()
+            .start_time(settings.start_time)
+            .playback_region(settings.playback_region)
+            .loop_region(settings.loop_region)
+            .volume(settings.volume)
+            .playback_rate(settings.playback_rate)
+            .panning(settings.panning)
+            .output_destination(settings.output_destination)
+            .fade_in_tween(settings.fade_in_tween);
+
+        StreamingSoundData::from_cursor(Cursor::new(self.bytes.clone()), settings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kira::sound::PlaybackPosition;
+
+    #[test]
+    fn default_loader_settings_keep_kira_defaults() {
+        let settings = StaticSoundSettings::from(&AudioLoaderSettings::default());
+        let defaults = StaticSoundSettings::default();
+
+        assert_eq!(settings.loop_region, defaults.loop_region);
+        assert_eq!(settings.volume, defaults.volume);
+        assert_eq!(settings.panning, defaults.panning);
+        assert_eq!(settings.reverse, defaults.reverse);
+    }
+
+    #[test]
+    fn loader_settings_are_baked_into_sound_settings() {
+        let settings = StaticSoundSettings::from(&AudioLoaderSettings {
+            loop_start: Some(1.5),
+            volume: Some(0.5),
+            panning: Some(0.2),
+            reverse: true,
+            ..Default::default()
+        });
+
+        let loop_region = settings.loop_region.unwrap();
+        assert_eq!(loop_region.start, PlaybackPosition::Seconds(1.5));
+        assert_eq!(loop_region.end, EndPosition::EndOfAudio);
+        assert_eq!(settings.volume, Value::Fixed(Volume::Amplitude(0.5)));
+        assert_eq!(settings.panning, Value::Fixed(0.2));
+        assert!(settings.reverse);
+    }
+
+    #[test]
+    fn resampling_keeps_the_signal_and_converts_sample_positions() {
+        let source = AudioSource {
+            sound: StaticSoundData {
+                sample_rate: 100,
+                frames: vec![Frame::new(0.5, -0.5); 100].into(),
+                settings: StaticSoundSettings::new().loop_region(Region {
+                    start: PlaybackPosition::Samples(50),
+                    end: EndPosition::EndOfAudio,
+                }),
+            },
+        };
+
+        let resampled = source.resampled(50);
+
+        assert_eq!(resampled.sound.sample_rate, 50);
+        assert_eq!(resampled.sound.frames.len(), 50);
+        assert!(resampled
+            .sound
+            .frames
+            .iter()
+            .all(|frame| (frame.left - 0.5).abs() < 1e-6 && (frame.right + 0.5).abs() < 1e-6));
+        assert_eq!(
+            resampled.sound.settings.loop_region.unwrap().start,
+            PlaybackPosition::Seconds(0.5)
+        );
+    }
+
+    #[test]
+    fn mono_sources_mix_both_channels() {
+        let source = AudioSource {
+            sound: StaticSoundData {
+                sample_rate: 100,
+                frames: vec![Frame::new(1., 0.)].into(),
+                settings: StaticSoundSettings::new(),
+            },
+        };
+
+        assert_eq!(source.to_mono().sound.frames[0], Frame::from_mono(0.5));
+    }
+
+    #[test]
+    fn waveform_has_the_range_of_each_part() {
+        let source = AudioSource {
+            sound: StaticSoundData {
+                sample_rate: 100,
+                frames: [0., 1., -0.5, 0.25, 0., 0.]
+                    .map(Frame::from_mono)
+                    .to_vec()
+                    .into(),
+                settings: StaticSoundSettings::new(),
+            },
+        };
+
+        assert_eq!(source.channel_count(), 1);
+        assert_eq!(source.waveform(3), vec![(0., 1.), (-0.5, 0.25), (0., 0.)]);
+        assert_eq!(source.waveform(1), vec![(-0.5, 1.)]);
+    }
+}
@@ -1,12 +1,12 @@
 use anyhow::Result;
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use kira::sound::static_sound::StaticSoundData;
 use kira::sound::FromFileError;
 use std::io::Cursor;
 use thiserror::Error;
 
-use crate::source::AudioSource;
+use crate::source::{AudioLoaderSettings, AudioSource};
 
 /// Possible errors that can be produced by [`OggLoader`]
 #[non_exhaustive]
@@ -26,19 +26,18 @@ pub struct OggLoader;
 
 impl AssetLoader for OggLoader {
     type Asset = AudioSource;
-    type Settings = ();
+    type Settings = AudioLoaderSettings;
     type Error = OggLoaderError;
 
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
-        _settings: &'a (),
+        settings: &'a AudioLoaderSettings,
         _load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut sound_bytes = vec![];
         reader.read_to_end(&mut sound_bytes).await?;
-        let sound =
-            StaticSoundData::from_cursor(Cursor::new(sound_bytes), StaticSoundSettings::default())?;
+        let sound = StaticSoundData::from_cursor(Cursor::new(sound_bytes), settings.into())?;
         Ok(AudioSource { sound })
     }
 
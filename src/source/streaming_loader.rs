@@ -0,0 +1,72 @@
+use anyhow::Result;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use kira::sound::streaming::{StreamingSoundData, StreamingSoundSettings};
+use kira::sound::FromFileError;
+use std::io::Cursor;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::source::StreamingAudioSource;
+
+/// Possible errors that can be produced by [`StreamingAudioLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum StreamingAudioLoaderError {
+    /// An [IO Error](std::io::Error)
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// An Error loading sound from a file. See [`FromFileError`]
+    #[error("Error while loading a sound: {0}")]
+    FileError(#[from] FromFileError),
+}
+
+/// Asset loader for streaming audio files.
+///
+/// It supports all enabled audio file formats. The file is only checked to be decodable while
+/// loading and decoded on the fly when playing it.
+#[derive(Default)]
+pub struct StreamingAudioLoader;
+
+impl AssetLoader for StreamingAudioLoader {
+    type Asset = StreamingAudioSource;
+    type Settings = ();
+    type Error = StreamingAudioLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut sound_bytes = vec![];
+        reader.read_to_end(&mut sound_bytes).await?;
+        let bytes: Arc<[u8]> = sound_bytes.into();
+        StreamingSoundData::from_cursor(
+            Cursor::new(bytes.clone()),
+            StreamingSoundSettings::default(),
+        )?;
+
+        Ok(StreamingAudioSource {
+            bytes,
+            settings: StreamingSoundSettings::default(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[
+            #[cfg(feature = "mp3")]
+            "mp3",
+            #[cfg(feature = "wav")]
+            "wav",
+            #[cfg(feature = "flac")]
+            "flac",
+            #[cfg(feature = "ogg")]
+            "ogg",
+            #[cfg(feature = "ogg")]
+            "oga",
+            #[cfg(feature = "ogg")]
+            "spx",
+        ]
+    }
+}
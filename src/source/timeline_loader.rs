@@ -0,0 +1,103 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::source::AudioCueSettings;
+use crate::timeline::{AudioTimeline, TimelineEvent};
+
+/// Asset loader for `.timeline.ron` files describing an [`AudioTimeline`]
+#[derive(Default)]
+pub struct AudioTimelineLoader;
+
+/// Content of a `.timeline.ron` file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimelineFile {
+    events: Vec<EventFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventFile {
+    at: f64,
+    /// Asset path of the sound
+    source: String,
+    /// Key of the dynamic channel to play the sound on
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    settings: AudioCueSettings,
+    #[serde(default)]
+    end: Option<f64>,
+    #[serde(default)]
+    fade_out: f64,
+}
+
+/// Possible errors that can be produced by [`AudioTimelineLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AudioTimelineLoaderError {
+    /// An [IO Error](std::io::Error)
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON Error](serde_ron::error::SpannedError)
+    #[error("Could not parse RON: {0}")]
+    RonError(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AudioTimelineLoader {
+    type Asset = AudioTimeline;
+    type Settings = ();
+    type Error = AudioTimelineLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let timeline: TimelineFile = ron::de::from_bytes(&bytes)?;
+
+        Ok(AudioTimeline::new(timeline.events.into_iter().map(
+            |event| TimelineEvent {
+                at: event.at,
+                source: load_context.load(event.source),
+                channel: event.channel.map(Into::into),
+                settings: event.settings,
+                end: event.end,
+                fade_out: event.fade_out,
+            },
+        )))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["timeline.ron"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timeline_events_only_need_a_time_and_source() {
+        let timeline: TimelineFile = ron::de::from_str(
+            r#"(
+                events: [
+                    (at: 1.5, source: "sounds/door.ogg"),
+                    (at: 4.0, source: "sounds/rain.ogg", settings: (looped: true), end: Some(9.0), fade_out: 1.0),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(timeline.events.len(), 2);
+        assert_eq!(timeline.events[0].source, "sounds/door.ogg");
+        assert_eq!(timeline.events[0].end, None);
+        assert!(timeline.events[1].settings.looped);
+        assert_eq!(timeline.events[1].fade_out, 1.);
+    }
+}
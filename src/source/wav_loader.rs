@@ -1,12 +1,12 @@
 use anyhow::Result;
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use kira::sound::static_sound::StaticSoundData;
 use kira::sound::FromFileError;
 use std::io::Cursor;
 use thiserror::Error;
 
-use crate::source::AudioSource;
+use crate::source::{AudioLoaderSettings, AudioSource};
 
 /// Asset loader for WAV files.
 #[derive(Default)]
@@ -26,19 +26,18 @@ pub enum WavLoaderError {
 
 impl AssetLoader for WavLoader {
     type Asset = AudioSource;
-    type Settings = ();
+    type Settings = AudioLoaderSettings;
     type Error = WavLoaderError;
 
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
-        _settings: &'a (),
+        settings: &'a AudioLoaderSettings,
         _load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut sound_bytes = vec![];
         reader.read_to_end(&mut sound_bytes).await?;
-        let sound =
-            StaticSoundData::from_cursor(Cursor::new(sound_bytes), StaticSoundSettings::default())?;
+        let sound = StaticSoundData::from_cursor(Cursor::new(sound_bytes), settings.into())?;
         Ok(AudioSource { sound })
     }
     fn extensions(&self) -> &[&str] {
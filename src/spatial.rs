@@ -1,13 +1,27 @@
+use crate::audio_output::AudioOutput;
+use crate::channel::Channel;
+use crate::quality::AudioQuality;
 use crate::{AudioInstance, AudioTween};
 use bevy::asset::{Assets, Handle};
-use bevy::ecs::component::Component;
-use bevy::prelude::{GlobalTransform, Query, Res, ResMut, Resource, With};
+use bevy::ecs::component::{Component, ComponentHooks, ComponentId, StorageType};
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::world::DeferredWorld;
+use bevy::math::Vec3;
+use bevy::prelude::{
+    Entity, GlobalTransform, Local, NonSendMut, Query, Res, ResMut, Resource, With,
+};
+use bevy::time::Time;
+use bevy::utils::HashMap;
+use kira::Volume;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Component for audio emitters
 ///
 /// Add [`Handle<AudioInstance>`]s to control their pan and volume based on emitter
-/// and receiver positions.
-#[derive(Component, Default)]
+/// and receiver positions. When the emitter is despawned or the component is removed, its
+/// instances are stopped according to the entity's [`DespawnBehavior`].
+#[derive(Default)]
 pub struct AudioEmitter {
     /// Audio instances that are played by this emitter
     ///
@@ -15,14 +29,73 @@ pub struct AudioEmitter {
     pub instances: Vec<Handle<AudioInstance>>,
 }
 
+impl Component for AudioEmitter {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(stop_removed_emitter);
+    }
+}
+
+/// Component to choose what happens to the sounds of an [`AudioEmitter`] when it is despawned
+///
+/// Emitters without this component stop their sounds immediately.
+#[derive(Component, Clone, Debug, Default)]
+pub enum DespawnBehavior {
+    /// Stop the sounds with the default [`AudioTween`]
+    #[default]
+    StopImmediately,
+    /// Fade the sounds out with the given tween
+    FadeOut(AudioTween),
+    /// Keep the sounds playing until they end
+    ///
+    /// Looped sounds keep playing until they are stopped through their handles or channel.
+    DetachAndFinish,
+}
+
+fn stop_removed_emitter(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let tween = match world.get::<DespawnBehavior>(entity) {
+        None | Some(DespawnBehavior::StopImmediately) => AudioTween::default(),
+        Some(DespawnBehavior::FadeOut(tween)) => tween.clone(),
+        Some(DespawnBehavior::DetachAndFinish) => return,
+    };
+    let Some(handles) = world
+        .get::<AudioEmitter>(entity)
+        .map(|emitter| emitter.instances.clone())
+    else {
+        return;
+    };
+    let Some(mut audio_instances) = world.get_resource_mut::<Assets<AudioInstance>>() else {
+        return;
+    };
+    for handle in handles {
+        if let Some(instance) = audio_instances.get_mut(&handle) {
+            instance.stop(tween.clone());
+        }
+    }
+}
+
+/// Component to keep the sounds of an [`AudioEmitter`] in stereo
+///
+/// Sounds played on an entity with [`AudioCommandsExt`](crate::AudioCommandsExt) are mixed down
+/// to mono, because spatial audio pans them. Add this component to opt out, for example for
+/// ambience that should keep its stereo image.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct KeepStereo;
+
 /// Component for the audio receiver
 ///
 /// Most likely you will want to add this component to your player or you camera.
 /// The entity needs a [`Transform`] and [`GlobalTransform`]. The view direction of the [`GlobalTransform`]
-/// will
+/// will decide which ear hears the sound of an emitter to its side.
+///
+/// [`Transform`]: bevy::prelude::Transform
 #[derive(Component)]
 pub struct AudioReceiver;
 
+/// Alternative name for the [`AudioReceiver`] component
+pub type AudioListener = AudioReceiver;
+
 /// Configuration resource for spatial audio
 ///
 /// If this resource is not added to the ECS, spatial audio is not applied.
@@ -32,18 +105,186 @@ pub struct SpatialAudio {
     pub max_distance: f32,
 }
 
+/// Component to choose how the volume of an emitter falls off with distance
+///
+/// Emitters without this component use the quadratic falloff configured by [`SpatialAudio`].
+#[derive(Component, Clone)]
+pub enum AttenuationModel {
+    /// The volume falls linearly from `1` at distance `0` to `0` at `max_distance`
+    Linear {
+        /// Distance at which the emitter becomes inaudible
+        max_distance: f32,
+    },
+    /// The volume falls with the inverse of the distance beyond `reference_distance`
+    ///
+    /// Good for open scenes, since emitters stay faintly audible far away.
+    Inverse {
+        /// Distance up to which the emitter plays at full volume
+        reference_distance: f32,
+        /// How quickly the volume falls off
+        rolloff: f32,
+    },
+    /// The volume falls exponentially with the distance beyond `reference_distance`
+    ///
+    /// Good for small interiors, since the volume drops quickly.
+    Exponential {
+        /// Distance up to which the emitter plays at full volume
+        reference_distance: f32,
+        /// How quickly the volume falls off
+        rolloff: f32,
+    },
+    /// Compute the volume from the distance with a custom curve
+    Custom(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+}
+
+impl AttenuationModel {
+    /// Create a model using the given curve from distance to volume
+    pub fn custom(curve: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        AttenuationModel::Custom(Arc::new(curve))
+    }
+
+    /// The volume between `0` and `1` at the given distance from the emitter
+    pub fn volume(&self, distance: f32) -> f32 {
+        let volume = match self {
+            AttenuationModel::Linear { max_distance } => 1. - distance / max_distance,
+            AttenuationModel::Inverse {
+                reference_distance,
+                rolloff,
+            } => {
+                reference_distance
+                    / (reference_distance
+                        + rolloff * (distance.max(*reference_distance) - reference_distance))
+            }
+            AttenuationModel::Exponential {
+                reference_distance,
+                rolloff,
+            } => (distance.max(*reference_distance) / reference_distance).powf(-rolloff),
+            AttenuationModel::Custom(curve) => curve(distance),
+        };
+
+        volume.clamp(0., 1.)
+    }
+}
+
+/// Component to opt an emitter into the Doppler effect
+///
+/// The playback rate of the emitter's instances is changed based on how fast the emitter and
+/// the receiver move towards or away from each other. This overwrites any other playback rate
+/// set on those instances.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Doppler {
+    /// Strength of the effect, where `0` disables it and `1` is physically accurate
+    pub intensity: f32,
+    /// Speed of sound in world units per second
+    pub speed_of_sound: f32,
+}
+
+impl Default for Doppler {
+    fn default() -> Self {
+        Doppler {
+            intensity: 1.,
+            speed_of_sound: 343.,
+        }
+    }
+}
+
+impl Doppler {
+    /// Playback rate for the given velocities and direction from the receiver to the emitter
+    fn playback_rate(
+        &self,
+        to_emitter: Vec3,
+        receiver_velocity: Vec3,
+        emitter_velocity: Vec3,
+    ) -> f64 {
+        let direction = to_emitter.normalize_or_zero();
+        // only allow moving with up to half the speed of sound to keep the rate sane
+        let max_speed = self.speed_of_sound / 2.;
+        let receiver_speed =
+            (receiver_velocity.dot(direction) * self.intensity).clamp(-max_speed, max_speed);
+        let emitter_speed =
+            (emitter_velocity.dot(direction) * self.intensity).clamp(-max_speed, max_speed);
+
+        ((self.speed_of_sound + receiver_speed) / (self.speed_of_sound + emitter_speed)) as f64
+    }
+}
+
+/// Component to send more of an emitter's sound to a reverb channel the further away it is
+///
+/// The emitter's sounds need to play on a channel that sends to the reverb channel, see
+/// [`ChannelSettings::with_send`](crate::ChannelSettings::with_send). The send is part of the
+/// channel, so emitters that should sound differently far away need their own channels, for
+/// example dynamic ones. If several emitters play on the same channel, the highest send wins.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// #[derive(Resource)]
+/// struct Reverb;
+///
+/// #[derive(Resource)]
+/// struct Ambience;
+///
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default(), AudioPlugin))
+///     .add_audio_channel_with_settings::<Reverb>(
+///         ChannelSettings::default().with_effect(ReverbBuilder::new().mix(1.)),
+///     )
+///     .add_audio_channel_with_settings::<Ambience>(
+///         ChannelSettings::default().with_send::<Reverb>(0.),
+///     );
+///
+/// fn spawn_waterfall(mut commands: Commands) {
+///     commands.spawn((
+///         AudioEmitter::default(),
+///         DistanceReverb::linear(Channel::typed::<Reverb>(), 5., 50.),
+///         TransformBundle::default(),
+///     ));
+/// }
+/// ```
+#[derive(Component, Clone)]
+pub struct DistanceReverb {
+    /// The channel with the reverb effect
+    pub send: Channel,
+    curve: Arc<dyn Fn(f32) -> f32 + Send + Sync>,
+}
+
+impl DistanceReverb {
+    /// Raise the send linearly from `0` at distance `near` to `1` at distance `far`
+    pub fn linear(send: Channel, near: f32, far: f32) -> Self {
+        Self::custom(send, move |distance| {
+            (distance - near) / (far - near).max(f32::EPSILON)
+        })
+    }
+
+    /// Compute the send volume from the distance with a custom curve
+    pub fn custom(send: Channel, curve: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        DistanceReverb {
+            send,
+            curve: Arc::new(curve),
+        }
+    }
+
+    /// The send volume between `0` and `1` at the given distance from the emitter
+    pub fn send_volume(&self, distance: f32) -> f32 {
+        (self.curve)(distance).clamp(0., 1.)
+    }
+}
+
 impl SpatialAudio {
     pub(crate) fn update(
         &self,
         receiver_transform: &GlobalTransform,
-        emitters: &Query<(&GlobalTransform, &AudioEmitter)>,
+        emitters: &Query<(&GlobalTransform, &AudioEmitter, Option<&AttenuationModel>)>,
         audio_instances: &mut Assets<AudioInstance>,
     ) {
-        for (emitter_transform, emitter) in emitters {
+        for (emitter_transform, emitter, attenuation) in emitters {
             let sound_path = emitter_transform.translation() - receiver_transform.translation();
-            let volume = (1. - sound_path.length() / self.max_distance)
-                .clamp(0., 1.)
-                .powi(2);
+            let volume = match attenuation {
+                Some(attenuation) => attenuation.volume(sound_path.length()),
+                None => (1. - sound_path.length() / self.max_distance)
+                    .clamp(0., 1.)
+                    .powi(2),
+            };
 
             let right_ear_angle = receiver_transform.right().angle_between(sound_path);
             let panning = (right_ear_angle.cos() + 1.) / 2.;
@@ -61,7 +302,7 @@ impl SpatialAudio {
 pub(crate) fn run_spatial_audio(
     spatial_audio: Res<SpatialAudio>,
     receiver: Query<&GlobalTransform, With<AudioReceiver>>,
-    emitters: Query<(&GlobalTransform, &AudioEmitter)>,
+    emitters: Query<(&GlobalTransform, &AudioEmitter, Option<&AttenuationModel>)>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
 ) {
     if let Ok(receiver_transform) = receiver.get_single() {
@@ -69,6 +310,106 @@ pub(crate) fn run_spatial_audio(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_doppler(
+    time: Res<Time>,
+    quality: Res<AudioQuality>,
+    receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &Doppler)>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut previous_time: Local<Option<Duration>>,
+    mut previous_receiver_position: Local<Option<Vec3>>,
+    mut previous_emitter_positions: Local<EntityHashMap<Vec3>>,
+) {
+    if !quality.doppler {
+        if previous_time.take().is_some() {
+            for (_, _, emitter, _) in &emitters {
+                for instance in emitter.instances.iter() {
+                    if let Some(instance) = audio_instances.get_mut(instance) {
+                        instance.set_playback_rate(1., AudioTween::default());
+                    }
+                }
+            }
+        }
+        *previous_receiver_position = None;
+        previous_emitter_positions.clear();
+        return;
+    }
+    let Ok(receiver_transform) = receiver.get_single() else {
+        return;
+    };
+    // The system might not run every frame, see `AudioQuality::spatial_update_interval`
+    let now = time.elapsed();
+    let delta = previous_time
+        .replace(now)
+        .map_or(0., |previous| (now - previous).as_secs_f32());
+    let receiver_position = receiver_transform.translation();
+    let receiver_velocity = previous_receiver_position
+        .replace(receiver_position)
+        .filter(|_| delta > 0.)
+        .map_or(Vec3::ZERO, |previous| {
+            (receiver_position - previous) / delta
+        });
+
+    previous_emitter_positions.retain(|entity, _| emitters.contains(*entity));
+    for (entity, emitter_transform, emitter, doppler) in &emitters {
+        let emitter_position = emitter_transform.translation();
+        let Some(previous) = previous_emitter_positions.insert(entity, emitter_position) else {
+            continue;
+        };
+        if delta <= 0. {
+            continue;
+        }
+        let emitter_velocity = (emitter_position - previous) / delta;
+        let playback_rate = doppler.playback_rate(
+            emitter_position - receiver_position,
+            receiver_velocity,
+            emitter_velocity,
+        );
+
+        for instance in emitter.instances.iter() {
+            if let Some(instance) = audio_instances.get_mut(instance) {
+                instance.set_playback_rate(playback_rate, AudioTween::default());
+            }
+        }
+    }
+}
+
+pub(crate) fn run_distance_reverb(
+    receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    emitters: Query<(&GlobalTransform, &AudioEmitter, &DistanceReverb)>,
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut sends: Local<HashMap<(Channel, Channel), f32>>,
+) {
+    let Ok(receiver_transform) = receiver.get_single() else {
+        return;
+    };
+    sends.clear();
+    for (emitter_transform, emitter, reverb) in &emitters {
+        let distance = emitter_transform
+            .translation()
+            .distance(receiver_transform.translation());
+        let volume = reverb.send_volume(distance);
+        for instance in emitter.instances.iter() {
+            let Some(channel) = audio_output.channel_of(instance) else {
+                continue;
+            };
+            let send = sends
+                .entry((channel.clone(), reverb.send.clone()))
+                .or_default();
+            *send = send.max(volume);
+        }
+    }
+    for ((channel, send), volume) in sends.iter() {
+        audio_output.set_send_volume(
+            channel,
+            send,
+            Volume::Amplitude(*volume as f64),
+            AudioTween::default().into(),
+        );
+    }
+}
+
 pub(crate) fn cleanup_stopped_spatial_instances(
     mut emitters: Query<&mut AudioEmitter>,
     instances: ResMut<Assets<AudioInstance>>,
@@ -78,10 +419,146 @@ pub(crate) fn cleanup_stopped_spatial_instances(
 
         handles.retain(|handle| {
             if let Some(instance) = instances.get(handle) {
-                instance.handle.state() != kira::sound::PlaybackState::Stopped
+                instance.is_alive()
             } else {
                 true
             }
         });
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instance::InstanceHandle;
+    use bevy::prelude::{default, World};
+    use kira::manager::backend::mock::MockBackend;
+    use kira::manager::AudioManager;
+    use kira::sound::static_sound::StaticSoundData;
+    use kira::tween::Value;
+
+    #[test]
+    fn attenuation_models_are_full_volume_close_to_the_emitter() {
+        let models = [
+            AttenuationModel::Linear { max_distance: 10. },
+            AttenuationModel::Inverse {
+                reference_distance: 1.,
+                rolloff: 1.,
+            },
+            AttenuationModel::Exponential {
+                reference_distance: 1.,
+                rolloff: 1.,
+            },
+            AttenuationModel::custom(|distance| 1. / (1. + distance)),
+        ];
+
+        for model in models {
+            assert_eq!(model.volume(0.), 1.);
+            assert!(model.volume(5.) < 1.);
+        }
+    }
+
+    #[test]
+    fn attenuation_models_fall_off_differently() {
+        let linear = AttenuationModel::Linear { max_distance: 10. };
+        let inverse = AttenuationModel::Inverse {
+            reference_distance: 1.,
+            rolloff: 1.,
+        };
+        let exponential = AttenuationModel::Exponential {
+            reference_distance: 1.,
+            rolloff: 2.,
+        };
+
+        assert_eq!(linear.volume(5.), 0.5);
+        assert_eq!(linear.volume(20.), 0.);
+        assert_eq!(inverse.volume(4.), 0.25);
+        assert_eq!(exponential.volume(4.), 1. / 16.);
+    }
+
+    #[test]
+    fn distance_reverb_sends_more_of_distant_emitters() {
+        let reverb = DistanceReverb::linear(Channel::dynamic("reverb"), 10., 30.);
+
+        assert_eq!(reverb.send_volume(0.), 0.);
+        assert_eq!(reverb.send_volume(20.), 0.5);
+        assert_eq!(reverb.send_volume(100.), 1.);
+
+        let custom = DistanceReverb::custom(Channel::dynamic("reverb"), |distance| distance * 2.);
+        assert_eq!(custom.send_volume(0.25), 0.5);
+        assert_eq!(custom.send_volume(5.), 1.);
+    }
+
+    #[test]
+    fn doppler_raises_pitch_of_approaching_emitters() {
+        let doppler = Doppler::default();
+        let to_emitter = Vec3::new(10., 0., 0.);
+
+        let approaching = doppler.playback_rate(to_emitter, Vec3::ZERO, Vec3::new(-34.3, 0., 0.));
+        let receding = doppler.playback_rate(to_emitter, Vec3::ZERO, Vec3::new(34.3, 0., 0.));
+        let passing = doppler.playback_rate(to_emitter, Vec3::ZERO, Vec3::new(0., 34.3, 0.));
+
+        assert!(approaching > 1.);
+        assert!(receding < 1.);
+        assert_eq!(passing, 1.);
+    }
+
+    #[test]
+    fn doppler_intensity_scales_the_effect() {
+        let to_emitter = Vec3::new(0., 0., 10.);
+        let receiver_velocity = Vec3::new(0., 0., 20.);
+        let full = Doppler::default().playback_rate(to_emitter, receiver_velocity, Vec3::ZERO);
+        let half = Doppler {
+            intensity: 0.5,
+            ..Default::default()
+        }
+        .playback_rate(to_emitter, receiver_velocity, Vec3::ZERO);
+        let disabled = Doppler {
+            intensity: 0.,
+            ..Default::default()
+        }
+        .playback_rate(to_emitter, receiver_velocity, Vec3::ZERO);
+
+        assert!(full > half);
+        assert!(half > 1.);
+        assert_eq!(disabled, 1.);
+    }
+
+    #[test]
+    fn despawned_emitters_stop_their_sounds() {
+        let mut manager = AudioManager::<MockBackend>::new(default()).unwrap();
+        let sound = StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap();
+        let mut audio_instances = Assets::<AudioInstance>::default();
+        let mut play = || {
+            let handle = manager.play(sound.clone()).unwrap();
+            audio_instances.add(AudioInstance::new(
+                InstanceHandle::Static(handle),
+                Value::Fixed(Volume::Amplitude(1.)),
+            ))
+        };
+        let stopped = play();
+        let detached = play();
+
+        let mut world = World::new();
+        world.insert_resource(audio_instances);
+        let emitter = world
+            .spawn(AudioEmitter {
+                instances: vec![stopped.clone()],
+            })
+            .id();
+        let detached_emitter = world
+            .spawn((
+                AudioEmitter {
+                    instances: vec![detached.clone()],
+                },
+                DespawnBehavior::DetachAndFinish,
+            ))
+            .id();
+        world.despawn(emitter);
+        world.despawn(detached_emitter);
+
+        let audio_instances = world.resource::<Assets<AudioInstance>>();
+        assert!(audio_instances.get(&stopped).unwrap().stopping);
+        assert!(!audio_instances.get(&detached).unwrap().stopping);
+    }
+}
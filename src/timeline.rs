@@ -0,0 +1,340 @@
+//! Play scripted sequences of sounds, for example the audio of a cutscene
+
+use crate::audio::AudioTween;
+use crate::channel::dynamic::{ChannelKey, DynamicAudioChannels};
+use crate::channel::AudioControl;
+use crate::instance::AudioInstance;
+use crate::source::{AudioCueSettings, AudioSource};
+use crate::Audio;
+use bevy::asset::{Asset, Assets, Handle};
+use bevy::ecs::component::Component;
+use bevy::ecs::system::{Query, Res, ResMut};
+use bevy::log::warn;
+use bevy::reflect::TypePath;
+use bevy::time::Time;
+use std::time::Duration;
+
+/// Time jumps of an [external clock](TimelineClock::External) larger than this are seeks
+const MAX_EXTERNAL_STEP: f64 = 0.25;
+
+/// A list of sounds starting at given times
+///
+/// Play a timeline with a [`TimelinePlayer`]. With the `settings_loader` feature, timelines can
+/// be loaded from `.timeline.ron` files:
+/// ```ron
+/// (
+///     events: [
+///         (at: 0.0, source: "music/cutscene.ogg", channel: Some("music"), settings: (fade_in: Some(2.0))),
+///         (at: 1.5, source: "sounds/door.ogg"),
+///         (at: 4.0, source: "sounds/rain.ogg", settings: (looped: true), end: Some(9.0), fade_out: 1.0),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Asset, TypePath)]
+pub struct AudioTimeline {
+    /// The sounds of the timeline, sorted by their start time
+    pub events: Vec<TimelineEvent>,
+}
+
+impl AudioTimeline {
+    /// Create a timeline from the given events
+    pub fn new(events: impl IntoIterator<Item = TimelineEvent>) -> Self {
+        let mut events: Vec<_> = events.into_iter().collect();
+        events.sort_by(|a, b| a.at.total_cmp(&b.at));
+
+        AudioTimeline { events }
+    }
+}
+
+/// A sound of an [`AudioTimeline`]
+#[derive(Clone, Debug)]
+pub struct TimelineEvent {
+    /// Time in seconds to start the sound at
+    pub at: f64,
+    /// The sound to play
+    pub source: Handle<AudioSource>,
+    /// Key of the dynamic channel to play the sound on
+    ///
+    /// If `None`, the sound is played on the [`Audio`] channel.
+    pub channel: Option<ChannelKey>,
+    /// Settings of the sound
+    pub settings: AudioCueSettings,
+    /// Time in seconds to stop the sound at, instead of letting it end on its own
+    pub end: Option<f64>,
+    /// Duration in seconds of the linear fade out at the end time
+    pub fade_out: f64,
+}
+
+impl TimelineEvent {
+    /// Play the sound at the given time in seconds
+    pub fn new(at: f64, source: Handle<AudioSource>) -> Self {
+        TimelineEvent {
+            at,
+            source,
+            channel: None,
+            settings: AudioCueSettings::default(),
+            end: None,
+            fade_out: 0.,
+        }
+    }
+
+    /// Is the sound of this event playing at the given time?
+    fn plays_at(&self, time: f64, audio_sources: &Assets<AudioSource>) -> bool {
+        if time < self.at || self.end.map_or(false, |end| time >= end) {
+            return false;
+        }
+        if self.settings.looped || self.settings.loop_start.is_some() {
+            return true;
+        }
+
+        audio_sources.get(&self.source).map_or(true, |source| {
+            let start = self.settings.start_position.unwrap_or(0.);
+            let rate = self.settings.playback_rate.unwrap_or(1.).abs();
+            (time - self.at) * rate < source.sound.duration().as_secs_f64() - start
+        })
+    }
+}
+
+/// What drives the time of a [`TimelinePlayer`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimelineClock {
+    /// The timeline advances with [`Time`] while playing
+    #[default]
+    Real,
+    /// The time is only changed with [`TimelinePlayer::set_time`]
+    ///
+    /// Use this to follow an animation. Jumps backward or by more than a quarter second are
+    /// handled like a [`seek`](TimelinePlayer::seek).
+    External,
+}
+
+/// Component playing an [`AudioTimeline`]
+///
+/// Seeking stops the playing sounds and restarts every sound that would be playing at the new
+/// time from the matching position, so the audio stays in sync with the rest of the sequence.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// # use std::time::Duration;
+/// fn start_cutscene(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let mut player = TimelinePlayer::new(asset_server.load("cutscenes/intro.timeline.ron"));
+///     player.play();
+///     commands.spawn(player);
+/// }
+///
+/// fn skip_intro(mut players: Query<&mut TimelinePlayer>) {
+///     for mut player in &mut players {
+///         player.seek(Duration::from_secs(12));
+///     }
+/// }
+/// ```
+#[derive(Component)]
+pub struct TimelinePlayer {
+    timeline: Handle<AudioTimeline>,
+    clock: TimelineClock,
+    time: f64,
+    playing: bool,
+    /// Time up to which events were started, `None` if the sounds need to be synced to `time`
+    cursor: Option<f64>,
+    paused_sounds: bool,
+    /// Playing sounds with the index of their event
+    sounds: Vec<(usize, Handle<AudioInstance>)>,
+}
+
+impl TimelinePlayer {
+    /// A paused player at the start of the timeline
+    pub fn new(timeline: Handle<AudioTimeline>) -> Self {
+        TimelinePlayer {
+            timeline,
+            clock: TimelineClock::default(),
+            time: 0.,
+            playing: false,
+            cursor: None,
+            paused_sounds: false,
+            sounds: vec![],
+        }
+    }
+
+    /// Set what drives the time of the player
+    pub fn with_clock(mut self, clock: TimelineClock) -> Self {
+        self.clock = clock;
+
+        self
+    }
+
+    /// Start or resume playing
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Pause playing, including all sounds of the timeline
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Returns `true` if the player is playing
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Jump to the given time
+    pub fn seek(&mut self, time: Duration) {
+        self.time = time.as_secs_f64();
+        self.cursor = None;
+    }
+
+    /// Set the time of a player with an [external clock](TimelineClock::External)
+    pub fn set_time(&mut self, time: Duration) {
+        let time = time.as_secs_f64();
+        if time < self.time || time - self.time > MAX_EXTERNAL_STEP {
+            self.cursor = None;
+        }
+        self.time = time;
+    }
+
+    /// The current time of the player
+    pub fn time(&self) -> Duration {
+        Duration::from_secs_f64(self.time.max(0.))
+    }
+
+    /// The timeline of this player
+    pub fn timeline(&self) -> &Handle<AudioTimeline> {
+        &self.timeline
+    }
+
+    /// Fade out all sounds that were started by this player
+    fn stop_sounds(&mut self, audio_instances: &mut Assets<AudioInstance>) {
+        for (_, sound) in self.sounds.drain(..) {
+            if let Some(instance) = audio_instances.get_mut(&sound) {
+                instance.stop(AudioTween::default());
+            }
+        }
+    }
+}
+
+fn play_event(
+    event: &TimelineEvent,
+    offset: f64,
+    audio: &Audio,
+    channels: &DynamicAudioChannels,
+) -> Option<Handle<AudioInstance>> {
+    let mut command = match &event.channel {
+        Some(key) => {
+            let Some(channel) = channels.get_channel_keyed(key) else {
+                warn!("Failed to play timeline sound, because there is no dynamic channel '{key}'");
+                return None;
+            };
+            channel.play(event.source.clone())
+        }
+        None => audio.play(event.source.clone()),
+    };
+    event.settings.configure(&mut command);
+    if offset > 0. {
+        let rate = event.settings.playback_rate.unwrap_or(1.).abs();
+        command.start_from(event.settings.start_position.unwrap_or(0.) + offset * rate);
+    }
+
+    Some(command.handle())
+}
+
+pub(crate) fn run_timeline_players(
+    mut players: Query<&mut TimelinePlayer>,
+    timelines: Res<Assets<AudioTimeline>>,
+    audio_sources: Res<Assets<AudioSource>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    audio: Res<Audio>,
+    channels: Res<DynamicAudioChannels>,
+    time: Res<Time>,
+) {
+    for mut player in &mut players {
+        let Some(timeline) = timelines.get(&player.timeline) else {
+            continue;
+        };
+        let player = &mut *player;
+        if player.cursor.is_none() {
+            player.stop_sounds(&mut audio_instances);
+        }
+        player
+            .sounds
+            .retain(|(_, sound)| audio_instances.contains(sound));
+        if player.playing == player.paused_sounds {
+            player.paused_sounds = !player.playing;
+            for (_, sound) in &player.sounds {
+                if let Some(instance) = audio_instances.get_mut(sound) {
+                    if player.playing {
+                        instance.resume(AudioTween::default());
+                    } else {
+                        instance.pause(AudioTween::default());
+                    }
+                }
+            }
+        }
+        if !player.playing {
+            continue;
+        }
+        if player.clock == TimelineClock::Real && player.cursor.is_some() {
+            player.time += time.delta_seconds_f64();
+        }
+        let now = player.time;
+
+        for (index, event) in timeline.events.iter().enumerate() {
+            let start = match player.cursor {
+                None => event.plays_at(now, &audio_sources),
+                Some(cursor) => cursor < event.at && event.at <= now,
+            };
+            if start {
+                if let Some(sound) = play_event(event, now - event.at, &audio, &channels) {
+                    player.sounds.push((index, sound));
+                }
+            }
+        }
+        if let Some(cursor) = player.cursor {
+            player.sounds.retain(|(index, sound)| {
+                let event = &timeline.events[*index];
+                let Some(end) = event.end.filter(|end| cursor < *end && *end <= now) else {
+                    return true;
+                };
+                if let Some(instance) = audio_instances.get_mut(sound) {
+                    let fade_out = now - end + event.fade_out;
+                    instance.stop(AudioTween::linear(Duration::from_secs_f64(
+                        fade_out.max(0.),
+                    )));
+                }
+
+                false
+            });
+        }
+        player.cursor = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looped_and_unloaded_sounds_play_until_their_end() {
+        let audio_sources = Assets::<AudioSource>::default();
+        let mut event = TimelineEvent::new(2., Handle::default());
+        event.end = Some(5.);
+
+        assert!(!event.plays_at(1., &audio_sources));
+        assert!(event.plays_at(2., &audio_sources));
+        assert!(event.plays_at(4.9, &audio_sources));
+        assert!(!event.plays_at(5., &audio_sources));
+    }
+
+    #[test]
+    fn external_jumps_are_seeks() {
+        let mut player = TimelinePlayer::new(Handle::default()).with_clock(TimelineClock::External);
+        player.cursor = Some(0.);
+
+        player.set_time(Duration::from_millis(100));
+        assert!(player.cursor.is_some());
+        player.set_time(Duration::from_millis(50));
+        assert!(player.cursor.is_none());
+        player.cursor = Some(0.05);
+        player.set_time(Duration::from_secs(3));
+        assert!(player.cursor.is_none());
+    }
+}
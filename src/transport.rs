@@ -0,0 +1,403 @@
+//! Musical timing in bars and beats on top of a clock
+
+use crate::audio::AudioTween;
+use crate::backend_settings::OutputLatency;
+use crate::channel::AudioControl;
+use crate::clock::AudioClock;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::event::{Event, EventWriter};
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::log::warn;
+use kira::clock::ClockSpeed;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// Resource keeping musical time in bars, beats, and ticks
+///
+/// The transport drives an [`AudioClock`] that ticks [`ticks_per_beat`](Self::ticks_per_beat)
+/// times per beat. Use it as the single timing source for quantized sounds with
+/// [`start_at_tick`](crate::PlayAudioCommand::start_at_tick), or to drive a
+/// [`StepSequencer`](crate::StepSequencer).
+///
+/// A new transport is stopped. Starting, stopping, and tempo changes are applied by the audio
+/// systems at the end of the frame. The [`position`](Self::position) is updated once per frame.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn setup(mut commands: Commands, audio: Res<Audio>) {
+///     let mut transport = Transport::new(&*audio, 120., 4);
+///     transport.start();
+///     commands.insert_resource(transport);
+/// }
+///
+/// fn play_stinger(
+///     transport: Res<Transport>,
+///     audio: Res<Audio>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     audio
+///         .play(asset_server.load("sounds/stinger.ogg"))
+///         .start_at_tick(transport.clock(), transport.next_bar_tick());
+/// }
+/// ```
+#[derive(Resource)]
+pub struct Transport {
+    clock: Handle<AudioClock>,
+    beats_per_minute: f64,
+    beats_per_bar: u32,
+    ticks_per_beat: u32,
+    ticks: u64,
+    playing: bool,
+    commands: Vec<TransportCommand>,
+    latency_compensation: Duration,
+    /// Index of the last beat that a [`BeatTick`] was sent for
+    last_beat: Option<u64>,
+}
+
+/// Position of a [`Transport`] in bars, beats, and ticks
+///
+/// All values start counting at zero. It is displayed as `bar:beat:tick`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransportPosition {
+    /// Index of the bar
+    pub bar: u64,
+    /// Index of the beat in its bar
+    pub beat: u32,
+    /// Index of the tick in its beat
+    pub tick: u32,
+}
+
+/// Event sent when a [`Transport`] reaches a new beat
+///
+/// Events are delayed by the [`OutputLatency`](crate::OutputLatency) and the
+/// [latency compensation](Transport::set_latency_compensation) of the transport, so they line up
+/// with the beat that is heard. At most one event is sent per frame.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::prelude::*;
+/// fn pulse_lights(mut beats: EventReader<BeatTick>) {
+///     for beat in beats.read() {
+///         if beat.beat == 0 {
+///             info!("Bar {} started", beat.bar);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BeatTick {
+    /// Index of the bar
+    pub bar: u64,
+    /// Index of the beat in its bar
+    pub beat: u32,
+}
+
+impl Display for TransportPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.bar, self.beat, self.tick)
+    }
+}
+
+enum TransportCommand {
+    Start,
+    Pause,
+    Stop,
+    SetTempo(f64, AudioTween),
+}
+
+impl Transport {
+    /// The default number of clock ticks per beat
+    pub const DEFAULT_TICKS_PER_BEAT: u32 = 24;
+
+    /// Create a stopped transport with the given tempo and time signature
+    ///
+    /// The clock of the transport is added through the given channel.
+    pub fn new(audio: &impl AudioControl, beats_per_minute: f64, beats_per_bar: u32) -> Self {
+        Self::with_ticks_per_beat(
+            audio,
+            beats_per_minute,
+            beats_per_bar,
+            Self::DEFAULT_TICKS_PER_BEAT,
+        )
+    }
+
+    /// Create a stopped transport with the given tempo, time signature, and clock resolution
+    pub fn with_ticks_per_beat(
+        audio: &impl AudioControl,
+        beats_per_minute: f64,
+        beats_per_bar: u32,
+        ticks_per_beat: u32,
+    ) -> Self {
+        let ticks_per_beat = ticks_per_beat.max(1);
+        let clock = audio.add_clock(ClockSpeed::TicksPerMinute(
+            beats_per_minute * ticks_per_beat as f64,
+        ));
+
+        Transport {
+            clock,
+            beats_per_minute,
+            beats_per_bar: beats_per_bar.max(1),
+            ticks_per_beat,
+            ticks: 0,
+            playing: false,
+            commands: vec![TransportCommand::Stop],
+            latency_compensation: Duration::ZERO,
+            last_beat: None,
+        }
+    }
+
+    /// Start or resume the transport
+    pub fn start(&mut self) {
+        self.commands.push(TransportCommand::Start);
+    }
+
+    /// Pause the transport at its current position
+    pub fn pause(&mut self) {
+        self.commands.push(TransportCommand::Pause);
+    }
+
+    /// Stop the transport and reset it to the start of the first bar
+    pub fn stop(&mut self) {
+        self.commands.push(TransportCommand::Stop);
+    }
+
+    /// Change the tempo with the given easing
+    ///
+    /// The position in bars and beats is not affected by tempo changes.
+    pub fn set_tempo(&mut self, beats_per_minute: f64, tween: AudioTween) {
+        self.beats_per_minute = beats_per_minute;
+        self.commands
+            .push(TransportCommand::SetTempo(beats_per_minute, tween));
+    }
+
+    /// Delay [`BeatTick`] events by the given output latency
+    pub fn with_latency_compensation(mut self, latency: Duration) -> Self {
+        self.set_latency_compensation(latency);
+
+        self
+    }
+
+    /// Delay [`BeatTick`] events by the given output latency
+    ///
+    /// Sounds are heard some time after the audio backend processed them. This is added to the
+    /// [`OutputLatency`](crate::OutputLatency) shared by all beat events, for example to
+    /// compensate for a slow effect chain on the channels of this transport.
+    pub fn set_latency_compensation(&mut self, latency: Duration) {
+        self.latency_compensation = latency;
+    }
+
+    /// The tempo in beats per minute
+    ///
+    /// During a tempo ramp, this is the target tempo.
+    pub fn beats_per_minute(&self) -> f64 {
+        self.beats_per_minute
+    }
+
+    /// Number of beats in a bar
+    pub fn beats_per_bar(&self) -> u32 {
+        self.beats_per_bar
+    }
+
+    /// Number of clock ticks in a beat
+    pub fn ticks_per_beat(&self) -> u32 {
+        self.ticks_per_beat
+    }
+
+    /// The clock driving the transport
+    pub fn clock(&self) -> &Handle<AudioClock> {
+        &self.clock
+    }
+
+    /// Returns `true` if the transport is running
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Number of clock ticks since the transport was started
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The current position in bars, beats, and ticks
+    pub fn position(&self) -> TransportPosition {
+        self.position_at(self.ticks)
+    }
+
+    /// Convert a number of clock ticks into a position
+    pub fn position_at(&self, ticks: u64) -> TransportPosition {
+        let beats = ticks / self.ticks_per_beat as u64;
+
+        TransportPosition {
+            bar: beats / self.beats_per_bar as u64,
+            beat: (beats % self.beats_per_bar as u64) as u32,
+            tick: (ticks % self.ticks_per_beat as u64) as u32,
+        }
+    }
+
+    /// Convert a position into a number of clock ticks
+    pub fn tick_at(&self, position: TransportPosition) -> u64 {
+        (position.bar * self.beats_per_bar as u64 + position.beat as u64)
+            * self.ticks_per_beat as u64
+            + position.tick as u64
+    }
+
+    /// The clock tick of the next beat
+    pub fn next_beat_tick(&self) -> u64 {
+        let ticks_per_beat = self.ticks_per_beat as u64;
+
+        (self.ticks / ticks_per_beat + 1) * ticks_per_beat
+    }
+
+    /// The clock tick of the next bar
+    pub fn next_bar_tick(&self) -> u64 {
+        let ticks_per_bar = self.ticks_per_beat as u64 * self.beats_per_bar as u64;
+
+        (self.ticks / ticks_per_bar + 1) * ticks_per_bar
+    }
+
+    /// Find the latest beat reached at the given clock position, if no event was sent for it yet
+    fn beat_tick(
+        &mut self,
+        ticks: u64,
+        fractional_position: f64,
+        output_latency: Duration,
+    ) -> Option<BeatTick> {
+        let latency = self.latency_compensation + output_latency;
+        let latency_ticks =
+            latency.as_secs_f64() * self.beats_per_minute / 60. * self.ticks_per_beat as f64;
+        let compensated = ticks as f64 + fractional_position - latency_ticks;
+        if compensated < 0. {
+            self.last_beat = None;
+            return None;
+        }
+        let beat = (compensated / self.ticks_per_beat as f64).floor() as u64;
+        if self.last_beat == Some(beat) {
+            return None;
+        }
+        self.last_beat = Some(beat);
+
+        Some(BeatTick {
+            bar: beat / self.beats_per_bar as u64,
+            beat: (beat % self.beats_per_bar as u64) as u32,
+        })
+    }
+}
+
+pub(crate) fn update_transport(
+    mut transport: ResMut<Transport>,
+    mut clocks: ResMut<Assets<AudioClock>>,
+    output_latency: Res<OutputLatency>,
+    mut beats: EventWriter<BeatTick>,
+) {
+    if !transport.commands.is_empty() {
+        let Some(clock) = clocks.get_mut(&transport.clock) else {
+            return;
+        };
+        let ticks_per_beat = transport.ticks_per_beat as f64;
+        let mut stopped = false;
+        for command in transport.commands.drain(..) {
+            let error = match command {
+                TransportCommand::Start => clock.start(),
+                TransportCommand::Pause => clock.pause(),
+                TransportCommand::Stop => {
+                    stopped = true;
+                    clock.stop()
+                }
+                TransportCommand::SetTempo(beats_per_minute, tween) => clock.set_speed(
+                    ClockSpeed::TicksPerMinute(beats_per_minute * ticks_per_beat),
+                    tween,
+                ),
+            };
+            if let Some(error) = error {
+                warn!("Failed to control the transport clock: {:?}", error);
+            }
+        }
+        if stopped {
+            transport.last_beat = None;
+        }
+    }
+    let Some(clock) = clocks.get(&transport.clock) else {
+        return;
+    };
+    transport.ticks = clock.ticks();
+    transport.playing = clock.is_ticking();
+    if transport.playing {
+        if let Some(beat) = transport.beat_tick(
+            clock.ticks(),
+            clock.fractional_position(),
+            output_latency.offset,
+        ) {
+            beats.send(beat);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::typed::AudioChannel;
+    use crate::MainTrack;
+
+    #[test]
+    fn converts_between_ticks_and_positions() {
+        let transport =
+            Transport::with_ticks_per_beat(&AudioChannel::<MainTrack>::default(), 120., 3, 4);
+        let position = TransportPosition {
+            bar: 2,
+            beat: 1,
+            tick: 3,
+        };
+
+        assert_eq!(transport.tick_at(position), 31);
+        assert_eq!(transport.position_at(31), position);
+        assert_eq!(position.to_string(), "2:1:3");
+    }
+
+    #[test]
+    fn finds_next_beat_and_bar() {
+        let mut transport =
+            Transport::with_ticks_per_beat(&AudioChannel::<MainTrack>::default(), 120., 4, 4);
+        transport.ticks = 5;
+
+        assert_eq!(transport.next_beat_tick(), 8);
+        assert_eq!(transport.next_bar_tick(), 16);
+    }
+
+    #[test]
+    fn beat_ticks_are_delayed_by_latency() {
+        let mut transport =
+            Transport::with_ticks_per_beat(&AudioChannel::<MainTrack>::default(), 60., 2, 4)
+                .with_latency_compensation(Duration::from_millis(250));
+
+        assert_eq!(transport.beat_tick(0, 0.5, Duration::ZERO), None);
+        assert_eq!(
+            transport.beat_tick(1, 0., Duration::ZERO),
+            Some(BeatTick { bar: 0, beat: 0 })
+        );
+        assert_eq!(transport.beat_tick(4, 0.5, Duration::ZERO), None);
+        assert_eq!(
+            transport.beat_tick(5, 0., Duration::ZERO),
+            Some(BeatTick { bar: 0, beat: 1 })
+        );
+        assert_eq!(
+            transport.beat_tick(9, 0., Duration::ZERO),
+            Some(BeatTick { bar: 1, beat: 0 })
+        );
+        assert_eq!(transport.beat_tick(9, 0.5, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn output_latency_adds_to_the_latency_compensation() {
+        let mut transport =
+            Transport::with_ticks_per_beat(&AudioChannel::<MainTrack>::default(), 60., 2, 4)
+                .with_latency_compensation(Duration::from_millis(250));
+        let output_latency = Duration::from_millis(250);
+
+        assert_eq!(transport.beat_tick(1, 0.5, output_latency), None);
+        assert_eq!(
+            transport.beat_tick(2, 0., output_latency),
+            Some(BeatTick { bar: 0, beat: 0 })
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Set up the audio only after the first user input, as required by browsers
+
+use crate::audio_output::AudioOutput;
+use crate::AudioOutputDevice;
+use bevy::ecs::event::{Event, EventReader, EventWriter};
+use bevy::ecs::system::{NonSendMut, Res};
+use bevy::input::keyboard::{KeyCode, KeyboardInput};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::input::ButtonState;
+use bevy::utils::Instant;
+
+/// Event sent when the first user input unlocked the audio
+///
+/// Only sent if [`AudioSettings::unlock_on_input`](crate::AudioSettings::unlock_on_input) is
+/// enabled, which is the default on the web. Sounds played before it start now. Read it to hide
+/// a "click to start" hint, for example.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AudioUnlocked;
+
+/// Set up the audio on the first input that browsers count as user activation
+pub(crate) fn unlock_audio_on_input(
+    mut audio_output: NonSendMut<AudioOutput>,
+    device: Res<AudioOutputDevice>,
+    mut keyboard: EventReader<KeyboardInput>,
+    mut mouse: EventReader<MouseButtonInput>,
+    mut touch: EventReader<TouchInput>,
+    mut unlocked: EventWriter<AudioUnlocked>,
+) {
+    let key_pressed = keyboard
+        .read()
+        .any(|input| input.state == ButtonState::Pressed && input.key_code != KeyCode::Escape);
+    let mouse_pressed = mouse
+        .read()
+        .any(|input| input.state == ButtonState::Pressed);
+    let touch_ended = touch.read().any(|input| input.phase == TouchPhase::Ended);
+    if !audio_output.is_locked() || !(key_pressed || mouse_pressed || touch_ended) {
+        return;
+    }
+    audio_output.unlock(device.name.as_deref(), Instant::now());
+    unlocked.send(AudioUnlocked);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Audio, AudioControl, AudioSettings, AudioSource, MockAudioExt, MockAudioPlugin,
+        PlaybackState,
+    };
+    use bevy::asset::{AssetPlugin, Assets};
+    use bevy::ecs::entity::Entity;
+    use bevy::ecs::event::Events;
+    use bevy::input::mouse::MouseButton;
+    use bevy::input::InputPlugin;
+    use bevy::prelude::*;
+    use kira::sound::static_sound::StaticSoundData;
+
+    #[test]
+    fn sounds_played_before_the_first_input_start_when_unlocked() {
+        let mut app = App::new();
+        app.insert_resource(AudioSettings {
+            unlock_on_input: true,
+            ..default()
+        })
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            InputPlugin,
+            MockAudioPlugin,
+        ));
+        let source = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                sound: StaticSoundData::from_file("assets/sounds/plop.ogg", default()).unwrap(),
+            });
+        let instance = app.world().resource::<Audio>().play(source).handle();
+        app.update();
+        app.update();
+        assert!(!app
+            .world()
+            .non_send_resource::<AudioOutput>()
+            .is_available());
+        assert!(app.world().resource::<Audio>().has_pending_commands());
+        assert_eq!(app.audio_instance_state(&instance), PlaybackState::Stopped);
+
+        app.world_mut().send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state: ButtonState::Released,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        assert!(!app
+            .world()
+            .non_send_resource::<AudioOutput>()
+            .is_available());
+
+        app.world_mut().send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state: ButtonState::Pressed,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        assert!(app
+            .world()
+            .non_send_resource::<AudioOutput>()
+            .is_available());
+        assert_eq!(app.world().resource::<Events<AudioUnlocked>>().len(), 1);
+        assert!(!app.world().resource::<Audio>().has_pending_commands());
+        assert!(matches!(
+            app.audio_instance_state(&instance),
+            PlaybackState::Playing { .. }
+        ));
+    }
+}